@@ -3,9 +3,9 @@ use crate::providers::{
     StreamChunk, ToolCall,
 };
 use crate::tools::{
-    get_system_prompt, get_tool_definitions, validate_select_query, ColumnSummary,
-    ExecuteSelectQueryArgs, GetTableColumnsArgs, GetTableSampleArgs, QueryDataResult, TableSummary,
-    ToolError, ToolResult,
+    get_system_prompt, get_tool_definitions, validate_mongo_query, validate_select_query,
+    ColumnSummary, DescribeTableArgs, ExecuteSelectQueryArgs, GetTableColumnsArgs,
+    GetTableSampleArgs, QueryDataResult, TableSummary, ToolError, ToolResult,
 };
 use crate::types::{DatabaseOperations, DatabaseType};
 use serde::{Deserialize, Serialize};
@@ -399,6 +399,14 @@ impl Agent {
                     }),
                 }
             }
+            "describe_table" => {
+                match serde_json::from_str::<DescribeTableArgs>(&tool_call.arguments) {
+                    Ok(args) => self.execute_describe_table(args).await,
+                    Err(e) => ToolResult::Error(ToolError {
+                        error: format!("Invalid arguments: {}", e),
+                    }),
+                }
+            }
             "execute_select_query" => {
                 match serde_json::from_str::<ExecuteSelectQueryArgs>(&tool_call.arguments) {
                     Ok(args) => self.execute_select_query(args).await,
@@ -417,7 +425,7 @@ impl Agent {
             }
             _ => ToolResult::Error(ToolError {
                 error: format!(
-                    "Unknown tool: {}. Available tools: list_tables, get_table_columns, execute_select_query, get_table_sample. Use one of these names exactly.",
+                    "Unknown tool: {}. Available tools: list_tables, get_table_columns, describe_table, execute_select_query, get_table_sample. Use one of these names exactly.",
                     tool_call.name
                 ),
             }),
@@ -468,8 +476,23 @@ impl Agent {
         }
     }
 
+    async fn execute_describe_table(&self, args: DescribeTableArgs) -> ToolResult {
+        match self
+            .db
+            .describe_table(&self.connection_id, &args.schema, &args.table)
+            .await
+        {
+            Ok(details) => ToolResult::TableDetails(details),
+            Err(e) => ToolResult::Error(ToolError { error: e }),
+        }
+    }
+
     async fn execute_select_query(&self, args: ExecuteSelectQueryArgs) -> ToolResult {
-        if let Err(e) = validate_select_query(&args.query) {
+        let validation = match self.db_type {
+            DatabaseType::Mongodb => validate_mongo_query(&args.query),
+            _ => validate_select_query(&args.query),
+        };
+        if let Err(e) = validation {
             return ToolResult::Error(ToolError { error: e });
         }
 
@@ -1,4 +1,4 @@
-use crate::types::{DatabaseType, QueryResult};
+use crate::types::{DatabaseType, QueryResult, TableDetails};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
@@ -8,6 +8,7 @@ pub fn get_tool_definitions(db_type: DatabaseType) -> Vec<ToolDefinition> {
     vec![
         list_tables_tool(),
         get_table_columns_tool(db_type),
+        describe_table_tool(db_type),
         execute_select_query_tool(db_type),
         get_table_sample_tool(db_type),
     ]
@@ -54,6 +55,35 @@ fn get_table_columns_tool(db_type: DatabaseType) -> ToolDefinition {
     }
 }
 
+fn describe_table_tool(db_type: DatabaseType) -> ToolDefinition {
+    let schema_desc = match db_type {
+        DatabaseType::Postgres => "The schema name (usually 'public' for PostgreSQL)",
+        DatabaseType::Mysql => "The database/schema name",
+        DatabaseType::Sqlite => "The schema name (always 'main' for SQLite)",
+        DatabaseType::Redis => "The database index (usually 'db0' for Redis)",
+        DatabaseType::Mongodb => "The database name",
+    };
+
+    ToolDefinition {
+        name: "describe_table".to_string(),
+        description: "Get the full structure of a table: columns, indexes, foreign keys, primary key, unique and check constraints, and triggers. Use this to find join paths between tables and to write queries that use indexes.".to_string(),
+        parameters: ToolParameters {
+            param_type: "object".to_string(),
+            properties: json!({
+                "schema": {
+                    "type": "string",
+                    "description": schema_desc
+                },
+                "table": {
+                    "type": "string",
+                    "description": "The table name to describe"
+                }
+            }),
+            required: vec!["schema".to_string(), "table".to_string()],
+        },
+    }
+}
+
 fn execute_select_query_tool(db_type: DatabaseType) -> ToolDefinition {
     let query_desc = match db_type {
         DatabaseType::Postgres => {
@@ -69,7 +99,7 @@ fn execute_select_query_tool(db_type: DatabaseType) -> ToolDefinition {
             "The Redis command to execute (e.g., GET key, KEYS pattern, HGETALL key)."
         }
         DatabaseType::Mongodb => {
-            "MongoDB query execution is not supported. Use the data browser to explore collections."
+            "A read-only MongoDB shell query (e.g., db.users.find({age: {$gt: 30}}).limit(10), db.orders.aggregate([...]), countDocuments or distinct)."
         }
     };
 
@@ -132,6 +162,12 @@ pub struct GetTableColumnsArgs {
     pub table: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DescribeTableArgs {
+    pub schema: String,
+    pub table: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ExecuteSelectQueryArgs {
     pub query: String,
@@ -154,6 +190,7 @@ fn default_sample_limit() -> i64 {
 pub enum ToolResult {
     Tables(Vec<TableSummary>),
     Columns(Vec<ColumnSummary>),
+    TableDetails(TableDetails),
     QueryData(QueryDataResult),
     Error(ToolError),
 }
@@ -256,8 +293,9 @@ pub fn get_system_prompt(db_type: DatabaseType) -> String {
 - Documents are JSON-like BSON objects with flexible schemas
 - Each document has an _id field (ObjectId by default)
 - Use the Documents tab to browse collections
-- Query execution is not yet supported - use list_tables and get_table_columns to explore
-- Common field types: ObjectId, String, Number, Boolean, Date, Array, Object"#
+- Query with shell syntax: db.collection.find({...}).sort({...}).limit(n), aggregate([...]), countDocuments({...}), distinct("field")
+- Common field types: ObjectId, String, Number, Boolean, Date, Array, Object
+- Results show BSON types as Extended JSON ({"$oid": ...}, {"$date": ...}, {"$numberLong": ...}); filter on them with ObjectId("..."), ISODate("...") or the same Extended JSON"#
         }
     };
 
@@ -296,7 +334,7 @@ SQL Syntax Tips for {db_name}:
 
 ## Capabilities
 
-✅ List tables, examine schemas, run SELECT queries, explain SQL, debug errors
+✅ List tables, examine schemas, indexes and foreign keys, run SELECT queries, explain SQL, debug errors
 ❌ Cannot execute INSERT/UPDATE/DELETE (but can write examples for you to copy)
 
 ## Response Style
@@ -343,6 +381,16 @@ pub fn validate_select_query(query: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Only reading shell calls (find, aggregate without $out/$merge,
+/// countDocuments, distinct) are allowed
+pub fn validate_mongo_query(query: &str) -> Result<(), String> {
+    if querystudio_providers::mongo_query::is_read_only(query) {
+        Ok(())
+    } else {
+        Err("Only read-only find, aggregate, countDocuments and distinct queries are allowed for safety".to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -367,14 +415,14 @@ mod tests {
     #[test]
     fn test_tool_definitions_postgres() {
         let tools = get_tool_definitions(DatabaseType::Postgres);
-        assert_eq!(tools.len(), 4);
+        assert_eq!(tools.len(), 5);
         assert_eq!(tools[0].name, "list_tables");
     }
 
     #[test]
     fn test_tool_definitions_mysql() {
         let tools = get_tool_definitions(DatabaseType::Mysql);
-        assert_eq!(tools.len(), 4);
+        assert_eq!(tools.len(), 5);
 
         let query_tool = tools
             .iter()
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 
 // Re-export shared types from the providers crate
-pub use querystudio_providers::{ColumnInfo, DatabaseType, QueryResult, TableInfo};
+pub use querystudio_providers::{ColumnInfo, DatabaseType, QueryResult, TableDetails, TableInfo};
 
 #[async_trait]
 pub trait DatabaseOperations: Send + Sync {
@@ -12,6 +12,12 @@ pub trait DatabaseOperations: Send + Sync {
         schema: &str,
         table: &str,
     ) -> Result<Vec<ColumnInfo>, String>;
+    async fn describe_table(
+        &self,
+        connection_id: &str,
+        schema: &str,
+        table: &str,
+    ) -> Result<TableDetails, String>;
     async fn execute_query(&self, connection_id: &str, query: &str) -> Result<QueryResult, String>;
     async fn get_table_data(
         &self,
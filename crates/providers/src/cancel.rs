@@ -0,0 +1,77 @@
+use crate::ProviderError;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// Server-side hook that aborts the statement currently running on a connection
+/// (e.g. a Postgres cancel request or a MySQL `KILL QUERY`).
+#[async_trait]
+pub trait QueryCanceller: Send + Sync {
+    async fn cancel(&self) -> Result<(), ProviderError>;
+}
+
+/// Cancellation handle shared between a running query and whoever wants to stop it.
+///
+/// Providers register a [`QueryCanceller`] once they know which connection the
+/// query runs on, and race the query future against [`CancelToken::cancelled`]
+/// so the caller gets control back even if the server ignores the request.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<CancelInner>,
+}
+
+#[derive(Default)]
+struct CancelInner {
+    cancelled: AtomicBool,
+    notify: Notify,
+    canceller: Mutex<Option<Arc<dyn QueryCanceller>>>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn set_canceller(&self, canceller: impl QueryCanceller + 'static) {
+        *self.inner.canceller.lock().unwrap() = Some(Arc::new(canceller));
+    }
+
+    /// Drop the registered canceller, e.g. before a pooled connection is handed
+    /// back so a late cancel cannot hit someone else's statement.
+    pub fn clear_canceller(&self) {
+        self.inner.canceller.lock().unwrap().take();
+    }
+
+    pub async fn cancel(&self) -> Result<(), ProviderError> {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+
+        let canceller = self.inner.canceller.lock().unwrap().clone();
+        match canceller {
+            Some(canceller) => canceller.cancel().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Resolves once [`CancelToken::cancel`] has been called.
+    pub async fn cancelled(&self) {
+        let notified = self.inner.notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        if self.is_cancelled() {
+            return;
+        }
+
+        notified.await;
+    }
+}
+
+pub(crate) fn cancelled_error() -> ProviderError {
+    ProviderError::new("Query was cancelled")
+}
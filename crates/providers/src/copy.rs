@@ -0,0 +1,867 @@
+//! Copy a table between two connections, which may be of different database
+//! types. Column types are mapped through a database-independent
+//! classification, the target table is created to match, and rows are read
+//! in pages and written with the target's bulk import path.
+
+use crate::import::{ImportReport, ImportRowError};
+use crate::{
+    cancel, filter, mongodb, sql, CancelToken, ColumnFilter, ColumnInfo, DatabaseProvider,
+    DatabaseType, ImportBatch, ImportBatches, ImportErrorMode, ImportSummary, LogicalType,
+    PageRequest, ProviderError, QueryResult, QueryStreamEvent, TableQuery,
+    DEFAULT_STREAM_BATCH_SIZE,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableRef {
+    pub schema: String,
+    pub table: String,
+}
+
+/// What happens to a target table that already exists. A missing target is
+/// always created from the source's columns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyMode {
+    /// Insert the rows next to the ones already there
+    #[default]
+    Append,
+    /// Recreate the table from the source. The new table is filled under a
+    /// temporary name and only swapped in once the copy succeeded.
+    Replace,
+    /// Insert new rows and update those whose primary key already exists
+    Upsert,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CopyOptions {
+    /// Only copy source rows matching these
+    #[serde(default)]
+    pub filters: Vec<ColumnFilter>,
+    /// Copy at most this many rows
+    #[serde(default)]
+    pub limit: Option<u64>,
+    #[serde(default)]
+    pub mode: CopyMode,
+    #[serde(default)]
+    pub on_error: ImportErrorMode,
+    /// Rows per page read from the source
+    #[serde(default)]
+    pub batch_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CopySummary {
+    pub rows_copied: u64,
+    pub rows_skipped: u64,
+    /// The first row errors; row numbers count the copied rows from 1
+    pub errors: Vec<ImportRowError>,
+    pub created_table: bool,
+}
+
+/// Progress of a running copy, sent after every page read
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum CopyEvent {
+    Progress { rows: u64 },
+    Done(CopySummary),
+    Error(String),
+}
+
+/// Copy `from` on `source` into `to` on `target`, reporting rows read
+/// through `progress`. A table created for the copy is dropped again if the
+/// copy fails.
+pub async fn copy_table(
+    source: &dyn DatabaseProvider,
+    from: &TableRef,
+    target: &dyn DatabaseProvider,
+    to: &TableRef,
+    options: &CopyOptions,
+    cancel: &CancelToken,
+    progress: &mpsc::Sender<CopyEvent>,
+) -> Result<CopySummary, ProviderError> {
+    let source_type = source.database_type();
+    let target_type = target.database_type();
+    match (target_type, options.mode) {
+        (DatabaseType::Redis, _) => {
+            return Err(ProviderError::new("Copying into Redis is not supported"))
+        }
+        (DatabaseType::Mongodb, CopyMode::Replace | CopyMode::Upsert) => {
+            return Err(ProviderError::new(
+                "Only appending is supported when copying into MongoDB",
+            ))
+        }
+        _ => {}
+    }
+
+    let source_columns = source.get_table_columns(&from.schema, &from.table).await?;
+    if source_columns.is_empty() {
+        return Err(ProviderError::new(format!(
+            "Table {}.{} does not exist",
+            from.schema, from.table
+        )));
+    }
+    let columns: Vec<String> = source_columns.iter().map(|c| c.name.clone()).collect();
+
+    let existing = target.get_table_columns(&to.schema, &to.table).await?;
+    let exists = !existing.is_empty();
+    if exists && options.mode != CopyMode::Replace && target_type != DatabaseType::Mongodb {
+        for column in &columns {
+            if !existing.iter().any(|c| &c.name == column) {
+                return Err(ProviderError::new(format!(
+                    "Table {}.{} has no column {}",
+                    to.schema, to.table, column
+                ))
+                .with_hint("Copy with replace to recreate the table from the source."));
+            }
+        }
+    }
+
+    let key_source = if exists { &existing } else { &source_columns };
+    let key: Vec<String> = key_source
+        .iter()
+        .filter(|c| c.is_primary_key)
+        .map(|c| c.name.clone())
+        .collect();
+    if options.mode == CopyMode::Upsert && key.is_empty() {
+        return Err(ProviderError::new(format!(
+            "Table {}.{} has no primary key",
+            to.schema, to.table
+        ))
+        .with_hint("Upserting matches rows by primary key; append or replace instead."));
+    }
+
+    let staging = (options.mode == CopyMode::Replace && exists).then(|| {
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        format!("{}_copy_{}", to.table, &suffix[..8])
+    });
+    let write_table = staging.as_deref().unwrap_or(&to.table);
+    let create = target_type != DatabaseType::Mongodb && (!exists || staging.is_some());
+    if create {
+        let name = sql::table_name(&to.schema, write_table, target_type);
+        let statement = create_table_sql(target_type, &name, &source_columns, source_type);
+        target.execute_query(&statement).await?;
+    }
+
+    let keyset = match source_type {
+        DatabaseType::Mongodb => true,
+        DatabaseType::Redis => false,
+        _ => source_columns.iter().any(|c| c.is_primary_key),
+    };
+    let batch_size = options
+        .batch_size
+        .unwrap_or(DEFAULT_STREAM_BATCH_SIZE)
+        .max(1);
+    let (tx, rx) = mpsc::channel(2);
+    let consume = async {
+        match options.mode {
+            CopyMode::Upsert => {
+                upsert_rows(
+                    target,
+                    &to.schema,
+                    write_table,
+                    &columns,
+                    &key,
+                    rx,
+                    options.on_error,
+                )
+                .await
+            }
+            _ => {
+                target
+                    .import_rows(&to.schema, write_table, &columns, rx, options.on_error)
+                    .await
+            }
+        }
+    };
+    let produce = async {
+        let reader = Reader {
+            source,
+            from,
+            columns: &columns,
+            keyset,
+            plain_values: source_type == DatabaseType::Mongodb
+                && target_type != DatabaseType::Mongodb,
+            options,
+            batch_size,
+        };
+        // Dropping `tx` afterwards tells the target to commit
+        if let Err(e) = reader.run(&tx, cancel, progress).await {
+            let _ = tx.send(Err(e)).await;
+        }
+        drop(tx);
+    };
+
+    let (written, ()) = tokio::join!(consume, produce);
+    let written = match written {
+        Ok(written) => written,
+        Err(e) => {
+            if create {
+                let name = sql::table_name(&to.schema, write_table, target_type);
+                let _ = target.execute_query(&format!("DROP TABLE {}", name)).await;
+            }
+            return Err(e);
+        }
+    };
+    if let Some(staging) = &staging {
+        swap_tables(target, &to.schema, staging, &to.table).await?;
+    }
+
+    Ok(CopySummary {
+        rows_copied: written.rows_imported,
+        rows_skipped: written.rows_skipped,
+        errors: written.errors,
+        created_table: create,
+    })
+}
+
+/// Replace `table` with the freshly filled `staging` table. Both renames
+/// happen at once, so readers see either table whole, and the old table is
+/// dropped only once the swap has succeeded.
+async fn swap_tables(
+    target: &dyn DatabaseProvider,
+    schema: &str,
+    staging: &str,
+    table: &str,
+) -> Result<(), ProviderError> {
+    let db_type = target.database_type();
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    let old = format!("{}_old_{}", table, &suffix[..8]);
+    let name = sql::table_name(schema, table, db_type);
+    let old_name = sql::table_name(schema, &old, db_type);
+    let staging_name = sql::table_name(schema, staging, db_type);
+
+    if db_type == DatabaseType::Mysql {
+        // One RENAME TABLE moves both tables atomically
+        target
+            .execute_query(&format!(
+                "RENAME TABLE {} TO {}, {} TO {}",
+                name, old_name, staging_name, name
+            ))
+            .await?;
+    } else {
+        let rename = |from: &str, to: &str| {
+            format!(
+                "ALTER TABLE {} RENAME TO {}",
+                from,
+                sql::quote_identifier(to, db_type)
+            )
+        };
+        let transaction = target.begin_transaction().await?;
+        for statement in [rename(&name, &old), rename(&staging_name, table)] {
+            if let Err(e) = target
+                .execute_in_transaction(&transaction, &statement)
+                .await
+            {
+                let _ = target.rollback_transaction(&transaction).await;
+                return Err(e);
+            }
+        }
+        target.commit_transaction(&transaction).await?;
+    }
+    target
+        .execute_query(&format!("DROP TABLE {}", old_name))
+        .await
+        .map(|_| ())
+}
+
+/// Pages through the source rows that match the filters
+struct Reader<'a> {
+    source: &'a dyn DatabaseProvider,
+    from: &'a TableRef,
+    columns: &'a [String],
+    /// Page by primary key. SQL tables without one are streamed in a single
+    /// query and Redis keys are read with OFFSET paging.
+    keyset: bool,
+    /// Unwrap MongoDB's Extended JSON values for a SQL target
+    plain_values: bool,
+    options: &'a CopyOptions,
+    batch_size: usize,
+}
+
+impl Reader<'_> {
+    async fn run(
+        &self,
+        tx: &mpsc::Sender<Result<ImportBatch, ProviderError>>,
+        cancel: &CancelToken,
+        progress: &mpsc::Sender<CopyEvent>,
+    ) -> Result<(), ProviderError> {
+        let db_type = self.source.database_type();
+        if !self.keyset && !matches!(db_type, DatabaseType::Mongodb | DatabaseType::Redis) {
+            return self.stream(tx, cancel, progress).await;
+        }
+        let mut request = PageRequest {
+            filters: self.options.filters.clone(),
+            key: None,
+            direction: Default::default(),
+            cursor: None,
+            limit: 0,
+        };
+        let query = TableQuery {
+            filters: self.options.filters.clone(),
+            sort: Vec::new(),
+        };
+        let mut read = 0;
+
+        loop {
+            if cancel.is_cancelled() {
+                return Err(cancel::cancelled_error());
+            }
+            let wanted = match self.options.limit {
+                Some(limit) => limit.saturating_sub(read).min(self.batch_size as u64),
+                None => self.batch_size as u64,
+            };
+            if wanted == 0 {
+                return Ok(());
+            }
+
+            let (result, more) = if self.keyset {
+                request.limit = wanted as i64;
+                let page = self
+                    .source
+                    .get_table_page(&self.from.schema, &self.from.table, &request)
+                    .await?;
+                let more = page.next_cursor.is_some();
+                request.cursor = page.next_cursor;
+                (page.result, more)
+            } else {
+                let result = self
+                    .source
+                    .get_table_data_filtered(
+                        &self.from.schema,
+                        &self.from.table,
+                        &query,
+                        wanted as i64,
+                        read as i64,
+                    )
+                    .await?;
+                let more = result.rows.len() as u64 == wanted;
+                (result, more)
+            };
+
+            let batch = ImportBatch {
+                first_row: read + 1,
+                rows: self.arrange(result),
+            };
+            read += batch.rows.len() as u64;
+            if !batch.rows.is_empty() {
+                // A closed channel means the target stopped and has the error
+                if tx.send(Ok(batch)).await.is_err() {
+                    return Ok(());
+                }
+                let _ = progress.send(CopyEvent::Progress { rows: read }).await;
+            }
+            if !more {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Read a SQL table without a primary key in one streamed query. Its
+    /// rows have no order that OFFSET pages could rely on, so separate
+    /// pages could repeat or skip rows.
+    async fn stream(
+        &self,
+        tx: &mpsc::Sender<Result<ImportBatch, ProviderError>>,
+        cancel: &CancelToken,
+        progress: &mpsc::Sender<CopyEvent>,
+    ) -> Result<(), ProviderError> {
+        let db_type = self.source.database_type();
+        let mut query = format!(
+            "SELECT * FROM {}{}",
+            sql::table_name(&self.from.schema, &self.from.table, db_type),
+            filter::literal_where_clause(db_type, &self.options.filters)?
+        );
+        if let Some(limit) = self.options.limit {
+            query.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        // Stopping early drops `rx`, which ends the query
+        let (rows_tx, mut rx) = mpsc::channel(2);
+        let produce = self
+            .source
+            .execute_query_stream(&query, self.batch_size, rows_tx, cancel);
+        let consume = async move {
+            let mut columns = Vec::new();
+            let mut read = 0;
+            while let Some(event) = rx.recv().await {
+                match event {
+                    QueryStreamEvent::Columns(names) => columns = names,
+                    QueryStreamEvent::Rows(rows) => {
+                        let result = QueryResult {
+                            columns: columns.clone(),
+                            rows,
+                            ..Default::default()
+                        };
+                        let batch = ImportBatch {
+                            first_row: read + 1,
+                            rows: self.arrange(result),
+                        };
+                        read += batch.rows.len() as u64;
+                        // A closed channel means the target stopped and has the error
+                        if tx.send(Ok(batch)).await.is_err() {
+                            return Ok(());
+                        }
+                        let _ = progress.send(CopyEvent::Progress { rows: read }).await;
+                    }
+                    QueryStreamEvent::Done { .. } => {}
+                    QueryStreamEvent::Error(e) => return Err(ProviderError::new(e)),
+                }
+            }
+            Ok(())
+        };
+
+        let (produced, consumed) = tokio::join!(produce, consume);
+        consumed?;
+        produced
+    }
+
+    /// Rows in the order of `columns`. MongoDB pages only have the fields
+    /// their documents use, so missing ones become null.
+    fn arrange(&self, result: QueryResult) -> Vec<Vec<Value>> {
+        let positions: Vec<Option<usize>> = self
+            .columns
+            .iter()
+            .map(|column| result.columns.iter().position(|c| c == column))
+            .collect();
+        result
+            .rows
+            .into_iter()
+            .map(|row| {
+                positions
+                    .iter()
+                    .map(|position| position.map_or(Value::Null, |i| row[i].clone()))
+                    .map(|value| {
+                        if self.plain_values {
+                            mongodb::plain_value(value)
+                        } else {
+                            value
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Upsert the batches in one transaction. A batch the database rejects is
+/// retried row by row under savepoints to find the failing rows.
+async fn upsert_rows(
+    target: &dyn DatabaseProvider,
+    schema: &str,
+    table: &str,
+    columns: &[String],
+    key: &[String],
+    mut batches: ImportBatches,
+    on_error: ImportErrorMode,
+) -> Result<ImportSummary, ProviderError> {
+    let db_type = target.database_type();
+    let name = sql::table_name(schema, table, db_type);
+    let transaction = target.begin_transaction().await?;
+    let run = |statement: String| {
+        let transaction = transaction.clone();
+        async move {
+            target
+                .execute_in_transaction(&transaction, &statement)
+                .await
+                .map(|_| ())
+        }
+    };
+
+    let mut report = ImportReport::new(on_error);
+    let mut result = Ok(());
+    while let Some(batch) = batches.recv().await {
+        let batch = match batch {
+            Ok(batch) => batch,
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+        result = async {
+            run("SAVEPOINT copy_batch".to_string()).await?;
+            let upsert = upsert_sql(db_type, &name, columns, key, &batch.rows);
+            if run(upsert).await.is_ok() {
+                report.imported(batch.rows.len() as u64);
+                return run("RELEASE SAVEPOINT copy_batch".to_string()).await;
+            }
+
+            run("ROLLBACK TO SAVEPOINT copy_batch".to_string()).await?;
+            for (offset, row) in batch.rows.iter().enumerate() {
+                run("SAVEPOINT copy_row".to_string()).await?;
+                let upsert = upsert_sql(db_type, &name, columns, key, std::slice::from_ref(row));
+                match run(upsert).await {
+                    Ok(()) => {
+                        report.imported(1);
+                        run("RELEASE SAVEPOINT copy_row".to_string()).await?;
+                    }
+                    Err(e) => {
+                        run("ROLLBACK TO SAVEPOINT copy_row".to_string()).await?;
+                        report.row_failed(batch.first_row + offset as u64, e)?;
+                    }
+                }
+            }
+            run("RELEASE SAVEPOINT copy_batch".to_string()).await
+        }
+        .await;
+        if result.is_err() {
+            break;
+        }
+    }
+    if let Err(e) = result {
+        let _ = target.rollback_transaction(&transaction).await;
+        return Err(e);
+    }
+
+    target.commit_transaction(&transaction).await?;
+    Ok(report.finish())
+}
+
+/// `INSERT` of `rows` into `target` (an already quoted table name) that
+/// updates the other columns of rows whose `key` already exists
+pub(crate) fn upsert_sql(
+    db_type: DatabaseType,
+    target: &str,
+    columns: &[String],
+    key: &[String],
+    rows: &[Vec<Value>],
+) -> String {
+    let quote = |column: &String| sql::quote_identifier(column, db_type);
+    let names: Vec<String> = columns.iter().map(quote).collect();
+    let values: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let literals: Vec<String> = row
+                .iter()
+                .map(|value| sql::literal(value, db_type))
+                .collect();
+            format!("({})", literals.join(", "))
+        })
+        .collect();
+    let updated: Vec<String> = columns
+        .iter()
+        .filter(|column| !key.contains(column))
+        .map(quote)
+        .collect();
+
+    let conflict = match db_type {
+        DatabaseType::Mysql => {
+            // Updating a key column to itself turns the duplicate into a no-op
+            let assignments: Vec<String> = if updated.is_empty() {
+                vec![format!("{} = {}", quote(&key[0]), quote(&key[0]))]
+            } else {
+                updated
+                    .iter()
+                    .map(|column| format!("{} = VALUES({})", column, column))
+                    .collect()
+            };
+            format!("ON DUPLICATE KEY UPDATE {}", assignments.join(", "))
+        }
+        _ => {
+            let key: Vec<String> = key.iter().map(quote).collect();
+            if updated.is_empty() {
+                format!("ON CONFLICT ({}) DO NOTHING", key.join(", "))
+            } else {
+                let assignments: Vec<String> = updated
+                    .iter()
+                    .map(|column| format!("{} = excluded.{}", column, column))
+                    .collect();
+                format!(
+                    "ON CONFLICT ({}) DO UPDATE SET {}",
+                    key.join(", "),
+                    assignments.join(", ")
+                )
+            }
+        }
+    };
+
+    format!(
+        "INSERT INTO {} ({}) VALUES {} {}",
+        target,
+        names.join(", "),
+        values.join(", "),
+        conflict
+    )
+}
+
+/// `CREATE TABLE` for `name` (already quoted) with the source's columns,
+/// nullability and primary key in the target's types
+pub(crate) fn create_table_sql(
+    db_type: DatabaseType,
+    name: &str,
+    columns: &[ColumnInfo],
+    source_type: DatabaseType,
+) -> String {
+    let mut definitions: Vec<String> = columns
+        .iter()
+        .map(|column| {
+            let mut definition = format!(
+                "{} {}",
+                sql::quote_identifier(&column.name, db_type),
+                map_type(
+                    column.declared_type(),
+                    source_type,
+                    db_type,
+                    column.is_primary_key
+                )
+            );
+            if !column.is_nullable {
+                definition.push_str(" NOT NULL");
+            }
+            definition
+        })
+        .collect();
+    let key: Vec<String> = columns
+        .iter()
+        .filter(|column| column.is_primary_key)
+        .map(|column| sql::quote_identifier(&column.name, db_type))
+        .collect();
+    if !key.is_empty() {
+        definitions.push(format!("PRIMARY KEY ({})", key.join(", ")));
+    }
+    format!("CREATE TABLE {} ({})", name, definitions.join(", "))
+}
+
+/// The `to` type for a `from` column's declared type. Text lengths, decimal
+/// precision and scale, and Postgres array element types carry over where
+/// the target can declare them; other types map to a fixed type.
+pub fn map_type(
+    data_type: &str,
+    from: DatabaseType,
+    to: DatabaseType,
+    primary_key: bool,
+) -> String {
+    let name = data_type.trim().to_ascii_lowercase();
+    let with_time_zone = name.contains("with time zone") || name.ends_with("tz");
+    let logical = classify(&name, from);
+    if to == DatabaseType::Sqlite {
+        return sqlite_type(logical).to_string();
+    }
+    if let Some(sized) = sized_type(&name, logical, from, to, primary_key) {
+        return sized;
+    }
+    let mapped = match (logical, to) {
+        (LogicalType::Boolean, _) => "BOOLEAN",
+        (LogicalType::Integer, _) => {
+            if name.contains("small") || name.starts_with("tinyint") || name == "int2" {
+                "SMALLINT"
+            } else if name.contains("unsigned") || name.contains("big") {
+                "BIGINT"
+            } else if matches!(from, DatabaseType::Postgres | DatabaseType::Mysql)
+                && matches!(
+                    name.as_str(),
+                    "integer" | "int" | "int4" | "mediumint" | "serial"
+                )
+            {
+                "INTEGER"
+            } else {
+                // SQLite integers and MongoDB int64 are 64-bit
+                "BIGINT"
+            }
+        }
+        (LogicalType::Float, DatabaseType::Mysql) => "DOUBLE",
+        (LogicalType::Float, _) => "DOUBLE PRECISION",
+        (LogicalType::Decimal, DatabaseType::Mysql) => "DECIMAL(65, 30)",
+        (LogicalType::Decimal, _) => "NUMERIC",
+        (LogicalType::Uuid, DatabaseType::Mysql) => "CHAR(36)",
+        (LogicalType::Uuid, _) => "UUID",
+        (LogicalType::Json, DatabaseType::Mysql) => "JSON",
+        (LogicalType::Json, _) => "JSONB",
+        (LogicalType::Date, _) => "DATE",
+        (LogicalType::Time, DatabaseType::Mysql) => "TIME(6)",
+        (LogicalType::Time, _) if with_time_zone => "TIMETZ",
+        (LogicalType::Time, _) => "TIME",
+        (LogicalType::Timestamp, DatabaseType::Mysql) => "DATETIME(6)",
+        (LogicalType::Timestamp, _) if with_time_zone => "TIMESTAMPTZ",
+        (LogicalType::Timestamp, _) => "TIMESTAMP",
+        (LogicalType::Interval, DatabaseType::Postgres) => "INTERVAL",
+        (LogicalType::Binary, DatabaseType::Mysql) => "LONGBLOB",
+        (LogicalType::Binary, _) => "BYTEA",
+        // MySQL can only index text with a length
+        (_, DatabaseType::Mysql) if primary_key => "VARCHAR(255)",
+        (_, DatabaseType::Mysql) => "LONGTEXT",
+        _ => "TEXT",
+    };
+    mapped.to_string()
+}
+
+/// The type with the length, precision and scale or the array element type
+/// of the lower-cased `name`, where `to` can declare them
+fn sized_type(
+    name: &str,
+    logical: LogicalType,
+    from: DatabaseType,
+    to: DatabaseType,
+    primary_key: bool,
+) -> Option<String> {
+    use DatabaseType::{Mysql, Postgres};
+
+    if from == Postgres && to == Postgres && name.ends_with("[]") {
+        return Some(name.to_string());
+    }
+    let (base, rest) = name.split_once('(')?;
+    let args = rest
+        .split_once(')')?
+        .0
+        .split(',')
+        .map(|arg| arg.trim().parse::<u32>().ok())
+        .collect::<Option<Vec<u32>>>()?;
+    match (logical, args.as_slice()) {
+        (LogicalType::Decimal, &[precision, ..]) if args.len() <= 2 => {
+            let scale = args.get(1).copied().unwrap_or(0);
+            match to {
+                Mysql if precision <= 65 && scale <= 30 => {
+                    Some(format!("DECIMAL({}, {})", precision, scale))
+                }
+                Postgres => Some(format!("NUMERIC({}, {})", precision, scale)),
+                _ => None,
+            }
+        }
+        (LogicalType::Text, &[length]) => {
+            let fixed = matches!(base.trim(), "char" | "character" | "nchar");
+            let varying = matches!(
+                base.trim(),
+                "varchar" | "character varying" | "nvarchar" | "varchar2"
+            );
+            match to {
+                // Index keys are limited to 3072 bytes, 768 characters of utf8mb4
+                Mysql if primary_key && length > 768 => None,
+                Mysql if fixed && length <= 255 => Some(format!("CHAR({})", length)),
+                // The longest VARCHAR that fits a row in utf8mb4
+                Mysql if (fixed || varying) && length <= 16383 => {
+                    Some(format!("VARCHAR({})", length))
+                }
+                Postgres if fixed => Some(format!("CHAR({})", length)),
+                Postgres if varying => Some(format!("VARCHAR({})", length)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn sqlite_type(logical: LogicalType) -> &'static str {
+    match logical {
+        LogicalType::Boolean | LogicalType::Integer => "INTEGER",
+        LogicalType::Float => "REAL",
+        LogicalType::Decimal => "NUMERIC",
+        LogicalType::Binary => "BLOB",
+        _ => "TEXT",
+    }
+}
+
+/// Classify a lower-cased column type name
+fn classify(name: &str, from: DatabaseType) -> LogicalType {
+    let base = name.split('(').next().unwrap_or(name).trim();
+    let base = base.strip_suffix(" unsigned").unwrap_or(base);
+    match (base, from) {
+        // BSON dates carry a time
+        ("date", DatabaseType::Mongodb) => LogicalType::Timestamp,
+        (_, DatabaseType::Postgres) if base.ends_with("[]") => LogicalType::Json,
+        ("tinyint", DatabaseType::Mysql) if name == "tinyint(1)" => LogicalType::Boolean,
+        ("boolean" | "bool", _) => LogicalType::Boolean,
+        (
+            "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "int2" | "int4"
+            | "int8" | "serial" | "smallserial" | "bigserial" | "year" | "int32" | "int64",
+            _,
+        ) => LogicalType::Integer,
+        ("real" | "float" | "double" | "double precision" | "float4" | "float8", _) => {
+            LogicalType::Float
+        }
+        ("numeric" | "decimal" | "money" | "decimal128", _) => LogicalType::Decimal,
+        ("uuid", _) => LogicalType::Uuid,
+        ("json" | "jsonb" | "array" | "object", _) => LogicalType::Json,
+        ("date", _) => LogicalType::Date,
+        ("time" | "time without time zone" | "time with time zone" | "timetz", _) => {
+            LogicalType::Time
+        }
+        (
+            "timestamp"
+            | "timestamp without time zone"
+            | "timestamp with time zone"
+            | "timestamptz"
+            | "datetime",
+            _,
+        ) => LogicalType::Timestamp,
+        ("interval", _) => LogicalType::Interval,
+        ("bytea" | "blob" | "tinyblob" | "mediumblob" | "longblob" | "binary" | "varbinary", _) => {
+            LogicalType::Binary
+        }
+        // SQLite accepts any type name and derives an affinity from it
+        (_, DatabaseType::Sqlite) if base.contains("int") => LogicalType::Integer,
+        (_, DatabaseType::Sqlite) if base.contains("blob") => LogicalType::Binary,
+        (_, DatabaseType::Sqlite)
+            if base.contains("real") || base.contains("floa") || base.contains("doub") =>
+        {
+            LogicalType::Float
+        }
+        _ => LogicalType::Text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn types_map_between_dialects() {
+        use DatabaseType::*;
+        assert_eq!(map_type("integer", Postgres, Mysql, false), "INTEGER");
+        assert_eq!(map_type("int unsigned", Mysql, Postgres, false), "BIGINT");
+        assert_eq!(map_type("INTEGER", Sqlite, Postgres, true), "BIGINT");
+        assert_eq!(
+            map_type("timestamp with time zone", Postgres, Postgres, false),
+            "TIMESTAMPTZ"
+        );
+        assert_eq!(map_type("datetime", Mysql, Sqlite, false), "TEXT");
+        assert_eq!(map_type("jsonb", Postgres, Mysql, false), "JSON");
+        assert_eq!(map_type("integer[]", Postgres, Sqlite, false), "TEXT");
+        assert_eq!(map_type("tinyint(1)", Mysql, Postgres, false), "BOOLEAN");
+        assert_eq!(
+            map_type("character varying", Postgres, Mysql, true),
+            "VARCHAR(255)"
+        );
+        assert_eq!(map_type("date", Mongodb, Postgres, false), "TIMESTAMP");
+        assert_eq!(map_type("VARCHAR(40)", Sqlite, Mysql, false), "VARCHAR(40)");
+        assert_eq!(
+            map_type("character varying(40)", Postgres, Postgres, false),
+            "VARCHAR(40)"
+        );
+        assert_eq!(map_type("char(2)", Mysql, Postgres, true), "CHAR(2)");
+        assert_eq!(
+            map_type("numeric(10,2)", Postgres, Mysql, false),
+            "DECIMAL(10, 2)"
+        );
+        assert_eq!(
+            map_type("decimal(12,4) unsigned", Mysql, Postgres, false),
+            "NUMERIC(12, 4)"
+        );
+        assert_eq!(
+            map_type("numeric", Postgres, Mysql, false),
+            "DECIMAL(65, 30)"
+        );
+        assert_eq!(
+            map_type("integer[]", Postgres, Postgres, false),
+            "integer[]"
+        );
+        assert_eq!(map_type("integer[]", Postgres, Mysql, false), "JSON");
+    }
+
+    #[test]
+    fn upserts_update_non_key_columns() {
+        let columns = vec!["id".to_string(), "name".to_string()];
+        let key = vec!["id".to_string()];
+        let rows = vec![vec![json!(1), json!("it's")], vec![json!(2), json!(null)]];
+        assert_eq!(
+            upsert_sql(DatabaseType::Postgres, r#""t""#, &columns, &key, &rows),
+            r#"INSERT INTO "t" ("id", "name") VALUES (1, 'it''s'), (2, NULL) ON CONFLICT ("id") DO UPDATE SET "name" = excluded."name""#
+        );
+        assert_eq!(
+            upsert_sql(DatabaseType::Mysql, "`t`", &columns, &key, &rows[..1]),
+            "INSERT INTO `t` (`id`, `name`) VALUES (1, 'it''s') ON DUPLICATE KEY UPDATE `name` = VALUES(`name`)"
+        );
+    }
+}
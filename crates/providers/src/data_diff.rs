@@ -0,0 +1,639 @@
+//! Compare the rows of two tables, which may live on different connections,
+//! matching them by key. Both tables are read in pages and each page is
+//! looked up on the other side by its keys, so neither table is held in
+//! memory and the databases' key orderings never have to agree. Each page
+//! is compared by hash first; only pages whose hashes differ are compared
+//! row by row and column by column.
+
+use crate::copy::TableRef;
+use crate::{
+    cancel, mongodb, CancelToken, ColumnFilter, ColumnInfo, DatabaseProvider, DatabaseType,
+    FilterOperator, PageRequest, ProviderError, QueryResult, SortColumn, TableQuery,
+    DEFAULT_STREAM_BATCH_SIZE,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DataDiffOptions {
+    /// Columns that identify a row; the source's primary key when empty
+    #[serde(default)]
+    pub key: Vec<String>,
+    /// Columns to compare; all columns both tables have when empty
+    #[serde(default)]
+    pub columns: Vec<String>,
+    /// Rows per page read from either table
+    #[serde(default)]
+    pub batch_size: Option<usize>,
+}
+
+/// How a row of the target differs from the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowChange {
+    /// Only in the target
+    Inserted,
+    /// Only in the source
+    Deleted,
+    Changed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnDiff {
+    pub column: String,
+    pub source: Value,
+    pub target: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RowDiff {
+    pub change: RowChange,
+    /// Key values, in the order of `DataDiffSummary::key`
+    pub key: Vec<Value>,
+    /// The differing columns of a changed row; empty otherwise
+    pub columns: Vec<ColumnDiff>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DataDiffSummary {
+    pub key: Vec<String>,
+    /// Columns that were compared
+    pub columns: Vec<String>,
+    pub inserted: u64,
+    pub deleted: u64,
+    pub changed: u64,
+    pub unchanged: u64,
+}
+
+/// Results of a running diff. Differences are sent a page at a time; rows
+/// counts the rows read from both tables so far.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DataDiffEvent {
+    Rows(Vec<RowDiff>),
+    Progress { rows: u64 },
+    Done(DataDiffSummary),
+    Error(String),
+}
+
+/// One side of the comparison
+struct Side<'a> {
+    provider: &'a dyn DatabaseProvider,
+    table: &'a TableRef,
+    /// Page by primary key rather than with OFFSET
+    keyset: bool,
+}
+
+/// Compare `from` on `source` with `to` on `target`, sending the rows that
+/// differ through `events`. The source is read first to find deleted and
+/// changed rows, then the target for inserted ones.
+pub async fn diff_data(
+    source: &dyn DatabaseProvider,
+    from: &TableRef,
+    target: &dyn DatabaseProvider,
+    to: &TableRef,
+    options: &DataDiffOptions,
+    cancel: &CancelToken,
+    events: &mpsc::Sender<DataDiffEvent>,
+) -> Result<DataDiffSummary, ProviderError> {
+    for provider in [source, target] {
+        if provider.database_type() == DatabaseType::Redis {
+            return Err(ProviderError::new("Comparing Redis data is not supported"));
+        }
+    }
+
+    let source_columns = source.get_table_columns(&from.schema, &from.table).await?;
+    let target_columns = target.get_table_columns(&to.schema, &to.table).await?;
+    for (columns, table) in [(&source_columns, from), (&target_columns, to)] {
+        if columns.is_empty() {
+            return Err(ProviderError::new(format!(
+                "Table {}.{} does not exist",
+                table.schema, table.table
+            )));
+        }
+    }
+
+    let key = if options.key.is_empty() {
+        source_columns
+            .iter()
+            .filter(|c| c.is_primary_key)
+            .map(|c| c.name.clone())
+            .collect()
+    } else {
+        options.key.clone()
+    };
+    if key.is_empty() {
+        return Err(ProviderError::new(format!(
+            "Table {}.{} has no primary key",
+            from.schema, from.table
+        ))
+        .with_hint("Choose the columns that identify a row to match rows by."));
+    }
+    let columns: Vec<String> = if options.columns.is_empty() {
+        source_columns
+            .iter()
+            .map(|c| c.name.clone())
+            .filter(|name| target_columns.iter().any(|c| &c.name == name))
+            .filter(|name| !key.contains(name))
+            .collect()
+    } else {
+        options.columns.clone()
+    };
+    for (table_columns, table) in [(&source_columns, from), (&target_columns, to)] {
+        for column in key.iter().chain(&columns) {
+            if !table_columns.iter().any(|c| &c.name == column) {
+                return Err(ProviderError::new(format!(
+                    "Table {}.{} has no column {}",
+                    table.schema, table.table, column
+                )));
+            }
+        }
+    }
+
+    // Keyset pages follow the primary key, so other keys are read with OFFSET
+    let keyset = |provider: &dyn DatabaseProvider, table_columns: &[ColumnInfo]| {
+        let primary_key: Vec<&String> = table_columns
+            .iter()
+            .filter(|c| c.is_primary_key)
+            .map(|c| &c.name)
+            .collect();
+        match provider.database_type() {
+            DatabaseType::Mongodb => key == ["_id"],
+            _ => primary_key.len() == key.len() && primary_key.iter().all(|c| key.contains(c)),
+        }
+    };
+    let source = Side {
+        provider: source,
+        table: from,
+        keyset: keyset(source, &source_columns),
+    };
+    let target = Side {
+        provider: target,
+        table: to,
+        keyset: keyset(target, &target_columns),
+    };
+
+    let mut diff = Diff {
+        key,
+        columns,
+        plain_values: (source.provider.database_type() == DatabaseType::Mongodb)
+            != (target.provider.database_type() == DatabaseType::Mongodb),
+        batch_size: options
+            .batch_size
+            .unwrap_or(DEFAULT_STREAM_BATCH_SIZE)
+            .max(1),
+        cancel,
+        events,
+        summary: DataDiffSummary::default(),
+        rows_read: 0,
+    };
+    diff.scan(&source, &target, false).await?;
+    diff.scan(&target, &source, true).await?;
+
+    let Diff {
+        key,
+        columns,
+        mut summary,
+        ..
+    } = diff;
+    summary.key = key;
+    summary.columns = columns;
+    Ok(summary)
+}
+
+struct Diff<'a> {
+    key: Vec<String>,
+    columns: Vec<String>,
+    /// Unwrap MongoDB's Extended JSON values to compare them with SQL ones
+    plain_values: bool,
+    batch_size: usize,
+    cancel: &'a CancelToken,
+    events: &'a mpsc::Sender<DataDiffEvent>,
+    summary: DataDiffSummary,
+    rows_read: u64,
+}
+
+impl Diff<'_> {
+    /// Read `side` page by page and look each page up in `other`. Reading
+    /// the target (`reverse`) only reports rows missing from the source;
+    /// the rows both have were compared while reading the source.
+    async fn scan(
+        &mut self,
+        side: &Side<'_>,
+        other: &Side<'_>,
+        reverse: bool,
+    ) -> Result<(), ProviderError> {
+        let mut request = PageRequest {
+            filters: Vec::new(),
+            key: None,
+            direction: Default::default(),
+            cursor: None,
+            limit: self.batch_size as i64,
+        };
+        let mut offset = 0;
+
+        loop {
+            if self.cancel.is_cancelled() {
+                return Err(cancel::cancelled_error());
+            }
+            let (result, more) = if side.keyset {
+                let page = side
+                    .provider
+                    .get_table_page(&side.table.schema, &side.table.table, &request)
+                    .await?;
+                request.cursor = page.next_cursor;
+                (page.result, request.cursor.is_some())
+            } else {
+                let result = self.read_sorted(side, Vec::new(), offset).await?;
+                offset += result.rows.len() as i64;
+                let more = result.rows.len() == self.batch_size;
+                (result, more)
+            };
+            let result = self.plain(result);
+            self.rows_read += result.rows.len() as u64;
+
+            let rows = Rows::new(&result, &self.key, &self.columns);
+            let matches = self.lookup(other, &rows).await?;
+            let mut diffs = Vec::new();
+            // A page whose rows all match with the same hash is unchanged
+            let same_page = !reverse
+                && matches.len() == result.rows.len()
+                && rows.chunk_hash()
+                    == chunk_hash(matches.iter().map(|(key, row)| (key, row.digest)));
+            if same_page {
+                self.summary.unchanged += result.rows.len() as u64;
+            }
+            for row in result.rows.iter().filter(|_| !same_page) {
+                let key = rows.key(row);
+                let matched = matches.get(&key_string(&key));
+                match (matched, reverse) {
+                    (None, false) => {
+                        self.summary.deleted += 1;
+                        diffs.push(RowDiff {
+                            change: RowChange::Deleted,
+                            key,
+                            columns: Vec::new(),
+                        });
+                    }
+                    (None, true) => {
+                        self.summary.inserted += 1;
+                        diffs.push(RowDiff {
+                            change: RowChange::Inserted,
+                            key,
+                            columns: Vec::new(),
+                        });
+                    }
+                    (Some(other_row), false) => {
+                        let columns = if rows.digest(row) == other_row.digest {
+                            Vec::new()
+                        } else {
+                            rows.compare(row, &other_row.values)
+                        };
+                        if columns.is_empty() {
+                            self.summary.unchanged += 1;
+                        } else {
+                            self.summary.changed += 1;
+                            diffs.push(RowDiff {
+                                change: RowChange::Changed,
+                                key,
+                                columns,
+                            });
+                        }
+                    }
+                    (Some(_), true) => {}
+                }
+            }
+
+            if !diffs.is_empty() {
+                let _ = self.events.send(DataDiffEvent::Rows(diffs)).await;
+            }
+            let _ = self
+                .events
+                .send(DataDiffEvent::Progress {
+                    rows: self.rows_read,
+                })
+                .await;
+            if !more {
+                return Ok(());
+            }
+        }
+    }
+
+    /// `result` with Extended JSON unwrapped if `plain_values` is set
+    fn plain(&self, mut result: QueryResult) -> QueryResult {
+        if self.plain_values {
+            for row in &mut result.rows {
+                for value in row.iter_mut() {
+                    *value = mongodb::plain_value(std::mem::take(value));
+                }
+            }
+        }
+        result
+    }
+
+    /// A page of `side` ordered by the key columns
+    async fn read_sorted(
+        &self,
+        side: &Side<'_>,
+        filters: Vec<ColumnFilter>,
+        offset: i64,
+    ) -> Result<QueryResult, ProviderError> {
+        let query = TableQuery {
+            filters,
+            sort: self
+                .key
+                .iter()
+                .map(|column| SortColumn {
+                    column: column.clone(),
+                    direction: Default::default(),
+                    nulls: None,
+                })
+                .collect(),
+        };
+        side.provider
+            .get_table_data_filtered(
+                &side.table.schema,
+                &side.table.table,
+                &query,
+                self.batch_size as i64,
+                offset,
+            )
+            .await
+    }
+
+    /// The rows of `side` with the keys of `rows`, by key. Every key column
+    /// is filtered to the page's values for it; a composite key can still
+    /// read combinations the page does not have, which are dropped.
+    async fn lookup(
+        &self,
+        side: &Side<'_>,
+        rows: &Rows<'_>,
+    ) -> Result<HashMap<String, OtherRow>, ProviderError> {
+        let mut matches = HashMap::new();
+        if rows.result.rows.is_empty() {
+            return Ok(matches);
+        }
+        let wanted: HashSet<String> = rows
+            .result
+            .rows
+            .iter()
+            .map(|row| key_string(&rows.key(row)))
+            .collect();
+        let filters = self
+            .key
+            .iter()
+            .zip(&rows.key_indexes)
+            .map(|(column, &index)| {
+                let mut values: Vec<Value> = Vec::new();
+                for row in &rows.result.rows {
+                    let value = Rows::get(row, index);
+                    if !values.contains(&value) {
+                        values.push(value);
+                    }
+                }
+                ColumnFilter {
+                    column: column.clone(),
+                    operator: FilterOperator::In,
+                    value: Value::Array(values),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut offset = 0;
+        loop {
+            let result = self.read_sorted(side, filters.clone(), offset).await?;
+            let result = self.plain(result);
+            offset += result.rows.len() as i64;
+            let found = Rows::new(&result, &self.key, &self.columns);
+            for row in &result.rows {
+                let key = key_string(&found.key(row));
+                if wanted.contains(&key) {
+                    let row = OtherRow {
+                        digest: found.digest(row),
+                        values: found.values(row),
+                    };
+                    matches.insert(key, row);
+                }
+            }
+            if result.rows.len() < self.batch_size {
+                return Ok(matches);
+            }
+        }
+    }
+}
+
+/// A looked up row: the hash of its compared columns and their values, in
+/// `Diff::columns` order
+struct OtherRow {
+    digest: u64,
+    values: Vec<Value>,
+}
+
+/// Column positions of the key and compared columns in a result
+struct Rows<'a> {
+    result: &'a QueryResult,
+    columns: &'a [String],
+    key_indexes: Vec<usize>,
+    column_indexes: Vec<usize>,
+}
+
+impl<'a> Rows<'a> {
+    fn new(result: &'a QueryResult, key: &[String], columns: &'a [String]) -> Self {
+        let index = |name: &String| {
+            result
+                .columns
+                .iter()
+                .position(|c| c == name)
+                .unwrap_or(usize::MAX)
+        };
+        Self {
+            result,
+            columns,
+            key_indexes: key.iter().map(index).collect(),
+            column_indexes: columns.iter().map(index).collect(),
+        }
+    }
+
+    fn get(row: &[Value], index: usize) -> Value {
+        // Documents may lack a field altogether
+        row.get(index).cloned().unwrap_or(Value::Null)
+    }
+
+    fn key(&self, row: &[Value]) -> Vec<Value> {
+        self.key_indexes
+            .iter()
+            .map(|&i| Self::get(row, i))
+            .collect()
+    }
+
+    fn values(&self, row: &[Value]) -> Vec<Value> {
+        self.column_indexes
+            .iter()
+            .map(|&i| Self::get(row, i))
+            .collect()
+    }
+
+    /// Hash of the compared columns' canonical values
+    fn digest(&self, row: &[Value]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for value in self.values(row) {
+            canonical(&value).to_string().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    fn chunk_hash(&self) -> u64 {
+        chunk_hash(
+            self.result
+                .rows
+                .iter()
+                .map(|row| (key_string(&self.key(row)), self.digest(row))),
+        )
+    }
+
+    fn compare(&self, row: &[Value], other: &[Value]) -> Vec<ColumnDiff> {
+        self.columns
+            .iter()
+            .zip(self.values(row))
+            .zip(other)
+            .filter(|((_, value), other)| !values_equal(value, other))
+            .map(|((column, value), other)| ColumnDiff {
+                column: column.clone(),
+                source: value,
+                target: other.clone(),
+            })
+            .collect()
+    }
+}
+
+/// `value` in the form rows are hashed in: integral numbers as exact
+/// integers and booleans as 0 or 1. Values with the same canonical form
+/// always satisfy `values_equal`.
+fn canonical(value: &Value) -> Value {
+    match value {
+        Value::Bool(b) => Value::from(u8::from(*b)),
+        Value::Number(n) if n.is_f64() => match n.as_f64() {
+            // Beyond 2^53 a float no longer tells neighbouring integers apart
+            Some(f) if f.fract() == 0.0 && f.abs() < 9e15 => Value::from(f as i64),
+            _ => value.clone(),
+        },
+        _ => value.clone(),
+    }
+}
+
+/// Map key for a row's key values. Numbers are normalized so that `1` and
+/// `1.0` from different drivers match.
+fn key_string(key: &[Value]) -> String {
+    let normalized: Vec<Value> = key.iter().map(canonical).collect();
+    serde_json::to_string(&normalized).unwrap_or_default()
+}
+
+/// Hash of a page's rows, independent of their order
+fn chunk_hash<K: AsRef<str>>(rows: impl Iterator<Item = (K, u64)>) -> u64 {
+    rows.fold(0, |sum: u64, (key, digest)| {
+        let mut hasher = DefaultHasher::new();
+        key.as_ref().hash(&mut hasher);
+        digest.hash(&mut hasher);
+        sum.wrapping_add(hasher.finish())
+    })
+}
+
+/// Integers compare exactly; with a float on either side both compare as
+/// floats.
+fn numbers_equal(x: &Number, y: &Number) -> bool {
+    let integer = |n: &Number| {
+        n.as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+    };
+    match (integer(x), integer(y)) {
+        (Some(x), Some(y)) => x == y,
+        _ => x.as_f64() == y.as_f64(),
+    }
+}
+
+/// Whether two values are the same once the representation differences
+/// between drivers are taken out: numbers or booleans as text, booleans as
+/// 0/1, and JSON stored as text.
+fn values_equal(a: &Value, b: &Value) -> bool {
+    if a == b {
+        return true;
+    }
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => numbers_equal(x, y),
+        (Value::Bool(x), Value::Number(n)) | (Value::Number(n), Value::Bool(x)) => {
+            numbers_equal(n, &Number::from(u8::from(*x)))
+        }
+        (Value::String(s), Value::Number(n)) | (Value::Number(n), Value::String(s)) => s
+            .trim()
+            .parse::<Number>()
+            .is_ok_and(|parsed| numbers_equal(&parsed, n)),
+        (Value::String(s), Value::Bool(x)) | (Value::Bool(x), Value::String(s)) => {
+            matches!(
+                (s.to_ascii_lowercase().as_str(), x),
+                ("true" | "t" | "1", true) | ("false" | "f" | "0", false)
+            )
+        }
+        (Value::String(s), other @ (Value::Array(_) | Value::Object(_)))
+        | (other @ (Value::Array(_) | Value::Object(_)), Value::String(s)) => {
+            serde_json::from_str::<Value>(s).is_ok_and(|parsed| &parsed == other)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn values_equal_across_drivers() {
+        assert!(values_equal(&json!(1), &json!(1.0)));
+        assert!(values_equal(&json!("12.50"), &json!(12.5)));
+        assert!(values_equal(&json!(true), &json!(1)));
+        assert!(values_equal(&json!("f"), &json!(false)));
+        assert!(values_equal(
+            &json!({"a": [1, 2]}),
+            &json!("{\"a\": [1, 2]}")
+        ));
+        assert!(!values_equal(&json!("a"), &json!("b")));
+        assert!(!values_equal(&json!(null), &json!(0)));
+        assert_eq!(
+            key_string(&[json!(1.0), json!("x")]),
+            key_string(&[json!(1), json!("x")])
+        );
+    }
+
+    #[test]
+    fn large_integers_compare_exactly() {
+        let big = 1_u64 << 60;
+        assert!(!values_equal(&json!(big), &json!(big + 1)));
+        assert!(!values_equal(&json!(big.to_string()), &json!(big + 1)));
+        assert!(values_equal(&json!(big.to_string()), &json!(big)));
+        assert!(values_equal(&json!(-3), &json!(-3.0)));
+    }
+
+    #[test]
+    fn equal_canonical_rows_hash_the_same() {
+        let result = QueryResult {
+            columns: vec!["id".to_string(), "active".to_string()],
+            rows: vec![vec![json!(1), json!(true)], vec![json!(2), json!(false)]],
+            ..Default::default()
+        };
+        let other = QueryResult {
+            rows: vec![vec![json!(2.0), json!(0)], vec![json!(1.0), json!(1)]],
+            ..result.clone()
+        };
+        let key = ["id".to_string()];
+        let columns = ["active".to_string()];
+        assert_eq!(
+            Rows::new(&result, &key, &columns).chunk_hash(),
+            Rows::new(&other, &key, &columns).chunk_hash()
+        );
+    }
+}
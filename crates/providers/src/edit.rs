@@ -0,0 +1,324 @@
+//! Row edits from the data grid, turned into parameterized statements that
+//! identify rows by primary key.
+
+use crate::{sql, ColumnInfo, DatabaseType, ProviderError};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// One change to a table's rows. `key` holds the row's primary key values
+/// (or, for tables without one, the values of every column to match on).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum RowEdit {
+    /// Columns left out take their default
+    Insert {
+        values: Map<String, Value>,
+    },
+    Update {
+        key: Map<String, Value>,
+        values: Map<String, Value>,
+    },
+    Delete {
+        key: Map<String, Value>,
+    },
+}
+
+/// A statement for one edit with its driver-bound parameters
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EditStatement {
+    pub sql: String,
+    pub params: Vec<Value>,
+    /// Updates and deletes must hit exactly one row
+    pub single_row: bool,
+}
+
+impl EditStatement {
+    /// Reject updates and deletes that matched no row or several, so the
+    /// batch is rolled back instead of silently doing the wrong thing.
+    pub fn check_rows_affected(
+        &self,
+        index: usize,
+        rows_affected: u64,
+    ) -> Result<(), ProviderError> {
+        if !self.single_row || rows_affected == 1 {
+            return Ok(());
+        }
+        let err = if rows_affected == 0 {
+            ProviderError::new(format!("Row edit {} matched no row", index + 1))
+                .with_hint("The row may have been changed or deleted since it was loaded.")
+        } else {
+            ProviderError::new(format!(
+                "Row edit {} matched {} rows",
+                index + 1,
+                rows_affected
+            ))
+            .with_hint(
+                "Rows without a primary key can only be edited when their values are unique.",
+            )
+        };
+        Err(err)
+    }
+}
+
+/// Prefix a database error with the edit that caused it
+pub(crate) fn edit_failed(index: usize, mut err: ProviderError) -> ProviderError {
+    err.message = format!("Row edit {} failed: {}", index + 1, err.message);
+    err
+}
+
+/// Build one statement per edit against `schema.table`, whose columns are
+/// given by `get_table_columns`.
+pub(crate) fn build_statements(
+    db_type: DatabaseType,
+    schema: &str,
+    table: &str,
+    columns: &[ColumnInfo],
+    edits: &[RowEdit],
+    allow_without_primary_key: bool,
+) -> Result<Vec<EditStatement>, ProviderError> {
+    if columns.is_empty() {
+        return Err(ProviderError::new(format!(
+            "Table {}.{} does not exist",
+            schema, table
+        )));
+    }
+    let primary_key: Vec<&str> = columns
+        .iter()
+        .filter(|column| column.is_primary_key)
+        .map(|column| column.name.as_str())
+        .collect();
+    let needs_key = edits
+        .iter()
+        .any(|edit| !matches!(edit, RowEdit::Insert { .. }));
+    if primary_key.is_empty() && needs_key && !allow_without_primary_key {
+        return Err(
+            ProviderError::new(format!("Table {}.{} has no primary key", schema, table)).with_hint(
+                "Allow editing without a primary key to match rows on all of their values.",
+            ),
+        );
+    }
+
+    let builder = StatementBuilder {
+        db_type,
+        target: sql::qualified_name(schema, table, db_type),
+        columns,
+        primary_key,
+    };
+    edits
+        .iter()
+        .enumerate()
+        .map(|(index, edit)| builder.build(edit).map_err(|e| edit_failed(index, e)))
+        .collect()
+}
+
+struct StatementBuilder<'a> {
+    db_type: DatabaseType,
+    target: String,
+    columns: &'a [ColumnInfo],
+    primary_key: Vec<&'a str>,
+}
+
+impl StatementBuilder<'_> {
+    fn build(&self, edit: &RowEdit) -> Result<EditStatement, ProviderError> {
+        let mut params = Vec::new();
+        let sql = match edit {
+            RowEdit::Insert { values } => self.insert(values, &mut params)?,
+            RowEdit::Update { key, values } => {
+                if values.is_empty() {
+                    return Err(ProviderError::new("No columns to update"));
+                }
+                let assignments = values
+                    .iter()
+                    .map(|(column, value)| {
+                        let column = self.column(column)?;
+                        Ok(format!("{} = {}", column, self.bind(value, &mut params)))
+                    })
+                    .collect::<Result<Vec<_>, ProviderError>>()?;
+                let condition = self.key_condition(key, &mut params)?;
+                format!(
+                    "UPDATE {} SET {} WHERE {}",
+                    self.target,
+                    assignments.join(", "),
+                    condition
+                )
+            }
+            RowEdit::Delete { key } => {
+                let condition = self.key_condition(key, &mut params)?;
+                format!("DELETE FROM {} WHERE {}", self.target, condition)
+            }
+        };
+
+        Ok(EditStatement {
+            sql,
+            params,
+            single_row: !matches!(edit, RowEdit::Insert { .. }),
+        })
+    }
+
+    fn insert(
+        &self,
+        values: &Map<String, Value>,
+        params: &mut Vec<Value>,
+    ) -> Result<String, ProviderError> {
+        if values.is_empty() {
+            return Ok(match self.db_type {
+                DatabaseType::Mysql => format!("INSERT INTO {} () VALUES ()", self.target),
+                _ => format!("INSERT INTO {} DEFAULT VALUES", self.target),
+            });
+        }
+        let names = values
+            .keys()
+            .map(|column| self.column(column))
+            .collect::<Result<Vec<_>, ProviderError>>()?;
+        let placeholders: Vec<String> = values
+            .values()
+            .map(|value| self.bind(value, params))
+            .collect();
+        Ok(format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            self.target,
+            names.join(", "),
+            placeholders.join(", ")
+        ))
+    }
+
+    /// `WHERE` clause matching the primary key, or every given column when
+    /// the table has none
+    fn key_condition(
+        &self,
+        key: &Map<String, Value>,
+        params: &mut Vec<Value>,
+    ) -> Result<String, ProviderError> {
+        let key_columns: Vec<&str> = if self.primary_key.is_empty() {
+            key.keys().map(String::as_str).collect()
+        } else {
+            self.primary_key.clone()
+        };
+        if key_columns.is_empty() {
+            return Err(ProviderError::new("Row key is empty"));
+        }
+
+        key_columns
+            .iter()
+            .map(|column| {
+                let value = key.get(*column).ok_or_else(|| {
+                    ProviderError::new(format!("Missing primary key column {}", column))
+                })?;
+                let quoted = self.column(column)?;
+                Ok(if value.is_null() {
+                    format!("{} IS NULL", quoted)
+                } else {
+                    format!("{} = {}", quoted, self.bind(value, params))
+                })
+            })
+            .collect::<Result<Vec<_>, ProviderError>>()
+            .map(|conditions| conditions.join(" AND "))
+    }
+
+    fn column(&self, name: &str) -> Result<String, ProviderError> {
+        if self.columns.iter().any(|column| column.name == name) {
+            Ok(sql::quote_identifier(name, self.db_type))
+        } else {
+            Err(ProviderError::new(format!("Unknown column {}", name)))
+        }
+    }
+
+    fn bind(&self, value: &Value, params: &mut Vec<Value>) -> String {
+        params.push(value.clone());
+        sql::placeholder(self.db_type, params.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn column(name: &str, is_primary_key: bool) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            data_type: "integer".to_string(),
+            full_type: None,
+            nested_paths: Vec::new(),
+            is_nullable: !is_primary_key,
+            is_primary_key,
+            has_default: false,
+            default_value: None,
+        }
+    }
+
+    fn edit(value: Value) -> RowEdit {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn edits_bind_values_and_match_on_primary_key() {
+        let columns = [column("id", true), column("name", false)];
+        let edits = [
+            edit(json!({"op": "insert", "values": {"id": 3, "name": "c"}})),
+            edit(json!({"op": "update", "key": {"id": 1, "name": "old"}, "values": {"name": "a"}})),
+            edit(json!({"op": "delete", "key": {"id": 2}})),
+        ];
+
+        let statements = build_statements(
+            DatabaseType::Postgres,
+            "public",
+            "t",
+            &columns,
+            &edits,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            statements[0].sql,
+            r#"INSERT INTO "public"."t" ("id", "name") VALUES ($1, $2)"#
+        );
+        assert_eq!(
+            statements[1].sql,
+            r#"UPDATE "public"."t" SET "name" = $1 WHERE "id" = $2"#
+        );
+        assert_eq!(statements[1].params, vec![json!("a"), json!(1)]);
+        assert!(statements[1].single_row);
+
+        let statements =
+            build_statements(DatabaseType::Mysql, "db", "t", &columns, &edits[2..], false).unwrap();
+        assert_eq!(statements[0].sql, "DELETE FROM `db`.`t` WHERE `id` = ?");
+    }
+
+    #[test]
+    fn tables_without_primary_key_need_opt_in() {
+        let columns = [column("a", false), column("b", false)];
+        let edits = [edit(json!({"op": "delete", "key": {"a": 1, "b": null}}))];
+
+        assert!(
+            build_statements(DatabaseType::Sqlite, "main", "t", &columns, &edits, false).is_err()
+        );
+        let statements =
+            build_statements(DatabaseType::Sqlite, "main", "t", &columns, &edits, true).unwrap();
+        assert_eq!(
+            statements[0].sql,
+            r#"DELETE FROM "main"."t" WHERE "a" = ? AND "b" IS NULL"#
+        );
+        assert_eq!(statements[0].params, vec![json!(1)]);
+    }
+
+    #[test]
+    fn unknown_columns_and_partial_keys_are_rejected() {
+        let columns = [column("id", true), column("name", false)];
+        let unknown = [edit(json!({"op": "insert", "values": {"nope": 1}}))];
+        let partial = [edit(json!({"op": "delete", "key": {"name": "a"}}))];
+
+        for edits in [&unknown[..], &partial[..]] {
+            let err = build_statements(
+                DatabaseType::Postgres,
+                "public",
+                "t",
+                &columns,
+                edits,
+                false,
+            )
+            .unwrap_err();
+            assert!(err.message.starts_with("Row edit 1 failed"));
+        }
+    }
+}
@@ -0,0 +1,568 @@
+//! Query plans normalized into one tree shape across databases, so the plan
+//! viewer does not need to understand each engine's EXPLAIN output.
+
+use crate::ProviderError;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryPlan {
+    pub root: PlanNode,
+    pub planning_time_ms: Option<f64>,
+    /// Only known when the statement was actually run (`analyze`)
+    pub execution_time_ms: Option<f64>,
+    /// The plan exactly as the database returned it
+    pub raw: Value,
+}
+
+/// One operator of the plan. Row counts and times are per execution of the
+/// node, as the databases report them; multiply by `loops` for totals.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PlanNode {
+    /// Operator name, e.g. `Seq Scan`, `Table scan on t` or `IXSCAN`
+    pub node_type: String,
+    /// Table or collection the node reads
+    pub relation: Option<String>,
+    pub index: Option<String>,
+    /// Conditions, sort keys and other operator details as text
+    pub detail: Option<String>,
+    pub estimated_rows: Option<f64>,
+    pub actual_rows: Option<f64>,
+    /// Rows, index keys or documents read to produce the output
+    pub rows_examined: Option<f64>,
+    /// Planner cost in the database's own units
+    pub estimated_cost: Option<f64>,
+    pub actual_time_ms: Option<f64>,
+    pub loops: Option<f64>,
+    pub buffers: Option<PlanBuffers>,
+    pub children: Vec<PlanNode>,
+}
+
+/// Block counts from PostgreSQL's `BUFFERS` option
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PlanBuffers {
+    pub shared_hit: u64,
+    pub shared_read: u64,
+    pub shared_dirtied: u64,
+    pub shared_written: u64,
+    pub temp_read: u64,
+    pub temp_written: u64,
+}
+
+fn unexpected_format(db: &str) -> ProviderError {
+    ProviderError::new(format!("Unexpected {} EXPLAIN output", db))
+}
+
+/// Numbers arrive as JSON numbers or, in MySQL's cost_info, as strings
+fn number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn text(value: Option<&Value>) -> Option<String> {
+    value.and_then(Value::as_str).map(str::to_string)
+}
+
+/// `EXPLAIN (FORMAT JSON)` output: a one-element array holding `Plan`
+pub(crate) fn from_postgres(raw: Value) -> Result<QueryPlan, ProviderError> {
+    let top = raw.get(0).ok_or_else(|| unexpected_format("PostgreSQL"))?;
+    let plan = top
+        .get("Plan")
+        .ok_or_else(|| unexpected_format("PostgreSQL"))?;
+
+    Ok(QueryPlan {
+        root: postgres_node(plan),
+        planning_time_ms: number(top.get("Planning Time")),
+        execution_time_ms: number(top.get("Execution Time")),
+        raw,
+    })
+}
+
+const POSTGRES_DETAIL_KEYS: &[&str] = &[
+    "Join Type",
+    "Index Cond",
+    "Recheck Cond",
+    "Hash Cond",
+    "Merge Cond",
+    "Join Filter",
+    "Filter",
+    "Sort Key",
+    "Group Key",
+];
+
+fn postgres_node(plan: &Value) -> PlanNode {
+    let detail: Vec<String> = POSTGRES_DETAIL_KEYS
+        .iter()
+        .filter_map(|key| {
+            let value = match plan.get(*key)? {
+                Value::String(s) => s.clone(),
+                Value::Array(items) => items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                _ => return None,
+            };
+            Some(format!("{}: {}", key, value))
+        })
+        .collect();
+
+    let block = |key: &str| plan.get(key).and_then(Value::as_u64);
+    let buffers = block("Shared Hit Blocks").map(|shared_hit| PlanBuffers {
+        shared_hit,
+        shared_read: block("Shared Read Blocks").unwrap_or(0),
+        shared_dirtied: block("Shared Dirtied Blocks").unwrap_or(0),
+        shared_written: block("Shared Written Blocks").unwrap_or(0),
+        temp_read: block("Temp Read Blocks").unwrap_or(0),
+        temp_written: block("Temp Written Blocks").unwrap_or(0),
+    });
+
+    PlanNode {
+        node_type: text(plan.get("Node Type")).unwrap_or_default(),
+        relation: text(plan.get("Relation Name")),
+        index: text(plan.get("Index Name")),
+        detail: (!detail.is_empty()).then(|| detail.join("; ")),
+        estimated_rows: number(plan.get("Plan Rows")),
+        actual_rows: number(plan.get("Actual Rows")),
+        rows_examined: None,
+        estimated_cost: number(plan.get("Total Cost")),
+        actual_time_ms: number(plan.get("Actual Total Time")),
+        loops: number(plan.get("Actual Loops")),
+        buffers,
+        children: plan
+            .get("Plans")
+            .and_then(Value::as_array)
+            .map(|plans| plans.iter().map(postgres_node).collect())
+            .unwrap_or_default(),
+    }
+}
+
+/// `EXPLAIN FORMAT=JSON` output, or MariaDB's `ANALYZE FORMAT=JSON` which adds
+/// `r_*` fields with actual figures
+pub(crate) fn from_mysql_json(raw: Value) -> Result<QueryPlan, ProviderError> {
+    let block = raw
+        .get("query_block")
+        .ok_or_else(|| unexpected_format("MySQL"))?;
+
+    Ok(QueryPlan {
+        root: mysql_node("query_block", block),
+        planning_time_ms: None,
+        execution_time_ms: number(block.get("r_total_time_ms")),
+        raw,
+    })
+}
+
+fn mysql_node(name: &str, value: &Value) -> PlanNode {
+    let mut node = PlanNode {
+        node_type: name.to_string(),
+        ..Default::default()
+    };
+    let Some(object) = value.as_object() else {
+        return node;
+    };
+    let cost_info = object.get("cost_info");
+
+    if name == "table" {
+        let access_type = object.get("access_type").and_then(Value::as_str);
+        node.node_type = match access_type {
+            Some("ALL") => "Full table scan".to_string(),
+            Some("index") => "Full index scan".to_string(),
+            Some("range") => "Index range scan".to_string(),
+            Some(other) => format!("Index lookup ({})", other),
+            None => "Table".to_string(),
+        };
+        node.relation = text(object.get("table_name"));
+        node.index = text(object.get("key"));
+        node.detail = text(object.get("attached_condition"));
+        node.estimated_rows =
+            number(object.get("rows_produced_per_join")).or_else(|| number(object.get("rows")));
+        node.rows_examined = number(object.get("rows_examined_per_scan"));
+        node.estimated_cost = cost_info.and_then(|c| number(c.get("prefix_cost")));
+    } else {
+        node.estimated_cost = cost_info
+            .and_then(|c| number(c.get("query_cost")).or_else(|| number(c.get("sort_cost"))));
+    }
+    node.actual_rows = number(object.get("r_rows"));
+    node.actual_time_ms = number(object.get("r_total_time_ms"));
+    node.loops = number(object.get("r_loops"));
+
+    for (key, child) in object {
+        if key == "cost_info" {
+            continue;
+        }
+        match child {
+            Value::Object(_) => node.children.push(mysql_node(key, child)),
+            Value::Array(items) if items.iter().any(Value::is_object) => {
+                let mut group = PlanNode {
+                    node_type: key.clone(),
+                    ..Default::default()
+                };
+                for item in items.iter().filter(|item| item.is_object()) {
+                    // Arrays such as nested_loop wrap each entry as {"table": {...}}
+                    match item.as_object().filter(|o| o.len() == 1) {
+                        Some(wrapper) => {
+                            let (inner_key, inner) = wrapper.iter().next().unwrap();
+                            group.children.push(mysql_node(inner_key, inner));
+                        }
+                        None => group.children.push(mysql_node(key, item)),
+                    }
+                }
+                node.children.push(group);
+            }
+            _ => {}
+        }
+    }
+    node
+}
+
+/// MySQL's `EXPLAIN ANALYZE`, which only produces an indented text tree:
+///
+/// ```text
+/// -> Filter: (t.a > 1)  (cost=0.55 rows=2) (actual time=0.03..0.04 rows=2 loops=1)
+///     -> Table scan on t  (cost=0.55 rows=3) (actual time=0.02..0.03 rows=3 loops=1)
+/// ```
+pub(crate) fn from_mysql_tree(raw: &str) -> Result<QueryPlan, ProviderError> {
+    // Nodes waiting for their children, by depth
+    let mut stack: Vec<(usize, PlanNode)> = Vec::new();
+    let mut roots = Vec::new();
+
+    for line in raw.lines() {
+        let trimmed = line.trim_start();
+        let Some(content) = trimmed.strip_prefix("-> ") else {
+            continue;
+        };
+        let depth = line.len() - trimmed.len();
+        let node = mysql_tree_node(content);
+
+        while let Some((top_depth, _)) = stack.last() {
+            if *top_depth < depth {
+                break;
+            }
+            let (_, done) = stack.pop().unwrap();
+            match stack.last_mut() {
+                Some((_, parent)) => parent.children.push(done),
+                None => roots.push(done),
+            }
+        }
+        stack.push((depth, node));
+    }
+    while let Some((_, done)) = stack.pop() {
+        match stack.last_mut() {
+            Some((_, parent)) => parent.children.push(done),
+            None => roots.push(done),
+        }
+    }
+
+    let root = match roots.len() {
+        0 => return Err(unexpected_format("MySQL")),
+        1 => roots.pop().unwrap(),
+        _ => PlanNode {
+            node_type: "Query".to_string(),
+            children: roots,
+            ..Default::default()
+        },
+    };
+    Ok(QueryPlan {
+        execution_time_ms: root.actual_time_ms,
+        root,
+        planning_time_ms: None,
+        raw: Value::String(raw.to_string()),
+    })
+}
+
+fn mysql_tree_node(content: &str) -> PlanNode {
+    let (name, stats) = match content.find("  (") {
+        Some(pos) => (&content[..pos], &content[pos..]),
+        None => (content, ""),
+    };
+    // The estimate group comes first, the measured group second
+    let (estimate, actual) = match stats.find("(actual ") {
+        Some(pos) => (&stats[..pos], &stats[pos..]),
+        None => (stats, ""),
+    };
+
+    let relation = name
+        .split_once(" on ")
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+        .map(str::to_string);
+    let index = name
+        .split_once(" using ")
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+        .map(str::to_string);
+
+    PlanNode {
+        node_type: name.to_string(),
+        relation,
+        index,
+        estimated_rows: tree_stat(estimate, "rows="),
+        actual_rows: tree_stat(actual, "rows="),
+        estimated_cost: tree_stat(estimate, "cost="),
+        // "time=first..last": the time to the last row covers the whole node
+        actual_time_ms: actual
+            .split_once("time=")
+            .and_then(|(_, rest)| rest.split_once(".."))
+            .and_then(|(_, last)| parse_leading_number(last)),
+        loops: tree_stat(actual, "loops="),
+        ..Default::default()
+    }
+}
+
+fn tree_stat(group: &str, key: &str) -> Option<f64> {
+    group
+        .split_once(key)
+        .and_then(|(_, rest)| parse_leading_number(rest))
+}
+
+fn parse_leading_number(s: &str) -> Option<f64> {
+    let end = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == 'e' || c == '+' || c == '-'))
+        .unwrap_or(s.len());
+    s[..end].trim_end_matches('.').parse().ok()
+}
+
+/// `EXPLAIN QUERY PLAN` rows as `(id, parent, detail)`. SQLite reports no
+/// costs or row counts, only the operator text.
+pub(crate) fn from_sqlite(rows: Vec<(i64, i64, String)>) -> QueryPlan {
+    let raw = Value::Array(
+        rows.iter()
+            .map(|(id, parent, detail)| {
+                serde_json::json!({ "id": id, "parent": parent, "detail": detail })
+            })
+            .collect(),
+    );
+
+    fn build(rows: &[(i64, i64, String)], parent: i64) -> Vec<PlanNode> {
+        rows.iter()
+            .filter(|(id, row_parent, _)| *row_parent == parent && *id != parent)
+            .map(|(id, _, detail)| {
+                let mut node = sqlite_node(detail);
+                node.children = build(rows, *id);
+                node
+            })
+            .collect()
+    }
+
+    let mut roots = build(&rows, 0);
+    let root = if roots.len() == 1 {
+        roots.pop().unwrap()
+    } else {
+        PlanNode {
+            node_type: "QUERY PLAN".to_string(),
+            children: roots,
+            ..Default::default()
+        }
+    };
+
+    QueryPlan {
+        root,
+        planning_time_ms: None,
+        execution_time_ms: None,
+        raw,
+    }
+}
+
+fn sqlite_node(detail: &str) -> PlanNode {
+    let mut words = detail.split_whitespace();
+    let node_type = words.next().unwrap_or_default().to_string();
+    let relation = match node_type.as_str() {
+        "SCAN" | "SEARCH" => words.next().map(str::to_string),
+        _ => None,
+    };
+    let index = ["USING COVERING INDEX ", "USING INDEX "]
+        .iter()
+        .find_map(|marker| detail.split_once(marker))
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+        .map(str::to_string);
+
+    PlanNode {
+        node_type,
+        relation,
+        index,
+        detail: Some(detail.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Output of the `explain` command, with `executionStats` when it was run
+pub(crate) fn from_mongodb(raw: Value) -> Result<QueryPlan, ProviderError> {
+    let root = match raw.get("stages").and_then(Value::as_array) {
+        // Aggregations list one entry per pipeline stage
+        Some(stages) => PlanNode {
+            node_type: "aggregate".to_string(),
+            children: stages.iter().map(mongodb_pipeline_stage).collect(),
+            ..Default::default()
+        },
+        None => mongodb_plan(&raw).ok_or_else(|| unexpected_format("MongoDB"))?,
+    };
+
+    Ok(QueryPlan {
+        root,
+        planning_time_ms: None,
+        execution_time_ms: number(raw.pointer("/executionStats/executionTimeMillis")),
+        raw,
+    })
+}
+
+/// The executed stages when available, otherwise the winning plan
+fn mongodb_plan(explain: &Value) -> Option<PlanNode> {
+    let stages = explain
+        .pointer("/executionStats/executionStages")
+        .or_else(|| {
+            let winning = explain.pointer("/queryPlanner/winningPlan")?;
+            // Plans from the slot-based engine nest the tree under queryPlan
+            Some(winning.get("queryPlan").unwrap_or(winning))
+        })?;
+
+    let mut root = mongodb_node(stages);
+    root.relation = text(explain.pointer("/queryPlanner/namespace"));
+    Some(root)
+}
+
+fn mongodb_pipeline_stage(stage: &Value) -> PlanNode {
+    let Some((name, body)) = stage.as_object().and_then(|o| o.iter().next()) else {
+        return PlanNode::default();
+    };
+    let mut node = match name.as_str() {
+        "$cursor" => mongodb_plan(body).unwrap_or_default(),
+        _ => PlanNode {
+            node_type: name.clone(),
+            detail: Some(body.to_string()),
+            ..Default::default()
+        },
+    };
+    if node.actual_rows.is_none() {
+        node.actual_rows = number(stage.get("nReturned"));
+    }
+    if node.actual_time_ms.is_none() {
+        node.actual_time_ms = number(stage.get("executionTimeMillisEstimate"));
+    }
+    node
+}
+
+fn mongodb_node(stage: &Value) -> PlanNode {
+    let mut children: Vec<PlanNode> = ["inputStage", "outerStage", "innerStage"]
+        .iter()
+        .filter_map(|key| stage.get(*key))
+        .map(mongodb_node)
+        .collect();
+    if let Some(inputs) = stage.get("inputStages").and_then(Value::as_array) {
+        children.extend(inputs.iter().map(mongodb_node));
+    }
+
+    PlanNode {
+        node_type: text(stage.get("stage")).unwrap_or_default(),
+        relation: None,
+        index: text(stage.get("indexName")),
+        detail: stage.get("filter").map(Value::to_string),
+        estimated_rows: None,
+        actual_rows: number(stage.get("nReturned")),
+        rows_examined: number(stage.get("docsExamined"))
+            .or_else(|| number(stage.get("keysExamined"))),
+        estimated_cost: None,
+        actual_time_ms: number(stage.get("executionTimeMillisEstimate")),
+        loops: None,
+        buffers: None,
+        children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn postgres_plan_is_normalized() {
+        let raw = json!([{
+            "Plan": {
+                "Node Type": "Hash Join", "Join Type": "Inner",
+                "Total Cost": 12.5, "Plan Rows": 10,
+                "Actual Total Time": 0.3, "Actual Rows": 4, "Actual Loops": 1,
+                "Hash Cond": "(c.parent_id = p.id)",
+                "Shared Hit Blocks": 3, "Shared Read Blocks": 1,
+                "Plans": [{
+                    "Node Type": "Seq Scan", "Relation Name": "child",
+                    "Total Cost": 1.0, "Plan Rows": 10, "Filter": "(x > 1)"
+                }]
+            },
+            "Planning Time": 0.1,
+            "Execution Time": 0.5
+        }]);
+
+        let plan = from_postgres(raw).unwrap();
+        assert_eq!(plan.execution_time_ms, Some(0.5));
+        assert_eq!(plan.root.node_type, "Hash Join");
+        assert_eq!(
+            plan.root.detail.as_deref(),
+            Some("Join Type: Inner; Hash Cond: (c.parent_id = p.id)")
+        );
+        assert_eq!(plan.root.buffers.as_ref().unwrap().shared_read, 1);
+        let scan = &plan.root.children[0];
+        assert_eq!(scan.relation.as_deref(), Some("child"));
+        assert_eq!(scan.estimated_rows, Some(10.0));
+        assert!(scan.buffers.is_none());
+    }
+
+    #[test]
+    fn mysql_json_tables_become_scan_nodes() {
+        let raw = json!({"query_block": {
+            "cost_info": {"query_cost": "2.40"},
+            "nested_loop": [
+                {"table": {"table_name": "p", "access_type": "ALL",
+                    "rows_examined_per_scan": 3, "rows_produced_per_join": 3,
+                    "cost_info": {"prefix_cost": "0.55"}}},
+                {"table": {"table_name": "c", "access_type": "ref", "key": "fk",
+                    "rows_produced_per_join": 6, "attached_condition": "(c.x > 1)"}}
+            ]
+        }});
+
+        let plan = from_mysql_json(raw).unwrap();
+        assert_eq!(plan.root.estimated_cost, Some(2.4));
+        let nested = &plan.root.children[0];
+        assert_eq!(nested.node_type, "nested_loop");
+        assert_eq!(nested.children[0].node_type, "Full table scan");
+        assert_eq!(nested.children[0].estimated_cost, Some(0.55));
+        assert_eq!(nested.children[1].index.as_deref(), Some("fk"));
+        assert_eq!(nested.children[1].detail.as_deref(), Some("(c.x > 1)"));
+    }
+
+    #[test]
+    fn mysql_tree_is_nested_by_indentation() {
+        let raw = "-> Nested loop inner join  (cost=1.2 rows=2) (actual time=0.05..0.09 rows=2 loops=1)\n    \
+                   -> Table scan on p  (cost=0.55 rows=3) (actual time=0.02..0.03 rows=3 loops=1)\n    \
+                   -> Index lookup on c using fk (parent_id=p.id)  (cost=0.3 rows=1) (actual time=0.01..0.01 rows=0.67 loops=3)\n";
+
+        let plan = from_mysql_tree(raw).unwrap();
+        assert_eq!(plan.root.node_type, "Nested loop inner join");
+        assert_eq!(plan.root.actual_time_ms, Some(0.09));
+        assert_eq!(plan.root.children.len(), 2);
+        let lookup = &plan.root.children[1];
+        assert_eq!(lookup.relation.as_deref(), Some("c"));
+        assert_eq!(lookup.index.as_deref(), Some("fk"));
+        assert_eq!(lookup.actual_rows, Some(0.67));
+        assert_eq!(lookup.loops, Some(3.0));
+    }
+
+    #[test]
+    fn sqlite_rows_are_linked_by_parent() {
+        let plan = from_sqlite(vec![
+            (2, 0, "SCAN p".to_string()),
+            (4, 0, "SEARCH c USING INDEX ce (parent_id=?)".to_string()),
+            (7, 0, "USE TEMP B-TREE FOR ORDER BY".to_string()),
+        ]);
+        assert_eq!(plan.root.node_type, "QUERY PLAN");
+        assert_eq!(plan.root.children.len(), 3);
+        assert_eq!(plan.root.children[1].relation.as_deref(), Some("c"));
+        assert_eq!(plan.root.children[1].index.as_deref(), Some("ce"));
+
+        let nested = from_sqlite(vec![
+            (1, 0, "CO-ROUTINE sub".to_string()),
+            (3, 1, "SCAN t".to_string()),
+        ]);
+        assert_eq!(nested.root.node_type, "CO-ROUTINE");
+        assert_eq!(nested.root.children[0].relation.as_deref(), Some("t"));
+    }
+}
@@ -0,0 +1,863 @@
+//! Export a table or query result to a file. Rows are written batch by batch
+//! as the provider streams them, so the full result is never held in memory.
+
+use crate::{
+    cancel, mongodb, sql, CancelToken, DatabaseProvider, DatabaseType, PageRequest, ProviderError,
+    QueryStreamEvent, DEFAULT_STREAM_BATCH_SIZE,
+};
+use arrow_array::builder::{BooleanBuilder, Float64Builder, Int64Builder, StringBuilder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// What to export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExportSource {
+    Table { schema: String, table: String },
+    Query { query: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv {
+        #[serde(default = "default_delimiter")]
+        delimiter: char,
+        #[serde(default)]
+        quoting: CsvQuoting,
+        #[serde(default = "default_true")]
+        header: bool,
+    },
+    /// One array of objects
+    Json,
+    /// One object per line
+    Ndjson,
+    SqlInsert {
+        /// Table the statements insert into; the source table by default
+        #[serde(default)]
+        table: Option<String>,
+        /// Dialect to write; the connection's by default
+        #[serde(default)]
+        dialect: Option<DatabaseType>,
+        #[serde(default = "default_rows_per_statement")]
+        rows_per_statement: usize,
+    },
+    Parquet,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_rows_per_statement() -> usize {
+    100
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvQuoting {
+    /// Only fields containing the delimiter, quotes or line breaks
+    #[default]
+    Necessary,
+    Always,
+    NonNumeric,
+    Never,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportOptions {
+    pub path: String,
+    #[serde(flatten)]
+    pub format: ExportFormat,
+    /// Rows per batch read from the database
+    #[serde(default)]
+    pub batch_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ExportSummary {
+    pub rows: u64,
+    /// Size of the written file
+    pub bytes: u64,
+}
+
+/// Progress of a running export, sent after every batch
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum ExportEvent {
+    Progress { rows: u64 },
+    Done(ExportSummary),
+    Error(String),
+}
+
+/// Write `source` to `options.path`, reporting progress through `progress`.
+/// A failed or cancelled export removes the partial file.
+pub async fn export(
+    provider: &dyn DatabaseProvider,
+    source: &ExportSource,
+    options: &ExportOptions,
+    cancel: &CancelToken,
+    progress: &mpsc::Sender<ExportEvent>,
+) -> Result<ExportSummary, ProviderError> {
+    // The file is written on a blocking thread fed one batch at a time
+    let (batches, receiver) = mpsc::channel(2);
+    let db_type = provider.database_type();
+    let (writer_source, format, path) =
+        (source.clone(), options.format.clone(), options.path.clone());
+    let writer = tokio::task::spawn_blocking(move || {
+        write_file(db_type, &writer_source, &format, &path, receiver)
+    });
+
+    let mut export = Export {
+        batches,
+        plain_values: db_type == DatabaseType::Mongodb
+            && !matches!(options.format, ExportFormat::Json | ExportFormat::Ndjson),
+        rows: 0,
+        progress,
+    };
+    let batch_size = options.batch_size.unwrap_or(DEFAULT_STREAM_BATCH_SIZE);
+    let read = export.run(provider, source, batch_size, cancel).await;
+    let rows = export.rows;
+    // Closing the channel lets the writer finish the file
+    drop(export);
+    let written = writer
+        .await
+        .map_err(|e| ProviderError::new(format!("Export task failed: {}", e)))
+        .and_then(|result| result);
+
+    // A writer error explains why sending to it failed, so it comes first
+    if let Err(e) = written.and(read) {
+        let _ = tokio::fs::remove_file(&options.path).await;
+        return Err(e);
+    }
+    let bytes = tokio::fs::metadata(&options.path)
+        .await
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+    Ok(ExportSummary { rows, bytes })
+}
+
+/// Column names and rows of one batch
+type Batch = (Vec<String>, Vec<Vec<Value>>);
+
+/// Create the file and write every batch received, then finish the format.
+/// Runs on a blocking thread.
+fn write_file(
+    db_type: DatabaseType,
+    source: &ExportSource,
+    format: &ExportFormat,
+    path: &str,
+    mut batches: mpsc::Receiver<Batch>,
+) -> Result<(), ProviderError> {
+    let file = File::create(path).map_err(|e| {
+        ProviderError::new(format!("Failed to create {}", path)).with_detail(e.to_string())
+    })?;
+    let mut writer = new_writer(db_type, source, format, path, file)?;
+    while let Some((columns, rows)) = batches.blocking_recv() {
+        writer.write(&columns, &rows)?;
+    }
+    writer.finish()
+}
+
+struct Export<'a> {
+    batches: mpsc::Sender<Batch>,
+    /// Unwrap MongoDB's Extended JSON values; JSON files keep them
+    plain_values: bool,
+    rows: u64,
+    progress: &'a mpsc::Sender<ExportEvent>,
+}
+
+impl Export<'_> {
+    async fn run(
+        &mut self,
+        provider: &dyn DatabaseProvider,
+        source: &ExportSource,
+        batch_size: usize,
+        cancel: &CancelToken,
+    ) -> Result<(), ProviderError> {
+        let db_type = provider.database_type();
+        match source {
+            ExportSource::Query { query } => self.stream(provider, query, batch_size, cancel).await,
+            ExportSource::Table { schema, table } => match db_type {
+                DatabaseType::Postgres | DatabaseType::Mysql => {
+                    let query = format!(
+                        "SELECT * FROM {}",
+                        sql::qualified_name(schema, table, db_type)
+                    );
+                    self.stream(provider, &query, batch_size, cancel).await
+                }
+                DatabaseType::Sqlite => {
+                    let query = format!("SELECT * FROM {}", sql::quote_identifier(table, db_type));
+                    self.stream(provider, &query, batch_size, cancel).await
+                }
+                DatabaseType::Mongodb | DatabaseType::Redis => {
+                    self.page_table(provider, schema, table, batch_size, cancel)
+                        .await
+                }
+            },
+        }
+    }
+
+    /// Hand a batch to the writer. An empty batch only announces the columns.
+    async fn write(
+        &mut self,
+        columns: &[String],
+        rows: Vec<Vec<Value>>,
+    ) -> Result<(), ProviderError> {
+        let count = rows.len() as u64;
+        let rows = if self.plain_values {
+            rows.into_iter()
+                .map(|row| row.into_iter().map(mongodb::plain_value).collect())
+                .collect()
+        } else {
+            rows
+        };
+        self.batches
+            .send((columns.to_vec(), rows))
+            .await
+            .map_err(|_| ProviderError::new("Export writer stopped"))?;
+        if count > 0 {
+            self.rows += count;
+            let _ = self
+                .progress
+                .send(ExportEvent::Progress { rows: self.rows })
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Write batches as the provider streams them. Stopping early drops the
+    /// receiver, which makes the provider's next send fail and end the query.
+    async fn stream(
+        &mut self,
+        provider: &dyn DatabaseProvider,
+        query: &str,
+        batch_size: usize,
+        cancel: &CancelToken,
+    ) -> Result<(), ProviderError> {
+        let (tx, mut rx) = mpsc::channel(2);
+        let produce = provider.execute_query_stream(query, batch_size, tx, cancel);
+        let consume = async move {
+            let mut columns = Vec::new();
+            while let Some(event) = rx.recv().await {
+                match event {
+                    QueryStreamEvent::Columns(names) => {
+                        columns = names;
+                        self.write(&columns, Vec::new()).await?;
+                    }
+                    QueryStreamEvent::Rows(rows) => self.write(&columns, rows).await?,
+                    QueryStreamEvent::Done { .. } => {}
+                    QueryStreamEvent::Error(e) => return Err(ProviderError::new(e)),
+                }
+            }
+            Ok(())
+        };
+
+        let (produced, consumed) = tokio::join!(produce, consume);
+        consumed?;
+        produced
+    }
+
+    /// MongoDB collections are read in `_id` order and Redis keys along one
+    /// SCAN cursor, both with keyset pages
+    async fn page_table(
+        &mut self,
+        provider: &dyn DatabaseProvider,
+        schema: &str,
+        table: &str,
+        batch_size: usize,
+        cancel: &CancelToken,
+    ) -> Result<(), ProviderError> {
+        let mut request = PageRequest {
+            filters: Vec::new(),
+            key: None,
+            direction: Default::default(),
+            cursor: None,
+            limit: batch_size as i64,
+        };
+        loop {
+            if cancel.is_cancelled() {
+                return Err(cancel::cancelled_error());
+            }
+            let page = provider.get_table_page(schema, table, &request).await?;
+            self.write(&page.result.columns, page.result.rows).await?;
+            match page.next_cursor {
+                Some(cursor) => request.cursor = Some(cursor),
+                None => return Ok(()),
+            }
+        }
+    }
+}
+
+fn new_writer(
+    db_type: DatabaseType,
+    source: &ExportSource,
+    format: &ExportFormat,
+    path: &str,
+    file: File,
+) -> Result<Box<dyn RowWriter>, ProviderError> {
+    let out = BufWriter::new(file);
+    Ok(match format {
+        ExportFormat::Csv {
+            delimiter,
+            quoting,
+            header,
+        } => {
+            let delimiter = u8::try_from(*delimiter)
+                .ok()
+                .filter(u8::is_ascii)
+                .ok_or_else(|| ProviderError::new("CSV delimiter must be an ASCII character"))?;
+            let quote_style = match quoting {
+                CsvQuoting::Necessary => csv::QuoteStyle::Necessary,
+                CsvQuoting::Always => csv::QuoteStyle::Always,
+                CsvQuoting::NonNumeric => csv::QuoteStyle::NonNumeric,
+                CsvQuoting::Never => csv::QuoteStyle::Never,
+            };
+            let writer = csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .quote_style(quote_style)
+                .from_writer(out);
+            let csv = CsvWriter {
+                writer,
+                header: *header,
+                columns: None,
+            };
+            // Documents can gain fields in any batch, so the header waits
+            // for all of them
+            if db_type == DatabaseType::Mongodb {
+                Box::new(SpooledWriter::new(path, move |_, _| Ok(Box::new(csv)))?)
+            } else {
+                Box::new(csv)
+            }
+        }
+        ExportFormat::Json => Box::new(JsonWriter {
+            out,
+            array: true,
+            first: true,
+        }),
+        ExportFormat::Ndjson => Box::new(JsonWriter {
+            out,
+            array: false,
+            first: true,
+        }),
+        ExportFormat::SqlInsert {
+            table,
+            dialect,
+            rows_per_statement,
+        } => {
+            let dialect = dialect.unwrap_or(db_type);
+            if matches!(dialect, DatabaseType::Redis | DatabaseType::Mongodb) {
+                return Err(ProviderError::new(format!(
+                    "{} has no SQL dialect to write INSERT statements in",
+                    dialect
+                ))
+                .with_hint("Choose PostgreSQL, MySQL or SQLite as the dialect."));
+            }
+            let target = match (table, source) {
+                (Some(table), _) => sql::quote_identifier(table, dialect),
+                (None, ExportSource::Table { table, .. }) => sql::quote_identifier(table, dialect),
+                (None, ExportSource::Query { .. }) => {
+                    return Err(ProviderError::new(
+                        "A table name is needed to export a query as INSERT statements",
+                    ))
+                }
+            };
+            Box::new(SqlInsertWriter {
+                out,
+                dialect,
+                target,
+                rows_per_statement: (*rows_per_statement).max(1),
+            })
+        }
+        ExportFormat::Parquet => Box::new(SpooledWriter::new(path, move |columns, kinds| {
+            Ok(Box::new(ParquetWriter::new(out, columns, kinds)?))
+        })?),
+    })
+}
+
+/// A file format. `write` receives each batch with its column names, which
+/// for MongoDB can differ between batches; a batch without rows only
+/// announces the columns.
+trait RowWriter: Send {
+    fn write(&mut self, columns: &[String], rows: &[Vec<Value>]) -> Result<(), ProviderError>;
+    fn finish(&mut self) -> Result<(), ProviderError>;
+}
+
+fn write_error(e: impl std::fmt::Display) -> ProviderError {
+    ProviderError::new("Failed to write export file").with_detail(e.to_string())
+}
+
+/// `row` arranged in the order of `header`, for formats with one set of
+/// columns for the whole file. Fields the header lacks are dropped.
+fn align<'a>(header: &[String], columns: &[String], row: &'a [Value]) -> Cow<'a, [Value]> {
+    if header == columns {
+        return Cow::Borrowed(row);
+    }
+    Cow::Owned(
+        header
+            .iter()
+            .map(|name| {
+                columns
+                    .iter()
+                    .position(|column| column == name)
+                    .and_then(|index| row.get(index).cloned())
+                    .unwrap_or(Value::Null)
+            })
+            .collect(),
+    )
+}
+
+/// Text of a value in a CSV field: strings unquoted, NULL empty
+fn text(value: &Value) -> Cow<'_, str> {
+    match value {
+        Value::Null => Cow::Borrowed(""),
+        Value::String(s) => Cow::Borrowed(s),
+        other => Cow::Owned(other.to_string()),
+    }
+}
+
+struct CsvWriter {
+    writer: csv::Writer<BufWriter<File>>,
+    header: bool,
+    columns: Option<Vec<String>>,
+}
+
+impl RowWriter for CsvWriter {
+    fn write(&mut self, columns: &[String], rows: &[Vec<Value>]) -> Result<(), ProviderError> {
+        if self.columns.is_none() {
+            if self.header {
+                self.writer.write_record(columns).map_err(write_error)?;
+            }
+            self.columns = Some(columns.to_vec());
+        }
+        let header = self.columns.as_deref().unwrap_or_default();
+        for row in rows {
+            let row = align(header, columns, row);
+            self.writer
+                .write_record(row.iter().map(|value| text(value).into_owned()))
+                .map_err(write_error)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ProviderError> {
+        self.writer.flush().map_err(write_error)
+    }
+}
+
+struct JsonWriter {
+    out: BufWriter<File>,
+    /// A JSON array rather than newline-delimited objects
+    array: bool,
+    first: bool,
+}
+
+impl RowWriter for JsonWriter {
+    fn write(&mut self, columns: &[String], rows: &[Vec<Value>]) -> Result<(), ProviderError> {
+        for row in rows {
+            let separator = match (self.array, self.first) {
+                (true, true) => "[\n  ",
+                (true, false) => ",\n  ",
+                (false, _) => "",
+            };
+            self.first = false;
+            // Written by hand to keep the columns in result order
+            let fields: Vec<String> = columns
+                .iter()
+                .zip(row)
+                .map(|(column, value)| format!("{}:{}", Value::String(column.clone()), value))
+                .collect();
+            write!(self.out, "{}{{{}}}", separator, fields.join(",")).map_err(write_error)?;
+            if !self.array {
+                self.out.write_all(b"\n").map_err(write_error)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ProviderError> {
+        if self.array {
+            let end: &[u8] = if self.first { b"[]\n" } else { b"\n]\n" };
+            self.out.write_all(end).map_err(write_error)?;
+        }
+        self.out.flush().map_err(write_error)
+    }
+}
+
+struct SqlInsertWriter {
+    out: BufWriter<File>,
+    dialect: DatabaseType,
+    /// Quoted table name
+    target: String,
+    rows_per_statement: usize,
+}
+
+/// Each statement names its batch's own columns, so fields that only later
+/// documents have are kept.
+impl RowWriter for SqlInsertWriter {
+    fn write(&mut self, columns: &[String], rows: &[Vec<Value>]) -> Result<(), ProviderError> {
+        let names: Vec<String> = columns
+            .iter()
+            .map(|column| sql::quote_identifier(column, self.dialect))
+            .collect();
+        for chunk in rows.chunks(self.rows_per_statement) {
+            let values: Vec<String> = chunk
+                .iter()
+                .map(|row| {
+                    let literals: Vec<String> = row
+                        .iter()
+                        .map(|value| sql::literal(value, self.dialect))
+                        .collect();
+                    format!("({})", literals.join(", "))
+                })
+                .collect();
+            writeln!(
+                self.out,
+                "INSERT INTO {} ({}) VALUES\n{};",
+                self.target,
+                names.join(", "),
+                values.join(",\n")
+            )
+            .map_err(write_error)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ProviderError> {
+        self.out.flush().map_err(write_error)
+    }
+}
+
+/// Type a column's values fit in Parquet, widened as values arrive:
+/// integers and floats mix as floats and any other mix becomes text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    /// Only nulls so far
+    Null,
+    Boolean,
+    Int64,
+    Float64,
+    Text,
+}
+
+impl ValueKind {
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => ValueKind::Null,
+            Value::Bool(_) => ValueKind::Boolean,
+            Value::Number(n) if n.is_i64() => ValueKind::Int64,
+            Value::Number(n) if n.is_f64() => ValueKind::Float64,
+            // Integers beyond i64 would lose digits as floats
+            _ => ValueKind::Text,
+        }
+    }
+
+    fn widen(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (ValueKind::Null, kind) | (kind, ValueKind::Null) => kind,
+            (ValueKind::Int64, ValueKind::Float64) | (ValueKind::Float64, ValueKind::Int64) => {
+                ValueKind::Float64
+            }
+            _ => ValueKind::Text,
+        }
+    }
+
+    fn data_type(self) -> DataType {
+        match self {
+            ValueKind::Boolean => DataType::Boolean,
+            ValueKind::Int64 => DataType::Int64,
+            ValueKind::Float64 => DataType::Float64,
+            ValueKind::Null | ValueKind::Text => DataType::Utf8,
+        }
+    }
+}
+
+type OpenWriter =
+    Box<dyn FnOnce(&[String], &[ValueKind]) -> Result<Box<dyn RowWriter>, ProviderError> + Send>;
+
+/// Holds batches in a spool file beside the export until the input ends,
+/// for formats that need every column, and the type of every value, before
+/// the first row is written. The columns are the union over all batches in
+/// order of first appearance.
+struct SpooledWriter {
+    path: PathBuf,
+    spool: BufWriter<File>,
+    columns: Vec<String>,
+    kinds: Vec<ValueKind>,
+    /// Creates the real writer once the columns are known
+    open: Option<OpenWriter>,
+}
+
+impl SpooledWriter {
+    fn new(
+        export_path: &str,
+        open: impl FnOnce(&[String], &[ValueKind]) -> Result<Box<dyn RowWriter>, ProviderError>
+            + Send
+            + 'static,
+    ) -> Result<Self, ProviderError> {
+        let path = PathBuf::from(format!("{}.spool", export_path));
+        let spool = File::create(&path).map_err(write_error)?;
+        Ok(Self {
+            path,
+            spool: BufWriter::new(spool),
+            columns: Vec::new(),
+            kinds: Vec::new(),
+            open: Some(Box::new(open)),
+        })
+    }
+}
+
+impl RowWriter for SpooledWriter {
+    fn write(&mut self, columns: &[String], rows: &[Vec<Value>]) -> Result<(), ProviderError> {
+        let indexes: Vec<usize> = columns
+            .iter()
+            .map(
+                |name| match self.columns.iter().position(|column| column == name) {
+                    Some(index) => index,
+                    None => {
+                        self.columns.push(name.clone());
+                        self.kinds.push(ValueKind::Null);
+                        self.columns.len() - 1
+                    }
+                },
+            )
+            .collect();
+        for row in rows {
+            for (value, &index) in row.iter().zip(&indexes) {
+                self.kinds[index] = self.kinds[index].widen(ValueKind::of(value));
+            }
+        }
+        serde_json::to_writer(&mut self.spool, &(columns, rows)).map_err(write_error)?;
+        self.spool.write_all(b"\n").map_err(write_error)
+    }
+
+    fn finish(&mut self) -> Result<(), ProviderError> {
+        self.spool.flush().map_err(write_error)?;
+        let Some(open) = self.open.take() else {
+            return Ok(());
+        };
+        let mut writer = open(&self.columns, &self.kinds)?;
+        writer.write(&self.columns, &[])?;
+
+        let spool = File::open(&self.path).map_err(write_error)?;
+        for line in BufReader::new(spool).lines() {
+            let (columns, rows): Batch =
+                serde_json::from_str(&line.map_err(write_error)?).map_err(write_error)?;
+            let rows: Vec<Vec<Value>> = rows
+                .iter()
+                .map(|row| align(&self.columns, &columns, row).into_owned())
+                .collect();
+            writer.write(&self.columns, &rows)?;
+        }
+        writer.finish()
+    }
+}
+
+impl Drop for SpooledWriter {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Booleans, integers and floats keep their type and everything else is
+/// written as text. Columns and types come from the whole export.
+struct ParquetWriter {
+    writer: Option<ArrowWriter<BufWriter<File>>>,
+    schema: Arc<Schema>,
+}
+
+/// Bounds the rows buffered in memory before a row group is flushed
+const PARQUET_ROW_GROUP_SIZE: usize = 64 * 1024;
+
+impl ParquetWriter {
+    fn new(
+        out: BufWriter<File>,
+        columns: &[String],
+        kinds: &[ValueKind],
+    ) -> Result<Self, ProviderError> {
+        let fields: Vec<Field> = columns
+            .iter()
+            .zip(kinds)
+            .map(|(name, kind)| Field::new(name, kind.data_type(), true))
+            .collect();
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(PARQUET_ROW_GROUP_SIZE)
+            .build();
+        let schema = Arc::new(Schema::new(fields));
+        let writer =
+            ArrowWriter::try_new(out, schema.clone(), Some(properties)).map_err(write_error)?;
+        Ok(Self {
+            writer: Some(writer),
+            schema,
+        })
+    }
+
+    fn column(&self, index: usize, rows: &[Vec<Value>]) -> Result<ArrayRef, ProviderError> {
+        let field = self.schema.field(index);
+        let mismatch = |value: &Value| {
+            ProviderError::new(format!(
+                "Column {} has values of more than one type ({})",
+                field.name(),
+                value
+            ))
+            .with_hint("Export to CSV or JSON instead.")
+        };
+        let values = rows
+            .iter()
+            .map(|row| row.get(index).unwrap_or(&Value::Null));
+        Ok(match field.data_type() {
+            DataType::Boolean => {
+                let mut builder = BooleanBuilder::with_capacity(rows.len());
+                for value in values {
+                    match value {
+                        Value::Null => builder.append_null(),
+                        Value::Bool(b) => builder.append_value(*b),
+                        other => return Err(mismatch(other)),
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+            DataType::Int64 => {
+                let mut builder = Int64Builder::with_capacity(rows.len());
+                for value in values {
+                    match value {
+                        Value::Null => builder.append_null(),
+                        other => {
+                            builder.append_value(other.as_i64().ok_or_else(|| mismatch(other))?)
+                        }
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+            DataType::Float64 => {
+                let mut builder = Float64Builder::with_capacity(rows.len());
+                for value in values {
+                    match value {
+                        Value::Null => builder.append_null(),
+                        other => {
+                            builder.append_value(other.as_f64().ok_or_else(|| mismatch(other))?)
+                        }
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+            _ => {
+                let mut builder = StringBuilder::new();
+                for value in values {
+                    match value {
+                        Value::Null => builder.append_null(),
+                        other => builder.append_value(text(other)),
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+        })
+    }
+}
+
+impl RowWriter for ParquetWriter {
+    /// `columns` are always the ones the writer was created with
+    fn write(&mut self, _columns: &[String], rows: &[Vec<Value>]) -> Result<(), ProviderError> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let arrays = (0..self.schema.fields().len())
+            .map(|index| self.column(index, rows))
+            .collect::<Result<Vec<_>, _>>()?;
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays).map_err(write_error)?;
+        match self.writer.as_mut() {
+            Some(writer) => writer.write(&batch).map_err(write_error),
+            None => Ok(()),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), ProviderError> {
+        // A result without rows still gets a valid file with its columns
+        if let Some(writer) = self.writer.take() {
+            writer.close().map_err(write_error)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn batches_with_other_columns_follow_the_first_header() {
+        let header = vec!["_id".to_string(), "name".to_string()];
+        let columns = vec!["_id".to_string(), "age".to_string(), "name".to_string()];
+        let row = vec![json!(1), json!(30), json!("a")];
+        assert_eq!(
+            align(&header, &columns, &row).into_owned(),
+            vec![json!(1), json!("a")]
+        );
+    }
+
+    #[test]
+    fn parquet_types_widen_across_batches() {
+        let kinds = [json!(1), json!(1.5), json!(null)]
+            .iter()
+            .fold(ValueKind::Null, |kind, value| {
+                kind.widen(ValueKind::of(value))
+            });
+        assert_eq!(kinds.data_type(), DataType::Float64);
+        assert_eq!(
+            ValueKind::Boolean.widen(ValueKind::Int64).data_type(),
+            DataType::Utf8
+        );
+        assert_eq!(ValueKind::of(&json!(u64::MAX)), ValueKind::Text);
+        assert_eq!(ValueKind::Null.data_type(), DataType::Utf8);
+    }
+
+    #[test]
+    fn spooled_csv_has_every_field_in_the_header() {
+        let path = std::env::temp_dir().join(format!("export-{}.csv", uuid::Uuid::new_v4()));
+        let path = path.to_str().unwrap();
+        let format = ExportFormat::Csv {
+            delimiter: ',',
+            quoting: CsvQuoting::Necessary,
+            header: true,
+        };
+        let source = ExportSource::Query {
+            query: String::new(),
+        };
+        let file = File::create(path).unwrap();
+        let mut writer = new_writer(DatabaseType::Mongodb, &source, &format, path, file).unwrap();
+        writer
+            .write(&["_id".to_string()], &[vec![json!(1)]])
+            .unwrap();
+        writer
+            .write(
+                &["_id".to_string(), "late".to_string()],
+                &[vec![json!(2), json!("x")]],
+            )
+            .unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+
+        let written = std::fs::read_to_string(path).unwrap();
+        let _ = std::fs::remove_file(path);
+        assert_eq!(written, "_id,late\n1,\n2,x\n");
+        assert!(!std::path::Path::new(&format!("{}.spool", path)).exists());
+    }
+}
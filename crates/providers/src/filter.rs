@@ -0,0 +1,372 @@
+//! Filter and sort specs for browsing table data, and their translation to
+//! SQL with bound parameters.
+
+use crate::{sql, DatabaseType, ProviderError, QueryResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Filters (combined with AND) and sort order for `get_table_data_filtered`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableQuery {
+    #[serde(default)]
+    pub filters: Vec<ColumnFilter>,
+    #[serde(default)]
+    pub sort: Vec<SortColumn>,
+}
+
+impl TableQuery {
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty() && self.sort.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnFilter {
+    pub column: String,
+    pub operator: FilterOperator,
+    /// Compared value; an array for `in` / `not_in`, unused for null checks
+    #[serde(default)]
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOperator {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    /// Case-insensitive substring match on the value as text
+    Contains,
+    NotContains,
+    StartsWith,
+    EndsWith,
+    In,
+    NotIn,
+    IsNull,
+    IsNotNull,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortColumn {
+    pub column: String,
+    #[serde(default)]
+    pub direction: SortDirection,
+    /// Where NULLs go; the database's default when unset
+    #[serde(default)]
+    pub nulls: Option<NullsOrder>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+/// `SELECT *` from `target` (an already quoted table name) with the filters,
+/// order and page applied. Values are appended to `params`.
+pub(crate) fn select_sql(
+    db_type: DatabaseType,
+    target: &str,
+    query: &TableQuery,
+    limit: i64,
+    offset: i64,
+    params: &mut Vec<Value>,
+) -> Result<String, ProviderError> {
+    Ok(format!(
+        "SELECT * FROM {}{}{} LIMIT {} OFFSET {}",
+        target,
+        where_clause(db_type, &query.filters, params)?,
+        order_by_clause(db_type, &query.sort),
+        limit,
+        offset
+    ))
+}
+
+pub(crate) fn count_sql(
+    db_type: DatabaseType,
+    target: &str,
+    filters: &[ColumnFilter],
+    params: &mut Vec<Value>,
+) -> Result<String, ProviderError> {
+    Ok(format!(
+        "SELECT COUNT(*) FROM {}{}",
+        target,
+        where_clause(db_type, filters, params)?
+    ))
+}
+
+/// The single value of a `COUNT(*)` result
+pub(crate) fn count_from_result(result: &QueryResult) -> i64 {
+    result
+        .rows
+        .first()
+        .and_then(|row| row.first())
+        .and_then(|value| match value {
+            Value::Number(n) => n.as_i64(),
+            Value::String(s) => s.parse().ok(),
+            _ => None,
+        })
+        .unwrap_or(0)
+}
+
+/// ` WHERE ...` for the filters, or an empty string when there are none
+pub(crate) fn where_clause(
+    db_type: DatabaseType,
+    filters: &[ColumnFilter],
+    params: &mut Vec<Value>,
+) -> Result<String, ProviderError> {
+    if filters.is_empty() {
+        return Ok(String::new());
+    }
+    let mut bind = |value: &Value| {
+        params.push(value.clone());
+        sql::placeholder(db_type, params.len())
+    };
+    conditions(db_type, filters, &mut bind)
+}
+
+/// ` WHERE ...` with the values written as literals, for statements that
+/// cannot take parameters such as streamed queries
+pub(crate) fn literal_where_clause(
+    db_type: DatabaseType,
+    filters: &[ColumnFilter],
+) -> Result<String, ProviderError> {
+    if filters.is_empty() {
+        return Ok(String::new());
+    }
+    conditions(db_type, filters, &mut |value| sql::literal(value, db_type))
+}
+
+fn conditions(
+    db_type: DatabaseType,
+    filters: &[ColumnFilter],
+    bind: &mut dyn FnMut(&Value) -> String,
+) -> Result<String, ProviderError> {
+    let conditions = filters
+        .iter()
+        .map(|filter| condition(db_type, filter, bind))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!(" WHERE {}", conditions.join(" AND ")))
+}
+
+fn condition(
+    db_type: DatabaseType,
+    filter: &ColumnFilter,
+    bind: &mut dyn FnMut(&Value) -> String,
+) -> Result<String, ProviderError> {
+    let column = sql::quote_identifier(&filter.column, db_type);
+
+    let comparison = |op: &str, bind: &mut dyn FnMut(&Value) -> String| {
+        if filter.value.is_array() || filter.value.is_object() {
+            return Err(invalid_value(filter, "a single value"));
+        }
+        Ok(format!("{} {} {}", column, op, bind(&filter.value)))
+    };
+
+    match filter.operator {
+        FilterOperator::Eq if filter.value.is_null() => Ok(format!("{} IS NULL", column)),
+        FilterOperator::Ne if filter.value.is_null() => Ok(format!("{} IS NOT NULL", column)),
+        FilterOperator::Eq => comparison("=", bind),
+        FilterOperator::Ne => comparison("<>", bind),
+        FilterOperator::Lt => comparison("<", bind),
+        FilterOperator::Lte => comparison("<=", bind),
+        FilterOperator::Gt => comparison(">", bind),
+        FilterOperator::Gte => comparison(">=", bind),
+        FilterOperator::IsNull => Ok(format!("{} IS NULL", column)),
+        FilterOperator::IsNotNull => Ok(format!("{} IS NOT NULL", column)),
+        FilterOperator::Contains
+        | FilterOperator::NotContains
+        | FilterOperator::StartsWith
+        | FilterOperator::EndsWith => {
+            let text = match &filter.value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                _ => return Err(invalid_value(filter, "text")),
+            };
+            let escaped = escape_like(&text);
+            let pattern = match filter.operator {
+                FilterOperator::StartsWith => format!("{}%", escaped),
+                FilterOperator::EndsWith => format!("%{}", escaped),
+                _ => format!("%{}%", escaped),
+            };
+            let negate = if filter.operator == FilterOperator::NotContains {
+                "NOT "
+            } else {
+                ""
+            };
+            // Postgres LIKE is case-sensitive and only works on text; MySQL
+            // and SQLite compare case-insensitively already.
+            let (operand, like) = match db_type {
+                DatabaseType::Postgres => (format!("CAST({} AS text)", column), "ILIKE"),
+                _ => (column.clone(), "LIKE"),
+            };
+            Ok(format!(
+                "{} {}{} {} ESCAPE '!'",
+                operand,
+                negate,
+                like,
+                bind(&Value::String(pattern))
+            ))
+        }
+        FilterOperator::In | FilterOperator::NotIn => {
+            let Value::Array(values) = &filter.value else {
+                return Err(invalid_value(filter, "an array of values"));
+            };
+            let negate = filter.operator == FilterOperator::NotIn;
+            if values.is_empty() {
+                // Nothing is in an empty list
+                return Ok(if negate { "1 = 1" } else { "1 = 0" }.to_string());
+            }
+            let placeholders: Vec<String> = values.iter().map(&mut *bind).collect();
+            Ok(format!(
+                "{} {}IN ({})",
+                column,
+                if negate { "NOT " } else { "" },
+                placeholders.join(", ")
+            ))
+        }
+    }
+}
+
+fn invalid_value(filter: &ColumnFilter, expected: &str) -> ProviderError {
+    ProviderError::new(format!(
+        "Filter on {} needs {} to compare with",
+        filter.column, expected
+    ))
+}
+
+/// Escape LIKE wildcards with `!`, which needs no escaping in any dialect's
+/// string literals (unlike backslash in MySQL)
+fn escape_like(text: &str) -> String {
+    text.replace('!', "!!")
+        .replace('%', "!%")
+        .replace('_', "!_")
+}
+
+/// ` ORDER BY ...` for the sort columns, or an empty string
+pub(crate) fn order_by_clause(db_type: DatabaseType, sort: &[SortColumn]) -> String {
+    if sort.is_empty() {
+        return String::new();
+    }
+    let terms: Vec<String> = sort
+        .iter()
+        .map(|sort| {
+            let column = sql::quote_identifier(&sort.column, db_type);
+            let direction = match sort.direction {
+                SortDirection::Asc => "ASC",
+                SortDirection::Desc => "DESC",
+            };
+            match (sort.nulls, db_type) {
+                (None, _) => format!("{} {}", column, direction),
+                // MySQL has no NULLS FIRST/LAST; sort on IS NULL first instead
+                (Some(nulls), DatabaseType::Mysql) => format!(
+                    "{} IS NULL {}, {} {}",
+                    column,
+                    if nulls == NullsOrder::First {
+                        "DESC"
+                    } else {
+                        "ASC"
+                    },
+                    column,
+                    direction
+                ),
+                (Some(NullsOrder::First), _) => format!("{} {} NULLS FIRST", column, direction),
+                (Some(NullsOrder::Last), _) => format!("{} {} NULLS LAST", column, direction),
+            }
+        })
+        .collect();
+    format!(" ORDER BY {}", terms.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn query(value: Value) -> TableQuery {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn filters_and_sort_become_bound_sql() {
+        let query = query(json!({
+            "filters": [
+                {"column": "age", "operator": "gte", "value": 18},
+                {"column": "name", "operator": "contains", "value": "50%_off"},
+                {"column": "id", "operator": "in", "value": [1, 2]},
+                {"column": "deleted_at", "operator": "is_null"}
+            ],
+            "sort": [{"column": "name", "direction": "desc", "nulls": "last"}]
+        }));
+
+        let mut params = Vec::new();
+        let sql = select_sql(
+            DatabaseType::Postgres,
+            r#""public"."users""#,
+            &query,
+            50,
+            100,
+            &mut params,
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            r#"SELECT * FROM "public"."users" WHERE "age" >= $1 AND CAST("name" AS text) ILIKE $2 ESCAPE '!' AND "id" IN ($3, $4) AND "deleted_at" IS NULL ORDER BY "name" DESC NULLS LAST LIMIT 50 OFFSET 100"#
+        );
+        assert_eq!(
+            params,
+            vec![json!(18), json!("%50!%!_off%"), json!(1), json!(2)]
+        );
+    }
+
+    #[test]
+    fn mysql_emulates_nulls_ordering() {
+        let sort = query(json!({"sort": [{"column": "a", "nulls": "first"}]})).sort;
+        assert_eq!(
+            order_by_clause(DatabaseType::Mysql, &sort),
+            " ORDER BY `a` IS NULL DESC, `a` ASC"
+        );
+    }
+
+    #[test]
+    fn invalid_filter_values_are_rejected() {
+        let filters =
+            query(json!({"filters": [{"column": "a", "operator": "in", "value": 1}]})).filters;
+        assert!(where_clause(DatabaseType::Sqlite, &filters, &mut Vec::new()).is_err());
+
+        let filters =
+            query(json!({"filters": [{"column": "a", "operator": "eq", "value": null}]})).filters;
+        let mut params = Vec::new();
+        assert_eq!(
+            where_clause(DatabaseType::Sqlite, &filters, &mut params).unwrap(),
+            r#" WHERE "a" IS NULL"#
+        );
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn literal_filters_are_escaped() {
+        let filters = query(json!({"filters": [
+            {"column": "name", "operator": "eq", "value": "O'Brien"},
+            {"column": "id", "operator": "in", "value": [1, 2]}
+        ]}))
+        .filters;
+        assert_eq!(
+            literal_where_clause(DatabaseType::Mysql, &filters).unwrap(),
+            " WHERE `name` = 'O''Brien' AND `id` IN (1, 2)"
+        );
+    }
+}
@@ -0,0 +1,811 @@
+//! Bulk import of CSV, JSON and NDJSON files into a table. The file is read
+//! in batches that each provider inserts through its fastest native path.
+
+use crate::{cancel, sql, CancelToken, DatabaseProvider, DatabaseType, ProviderError};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum ImportFormat {
+    Csv {
+        #[serde(default = "default_delimiter")]
+        delimiter: char,
+        /// Without a header row, columns are named `column_1`, `column_2`, ...
+        #[serde(default = "default_true")]
+        header: bool,
+    },
+    /// One array of objects. The file is parsed whole; use NDJSON for large
+    /// files.
+    Json,
+    /// One object per line
+    Ndjson,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// What happens to rows the database rejects
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportErrorMode {
+    /// Record the row's error and carry on with the next row
+    Skip,
+    /// Stop and roll back everything imported so far (MongoDB keeps the rows
+    /// inserted before the failing one)
+    #[default]
+    Abort,
+}
+
+/// A file column and the table column it is imported into
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    pub source: String,
+    pub target: String,
+    /// Column type when the table is created; inferred from the file when unset
+    #[serde(default)]
+    pub data_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportOptions {
+    pub path: String,
+    #[serde(flatten)]
+    pub format: ImportFormat,
+    pub schema: String,
+    pub table: String,
+    /// Create the table from the inferred column types first
+    #[serde(default)]
+    pub create_table: bool,
+    /// Columns to import; every file column under its own name when empty
+    #[serde(default)]
+    pub columns: Vec<ColumnMapping>,
+    #[serde(default)]
+    pub on_error: ImportErrorMode,
+    #[serde(default)]
+    pub batch_size: Option<usize>,
+    /// Rows read to infer column types (and, for JSON, column names)
+    #[serde(default)]
+    pub sample_size: Option<usize>,
+}
+
+const DEFAULT_BATCH_SIZE: usize = 1000;
+const DEFAULT_SAMPLE_SIZE: usize = 1000;
+/// Row errors kept in the summary; the rest are only counted
+const MAX_REPORTED_ERRORS: usize = 100;
+
+/// Column type inferred from sampled values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InferredType {
+    Boolean,
+    Integer,
+    Float,
+    Date,
+    Timestamp,
+    Text,
+    Json,
+}
+
+impl InferredType {
+    /// Column type to create the table with
+    pub fn sql_type(self, db_type: DatabaseType) -> &'static str {
+        match (self, db_type) {
+            (InferredType::Boolean, DatabaseType::Sqlite) => "INTEGER",
+            (InferredType::Boolean, _) => "BOOLEAN",
+            (InferredType::Integer, DatabaseType::Sqlite) => "INTEGER",
+            (InferredType::Integer, _) => "BIGINT",
+            (InferredType::Float, DatabaseType::Postgres) => "DOUBLE PRECISION",
+            (InferredType::Float, DatabaseType::Mysql) => "DOUBLE",
+            (InferredType::Float, _) => "REAL",
+            (InferredType::Date, DatabaseType::Sqlite) => "TEXT",
+            (InferredType::Date, _) => "DATE",
+            (InferredType::Timestamp, DatabaseType::Postgres) => "TIMESTAMP",
+            (InferredType::Timestamp, DatabaseType::Mysql) => "DATETIME",
+            (InferredType::Json, DatabaseType::Postgres) => "JSONB",
+            (InferredType::Json, DatabaseType::Mysql) => "JSON",
+            _ => "TEXT",
+        }
+    }
+
+    fn of(value: &Value) -> Option<Self> {
+        Some(match value {
+            Value::Null => return None,
+            Value::Bool(_) => InferredType::Boolean,
+            Value::Number(n) if n.is_i64() || n.is_u64() => InferredType::Integer,
+            Value::Number(_) => InferredType::Float,
+            Value::Array(_) | Value::Object(_) => InferredType::Json,
+            Value::String(s) => Self::of_text(s),
+        })
+    }
+
+    fn of_text(text: &str) -> Self {
+        // Leading zeros (zip codes, ids) are kept as text
+        let leading_zero = text.len() > 1 && text.starts_with('0') && !text.starts_with("0.");
+        if !leading_zero && text.parse::<i64>().is_ok() {
+            InferredType::Integer
+        } else if !leading_zero && text.parse::<f64>().is_ok_and(f64::is_finite) {
+            InferredType::Float
+        } else if text.eq_ignore_ascii_case("true") || text.eq_ignore_ascii_case("false") {
+            InferredType::Boolean
+        } else if chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok() {
+            InferredType::Date
+        } else if chrono::DateTime::parse_from_rfc3339(text).is_ok()
+            || chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f").is_ok()
+            || chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f").is_ok()
+        {
+            InferredType::Timestamp
+        } else {
+            InferredType::Text
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        use InferredType::*;
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Integer, Float) | (Float, Integer) => Float,
+            (Date, Timestamp) | (Timestamp, Date) => Timestamp,
+            _ => Text,
+        }
+    }
+
+    /// Text from a CSV file as the JSON value the column type expects. Values
+    /// that do not parse are passed on for the database to reject.
+    fn convert(self, value: Value) -> Value {
+        let Value::String(text) = &value else {
+            return value;
+        };
+        let parsed = match self {
+            InferredType::Boolean => text
+                .to_ascii_lowercase()
+                .parse::<bool>()
+                .ok()
+                .map(Value::Bool),
+            InferredType::Integer => text.parse::<i64>().ok().map(Value::from),
+            InferredType::Float => text
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number),
+            _ => None,
+        };
+        parsed.unwrap_or(value)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportColumn {
+    pub name: String,
+    pub data_type: InferredType,
+}
+
+/// File columns with their inferred types and the first rows, for mapping
+/// columns before importing
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportPreview {
+    pub columns: Vec<ImportColumn>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportRowError {
+    /// 1-based data row in the file, not counting the header
+    pub row: u64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportSummary {
+    pub rows_imported: u64,
+    pub rows_skipped: u64,
+    /// The first row errors; `rows_skipped` counts them all
+    pub errors: Vec<ImportRowError>,
+}
+
+/// Rows handed to `DatabaseProvider::import_rows`, in target column order
+#[derive(Debug, Clone)]
+pub struct ImportBatch {
+    /// File row number of `rows[0]`
+    pub first_row: u64,
+    pub rows: Vec<Vec<Value>>,
+}
+
+/// Batches to import. An `Err` means the import was stopped (cancelled, or a
+/// row could not be read under `Abort`) and must be rolled back.
+pub type ImportBatches = mpsc::Receiver<Result<ImportBatch, ProviderError>>;
+
+/// Progress of a running import, sent after every batch read
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum ImportEvent {
+    Progress { rows: u64 },
+    Done(ImportSummary),
+    Error(String),
+}
+
+/// Counts imported rows and applies the error mode to failed ones
+pub(crate) struct ImportReport {
+    on_error: ImportErrorMode,
+    summary: ImportSummary,
+}
+
+impl ImportReport {
+    pub fn new(on_error: ImportErrorMode) -> Self {
+        Self {
+            on_error,
+            summary: ImportSummary::default(),
+        }
+    }
+
+    pub fn imported(&mut self, rows: u64) {
+        self.summary.rows_imported += rows;
+    }
+
+    /// Record a rejected row. Under `Abort` this returns the error that ends
+    /// the import.
+    pub fn row_failed(&mut self, row: u64, mut err: ProviderError) -> Result<(), ProviderError> {
+        if self.on_error == ImportErrorMode::Abort {
+            err.message = format!("Row {}: {}", row, err.message);
+            return Err(err);
+        }
+        self.summary.rows_skipped += 1;
+        if self.summary.errors.len() < MAX_REPORTED_ERRORS {
+            self.summary.errors.push(ImportRowError {
+                row,
+                message: err.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> ImportSummary {
+        self.summary
+    }
+}
+
+/// `INSERT` of `rows` rows into `target` (an already quoted table name)
+pub(crate) fn insert_sql(
+    db_type: DatabaseType,
+    target: &str,
+    columns: &[String],
+    rows: usize,
+) -> String {
+    let names: Vec<String> = columns
+        .iter()
+        .map(|column| sql::quote_identifier(column, db_type))
+        .collect();
+    let mut index = 0;
+    let values: Vec<String> = (0..rows)
+        .map(|_| {
+            let placeholders: Vec<String> = columns
+                .iter()
+                .map(|_| {
+                    index += 1;
+                    sql::placeholder(db_type, index)
+                })
+                .collect();
+            format!("({})", placeholders.join(", "))
+        })
+        .collect();
+    format!(
+        "INSERT INTO {} ({}) VALUES {}",
+        target,
+        names.join(", "),
+        values.join(", ")
+    )
+}
+
+/// Rows as CSV text for Postgres `COPY ... (FORMAT csv)`: NULL is an
+/// unquoted empty field and every value is quoted.
+pub(crate) fn copy_csv(rows: &[Vec<Value>]) -> String {
+    let mut out = String::new();
+    for row in rows {
+        for (index, value) in row.iter().enumerate() {
+            if index > 0 {
+                out.push(',');
+            }
+            let text = match value {
+                Value::Null => continue,
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            out.push('"');
+            out.push_str(&text.replace('"', "\"\""));
+            out.push('"');
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// A row of the file: positional for CSV, an object for JSON
+enum Record {
+    Row(Vec<Value>),
+    Object(Map<String, Value>),
+}
+
+type Records = Box<dyn Iterator<Item = Result<Record, String>> + Send>;
+
+/// The file opened for reading, with its first rows read ahead
+struct Source {
+    columns: Vec<String>,
+    types: Vec<InferredType>,
+    sample: Vec<Result<Record, String>>,
+    rest: Records,
+}
+
+fn open_error(path: &str, e: impl std::fmt::Display) -> ProviderError {
+    ProviderError::new(format!("Failed to read {}", path)).with_detail(e.to_string())
+}
+
+impl Source {
+    fn open(path: &str, format: &ImportFormat, sample_size: usize) -> Result<Self, ProviderError> {
+        let file = File::open(path).map_err(|e| open_error(path, e))?;
+        let (header, mut records): (Option<Vec<String>>, Records) = match format {
+            ImportFormat::Csv { delimiter, header } => {
+                let delimiter = u8::try_from(*delimiter)
+                    .ok()
+                    .filter(u8::is_ascii)
+                    .ok_or_else(|| {
+                        ProviderError::new("CSV delimiter must be an ASCII character")
+                    })?;
+                let mut reader = csv::ReaderBuilder::new()
+                    .delimiter(delimiter)
+                    .has_headers(*header)
+                    .flexible(true)
+                    .from_reader(BufReader::new(file));
+                let names = if *header {
+                    let headers = reader.headers().map_err(|e| open_error(path, e))?;
+                    Some(headers.iter().map(str::to_string).collect())
+                } else {
+                    None
+                };
+                let records = reader.into_records().map(|record| {
+                    record.map_err(|e| e.to_string()).map(|record| {
+                        Record::Row(
+                            record
+                                .iter()
+                                .map(|field| match field {
+                                    "" => Value::Null,
+                                    text => Value::String(text.to_string()),
+                                })
+                                .collect(),
+                        )
+                    })
+                });
+                (names, Box::new(records))
+            }
+            ImportFormat::Json => {
+                let objects: Vec<Value> = serde_json::from_reader(BufReader::new(file))
+                    .map_err(|e| open_error(path, e))?;
+                (None, Box::new(objects.into_iter().map(object_record)))
+            }
+            ImportFormat::Ndjson => {
+                let lines = BufReader::new(file)
+                    .lines()
+                    .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+                    .map(|line| {
+                        let line = line.map_err(|e| e.to_string())?;
+                        let value = serde_json::from_str(&line).map_err(|e| e.to_string())?;
+                        object_record(value)
+                    });
+                (None, Box::new(lines))
+            }
+        };
+
+        let sample: Vec<Result<Record, String>> = records.by_ref().take(sample_size).collect();
+        let columns = match header {
+            Some(names) => names,
+            None => sample_columns(&sample),
+        };
+        let mut types: Vec<Option<InferredType>> = vec![None; columns.len()];
+        for record in sample.iter().flatten() {
+            for (index, value) in record_values(record, &columns).iter().enumerate() {
+                if let Some(found) = InferredType::of(value) {
+                    types[index] = Some(types[index].map_or(found, |ty| ty.merge(found)));
+                }
+            }
+        }
+
+        Ok(Self {
+            columns,
+            types: types
+                .into_iter()
+                .map(|ty| ty.unwrap_or(InferredType::Text))
+                .collect(),
+            sample,
+            rest: records,
+        })
+    }
+
+    fn inferred_columns(&self) -> Vec<ImportColumn> {
+        self.columns
+            .iter()
+            .zip(&self.types)
+            .map(|(name, data_type)| ImportColumn {
+                name: name.clone(),
+                data_type: *data_type,
+            })
+            .collect()
+    }
+
+    /// Every row of the file as typed values for all columns
+    fn rows(self) -> impl Iterator<Item = Result<Vec<Value>, String>> {
+        let Source {
+            columns,
+            types,
+            sample,
+            rest,
+        } = self;
+        sample.into_iter().chain(rest).map(move |record| {
+            record.map(|record| {
+                record_values(&record, &columns)
+                    .into_iter()
+                    .zip(&types)
+                    .map(|(value, ty)| ty.convert(value))
+                    .collect()
+            })
+        })
+    }
+}
+
+fn object_record(value: Value) -> Result<Record, String> {
+    match value {
+        Value::Object(object) => Ok(Record::Object(object)),
+        other => Err(format!("Expected an object, found {}", other)),
+    }
+}
+
+/// Without a header, columns are every key seen in the sampled objects, or
+/// numbered for CSV
+fn sample_columns(sample: &[Result<Record, String>]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for record in sample.iter().flatten() {
+        match record {
+            Record::Row(values) => {
+                for index in columns.len()..values.len() {
+                    columns.push(format!("column_{}", index + 1));
+                }
+            }
+            Record::Object(object) => {
+                for key in object.keys() {
+                    if !columns.contains(key) {
+                        columns.push(key.clone());
+                    }
+                }
+            }
+        }
+    }
+    columns
+}
+
+fn record_values(record: &Record, columns: &[String]) -> Vec<Value> {
+    match record {
+        Record::Row(values) => (0..columns.len())
+            .map(|index| values.get(index).cloned().unwrap_or(Value::Null))
+            .collect(),
+        Record::Object(object) => columns
+            .iter()
+            .map(|column| object.get(column).cloned().unwrap_or(Value::Null))
+            .collect(),
+    }
+}
+
+/// The file's columns with inferred types and its first rows
+pub fn preview(options: &ImportOptions) -> Result<ImportPreview, ProviderError> {
+    let sample_size = options.sample_size.unwrap_or(DEFAULT_SAMPLE_SIZE);
+    let source = Source::open(&options.path, &options.format, sample_size)?;
+    let columns = source.inferred_columns();
+    let rows = source
+        .rows()
+        .take(sample_size.min(100))
+        .filter_map(Result::ok)
+        .collect();
+    Ok(ImportPreview { columns, rows })
+}
+
+/// Import the file described by `options`, reporting rows read through
+/// `progress`.
+pub async fn import(
+    provider: &dyn DatabaseProvider,
+    options: &ImportOptions,
+    cancel: &CancelToken,
+    progress: &mpsc::Sender<ImportEvent>,
+) -> Result<ImportSummary, ProviderError> {
+    let db_type = provider.database_type();
+    let sample_size = options.sample_size.unwrap_or(DEFAULT_SAMPLE_SIZE);
+    let (path, format) = (options.path.clone(), options.format.clone());
+    let source = tokio::task::spawn_blocking(move || Source::open(&path, &format, sample_size))
+        .await
+        .map_err(task_error)??;
+
+    let mappings: Vec<ColumnMapping> = if options.columns.is_empty() {
+        source
+            .columns
+            .iter()
+            .map(|column| ColumnMapping {
+                source: column.clone(),
+                target: column.clone(),
+                data_type: None,
+            })
+            .collect()
+    } else {
+        options.columns.clone()
+    };
+    let indexes = mappings
+        .iter()
+        .map(|mapping| {
+            source
+                .columns
+                .iter()
+                .position(|column| *column == mapping.source)
+                .ok_or_else(|| {
+                    ProviderError::new(format!("Column {} is not in the file", mapping.source))
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if mappings.is_empty() {
+        return Err(ProviderError::new("The file has no columns to import"));
+    }
+    let targets: Vec<String> = mappings.iter().map(|m| m.target.clone()).collect();
+
+    // Collections are created by the first insert, so only SQL tables are
+    // dropped again
+    let created = options.create_table && db_type != DatabaseType::Mongodb;
+    if options.create_table {
+        create_table(provider, options, &source.types, &mappings, &indexes).await?;
+    } else if db_type != DatabaseType::Mongodb {
+        check_table(provider, options, &targets).await?;
+    }
+
+    let batch_size = options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let (tx, rx) = mpsc::channel(2);
+    let consume = provider.import_rows(
+        &options.schema,
+        &options.table,
+        &targets,
+        rx,
+        options.on_error,
+    );
+    let (on_error, cancel, progress) = (options.on_error, cancel.clone(), progress.clone());
+    let produce = tokio::task::spawn_blocking(move || {
+        read_batches(
+            source, indexes, batch_size, on_error, tx, &cancel, &progress,
+        )
+    });
+
+    let (imported, read) = tokio::join!(consume, produce);
+    let imported = imported.and_then(|summary| Ok((summary, read.map_err(task_error)?)));
+    let (mut summary, read) = match imported {
+        Ok(done) => done,
+        Err(e) => {
+            if created {
+                drop_table(provider, options).await;
+            }
+            return Err(e);
+        }
+    };
+    summary.rows_skipped += read.rows_skipped;
+    summary.errors.extend(read.errors);
+    summary.errors.sort_by_key(|error| error.row);
+    summary.errors.truncate(MAX_REPORTED_ERRORS);
+    Ok(summary)
+}
+
+fn task_error(e: tokio::task::JoinError) -> ProviderError {
+    ProviderError::new(format!("Import task failed: {}", e))
+}
+
+/// Send the file's rows (the `indexes` columns of each) in batches of
+/// consecutive rows. Rows that cannot be read are reported here; dropping
+/// `tx` at the end tells the provider to commit. Runs on a blocking thread.
+fn read_batches(
+    source: Source,
+    indexes: Vec<usize>,
+    batch_size: usize,
+    on_error: ImportErrorMode,
+    tx: mpsc::Sender<Result<ImportBatch, ProviderError>>,
+    cancel: &CancelToken,
+    progress: &mpsc::Sender<ImportEvent>,
+) -> ImportSummary {
+    let mut report = ImportReport::new(on_error);
+    let mut rows = source.rows();
+    let mut row_number = 0;
+    let mut batch = ImportBatch {
+        first_row: 1,
+        rows: Vec::with_capacity(batch_size),
+    };
+
+    loop {
+        let next = rows.next();
+        let done = next.is_none();
+        if let Some(row) = next {
+            row_number += 1;
+            match row {
+                Ok(values) => batch
+                    .rows
+                    .push(indexes.iter().map(|&index| values[index].clone()).collect()),
+                Err(e) => {
+                    if let Err(e) = report.row_failed(row_number, ProviderError::new(e)) {
+                        let _ = tx.blocking_send(Err(e));
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Batches end when full, at the end of the file, and before a row
+        // that failed to read so their rows stay consecutive
+        let full = batch.rows.len() >= batch_size;
+        let failed = batch.first_row + batch.rows.len() as u64 <= row_number;
+        if !(full || failed || done) {
+            continue;
+        }
+        if cancel.is_cancelled() {
+            let _ = tx.blocking_send(Err(cancel::cancelled_error()));
+            break;
+        }
+        let next_batch = ImportBatch {
+            first_row: row_number + 1,
+            rows: Vec::with_capacity(batch_size),
+        };
+        let batch = std::mem::replace(&mut batch, next_batch);
+        if !batch.rows.is_empty() {
+            if tx.blocking_send(Ok(batch)).is_err() {
+                break;
+            }
+            let _ = progress.blocking_send(ImportEvent::Progress { rows: row_number });
+        }
+        if done {
+            break;
+        }
+    }
+    report.finish()
+}
+
+async fn create_table(
+    provider: &dyn DatabaseProvider,
+    options: &ImportOptions,
+    types: &[InferredType],
+    mappings: &[ColumnMapping],
+    indexes: &[usize],
+) -> Result<(), ProviderError> {
+    let db_type = provider.database_type();
+    match db_type {
+        // Collections are created by the first insert
+        DatabaseType::Mongodb => return Ok(()),
+        DatabaseType::Redis => {
+            return Err(ProviderError::new(
+                "Import is not supported for this database type",
+            ))
+        }
+        _ => {}
+    }
+    let columns: Vec<String> = mappings
+        .iter()
+        .zip(indexes)
+        .map(|(mapping, &index)| {
+            let data_type = mapping
+                .data_type
+                .as_deref()
+                .unwrap_or_else(|| types[index].sql_type(db_type));
+            format!(
+                "{} {}",
+                sql::quote_identifier(&mapping.target, db_type),
+                data_type
+            )
+        })
+        .collect();
+    let statement = format!(
+        "CREATE TABLE {} ({})",
+        sql::qualified_name(&options.schema, &options.table, db_type),
+        columns.join(", ")
+    );
+    provider.execute_query(&statement).await.map(|_| ())
+}
+
+/// Remove a table `create_table` made for an import that failed. The
+/// import's own error is the one reported, so a failed drop is ignored.
+async fn drop_table(provider: &dyn DatabaseProvider, options: &ImportOptions) {
+    let db_type = provider.database_type();
+    let statement = format!(
+        "DROP TABLE {}",
+        sql::qualified_name(&options.schema, &options.table, db_type)
+    );
+    let _ = provider.execute_query(&statement).await;
+}
+
+/// The target table must exist and have every mapped column
+async fn check_table(
+    provider: &dyn DatabaseProvider,
+    options: &ImportOptions,
+    targets: &[String],
+) -> Result<(), ProviderError> {
+    let columns = provider
+        .get_table_columns(&options.schema, &options.table)
+        .await?;
+    if columns.is_empty() {
+        return Err(ProviderError::new(format!(
+            "Table {}.{} does not exist",
+            options.schema, options.table
+        ))
+        .with_hint("Enable creating the table to import into a new one."));
+    }
+    for target in targets {
+        if !columns.iter().any(|column| &column.name == target) {
+            return Err(ProviderError::new(format!(
+                "Table {}.{} has no column {}",
+                options.schema, options.table, target
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn types_are_inferred_from_text() {
+        let infer = |values: &[&str]| {
+            values
+                .iter()
+                .map(|value| InferredType::of_text(value))
+                .reduce(InferredType::merge)
+                .unwrap()
+        };
+        assert_eq!(infer(&["1", "-20"]), InferredType::Integer);
+        assert_eq!(infer(&["1", "2.5"]), InferredType::Float);
+        assert_eq!(infer(&["007", "12"]), InferredType::Text);
+        assert_eq!(infer(&["TRUE", "false"]), InferredType::Boolean);
+        assert_eq!(
+            infer(&["2024-01-31", "2024-02-01 10:00:00"]),
+            InferredType::Timestamp
+        );
+        assert_eq!(infer(&["1", "x"]), InferredType::Text);
+        assert_eq!(InferredType::Integer.convert(json!("42")), json!(42));
+        assert_eq!(InferredType::Integer.convert(json!("4x")), json!("4x"));
+    }
+
+    #[test]
+    fn rows_become_copy_csv_and_multi_row_inserts() {
+        let rows = vec![
+            vec![json!(1), json!("say \"hi\""), json!(null)],
+            vec![json!(2), json!(""), json!({"a": 1})],
+        ];
+        assert_eq!(
+            copy_csv(&rows),
+            "\"1\",\"say \"\"hi\"\"\",\n\"2\",\"\",\"{\"\"a\"\":1}\"\n"
+        );
+        let columns = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(
+            insert_sql(DatabaseType::Postgres, r#""t""#, &columns, 2),
+            r#"INSERT INTO "t" ("a", "b") VALUES ($1, $2), ($3, $4)"#
+        );
+    }
+
+    #[test]
+    fn skipped_rows_are_reported_and_abort_stops() {
+        let mut report = ImportReport::new(ImportErrorMode::Skip);
+        report.row_failed(3, ProviderError::new("bad")).unwrap();
+        report.imported(2);
+        let summary = report.finish();
+        assert_eq!((summary.rows_imported, summary.rows_skipped), (2, 1));
+        assert_eq!(summary.errors[0].row, 3);
+
+        let mut report = ImportReport::new(ImportErrorMode::Abort);
+        let err = report.row_failed(3, ProviderError::new("bad")).unwrap_err();
+        assert_eq!(err.message, "Row 3: bad");
+    }
+}
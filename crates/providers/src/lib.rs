@@ -1,13 +1,67 @@
+pub mod cancel;
+pub mod copy;
+pub mod data_diff;
+pub mod edit;
+pub mod explain;
+pub mod export;
+pub mod filter;
+pub mod import;
+pub mod mongo_index;
+pub mod mongo_query;
+pub mod mongo_schema;
 pub mod mongodb;
 pub mod mysql;
+pub mod pagination;
 pub mod postgres;
+mod postgres_ddl;
+mod postgres_types;
 pub mod redis;
+pub mod schema;
+pub mod schema_diff;
+pub mod script;
+pub mod sql;
 pub mod sqlite;
+pub mod ssh;
+pub mod stream;
+pub mod tls;
+pub mod transaction;
+
+pub use cancel::{CancelToken, QueryCanceller};
+pub use copy::{CopyEvent, CopyMode, CopyOptions, CopySummary, TableRef};
+pub use data_diff::{
+    ColumnDiff, DataDiffEvent, DataDiffOptions, DataDiffSummary, RowChange, RowDiff,
+};
+pub use edit::RowEdit;
+pub use explain::{PlanBuffers, PlanNode, QueryPlan};
+pub use export::{
+    CsvQuoting, ExportEvent, ExportFormat, ExportOptions, ExportSource, ExportSummary,
+};
+pub use filter::{ColumnFilter, FilterOperator, NullsOrder, SortColumn, SortDirection, TableQuery};
+pub use import::{
+    ColumnMapping, ImportBatch, ImportBatches, ImportErrorMode, ImportEvent, ImportFormat,
+    ImportOptions, ImportPreview, ImportSummary,
+};
+pub use mongo_index::{
+    CollectionIndex, CollectionStats, IndexKey, IndexKeyKind, IndexSpec, IndexUsage,
+};
+pub use mongo_schema::{DocumentSchema, FieldInfo, TypeCount};
+pub use pagination::{PageRequest, TablePage};
+pub use schema::{
+    ConstraintInfo, ConstraintKind, ForeignKeyInfo, IndexInfo, ObjectKind, RoutineInfo,
+    RoutineKind, SchemaObjects, SequenceInfo, TableDetails, TriggerInfo, ViewInfo,
+};
+pub use schema_diff::{Change, ObjectDiff, SchemaDiff, TableDiff};
+pub use script::{ScriptErrorMode, StatementResult};
+pub use ssh::{SshTunnel, SshTunnelConfig};
+pub use stream::{QueryStreamEvent, DEFAULT_STREAM_BATCH_SIZE};
+pub use tls::{SslMode, TlsConfig};
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::fmt;
+use std::time::Instant;
+use tokio::sync::mpsc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
@@ -91,16 +145,75 @@ pub struct TableInfo {
 pub struct ColumnInfo {
     pub name: String,
     pub data_type: String,
+    /// The type with its length, precision or element type, such as
+    /// `character varying(20)`, `integer[]` or `tinyint(1)`. `None` where
+    /// `data_type` already is the full type.
+    pub full_type: Option<String>,
+    /// Dotted paths of the fields inside this column's documents, which the
+    /// data browser can flatten into columns of their own (MongoDB only)
+    pub nested_paths: Vec<String>,
     pub is_nullable: bool,
     pub is_primary_key: bool,
     pub has_default: bool,
+    /// Default expression as the database reports it
+    pub default_value: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl ColumnInfo {
+    /// The type the column is declared with
+    pub fn declared_type(&self) -> &str {
+        self.full_type.as_deref().unwrap_or(&self.data_type)
+    }
+}
+
+/// Database-independent classification of a result column's type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogicalType {
+    Boolean,
+    Integer,
+    Float,
+    Decimal,
+    Text,
+    Uuid,
+    Json,
+    Date,
+    Time,
+    Timestamp,
+    Interval,
+    Binary,
+    Array,
+    #[default]
+    Other,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResultColumn {
+    pub name: String,
+    /// Type name as reported by the driver, e.g. `int4` or `VARCHAR`
+    pub native_type: String,
+    pub logical_type: LogicalType,
+    /// Unknown for expressions and drivers that don't report it
+    pub nullable: Option<bool>,
+    /// Table and column the value was read from, when it maps to one directly
+    pub source_schema: Option<String>,
+    pub source_table: Option<String>,
+    pub source_column: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct QueryResult {
     pub columns: Vec<String>,
+    /// Per-column type and origin details, in the same order as `columns`.
+    /// Empty for providers without typed result sets.
+    pub column_metadata: Vec<ResultColumn>,
     pub rows: Vec<Vec<serde_json::Value>>,
     pub row_count: usize,
+    /// Rows written by INSERT/UPDATE/DELETE and similar statements
+    pub rows_affected: Option<u64>,
+    pub last_insert_id: Option<i64>,
+    /// Statement kind and count, e.g. `UPDATE 42`
+    pub command_tag: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,6 +221,11 @@ pub struct QueryResult {
 pub enum ConnectionParams {
     ConnectionString {
         connection_string: String,
+        /// Overrides any TLS settings carried by the connection string
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tls: Option<TlsConfig>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ssh_tunnel: Option<SshTunnelConfig>,
     },
     Parameters {
         host: String,
@@ -115,19 +233,42 @@ pub enum ConnectionParams {
         database: String,
         username: String,
         password: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tls: Option<TlsConfig>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ssh_tunnel: Option<SshTunnelConfig>,
     },
 }
 
 impl ConnectionParams {
+    /// Explicit TLS settings; `None` leaves TLS to the connection string and
+    /// the provider's default.
+    pub fn tls(&self) -> Option<&TlsConfig> {
+        match self {
+            ConnectionParams::ConnectionString { tls, .. }
+            | ConnectionParams::Parameters { tls, .. } => tls.as_ref(),
+        }
+    }
+
+    pub fn ssh_tunnel(&self) -> Option<&SshTunnelConfig> {
+        match self {
+            ConnectionParams::ConnectionString { ssh_tunnel, .. }
+            | ConnectionParams::Parameters { ssh_tunnel, .. } => ssh_tunnel.as_ref(),
+        }
+    }
+
     pub fn to_postgres_string(&self) -> String {
         match self {
-            ConnectionParams::ConnectionString { connection_string } => connection_string.clone(),
+            ConnectionParams::ConnectionString {
+                connection_string, ..
+            } => connection_string.clone(),
             ConnectionParams::Parameters {
                 host,
                 port,
                 database,
                 username,
                 password,
+                ..
             } => format!(
                 "host={} port={} dbname={} user={} password={}",
                 host, port, database, username, password
@@ -137,13 +278,16 @@ impl ConnectionParams {
 
     pub fn to_mysql_url(&self) -> String {
         match self {
-            ConnectionParams::ConnectionString { connection_string } => connection_string.clone(),
+            ConnectionParams::ConnectionString {
+                connection_string, ..
+            } => connection_string.clone(),
             ConnectionParams::Parameters {
                 host,
                 port,
                 database,
                 username,
                 password,
+                ..
             } => format!(
                 "mysql://{}:{}@{}:{}/{}",
                 username, password, host, port, database
@@ -152,6 +296,38 @@ impl ConnectionParams {
     }
 }
 
+/// Remove a `key=value` option from a keyword/value connection string or URL
+/// query, returning its value.
+pub(crate) fn take_conn_option(conn_string: &mut String, key: &str) -> Option<String> {
+    let pattern = format!("{}=", key);
+    let start = conn_string
+        .match_indices(&pattern)
+        .map(|(i, _)| i)
+        .find(|&i| {
+            conn_string[..i]
+                .chars()
+                .next_back()
+                .is_none_or(|c| c.is_whitespace() || c == '?' || c == '&')
+        })?;
+
+    let value_start = start + pattern.len();
+    let value_end = conn_string[value_start..]
+        .find(|c: char| c.is_whitespace() || c == '&')
+        .map_or(conn_string.len(), |i| value_start + i);
+    let value = conn_string[value_start..value_end].to_string();
+
+    let end = if value_end < conn_string.len() {
+        value_end + 1
+    } else {
+        value_end
+    };
+    conn_string.replace_range(start..end, "");
+    let trimmed = conn_string.trim_end_matches(['&', '?', ' ']).len();
+    conn_string.truncate(trimmed);
+
+    Some(value)
+}
+
 #[async_trait]
 #[allow(dead_code)]
 pub trait DatabaseProvider: Send + Sync + Any {
@@ -171,8 +347,233 @@ pub trait DatabaseProvider: Send + Sync + Any {
         offset: i64,
     ) -> Result<QueryResult, ProviderError>;
     async fn execute_query(&self, query: &str) -> Result<QueryResult, ProviderError>;
+
+    /// Execute a query with positional parameters bound natively by the driver
+    /// (`$1` for Postgres, `?` for MySQL and SQLite).
+    async fn execute_query_with_params(
+        &self,
+        query: &str,
+        params: Vec<serde_json::Value>,
+    ) -> Result<QueryResult, ProviderError> {
+        if params.is_empty() {
+            return self.execute_query(query).await;
+        }
+
+        Err(
+            ProviderError::new("Query parameters are not supported for this database type")
+                .with_hint("Inline the values into the query instead."),
+        )
+    }
+
+    /// Execute a query that can be aborted through `cancel`.
+    /// The default races `execute_query` against the token and drops the
+    /// in-flight future on cancellation (which also kills any open MongoDB
+    /// cursor); SQL providers override this to stop the statement on the server.
+    async fn execute_query_cancellable(
+        &self,
+        query: &str,
+        cancel: &CancelToken,
+    ) -> Result<QueryResult, ProviderError> {
+        tokio::select! {
+            result = self.execute_query(query) => result,
+            _ = cancel.cancelled() => Err(cancel::cancelled_error()),
+        }
+    }
+
+    /// Execute a query and send the result through `tx` in batches of at most
+    /// `batch_size` rows instead of materializing it. Errors are returned, not sent.
+    /// The default runs `execute_query_cancellable` and chunks the result; SQL
+    /// providers override this to read rows off the wire as they arrive.
+    async fn execute_query_stream(
+        &self,
+        query: &str,
+        batch_size: usize,
+        tx: mpsc::Sender<QueryStreamEvent>,
+        cancel: &CancelToken,
+    ) -> Result<(), ProviderError> {
+        let result = self.execute_query_cancellable(query, cancel).await?;
+        stream::stream_query_result(result, batch_size, tx).await
+    }
+
+    /// Split `script` into statements for this dialect and run them in order,
+    /// returning one result or error per executed statement.
+    async fn execute_script(
+        &self,
+        script: &str,
+        on_error: ScriptErrorMode,
+        cancel: &CancelToken,
+    ) -> Result<Vec<StatementResult>, ProviderError> {
+        let mut recorder = script::ScriptRecorder::new(on_error);
+
+        for statement in sql::split_statements(script, self.database_type()) {
+            let started = Instant::now();
+            let result = self.execute_query_cancellable(&statement, cancel).await;
+            if !recorder.record(statement, result, started, cancel) {
+                break;
+            }
+        }
+
+        Ok(recorder.finish())
+    }
+
     async fn get_table_count(&self, schema: &str, table: &str) -> Result<i64, ProviderError>;
 
+    /// A page of table data with filters and ordering applied by the
+    /// database. The default only handles an empty query.
+    async fn get_table_data_filtered(
+        &self,
+        schema: &str,
+        table: &str,
+        query: &TableQuery,
+        limit: i64,
+        offset: i64,
+    ) -> Result<QueryResult, ProviderError> {
+        if query.is_empty() {
+            return self.get_table_data(schema, table, limit, offset).await;
+        }
+        Err(ProviderError::new(
+            "Filtering and sorting are not supported for this database type",
+        ))
+    }
+
+    /// Number of rows matching `filters`
+    async fn get_table_count_filtered(
+        &self,
+        schema: &str,
+        table: &str,
+        filters: &[ColumnFilter],
+    ) -> Result<i64, ProviderError> {
+        if filters.is_empty() {
+            return self.get_table_count(schema, table).await;
+        }
+        Err(ProviderError::new(
+            "Filtering is not supported for this database type",
+        ))
+    }
+
+    /// Insert imported rows into `schema.table` until `batches` closes,
+    /// inside one transaction where the database has them. Rows the database
+    /// rejects are skipped or end the import as `on_error` says.
+    async fn import_rows(
+        &self,
+        _schema: &str,
+        _table: &str,
+        _columns: &[String],
+        _batches: ImportBatches,
+        _on_error: ImportErrorMode,
+    ) -> Result<ImportSummary, ProviderError> {
+        Err(ProviderError::new(
+            "Import is not supported for this database type",
+        ))
+    }
+
+    /// One page of rows ordered by a unique key, continuing after
+    /// `request.cursor`. Unlike `OFFSET` paging, every page costs the same.
+    async fn get_table_page(
+        &self,
+        _schema: &str,
+        _table: &str,
+        _request: &PageRequest,
+    ) -> Result<TablePage, ProviderError> {
+        Err(ProviderError::new(
+            "Keyset pagination is not supported for this database type",
+        ))
+    }
+
+    /// Tables, views, sequences and routines on the connection. The default
+    /// only knows about `list_tables`.
+    async fn list_schema_objects(&self) -> Result<SchemaObjects, ProviderError> {
+        Ok(SchemaObjects {
+            tables: self.list_tables().await?,
+            ..Default::default()
+        })
+    }
+
+    /// Columns, indexes, foreign keys, constraints and triggers of one table.
+    /// The default only knows about `get_table_columns`.
+    async fn describe_table(
+        &self,
+        schema: &str,
+        table: &str,
+    ) -> Result<TableDetails, ProviderError> {
+        Ok(TableDetails {
+            schema: schema.to_string(),
+            name: table.to_string(),
+            columns: self.get_table_columns(schema, table).await?,
+            ..Default::default()
+        })
+    }
+
+    /// SQL that recreates the object, e.g. `CREATE TABLE` with its indexes.
+    async fn get_object_ddl(
+        &self,
+        _schema: &str,
+        _name: &str,
+        _kind: ObjectKind,
+    ) -> Result<String, ProviderError> {
+        Err(ProviderError::new(
+            "DDL generation is not supported for this database type",
+        ))
+    }
+
+    /// Plan of a query as a normalized tree. With `analyze` the statement is
+    /// run to collect actual row counts and timings, inside a transaction
+    /// that is rolled back where the database allows it.
+    async fn explain_query(
+        &self,
+        _query: &str,
+        _analyze: bool,
+    ) -> Result<QueryPlan, ProviderError> {
+        Err(ProviderError::new(
+            "Query plans are not supported for this database type",
+        ))
+    }
+
+    /// Start a transaction on a session reserved for it and return its id.
+    /// Statements sent through `execute_in_transaction` with that id run on
+    /// the same session until it is committed or rolled back.
+    async fn begin_transaction(&self) -> Result<String, ProviderError> {
+        Err(transaction::transactions_unsupported())
+    }
+
+    async fn execute_in_transaction(
+        &self,
+        _transaction_id: &str,
+        _query: &str,
+    ) -> Result<QueryResult, ProviderError> {
+        Err(transaction::transactions_unsupported())
+    }
+
+    async fn commit_transaction(&self, _transaction_id: &str) -> Result<(), ProviderError> {
+        Err(transaction::transactions_unsupported())
+    }
+
+    async fn rollback_transaction(&self, _transaction_id: &str) -> Result<(), ProviderError> {
+        Err(transaction::transactions_unsupported())
+    }
+
+    /// Ids of transactions that have been started but not yet finished
+    fn open_transactions(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Apply inserts, updates and deletes to a table's rows in one
+    /// transaction, identifying rows by primary key. Tables without one are
+    /// refused unless `allow_without_primary_key` is set, in which case rows
+    /// are matched on every value given in their key. Returns the number of
+    /// rows written.
+    async fn apply_row_edits(
+        &self,
+        _schema: &str,
+        _table: &str,
+        _edits: Vec<RowEdit>,
+        _allow_without_primary_key: bool,
+    ) -> Result<u64, ProviderError> {
+        Err(ProviderError::new(
+            "Row editing is not supported for this database type",
+        ))
+    }
+
     /// Insert a document into a collection (primarily for MongoDB)
     /// The document is a JSON object as a string
     async fn insert_document(
@@ -182,7 +583,7 @@ pub trait DatabaseProvider: Send + Sync + Any {
     ) -> Result<String, ProviderError> {
         Err(
             ProviderError::new("insert_document is not supported for this database type")
-                .with_hint("Use SQL INSERT statements via execute_query instead."),
+                .with_hint("Use apply_row_edits to insert rows into SQL tables."),
         )
     }
 
@@ -196,7 +597,7 @@ pub trait DatabaseProvider: Send + Sync + Any {
     ) -> Result<u64, ProviderError> {
         Err(
             ProviderError::new("update_document is not supported for this database type")
-                .with_hint("Use SQL UPDATE statements via execute_query instead."),
+                .with_hint("Use apply_row_edits to update rows of SQL tables."),
         )
     }
 
@@ -209,35 +610,123 @@ pub trait DatabaseProvider: Send + Sync + Any {
     ) -> Result<u64, ProviderError> {
         Err(
             ProviderError::new("delete_document is not supported for this database type")
-                .with_hint("Use SQL DELETE statements via execute_query instead."),
+                .with_hint("Use apply_row_edits to delete rows from SQL tables."),
         )
     }
+
+    /// Infer the fields of a collection's documents, nested ones included,
+    /// from a random sample of `sample_size` documents (MongoDB only)
+    async fn infer_document_schema(
+        &self,
+        _schema: &str,
+        _collection: &str,
+        _sample_size: Option<usize>,
+    ) -> Result<DocumentSchema, ProviderError> {
+        Err(ProviderError::new(
+            "Document schemas can only be inferred for MongoDB collections",
+        ))
+    }
+
+    /// Indexes of a collection with their size and usage (MongoDB only)
+    async fn list_collection_indexes(
+        &self,
+        _schema: &str,
+        _collection: &str,
+    ) -> Result<Vec<CollectionIndex>, ProviderError> {
+        Err(mongo_index::indexes_unsupported())
+    }
+
+    /// Create an index and return its name (MongoDB only)
+    async fn create_collection_index(
+        &self,
+        _schema: &str,
+        _collection: &str,
+        _spec: &IndexSpec,
+    ) -> Result<String, ProviderError> {
+        Err(mongo_index::indexes_unsupported())
+    }
+
+    async fn drop_collection_index(
+        &self,
+        _schema: &str,
+        _collection: &str,
+        _name: &str,
+    ) -> Result<(), ProviderError> {
+        Err(mongo_index::indexes_unsupported())
+    }
+
+    /// Document count and storage sizes of a collection (MongoDB only)
+    async fn collection_stats(
+        &self,
+        _schema: &str,
+        _collection: &str,
+    ) -> Result<CollectionStats, ProviderError> {
+        Err(ProviderError::new(
+            "Collection stats are only available for MongoDB",
+        ))
+    }
+}
+
+/// A connected provider and the SSH tunnel it reaches the database through.
+/// The tunnel must be kept alive for as long as the provider is used.
+pub struct ProviderConnection {
+    pub provider: Box<dyn DatabaseProvider>,
+    pub tunnel: Option<SshTunnel>,
 }
 
 pub async fn create_provider(
     db_type: DatabaseType,
     params: ConnectionParams,
-) -> Result<Box<dyn DatabaseProvider>, ProviderError> {
-    match db_type {
-        DatabaseType::Postgres => {
-            let provider = postgres::PostgresProvider::connect(params).await?;
-            Ok(Box::new(provider))
-        }
-        DatabaseType::Mysql => {
-            let provider = mysql::MysqlProvider::connect(params).await?;
-            Ok(Box::new(provider))
-        }
-        DatabaseType::Sqlite => {
-            let provider = sqlite::SqliteProvider::connect(params).await?;
-            Ok(Box::new(provider))
-        }
-        DatabaseType::Redis => {
-            let provider = redis::RedisProvider::connect(params).await?;
-            Ok(Box::new(provider))
-        }
-        DatabaseType::Mongodb => {
-            let provider = mongodb::MongoDbProvider::connect(params).await?;
-            Ok(Box::new(provider))
+) -> Result<ProviderConnection, ProviderError> {
+    let (params, tunnel) = match params.ssh_tunnel().cloned() {
+        Some(config) => {
+            let (host, port) = ssh::tunnel_target(&params, db_type)?;
+            let host_name = host.clone();
+            let tunnel = tokio::task::spawn_blocking(move || SshTunnel::open(&config, &host, port))
+                .await
+                .map_err(|e| ProviderError::new(format!("SSH tunnel task failed: {}", e)))??;
+            (
+                ssh::through_tunnel(&params, db_type, &host_name, tunnel.local_port()),
+                Some(tunnel),
+            )
         }
+        None => (params, None),
+    };
+
+    let provider: Box<dyn DatabaseProvider> = match db_type {
+        DatabaseType::Postgres => Box::new(postgres::PostgresProvider::connect(params).await?),
+        DatabaseType::Mysql => Box::new(mysql::MysqlProvider::connect(params).await?),
+        DatabaseType::Sqlite => Box::new(sqlite::SqliteProvider::connect(params).await?),
+        DatabaseType::Redis => Box::new(redis::RedisProvider::connect(params).await?),
+        DatabaseType::Mongodb => Box::new(mongodb::MongoDbProvider::connect(params).await?),
+    };
+
+    Ok(ProviderConnection { provider, tunnel })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_conn_option_handles_both_formats() {
+        let mut conn = "host=db sslmode=verify-full user=app".to_string();
+        assert_eq!(
+            take_conn_option(&mut conn, "sslmode").as_deref(),
+            Some("verify-full")
+        );
+        assert_eq!(conn, "host=db user=app");
+
+        let mut url = "postgres://app@db/main?sslmode=require&sslrootcert=/ca.pem".to_string();
+        assert_eq!(
+            take_conn_option(&mut url, "sslmode").as_deref(),
+            Some("require")
+        );
+        assert_eq!(
+            take_conn_option(&mut url, "sslrootcert").as_deref(),
+            Some("/ca.pem")
+        );
+        assert_eq!(url, "postgres://app@db/main");
+        assert_eq!(take_conn_option(&mut url, "sslkey"), None);
     }
 }
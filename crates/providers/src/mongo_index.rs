@@ -0,0 +1,360 @@
+//! MongoDB index management: a collection's indexes with their size and
+//! usage, the specification of new ones, and collection storage stats.
+//! Sizes come from `$collStats`, usage counters from `$indexStats`.
+
+use crate::mongodb::MongoDbProvider;
+use crate::ProviderError;
+use mongodb::bson::{Bson, Document};
+use mongodb::options::IndexOptions;
+use mongodb::IndexModel;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How a field is indexed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexKeyKind {
+    Ascending,
+    Descending,
+    Text,
+    Hashed,
+    #[serde(rename = "2d")]
+    Geo2d,
+    #[serde(rename = "2dsphere")]
+    Geo2dSphere,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexKey {
+    pub field: String,
+    pub kind: IndexKeyKind,
+}
+
+/// An index to create. One key makes a single-field index, several a
+/// compound one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexSpec {
+    pub keys: Vec<IndexKey>,
+    /// Generated from the keys when unset, e.g. `email_1`
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub unique: bool,
+    #[serde(default)]
+    pub sparse: bool,
+    /// Makes a TTL index: documents are removed this many seconds after
+    /// the date in the indexed field
+    #[serde(default)]
+    pub expire_after_seconds: Option<u64>,
+    /// Extended JSON filter; only the documents matching it are indexed
+    #[serde(default)]
+    pub partial_filter: Option<Value>,
+    /// Relative weights of the fields of a text index
+    #[serde(default)]
+    pub weights: Option<serde_json::Map<String, Value>>,
+    #[serde(default)]
+    pub default_language: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionIndex {
+    pub name: String,
+    /// Key fields in index order. A text index lists each indexed field.
+    pub keys: Vec<IndexKey>,
+    pub unique: bool,
+    pub sparse: bool,
+    pub hidden: bool,
+    pub expire_after_seconds: Option<u64>,
+    /// Extended JSON
+    pub partial_filter: Option<Value>,
+    pub weights: Option<Value>,
+    /// Bytes on disk; unset when the stats cannot be read
+    pub size: Option<u64>,
+    /// Unset when the stats cannot be read
+    pub usage: Option<IndexUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexUsage {
+    /// Operations that used the index since `since`
+    pub ops: u64,
+    /// When counting started (RFC 3339): the server's start or the index's
+    /// creation. Counters are per server and summed over shards.
+    pub since: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CollectionStats {
+    pub documents: u64,
+    /// Uncompressed size of the documents in bytes
+    pub size: u64,
+    pub average_document_size: u64,
+    /// Bytes allocated on disk
+    pub storage_size: u64,
+    /// Allocated bytes that are free for reuse
+    pub free_storage_size: u64,
+    pub indexes: u64,
+    pub total_index_size: u64,
+    pub capped: bool,
+}
+
+impl IndexKeyKind {
+    fn to_bson(self) -> Bson {
+        match self {
+            Self::Ascending => Bson::Int32(1),
+            Self::Descending => Bson::Int32(-1),
+            Self::Text => Bson::String("text".to_string()),
+            Self::Hashed => Bson::String("hashed".to_string()),
+            Self::Geo2d => Bson::String("2d".to_string()),
+            Self::Geo2dSphere => Bson::String("2dsphere".to_string()),
+        }
+    }
+
+    fn from_bson(value: &Bson) -> Option<Self> {
+        match value {
+            Bson::String(kind) => match kind.as_str() {
+                "text" => Some(Self::Text),
+                "hashed" => Some(Self::Hashed),
+                "2d" => Some(Self::Geo2d),
+                "2dsphere" => Some(Self::Geo2dSphere),
+                _ => None,
+            },
+            other => number(other).map(|direction| {
+                if direction < 0.0 {
+                    Self::Descending
+                } else {
+                    Self::Ascending
+                }
+            }),
+        }
+    }
+}
+
+pub(crate) fn indexes_unsupported() -> ProviderError {
+    ProviderError::new("Collection indexes can only be managed for MongoDB")
+        .with_hint("Manage indexes of SQL tables with CREATE INDEX and DROP INDEX.")
+}
+
+fn number(value: &Bson) -> Option<f64> {
+    match value {
+        Bson::Int32(n) => Some(*n as f64),
+        Bson::Int64(n) => Some(*n as f64),
+        Bson::Double(n) => Some(*n),
+        _ => None,
+    }
+}
+
+fn count(document: &Document, key: &str) -> u64 {
+    document.get(key).and_then(number).unwrap_or(0.0) as u64
+}
+
+/// An Extended JSON object as a document
+fn json_document(value: Value, what: &str) -> Result<Document, ProviderError> {
+    match Bson::try_from(value) {
+        Ok(Bson::Document(document)) => Ok(document),
+        Ok(_) => Err(ProviderError::new(format!("{} must be an object", what))),
+        Err(e) => Err(ProviderError::new(format!("Invalid {}: {}", what, e))),
+    }
+}
+
+/// The index `spec` describes, checked for options MongoDB would reject
+pub(crate) fn index_model(spec: &IndexSpec) -> Result<IndexModel, ProviderError> {
+    if spec.keys.is_empty() {
+        return Err(ProviderError::new("An index needs at least one key field"));
+    }
+    if spec.expire_after_seconds.is_some()
+        && !matches!(
+            spec.keys.as_slice(),
+            [IndexKey {
+                kind: IndexKeyKind::Ascending | IndexKeyKind::Descending,
+                ..
+            }]
+        )
+    {
+        return Err(ProviderError::new(
+            "A TTL index must have a single ascending or descending key",
+        )
+        .with_hint("Index only the date field documents expire by."));
+    }
+    let text = spec.keys.iter().any(|key| key.kind == IndexKeyKind::Text);
+    if !text && (spec.weights.is_some() || spec.default_language.is_some()) {
+        return Err(ProviderError::new(
+            "Weights and a default language only apply to text indexes",
+        ));
+    }
+
+    let keys: Document = spec
+        .keys
+        .iter()
+        .map(|key| (key.field.clone(), key.kind.to_bson()))
+        .collect();
+    let partial_filter = spec
+        .partial_filter
+        .clone()
+        .map(|filter| json_document(filter, "partial filter"))
+        .transpose()?;
+    let weights = spec
+        .weights
+        .clone()
+        .map(|weights| json_document(Value::Object(weights), "weights"))
+        .transpose()?;
+    let options = IndexOptions::builder()
+        .name(spec.name.clone().filter(|name| !name.is_empty()))
+        .unique(spec.unique.then_some(true))
+        .sparse(spec.sparse.then_some(true))
+        .expire_after(spec.expire_after_seconds.map(Duration::from_secs))
+        .partial_filter_expression(partial_filter)
+        .weights(weights)
+        .default_language(spec.default_language.clone())
+        .build();
+    Ok(IndexModel::builder().keys(keys).options(options).build())
+}
+
+/// A listed index, without its size and usage
+pub(crate) fn collection_index(model: IndexModel) -> CollectionIndex {
+    let options = model.options.unwrap_or_default();
+    let mut keys = Vec::new();
+    for (field, value) in &model.keys {
+        // Text indexes key on internal fields; the indexed fields are the weights
+        match field.as_str() {
+            "_fts" => keys.extend(options.weights.iter().flat_map(|weights| {
+                weights.keys().map(|field| IndexKey {
+                    field: field.clone(),
+                    kind: IndexKeyKind::Text,
+                })
+            })),
+            "_ftsx" => {}
+            _ => keys.extend(IndexKeyKind::from_bson(value).map(|kind| IndexKey {
+                field: field.clone(),
+                kind,
+            })),
+        }
+    }
+    let to_json = |document: Document| MongoDbProvider::bson_to_json(&Bson::Document(document));
+    CollectionIndex {
+        name: options.name.unwrap_or_default(),
+        keys,
+        unique: options.unique.unwrap_or(false),
+        sparse: options.sparse.unwrap_or(false),
+        hidden: options.hidden.unwrap_or(false),
+        expire_after_seconds: options.expire_after.map(|ttl| ttl.as_secs()),
+        partial_filter: options.partial_filter_expression.map(to_json),
+        weights: options.weights.map(to_json),
+        size: None,
+        usage: None,
+    }
+}
+
+/// Collection stats summed over the `storageStats` of each shard
+pub(crate) fn collection_stats(storage: &[Document]) -> CollectionStats {
+    let mut stats = CollectionStats::default();
+    for shard in storage {
+        stats.documents += count(shard, "count");
+        stats.size += count(shard, "size");
+        stats.storage_size += count(shard, "storageSize");
+        stats.free_storage_size += count(shard, "freeStorageSize");
+        stats.total_index_size += count(shard, "totalIndexSize");
+        stats.indexes = stats.indexes.max(count(shard, "nindexes"));
+        stats.capped |= shard.get_bool("capped").unwrap_or(false);
+    }
+    stats.average_document_size = stats.size.checked_div(stats.documents).unwrap_or(0);
+    stats
+}
+
+/// Index sizes by name, summed over the `storageStats` of each shard
+pub(crate) fn index_sizes(storage: &[Document]) -> HashMap<String, u64> {
+    let mut sizes = HashMap::new();
+    for sizes_of_shard in storage
+        .iter()
+        .filter_map(|s| s.get_document("indexSizes").ok())
+    {
+        for (name, size) in sizes_of_shard {
+            *sizes.entry(name.clone()).or_default() += number(size).unwrap_or(0.0) as u64;
+        }
+    }
+    sizes
+}
+
+/// Usage by index name from the documents `$indexStats` returns, one per
+/// index and server
+pub(crate) fn index_usage(stats: &[Document]) -> HashMap<String, IndexUsage> {
+    let mut usage: HashMap<String, (u64, Option<mongodb::bson::DateTime>)> = HashMap::new();
+    for stat in stats {
+        let (Ok(name), Ok(accesses)) = (stat.get_str("name"), stat.get_document("accesses")) else {
+            continue;
+        };
+        let entry = usage.entry(name.to_string()).or_default();
+        entry.0 += count(accesses, "ops");
+        if let Ok(since) = accesses.get_datetime("since") {
+            entry.1 = Some(entry.1.map_or(*since, |earliest| earliest.min(*since)));
+        }
+    }
+    usage
+        .into_iter()
+        .map(|(name, (ops, since))| {
+            let since = since.and_then(|since| since.try_to_rfc3339_string().ok());
+            (name, IndexUsage { ops, since })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::doc;
+    use serde_json::json;
+
+    #[test]
+    fn builds_index_models() {
+        let spec: IndexSpec = serde_json::from_value(json!({
+            "keys": [
+                {"field": "email", "kind": "ascending"},
+                {"field": "created", "kind": "descending"},
+            ],
+            "unique": true,
+            "partial_filter": {"created": {"$gt": {"$date": "2024-01-01T00:00:00Z"}}},
+        }))
+        .unwrap();
+        let model = index_model(&spec).unwrap();
+        assert_eq!(model.keys, doc! { "email": 1, "created": -1 });
+        let index = collection_index(model);
+        assert_eq!(index.keys, spec.keys);
+        assert!(index.unique);
+        assert_eq!(index.partial_filter, spec.partial_filter);
+
+        let ttl = IndexSpec {
+            expire_after_seconds: Some(3600),
+            ..spec.clone()
+        };
+        assert!(index_model(&ttl).is_err());
+        let ttl = IndexSpec {
+            keys: spec.keys[1..].to_vec(),
+            ..ttl
+        };
+        assert_eq!(
+            collection_index(index_model(&ttl).unwrap()).expire_after_seconds,
+            Some(3600)
+        );
+    }
+
+    #[test]
+    fn lists_text_index_fields() {
+        let model = IndexModel::builder()
+            .keys(doc! { "_fts": "text", "_ftsx": 1 })
+            .options(
+                IndexOptions::builder()
+                    .name("title_text_body_text".to_string())
+                    .weights(doc! { "body": 1, "title": 10 })
+                    .build(),
+            )
+            .build();
+        let fields: Vec<String> = collection_index(model)
+            .keys
+            .into_iter()
+            .map(|key| key.field)
+            .collect();
+        assert_eq!(fields, ["body", "title"]);
+    }
+}
@@ -0,0 +1,697 @@
+//! Queries typed into the MongoDB editor: mongosh-style calls such as
+//! `db.users.find({age: {$gt: 30}}).sort({name: 1}).limit(10)`, or a command
+//! document in JSON, which is run as `db.runCommand(...)`.
+//!
+//! Arguments are JavaScript object literals: keys may be unquoted, strings
+//! single-quoted, and `ObjectId(...)`, `ISODate(...)`, `new Date(...)`,
+//! `NumberLong(...)`, `NumberInt(...)`, `NumberDecimal(...)` and regex
+//! literals are understood. Extended JSON (`{"$oid": ...}`) works as well.
+
+use crate::ProviderError;
+use mongodb::bson::{Bson, Document};
+use mongodb::options::UpdateModifications;
+use serde_json::{json, Map, Value};
+
+const SUPPORTED_METHODS: &str = "Supported are find, findOne, aggregate, countDocuments, \
+    distinct, insertOne, insertMany, updateOne, updateMany, deleteOne and deleteMany.";
+
+#[derive(Debug)]
+pub(crate) enum MongoQuery {
+    Find {
+        collection: String,
+        filter: Document,
+        projection: Option<Document>,
+        sort: Option<Document>,
+        skip: Option<u64>,
+        limit: Option<i64>,
+    },
+    Aggregate {
+        collection: String,
+        pipeline: Vec<Document>,
+    },
+    Count {
+        collection: String,
+        filter: Document,
+    },
+    Distinct {
+        collection: String,
+        field: String,
+        filter: Document,
+    },
+    Insert {
+        collection: String,
+        documents: Vec<Document>,
+    },
+    Update {
+        collection: String,
+        filter: Document,
+        update: UpdateModifications,
+        many: bool,
+        upsert: bool,
+    },
+    Delete {
+        collection: String,
+        filter: Document,
+        many: bool,
+    },
+    /// A database command, e.g. `{"collStats": "users"}`
+    Command(Document),
+}
+
+impl MongoQuery {
+    /// Whether running the query cannot change data. Commands are never
+    /// considered read-only, and neither are pipelines that write with
+    /// `$out` or `$merge`.
+    pub(crate) fn is_read_only(&self) -> bool {
+        match self {
+            Self::Find { .. } | Self::Count { .. } | Self::Distinct { .. } => true,
+            Self::Aggregate { pipeline, .. } => !pipeline
+                .iter()
+                .any(|stage| stage.contains_key("$out") || stage.contains_key("$merge")),
+            _ => false,
+        }
+    }
+}
+
+/// Whether `query` parses and only reads data
+pub fn is_read_only(query: &str) -> bool {
+    parse(query).is_ok_and(|query| query.is_read_only())
+}
+
+pub(crate) fn parse(text: &str) -> Result<MongoQuery, ProviderError> {
+    let mut parser = Parser::new(text);
+    let query = parser.query()?;
+    parser.skip_whitespace();
+    while parser.eat(';') {
+        parser.skip_whitespace();
+    }
+    if parser.pos < parser.chars.len() {
+        return Err(parser.error("Unexpected text after the query"));
+    }
+    Ok(query)
+}
+
+/// A method call in the chain, e.g. `sort({a: 1})`
+struct Call {
+    name: String,
+    args: Vec<Value>,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(text: &str) -> Self {
+        Self {
+            chars: text.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn error(&self, message: &str) -> ProviderError {
+        ProviderError::new(format!(
+            "Invalid query: {} at position {}",
+            message,
+            self.pos + 1
+        ))
+        .with_hint(
+            "Write a call such as db.users.find({age: {$gt: 30}}).limit(10), \
+             or a command document in JSON.",
+        )
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ProviderError> {
+        self.skip_whitespace();
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", c)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match (self.peek(), self.chars.get(self.pos + 1)) {
+                (Some(c), _) if c.is_whitespace() => self.pos += 1,
+                (Some('/'), Some('/')) => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.pos += 1;
+                    }
+                }
+                (Some('/'), Some('*')) => {
+                    self.pos += 2;
+                    while self.pos < self.chars.len()
+                        && !(self.peek() == Some('*') && self.chars.get(self.pos + 1) == Some(&'/'))
+                    {
+                        self.pos += 1;
+                    }
+                    self.pos = (self.pos + 2).min(self.chars.len());
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn identifier(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        {
+            self.pos += 1;
+        }
+        (self.pos > start).then(|| self.chars[start..self.pos].iter().collect())
+    }
+
+    fn query(&mut self) -> Result<MongoQuery, ProviderError> {
+        self.skip_whitespace();
+        if self.peek() == Some('{') {
+            return Ok(MongoQuery::Command(document(self.value()?, "The command")?));
+        }
+        if self.identifier().as_deref() != Some("db") {
+            return Err(self.error("Expected db"));
+        }
+
+        // db.name.method(...), db.getCollection("name").method(...) or
+        // db["name"].method(...); names may contain dots
+        let mut collection: Vec<String> = Vec::new();
+        let method = loop {
+            self.skip_whitespace();
+            if collection.is_empty() && self.eat('[') {
+                self.skip_whitespace();
+                collection.push(self.string()?);
+                self.expect(']')?;
+                continue;
+            }
+            if !self.eat('.') {
+                return Err(self.error("Expected a method call"));
+            }
+            let name = self
+                .identifier()
+                .ok_or_else(|| self.error("Expected a name"))?;
+            self.skip_whitespace();
+            if self.peek() == Some('(') {
+                break name;
+            }
+            collection.push(name);
+        };
+        let args = self.arguments()?;
+
+        let (collection, call) = match (collection.is_empty(), method.as_str()) {
+            (true, "runCommand") => {
+                let command = args.into_iter().next().unwrap_or(Value::Null);
+                return Ok(MongoQuery::Command(document(command, "The command")?));
+            }
+            (true, "getCollection") => {
+                let Some(Value::String(name)) = args.into_iter().next() else {
+                    return Err(self.error("Expected a collection name"));
+                };
+                self.expect('.')?;
+                let method = self
+                    .identifier()
+                    .ok_or_else(|| self.error("Expected a method call"))?;
+                let args = self.arguments()?;
+                (name, Call { name: method, args })
+            }
+            (true, _) => return Err(self.error(&format!("Unsupported database method {}", method))),
+            (false, _) => (collection.join("."), Call { name: method, args }),
+        };
+
+        let mut chain = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if !self.eat('.') {
+                break;
+            }
+            let name = self
+                .identifier()
+                .ok_or_else(|| self.error("Expected a method call"))?;
+            let args = self.arguments()?;
+            chain.push(Call { name, args });
+        }
+        build(collection, call, chain)
+    }
+
+    fn arguments(&mut self) -> Result<Vec<Value>, ProviderError> {
+        self.expect('(')?;
+        let mut args = Vec::new();
+        self.skip_whitespace();
+        if self.eat(')') {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.value()?);
+            self.skip_whitespace();
+            if self.eat(')') {
+                return Ok(args);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ProviderError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.eat(']') {
+                        return Ok(Value::Array(items));
+                    }
+                    items.push(self.value()?);
+                    self.skip_whitespace();
+                    if !self.eat(',') {
+                        self.expect(']')?;
+                        return Ok(Value::Array(items));
+                    }
+                }
+            }
+            Some('"' | '\'') => self.string().map(Value::String),
+            Some('/') => self.regex(),
+            Some(c) if c == '-' || c == '.' || c.is_ascii_digit() => self.number(),
+            Some(_) => self.constant(),
+            None => Err(self.error("Unexpected end of query")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, ProviderError> {
+        self.expect('{')?;
+        let mut map = Map::new();
+        loop {
+            self.skip_whitespace();
+            if self.eat('}') {
+                return Ok(Value::Object(map));
+            }
+            let key = match self.peek() {
+                Some('"' | '\'') => self.string()?,
+                _ => self
+                    .identifier()
+                    .ok_or_else(|| self.error("Expected a field name"))?,
+            };
+            self.expect(':')?;
+            let value = self.value()?;
+            map.insert(key, value);
+            self.skip_whitespace();
+            if !self.eat(',') {
+                self.expect('}')?;
+                return Ok(Value::Object(map));
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ProviderError> {
+        let Some(quote @ ('"' | '\'')) = self.peek() else {
+            return Err(self.error("Expected a string"));
+        };
+        self.pos += 1;
+        let mut text = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("Unterminated string"));
+            };
+            self.pos += 1;
+            match c {
+                c if c == quote => return Ok(text),
+                '\\' => {
+                    let Some(escaped) = self.peek() else {
+                        return Err(self.error("Unterminated string"));
+                    };
+                    self.pos += 1;
+                    match escaped {
+                        'n' => text.push('\n'),
+                        't' => text.push('\t'),
+                        'r' => text.push('\r'),
+                        'b' => text.push('\u{8}'),
+                        'f' => text.push('\u{c}'),
+                        'u' => {
+                            let hex: String = self.chars.iter().skip(self.pos).take(4).collect();
+                            let code = u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("Invalid \\u escape"))?;
+                            self.pos += 4;
+                            text.push(code);
+                        }
+                        other => text.push(other),
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, ProviderError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        if let Ok(i) = text.parse::<i64>() {
+            return Ok(Value::from(i));
+        }
+        text.parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(|| self.error(&format!("Invalid number {}", text)))
+    }
+
+    /// `/pattern/flags`, as Extended JSON
+    fn regex(&mut self) -> Result<Value, ProviderError> {
+        self.pos += 1;
+        let mut pattern = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("Unterminated regular expression")),
+                Some('/') => break,
+                Some('\\') => {
+                    pattern.push('\\');
+                    self.pos += 1;
+                    if let Some(c) = self.peek() {
+                        pattern.push(c);
+                    }
+                }
+                Some(c) => pattern.push(c),
+            }
+            self.pos += 1;
+        }
+        self.pos += 1;
+        let mut options: Vec<char> = Vec::new();
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_alphabetic()) {
+            options.push(c);
+            self.pos += 1;
+        }
+        options.sort_unstable();
+        Ok(json!({ "$regularExpression": {
+            "pattern": pattern,
+            "options": options.into_iter().collect::<String>(),
+        }}))
+    }
+
+    /// Literals and the shell's type constructors, as Extended JSON
+    fn constant(&mut self) -> Result<Value, ProviderError> {
+        let start = self.pos;
+        let mut name = self
+            .identifier()
+            .ok_or_else(|| self.error("Unexpected character"))?;
+        match name.as_str() {
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            "null" | "undefined" => return Ok(Value::Null),
+            "new" => {
+                name = self
+                    .identifier()
+                    .ok_or_else(|| self.error("Expected a constructor"))?;
+            }
+            _ => {}
+        }
+        self.skip_whitespace();
+        if self.peek() != Some('(') {
+            self.pos = start;
+            return Err(self.error(&format!("Unknown value {}", name)));
+        }
+        let args = self.arguments()?;
+        let arg = args.into_iter().next();
+        let text = |value: &Option<Value>| match value {
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(Value::Number(n)) => Some(n.to_string()),
+            _ => None,
+        };
+        let value = match (name.as_str(), &arg) {
+            ("ObjectId", Some(Value::String(hex))) => json!({ "$oid": hex }),
+            ("ObjectId", None) => json!({ "$oid": mongodb::bson::oid::ObjectId::new().to_hex() }),
+            ("ISODate" | "Date", None) => {
+                let millis = chrono::Utc::now().timestamp_millis();
+                json!({ "$date": { "$numberLong": millis.to_string() } })
+            }
+            ("ISODate" | "Date", Some(Value::String(date))) => {
+                // Dates without a time are midnight UTC, as in the shell
+                let date = if date.len() == 10 {
+                    format!("{}T00:00:00Z", date)
+                } else {
+                    date.clone()
+                };
+                json!({ "$date": date })
+            }
+            ("ISODate" | "Date", Some(Value::Number(millis))) => {
+                json!({ "$date": { "$numberLong": millis.to_string() } })
+            }
+            ("NumberInt", Some(_)) => {
+                let n = text(&arg)
+                    .and_then(|t| t.parse::<i32>().ok())
+                    .ok_or_else(|| self.error("Invalid NumberInt"))?;
+                Value::from(n)
+            }
+            ("NumberLong", Some(_)) => json!({ "$numberLong": text(&arg).unwrap_or_default() }),
+            ("NumberDecimal", Some(_)) => {
+                json!({ "$numberDecimal": text(&arg).unwrap_or_default() })
+            }
+            _ => {
+                self.pos = start;
+                return Err(self.error(&format!("Unsupported constructor {}", name)));
+            }
+        };
+        Ok(value)
+    }
+}
+
+/// A query argument as a document, converting Extended JSON
+fn document(value: Value, what: &str) -> Result<Document, ProviderError> {
+    if !value.is_object() {
+        return Err(ProviderError::new(format!("{} must be a document", what)));
+    }
+    match Bson::try_from(value) {
+        Ok(Bson::Document(document)) => Ok(document),
+        Ok(_) => Err(ProviderError::new(format!("{} must be a document", what))),
+        Err(e) => Err(ProviderError::new(format!("{} is not valid: {}", what, e))),
+    }
+}
+
+fn documents(value: Value, what: &str) -> Result<Vec<Document>, ProviderError> {
+    match value {
+        Value::Array(items) => items.into_iter().map(|item| document(item, what)).collect(),
+        _ => Err(ProviderError::new(format!("{} must be an array", what))),
+    }
+}
+
+fn optional_document(value: Option<Value>, what: &str) -> Result<Document, ProviderError> {
+    match value {
+        None | Some(Value::Null) => Ok(Document::new()),
+        Some(value) => document(value, what),
+    }
+}
+
+fn integer(value: Option<Value>, method: &str) -> Result<i64, ProviderError> {
+    value
+        .as_ref()
+        .and_then(Value::as_i64)
+        .ok_or_else(|| ProviderError::new(format!("{}() takes a number", method)))
+}
+
+fn build(collection: String, call: Call, chain: Vec<Call>) -> Result<MongoQuery, ProviderError> {
+    let mut args = call.args.into_iter();
+    let query = match call.name.as_str() {
+        "find" | "findOne" => {
+            let filter = optional_document(args.next(), "The filter")?;
+            let projection = args
+                .next()
+                .map(|p| document(p, "The projection"))
+                .transpose()?;
+            let limit = (call.name == "findOne").then_some(1);
+            MongoQuery::Find {
+                collection,
+                filter,
+                projection,
+                sort: None,
+                skip: None,
+                limit,
+            }
+        }
+        "aggregate" => {
+            // The pipeline as an array, or as stages passed one by one
+            let pipeline = match args.next() {
+                Some(Value::Array(stages)) => documents(Value::Array(stages), "A pipeline stage")?,
+                Some(stage) => std::iter::once(stage)
+                    .chain(args)
+                    .map(|stage| document(stage, "A pipeline stage"))
+                    .collect::<Result<_, _>>()?,
+                None => Vec::new(),
+            };
+            MongoQuery::Aggregate {
+                collection,
+                pipeline,
+            }
+        }
+        "countDocuments" | "count" | "estimatedDocumentCount" => MongoQuery::Count {
+            collection,
+            filter: optional_document(args.next(), "The filter")?,
+        },
+        "distinct" => {
+            let Some(Value::String(field)) = args.next() else {
+                return Err(ProviderError::new("distinct() takes a field name"));
+            };
+            MongoQuery::Distinct {
+                collection,
+                field,
+                filter: optional_document(args.next(), "The filter")?,
+            }
+        }
+        "insertOne" => MongoQuery::Insert {
+            collection,
+            documents: vec![optional_document(args.next(), "The document")?],
+        },
+        "insertMany" => MongoQuery::Insert {
+            collection,
+            documents: documents(args.next().unwrap_or(Value::Null), "The documents")?,
+        },
+        "updateOne" | "updateMany" => {
+            let filter = optional_document(args.next(), "The filter")?;
+            let update = match args.next() {
+                Some(Value::Array(stages)) => UpdateModifications::Pipeline(documents(
+                    Value::Array(stages),
+                    "A pipeline stage",
+                )?),
+                Some(update) => UpdateModifications::Document(document(update, "The update")?),
+                None => {
+                    return Err(ProviderError::new(format!(
+                        "{}() takes an update",
+                        call.name
+                    )))
+                }
+            };
+            let options = optional_document(args.next(), "The options")?;
+            MongoQuery::Update {
+                collection,
+                filter,
+                update,
+                many: call.name == "updateMany",
+                upsert: options.get_bool("upsert").unwrap_or(false),
+            }
+        }
+        "deleteOne" | "deleteMany" => MongoQuery::Delete {
+            collection,
+            filter: optional_document(args.next(), "The filter")?,
+            many: call.name == "deleteMany",
+        },
+        other => {
+            return Err(
+                ProviderError::new(format!("Unsupported collection method {}", other))
+                    .with_hint(SUPPORTED_METHODS),
+            )
+        }
+    };
+
+    let mut query = query;
+    for call in chain {
+        let mut args = call.args.into_iter();
+        match (&mut query, call.name.as_str()) {
+            (_, "toArray" | "pretty") => {}
+            (MongoQuery::Find { sort, .. }, "sort") => {
+                *sort = Some(document(args.next().unwrap_or(Value::Null), "The sort")?);
+            }
+            (MongoQuery::Find { projection, .. }, "projection") => {
+                *projection = Some(document(
+                    args.next().unwrap_or(Value::Null),
+                    "The projection",
+                )?);
+            }
+            (MongoQuery::Find { limit, .. }, "limit") => {
+                *limit = Some(integer(args.next(), "limit")?)
+            }
+            (MongoQuery::Find { skip, .. }, "skip") => {
+                *skip = Some(integer(args.next(), "skip")?.max(0) as u64)
+            }
+            (_, name) => {
+                return Err(ProviderError::new(format!(
+                    "Unsupported cursor method {}",
+                    name
+                )));
+            }
+        }
+    }
+    Ok(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::{doc, oid::ObjectId, Regex};
+
+    #[test]
+    fn parses_shell_calls() {
+        let query = parse(concat!(
+            "db.users.find({_id: ObjectId('65a1b2c3d4e5f60718293a4b'), name: /^jo/i, ",
+            "'age': {$gte: 21}}, {name: 1})\n  .sort({age: -1, name: 1}).skip(5).limit(10);",
+        ))
+        .unwrap();
+        let MongoQuery::Find {
+            collection,
+            filter,
+            projection,
+            sort,
+            skip,
+            limit,
+        } = query
+        else {
+            panic!("expected find, got {:?}", query);
+        };
+        assert_eq!(collection, "users");
+        assert_eq!(
+            filter,
+            doc! {
+                "_id": ObjectId::parse_str("65a1b2c3d4e5f60718293a4b").unwrap(),
+                "name": Regex { pattern: "^jo".to_string(), options: "i".to_string() },
+                "age": { "$gte": 21 },
+            }
+        );
+        assert_eq!(projection, Some(doc! { "name": 1 }));
+        assert_eq!(sort, Some(doc! { "age": -1, "name": 1 }));
+        assert_eq!((skip, limit), (Some(5), Some(10)));
+
+        let query = parse(concat!(
+            r#"db.getCollection("log.events").aggregate("#,
+            r#"[{$match: {at: {$gt: ISODate("2024-01-01")}}}, {$count: "n"}])"#,
+        ))
+        .unwrap();
+        let MongoQuery::Aggregate {
+            collection,
+            pipeline,
+        } = &query
+        else {
+            panic!("expected aggregate, got {:?}", query);
+        };
+        assert_eq!(collection, "log.events");
+        assert_eq!(pipeline.len(), 2);
+        assert!(query.is_read_only());
+
+        assert!(matches!(
+            parse(r#"{"collStats": "users"}"#).unwrap(),
+            MongoQuery::Command(command) if command == doc! { "collStats": "users" }
+        ));
+        assert!(!is_read_only("db.users.deleteMany({})"));
+        assert!(!is_read_only("db.users.aggregate([{$out: 'copy'}])"));
+        assert!(parse("db.users.find({a: })").is_err());
+    }
+}
@@ -0,0 +1,261 @@
+//! Infer the shape of a MongoDB collection from a random sample of its
+//! documents. Every field path is recorded, including those inside nested
+//! documents and arrays of documents, with how often each BSON type occurs
+//! there and how many of the sampled documents have it.
+
+use mongodb::bson::{Bson, Document};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Documents sampled when no sample size is given
+pub const DEFAULT_SAMPLE_SIZE: usize = 1000;
+
+/// Documents sampled for `get_table_columns`, which runs on every page load,
+/// copy, import and diff
+pub const COLUMNS_SAMPLE_SIZE: usize = 100;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentSchema {
+    /// Documents the schema was inferred from
+    pub sampled: u64,
+    /// Top-level fields, `_id` first and the rest by name
+    pub fields: Vec<FieldInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldInfo {
+    pub name: String,
+    /// Dotted path from the document root, e.g. `address.city`. Fields of
+    /// documents inside arrays are reached through the array's path, as in
+    /// MongoDB queries.
+    pub path: String,
+    /// Occurrences of each type at this path, most frequent first
+    pub types: Vec<TypeCount>,
+    /// Types of the elements of arrays at this path
+    pub item_types: Vec<TypeCount>,
+    /// Sampled documents that have this path
+    pub count: u64,
+    /// `count` as a percentage of the sampled documents
+    pub presence: f64,
+    /// Fields of documents at this path, or of documents inside arrays here
+    pub fields: Vec<FieldInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TypeCount {
+    pub type_name: &'static str,
+    pub count: u64,
+}
+
+impl FieldInfo {
+    /// The column type shown for the field: its types joined by `|`,
+    /// leaving out null unless there is nothing else
+    pub fn data_type(&self) -> String {
+        let mut names: Vec<&str> = self
+            .types
+            .iter()
+            .map(|t| t.type_name)
+            .filter(|name| *name != "null")
+            .collect();
+        if names.is_empty() {
+            return "null".to_string();
+        }
+        names.sort_unstable();
+        names.join(" | ")
+    }
+
+    /// Dotted paths of every field below this one, parents before their
+    /// children
+    pub fn nested_paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        for field in &self.fields {
+            paths.push(field.path.clone());
+            paths.extend(field.nested_paths());
+        }
+        paths
+    }
+}
+
+/// Accumulates sampled documents into a `DocumentSchema`
+#[derive(Default)]
+pub(crate) struct SchemaBuilder {
+    sampled: u64,
+    fields: BTreeMap<String, FieldStats>,
+}
+
+#[derive(Default)]
+struct FieldStats {
+    types: BTreeMap<&'static str, u64>,
+    item_types: BTreeMap<&'static str, u64>,
+    count: u64,
+    /// Number of the last document counted, so that paths repeated inside
+    /// an array count once per document
+    last_document: u64,
+    fields: BTreeMap<String, FieldStats>,
+}
+
+impl SchemaBuilder {
+    pub(crate) fn add(&mut self, document: &Document) {
+        self.sampled += 1;
+        record_fields(&mut self.fields, document, self.sampled);
+    }
+
+    pub(crate) fn finish(self) -> DocumentSchema {
+        let sampled = self.sampled;
+        let mut fields = finish_fields(self.fields, "", sampled);
+        if let Some(index) = fields.iter().position(|f| f.name == "_id") {
+            let id = fields.remove(index);
+            fields.insert(0, id);
+        }
+        DocumentSchema { sampled, fields }
+    }
+}
+
+fn record_fields(fields: &mut BTreeMap<String, FieldStats>, document: &Document, number: u64) {
+    for (key, value) in document {
+        fields.entry(key.clone()).or_default().record(value, number);
+    }
+}
+
+impl FieldStats {
+    fn record(&mut self, value: &Bson, number: u64) {
+        if self.last_document != number {
+            self.last_document = number;
+            self.count += 1;
+        }
+        *self.types.entry(type_name(value)).or_default() += 1;
+        match value {
+            Bson::Document(document) => record_fields(&mut self.fields, document, number),
+            Bson::Array(items) => {
+                for item in items {
+                    *self.item_types.entry(type_name(item)).or_default() += 1;
+                    if let Bson::Document(document) = item {
+                        record_fields(&mut self.fields, document, number);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn finish_fields(
+    fields: BTreeMap<String, FieldStats>,
+    parent: &str,
+    sampled: u64,
+) -> Vec<FieldInfo> {
+    fields
+        .into_iter()
+        .map(|(name, stats)| {
+            let path = if parent.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", parent, name)
+            };
+            FieldInfo {
+                fields: finish_fields(stats.fields, &path, sampled),
+                name,
+                path,
+                types: type_counts(stats.types),
+                item_types: type_counts(stats.item_types),
+                count: stats.count,
+                presence: if sampled == 0 {
+                    0.0
+                } else {
+                    stats.count as f64 * 100.0 / sampled as f64
+                },
+            }
+        })
+        .collect()
+}
+
+fn type_counts(types: BTreeMap<&'static str, u64>) -> Vec<TypeCount> {
+    let mut counts: Vec<TypeCount> = types
+        .into_iter()
+        .map(|(type_name, count)| TypeCount { type_name, count })
+        .collect();
+    counts.sort_by_key(|t| std::cmp::Reverse(t.count));
+    counts
+}
+
+fn type_name(bson: &Bson) -> &'static str {
+    match bson {
+        Bson::Null => "null",
+        Bson::Boolean(_) => "boolean",
+        Bson::Int32(_) => "int32",
+        Bson::Int64(_) => "int64",
+        Bson::Double(_) => "double",
+        Bson::String(_) => "string",
+        Bson::Array(_) => "array",
+        Bson::Document(_) => "object",
+        Bson::ObjectId(_) => "objectId",
+        Bson::DateTime(_) => "date",
+        Bson::Binary(_) => "binary",
+        Bson::RegularExpression(_) => "regex",
+        Bson::Timestamp(_) => "timestamp",
+        Bson::Symbol(_) => "symbol",
+        Bson::Undefined => "undefined",
+        Bson::MaxKey => "maxKey",
+        Bson::MinKey => "minKey",
+        Bson::Decimal128(_) => "decimal128",
+        Bson::JavaScriptCode(_) => "javascript",
+        Bson::JavaScriptCodeWithScope(_) => "javascriptWithScope",
+        Bson::DbPointer(_) => "dbPointer",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::doc;
+
+    #[test]
+    fn infers_nested_paths() {
+        let mut builder = SchemaBuilder::default();
+        builder.add(&doc! {
+            "name": "a",
+            "_id": 1,
+            "address": { "city": "Oslo", "zip": 1234 },
+            "items": [{ "sku": "x" }, { "sku": "y", "qty": 2 }],
+        });
+        builder.add(&doc! {
+            "_id": 2,
+            "name": null,
+            "address": { "city": "Rome", "zip": "00100" },
+            "items": [],
+        });
+        let schema = builder.finish();
+
+        assert_eq!(schema.sampled, 2);
+        let names: Vec<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["_id", "address", "items", "name"]);
+
+        assert_eq!(
+            schema.fields[1].nested_paths(),
+            ["address.city", "address.zip"]
+        );
+        let zip = &schema.fields[1].fields[1];
+        assert_eq!(zip.path, "address.zip");
+        assert_eq!(zip.data_type(), "int32 | string");
+        assert_eq!(zip.presence, 100.0);
+
+        let items = &schema.fields[2];
+        assert_eq!(
+            items.item_types,
+            [TypeCount {
+                type_name: "object",
+                count: 2
+            }]
+        );
+        let qty = &items.fields[0];
+        assert_eq!(
+            (qty.path.as_str(), qty.count, qty.presence),
+            ("items.qty", 1, 50.0)
+        );
+        assert_eq!(items.fields[1].count, 1);
+
+        let name = &schema.fields[3];
+        assert_eq!(name.data_type(), "string");
+        assert_eq!(name.types[0].count, 1);
+    }
+}
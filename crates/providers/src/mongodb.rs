@@ -1,14 +1,29 @@
 use async_trait::async_trait;
 use crate::{
-    ColumnInfo, ConnectionParams, DatabaseProvider, DatabaseType, ProviderError,
-    QueryResult, TableInfo,
+    explain,
+    import::ImportReport,
+    mongo_index::{self, CollectionIndex, CollectionStats, IndexSpec},
+    mongo_query,
+    mongo_query::MongoQuery,
+    mongo_schema::{DocumentSchema, SchemaBuilder, COLUMNS_SAMPLE_SIZE, DEFAULT_SAMPLE_SIZE},
+    pagination, ColumnFilter, ColumnInfo, ConnectionParams, DatabaseProvider, DatabaseType,
+    FilterOperator, ImportBatch, ImportBatches, ImportErrorMode, ImportSummary, IndexInfo,
+    PageRequest, ProviderError, QueryPlan, QueryResult, SchemaObjects, SortColumn, SortDirection,
+    SslMode, TableDetails, TableInfo, TablePage, TableQuery, TlsConfig, ViewInfo,
 };
 use mongodb::{
+    action::Action,
     bson::{doc, oid::ObjectId, Bson, Document},
-    options::ClientOptions,
-    Client, Collection,
+    error::ErrorKind,
+    options::{ClientOptions, Tls, TlsOptions},
+    results::{CollectionSpecification, CollectionType},
+    Client, Collection, IndexModel,
 };
 use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Commands whose reply is a cursor over documents
+const CURSOR_COMMANDS: &[&str] = &["find", "aggregate", "listCollections", "listIndexes"];
 
 pub struct MongoDbProvider {
     client: Client,
@@ -18,7 +33,9 @@ pub struct MongoDbProvider {
 impl MongoDbProvider {
     pub async fn connect(params: ConnectionParams) -> Result<Self, ProviderError> {
         let (url, database) = match &params {
-            ConnectionParams::ConnectionString { connection_string } => {
+            ConnectionParams::ConnectionString {
+                connection_string, ..
+            } => {
                 // Parse database name from connection string
                 let db_name = Self::extract_database_from_url(connection_string)
                     .unwrap_or_else(|| "test".to_string());
@@ -30,6 +47,7 @@ impl MongoDbProvider {
                 database,
                 username,
                 password,
+                ..
             } => {
                 let url = if !username.is_empty() && !password.is_empty() {
                     format!(
@@ -43,10 +61,19 @@ impl MongoDbProvider {
             }
         };
 
-        let client_options = ClientOptions::parse(&url)
+        let mut client_options = ClientOptions::parse(&url)
             .await
             .map_err(|e| ProviderError::new(format!("Failed to parse MongoDB URL: {}", e)))?;
 
+        if let Some(tls) = params.tls() {
+            Self::apply_tls(&mut client_options, tls)?;
+        }
+        // Through a tunnel only the one forwarded host is reachable, so the
+        // driver must not go on to discover the other replica set members.
+        if params.ssh_tunnel().is_some() {
+            client_options.direct_connection = Some(true);
+        }
+
         let client = Client::with_options(client_options)
             .map_err(|e| ProviderError::new(format!("Failed to create MongoDB client: {}", e)))?;
 
@@ -66,6 +93,42 @@ impl MongoDbProvider {
         })
     }
 
+    /// The driver cannot negotiate TLS on a plain port, so `prefer` only
+    /// applies the certificate settings when the URL already enables TLS.
+    /// Hostnames are always checked once the chain is verified, which makes
+    /// `verify-ca` behave like `verify-full`.
+    fn apply_tls(options: &mut ClientOptions, tls: &TlsConfig) -> Result<(), ProviderError> {
+        let url_enables_tls = matches!(options.tls, Some(Tls::Enabled(_)));
+        match tls.mode {
+            SslMode::Disable => {
+                options.tls = Some(Tls::Disabled);
+                return Ok(());
+            }
+            SslMode::Prefer if !url_enables_tls => return Ok(()),
+            _ => {}
+        }
+
+        tls.require_address_as_server_name("MongoDB")?;
+        let cert_key_file_path = match (&tls.client_cert_path, &tls.client_key_path) {
+            (Some(cert), Some(key)) if cert != key => {
+                return Err(ProviderError::new(
+                    "MongoDB expects the client certificate and key in one PEM file",
+                )
+                .with_hint("Concatenate them into one file and use it for both paths."));
+            }
+            (cert, _) => cert.as_ref().map(PathBuf::from),
+        };
+
+        options.tls = Some(Tls::Enabled(
+            TlsOptions::builder()
+                .allow_invalid_certificates(!tls.verifies_certificate())
+                .ca_file_path(tls.ca_cert_path.as_ref().map(PathBuf::from))
+                .cert_key_file_path(cert_key_file_path)
+                .build(),
+        ));
+        Ok(())
+    }
+
     fn extract_database_from_url(url: &str) -> Option<String> {
         // MongoDB URL format: mongodb://[user:pass@]host[:port]/database[?options]
         let url = url.strip_prefix("mongodb://")?;
@@ -103,16 +166,13 @@ impl MongoDbProvider {
         self.client.database(&self.database)
     }
 
-    fn bson_to_json(bson: &Bson) -> serde_json::Value {
+    /// Relaxed Extended JSON, so ObjectIds, dates, decimals and binary
+    /// values keep their type when a document is edited and written back.
+    /// Int64 stays canonical (`$numberLong`): as a plain number it would
+    /// come back as an Int32, or lose precision in the frontend.
+    pub(crate) fn bson_to_json(bson: &Bson) -> serde_json::Value {
         match bson {
-            Bson::Null => serde_json::Value::Null,
-            Bson::Boolean(b) => serde_json::Value::Bool(*b),
-            Bson::Int32(i) => serde_json::Value::Number((*i).into()),
-            Bson::Int64(i) => serde_json::Value::Number((*i).into()),
-            Bson::Double(f) => serde_json::Number::from_f64(*f)
-                .map(serde_json::Value::Number)
-                .unwrap_or(serde_json::Value::Null),
-            Bson::String(s) => serde_json::Value::String(s.clone()),
+            Bson::Int64(_) => bson.clone().into_canonical_extjson(),
             Bson::Array(arr) => {
                 serde_json::Value::Array(arr.iter().map(Self::bson_to_json).collect())
             }
@@ -123,44 +183,443 @@ impl MongoDbProvider {
                     .collect();
                 serde_json::Value::Object(obj)
             }
-            Bson::ObjectId(oid) => serde_json::Value::String(oid.to_hex()),
-            Bson::DateTime(dt) => serde_json::Value::String(dt.to_string()),
-            Bson::Binary(bin) => {
-                use base64::Engine;
-                serde_json::Value::String(
-                    base64::engine::general_purpose::STANDARD.encode(&bin.bytes),
-                )
+            other => other.clone().into_relaxed_extjson(),
+        }
+    }
+
+    /// Parse a JSON document, reading Extended JSON values such as
+    /// `{"$oid": ...}` and `{"$date": ...}` as their BSON types
+    fn parse_document(json: &str, what: &str) -> Result<Document, ProviderError> {
+        let value: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| ProviderError::new(format!("Invalid {}: {}", what, e)))?;
+        match Bson::try_from(value) {
+            Ok(Bson::Document(doc)) => Ok(doc),
+            Ok(_) => Err(ProviderError::new(format!(
+                "Invalid {}: expected an object",
+                what
+            ))),
+            Err(e) => Err(ProviderError::new(format!("Invalid {}: {}", what, e))),
+        }
+    }
+
+    /// Documents matching `filter` as rows, with the union of their fields
+    /// as columns
+    async fn find_rows(
+        &self,
+        table: &str,
+        filter: Document,
+        sort: Document,
+        limit: i64,
+        offset: i64,
+    ) -> Result<QueryResult, ProviderError> {
+        let documents = self
+            .find_documents(table, filter, sort, limit, offset)
+            .await?;
+        Ok(Self::documents_to_result(documents))
+    }
+
+    async fn find_documents(
+        &self,
+        table: &str,
+        filter: Document,
+        sort: Document,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Document>, ProviderError> {
+        let db = self.get_database();
+        let collection: Collection<Document> = db.collection(table);
+
+        let mut cursor = collection
+            .find(filter)
+            .sort(sort)
+            .skip(offset as u64)
+            .limit(limit)
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to query collection: {}", e)))?;
+
+        let mut documents: Vec<Document> = Vec::new();
+
+        use futures_util::StreamExt;
+        while let Some(result) = cursor.next().await {
+            match result {
+                Ok(doc) => documents.push(doc),
+                Err(e) => {
+                    return Err(ProviderError::new(format!("Error reading document: {}", e)));
+                }
             }
-            Bson::RegularExpression(regex) => {
-                serde_json::json!({
-                    "$regex": regex.pattern.clone(),
-                    "$options": regex.options.clone()
-                })
+        }
+        Ok(documents)
+    }
+
+    async fn run_query(&self, query: MongoQuery) -> Result<QueryResult, ProviderError> {
+        use futures_util::TryStreamExt;
+
+        let db = self.get_database();
+        let query_error =
+            |e: mongodb::error::Error| ProviderError::new(format!("Failed to run query: {}", e));
+        let written = |tag: &str, count: u64| QueryResult {
+            rows_affected: Some(count),
+            command_tag: Some(format!("{} {}", tag, count)),
+            ..Default::default()
+        };
+
+        match query {
+            MongoQuery::Find {
+                collection,
+                filter,
+                projection,
+                sort,
+                skip,
+                limit,
+            } => {
+                let documents: Vec<Document> = db
+                    .collection::<Document>(&collection)
+                    .find(filter)
+                    .optional(projection, |find, p| find.projection(p))
+                    .optional(sort, |find, s| find.sort(s))
+                    .optional(skip, |find, n| find.skip(n))
+                    .optional(limit, |find, n| find.limit(n))
+                    .await
+                    .map_err(query_error)?
+                    .try_collect()
+                    .await
+                    .map_err(query_error)?;
+                Ok(Self::documents_to_result(documents))
             }
-            Bson::Timestamp(ts) => {
-                serde_json::json!({
-                    "t": ts.time,
-                    "i": ts.increment
+            MongoQuery::Aggregate {
+                collection,
+                pipeline,
+            } => {
+                let documents: Vec<Document> = db
+                    .collection::<Document>(&collection)
+                    .aggregate(pipeline)
+                    .await
+                    .map_err(query_error)?
+                    .try_collect()
+                    .await
+                    .map_err(query_error)?;
+                Ok(Self::documents_to_result(documents))
+            }
+            MongoQuery::Count { collection, filter } => {
+                let count = db
+                    .collection::<Document>(&collection)
+                    .count_documents(filter)
+                    .await
+                    .map_err(query_error)?;
+                Ok(QueryResult {
+                    columns: vec!["count".to_string()],
+                    rows: vec![vec![serde_json::Value::from(count)]],
+                    row_count: 1,
+                    ..Default::default()
                 })
             }
-            Bson::Symbol(s) => serde_json::Value::String(s.clone()),
-            Bson::Undefined => serde_json::Value::Null,
-            Bson::MaxKey => serde_json::Value::String("MaxKey".to_string()),
-            Bson::MinKey => serde_json::Value::String("MinKey".to_string()),
-            Bson::Decimal128(d) => serde_json::Value::String(d.to_string()),
-            Bson::JavaScriptCode(code) => serde_json::Value::String(code.clone()),
-            Bson::JavaScriptCodeWithScope(code) => {
-                serde_json::json!({
-                    "$code": code.code.clone(),
-                    "$scope": Self::bson_to_json(&Bson::Document(code.scope.clone()))
+            MongoQuery::Distinct {
+                collection,
+                field,
+                filter,
+            } => {
+                let values = db
+                    .collection::<Document>(&collection)
+                    .distinct(&field, filter)
+                    .await
+                    .map_err(query_error)?;
+                let rows: Vec<Vec<serde_json::Value>> = values
+                    .iter()
+                    .map(|value| vec![Self::bson_to_json(value)])
+                    .collect();
+                Ok(QueryResult {
+                    columns: vec![field],
+                    row_count: rows.len(),
+                    rows,
+                    ..Default::default()
                 })
             }
-            Bson::DbPointer(_ptr) => {
-                serde_json::json!({
-                    "$dbPointer": "<dbPointer>"
+            MongoQuery::Insert {
+                collection,
+                documents,
+            } => {
+                let result = db
+                    .collection::<Document>(&collection)
+                    .insert_many(documents)
+                    .await
+                    .map_err(query_error)?;
+                let mut ids: Vec<(usize, Bson)> = result.inserted_ids.into_iter().collect();
+                ids.sort_by_key(|(index, _)| *index);
+                let rows: Vec<Vec<serde_json::Value>> = ids
+                    .iter()
+                    .map(|(_, id)| vec![Self::bson_to_json(id)])
+                    .collect();
+                Ok(QueryResult {
+                    columns: vec!["_id".to_string()],
+                    row_count: rows.len(),
+                    rows,
+                    ..written("INSERT", ids.len() as u64)
                 })
             }
+            MongoQuery::Update {
+                collection,
+                filter,
+                update,
+                many,
+                upsert,
+            } => {
+                let collection = db.collection::<Document>(&collection);
+                let result = if many {
+                    collection.update_many(filter, update).upsert(upsert).await
+                } else {
+                    collection.update_one(filter, update).upsert(upsert).await
+                }
+                .map_err(query_error)?;
+                let upserted = result.upserted_id.is_some() as u64;
+                Ok(written("UPDATE", result.modified_count + upserted))
+            }
+            MongoQuery::Delete {
+                collection,
+                filter,
+                many,
+            } => {
+                let collection = db.collection::<Document>(&collection);
+                let result = if many {
+                    collection.delete_many(filter).await
+                } else {
+                    collection.delete_one(filter).await
+                }
+                .map_err(query_error)?;
+                Ok(written("DELETE", result.deleted_count))
+            }
+            MongoQuery::Command(command) => {
+                // Commands that answer with a cursor are read to the end
+                let cursor_command = command
+                    .keys()
+                    .next()
+                    .is_some_and(|name| CURSOR_COMMANDS.contains(&name.as_str()));
+                let documents: Vec<Document> = if cursor_command {
+                    db.run_cursor_command(command)
+                        .await
+                        .map_err(query_error)?
+                        .try_collect()
+                        .await
+                        .map_err(query_error)?
+                } else {
+                    vec![db.run_command(command).await.map_err(query_error)?]
+                };
+                Ok(Self::documents_to_result(documents))
+            }
+        }
+    }
+
+    /// Rows with one column per field seen in any document, `_id` first
+    fn documents_to_result(documents: Vec<Document>) -> QueryResult {
+        let mut rows: Vec<Vec<serde_json::Value>> = Vec::new();
+        let mut all_columns_set: HashSet<String> = HashSet::new();
+        let mut all_columns_order: Vec<String> = Vec::new();
+
+        // First pass: track all field names
+        for doc in &documents {
+            for key in doc.keys() {
+                if !all_columns_set.contains(key) {
+                    all_columns_set.insert(key.clone());
+                    all_columns_order.push(key.clone());
+                }
+            }
+        }
+
+        // Ensure _id is first if present
+        let mut columns: Vec<String> = Vec::new();
+        if all_columns_set.contains("_id") {
+            columns.push("_id".to_string());
+        }
+        for col in all_columns_order {
+            if col != "_id" {
+                columns.push(col);
+            }
+        }
+
+        // Second pass: convert documents to rows with consistent column order
+        for doc in documents {
+            let mut row: Vec<serde_json::Value> = Vec::new();
+            for col in &columns {
+                let value = doc
+                    .get(col)
+                    .map(Self::bson_to_json)
+                    .unwrap_or(serde_json::Value::Null);
+                row.push(value);
+            }
+            rows.push(row);
+        }
+
+        let row_count = rows.len();
+
+        QueryResult {
+            columns,
+            rows,
+            row_count,
+            ..Default::default()
+        }
+    }
+
+    /// A filter value as BSON. `_id` strings that look like ObjectIds are
+    /// matched as ObjectIds, since that is how the grid displays them.
+    fn filter_value(column: &str, value: &serde_json::Value) -> Result<Bson, ProviderError> {
+        if let (true, Some(hex)) = (column == "_id", value.as_str()) {
+            if let Ok(oid) = ObjectId::parse_str(hex) {
+                return Ok(Bson::ObjectId(oid));
+            }
+        }
+        Bson::try_from(value.clone())
+            .map_err(|e| ProviderError::new(format!("Invalid filter value for {}: {}", column, e)))
+    }
+
+    fn filter_document(filters: &[ColumnFilter]) -> Result<Document, ProviderError> {
+        let conditions = filters
+            .iter()
+            .map(Self::filter_condition)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(if conditions.is_empty() {
+            doc! {}
+        } else {
+            doc! { "$and": conditions }
+        })
+    }
+
+    fn filter_condition(filter: &ColumnFilter) -> Result<Document, ProviderError> {
+        let column = filter.column.as_str();
+        let value = || Self::filter_value(column, &filter.value);
+        let text_pattern = |pattern: String| {
+            Bson::RegularExpression(mongodb::bson::Regex {
+                pattern,
+                options: "i".to_string(),
+            })
+        };
+        let text = || match &filter.value {
+            serde_json::Value::String(s) => Ok(regex_escape(s)),
+            serde_json::Value::Number(n) => Ok(n.to_string()),
+            _ => Err(ProviderError::new(format!(
+                "Filter on {} needs text to compare with",
+                column
+            ))),
+        };
+        let values = || match &filter.value {
+            serde_json::Value::Array(items) => items
+                .iter()
+                .map(|item| Self::filter_value(column, item))
+                .collect::<Result<Vec<_>, _>>(),
+            _ => Err(ProviderError::new(format!(
+                "Filter on {} needs an array of values to compare with",
+                column
+            ))),
+        };
+
+        // `null` also matches documents where the field is missing
+        Ok(match filter.operator {
+            FilterOperator::Eq => doc! { column: value()? },
+            FilterOperator::Ne => doc! { column: { "$ne": value()? } },
+            FilterOperator::Lt => doc! { column: { "$lt": value()? } },
+            FilterOperator::Lte => doc! { column: { "$lte": value()? } },
+            FilterOperator::Gt => doc! { column: { "$gt": value()? } },
+            FilterOperator::Gte => doc! { column: { "$gte": value()? } },
+            FilterOperator::Contains => doc! { column: text_pattern(text()?) },
+            FilterOperator::NotContains => doc! { column: { "$not": text_pattern(text()?) } },
+            FilterOperator::StartsWith => doc! { column: text_pattern(format!("^{}", text()?)) },
+            FilterOperator::EndsWith => doc! { column: text_pattern(format!("{}$", text()?)) },
+            FilterOperator::In => doc! { column: { "$in": values()? } },
+            FilterOperator::NotIn => doc! { column: { "$nin": values()? } },
+            FilterOperator::IsNull => doc! { column: Bson::Null },
+            FilterOperator::IsNotNull => doc! { column: { "$ne": Bson::Null } },
+        })
+    }
+
+    /// MongoDB always sorts nulls and missing fields first in ascending
+    /// order, so `nulls` is not honoured.
+    fn sort_document(sort: &[SortColumn]) -> Document {
+        sort.iter()
+            .map(|column| {
+                let direction = match column.direction {
+                    SortDirection::Asc => 1,
+                    SortDirection::Desc => -1,
+                };
+                (column.column.clone(), Bson::Int32(direction))
+            })
+            .collect()
+    }
+
+    /// Insert one import batch. Null values are left out of the documents.
+    /// Under `Abort` the insert is ordered and stops at the first rejected
+    /// document; documents before it stay inserted.
+    async fn import_batch(
+        coll: &Collection<Document>,
+        columns: &[String],
+        batch: ImportBatch,
+        report: &mut ImportReport,
+        on_error: ImportErrorMode,
+    ) -> Result<(), ProviderError> {
+        let mut documents = Vec::with_capacity(batch.rows.len());
+        let mut row_numbers = Vec::with_capacity(batch.rows.len());
+        for (offset, row) in batch.rows.into_iter().enumerate() {
+            let row_number = batch.first_row + offset as u64;
+            let mut document = Document::new();
+            let mut failed = None;
+            for (column, value) in columns.iter().zip(row) {
+                if value.is_null() {
+                    continue;
+                }
+                match Bson::try_from(value) {
+                    Ok(value) => {
+                        document.insert(column.as_str(), value);
+                    }
+                    Err(e) => {
+                        failed = Some(ProviderError::new(format!(
+                            "Invalid value for {}: {}",
+                            column, e
+                        )));
+                        break;
+                    }
+                }
+            }
+            match failed {
+                Some(err) => report.row_failed(row_number, err)?,
+                None => {
+                    documents.push(document);
+                    row_numbers.push(row_number);
+                }
+            }
+        }
+        if documents.is_empty() {
+            return Ok(());
+        }
+
+        let count = documents.len() as u64;
+        let result = coll
+            .insert_many(documents)
+            .ordered(on_error == ImportErrorMode::Abort)
+            .await;
+        let err = match result {
+            Ok(_) => {
+                report.imported(count);
+                return Ok(());
+            }
+            Err(e) => e,
+        };
+        let write_errors = match *err.kind {
+            ErrorKind::InsertMany(ref failure) if failure.write_concern_error.is_none() => {
+                failure.write_errors.clone().unwrap_or_default()
+            }
+            _ => {
+                return Err(ProviderError::new(format!(
+                    "Failed to insert documents: {}",
+                    err
+                )))
+            }
+        };
+        report.imported(count - write_errors.len() as u64);
+        for write_error in write_errors {
+            let row_number = row_numbers
+                .get(write_error.index)
+                .copied()
+                .unwrap_or_default();
+            report.row_failed(row_number, ProviderError::new(write_error.message))?;
         }
+        Ok(())
     }
 
     async fn get_collection_count(&self, collection_name: &str) -> Result<i64, ProviderError> {
@@ -174,89 +633,63 @@ impl MongoDbProvider {
             .map_err(|e| ProviderError::new(format!("Failed to count documents: {}", e)))
     }
 
-    async fn infer_schema(
+    /// Infer the schema of `sample_size` documents picked at random with
+    /// `$sample`
+    async fn sample_schema(
         &self,
         collection_name: &str,
-    ) -> Result<Vec<(String, String)>, ProviderError> {
-        let db = self.get_database();
-        let collection: Collection<Document> = db.collection(collection_name);
-
-        // Sample a few documents to infer schema
+        sample_size: usize,
+    ) -> Result<DocumentSchema, ProviderError> {
+        let collection: Collection<Document> = self.get_database().collection(collection_name);
         let mut cursor = collection
-            .find(doc! {})
-            .limit(100)
+            .aggregate(vec![
+                doc! { "$sample": { "size": sample_size.max(1) as i64 } },
+            ])
             .await
-            .map_err(|e| ProviderError::new(format!("Failed to query collection: {}", e)))?;
+            .map_err(|e| ProviderError::new(format!("Failed to sample collection: {}", e)))?;
 
-        let mut field_types: std::collections::HashMap<String, std::collections::HashSet<String>> =
-            std::collections::HashMap::new();
-
-        use futures_util::StreamExt;
-        while let Some(result) = cursor.next().await {
-            if let Ok(doc) = result {
-                for (key, value) in doc.iter() {
-                    let type_name = Self::bson_type_name(value);
-                    field_types
-                        .entry(key.clone())
-                        .or_insert_with(std::collections::HashSet::new)
-                        .insert(type_name);
-                }
-            }
+        use futures_util::TryStreamExt;
+        let mut builder = SchemaBuilder::default();
+        while let Some(document) = cursor
+            .try_next()
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to sample collection: {}", e)))?
+        {
+            builder.add(&document);
         }
+        Ok(builder.finish())
+    }
 
-        // Convert to sorted list
-        let mut fields: Vec<(String, String)> = field_types
-            .into_iter()
-            .map(|(name, types)| {
-                let type_str = if types.len() == 1 {
-                    types.into_iter().next().unwrap()
-                } else {
-                    let mut sorted: Vec<_> = types.into_iter().collect();
-                    sorted.sort();
-                    sorted.join(" | ")
-                };
-                (name, type_str)
-            })
-            .collect();
-
-        fields.sort_by(|a, b| {
-            // Put _id first
-            if a.0 == "_id" {
-                std::cmp::Ordering::Less
-            } else if b.0 == "_id" {
-                std::cmp::Ordering::Greater
-            } else {
-                a.0.cmp(&b.0)
-            }
-        });
+    /// Run a one-stage pipeline that reports on `collection`
+    async fn collection_report(
+        &self,
+        collection: &str,
+        stage: Document,
+    ) -> Result<Vec<Document>, ProviderError> {
+        use futures_util::TryStreamExt;
 
-        Ok(fields)
+        let stats_error = |e: mongodb::error::Error| {
+            ProviderError::new(format!("Failed to read collection stats: {}", e))
+        };
+        self.get_database()
+            .collection::<Document>(collection)
+            .aggregate(vec![stage])
+            .await
+            .map_err(stats_error)?
+            .try_collect()
+            .await
+            .map_err(stats_error)
     }
 
-    fn bson_type_name(bson: &Bson) -> String {
-        match bson {
-            Bson::Null => "null".to_string(),
-            Bson::Boolean(_) => "boolean".to_string(),
-            Bson::Int32(_) => "int32".to_string(),
-            Bson::Int64(_) => "int64".to_string(),
-            Bson::Double(_) => "double".to_string(),
-            Bson::String(_) => "string".to_string(),
-            Bson::Array(_) => "array".to_string(),
-            Bson::Document(_) => "object".to_string(),
-            Bson::ObjectId(_) => "objectId".to_string(),
-            Bson::DateTime(_) => "date".to_string(),
-            Bson::Binary(_) => "binary".to_string(),
-            Bson::RegularExpression(_) => "regex".to_string(),
-            Bson::Timestamp(_) => "timestamp".to_string(),
-            Bson::Symbol(_) => "symbol".to_string(),
-            Bson::Undefined => "undefined".to_string(),
-            Bson::MaxKey => "maxKey".to_string(),
-            Bson::MinKey => "minKey".to_string(),
-            Bson::Decimal128(_) => "decimal128".to_string(),
-            Bson::JavaScriptCode(_) => "javascript".to_string(),
-            Bson::JavaScriptCodeWithScope(_) => "javascriptWithScope".to_string(),
-            Bson::DbPointer(_) => "dbPointer".to_string(),
-        }
+    /// The `storageStats` of `collection`, one per shard
+    async fn storage_stats(&self, collection: &str) -> Result<Vec<Document>, ProviderError> {
+        let reports = self
+            .collection_report(collection, doc! { "$collStats": { "storageStats": {} } })
+            .await?;
+        Ok(reports
+            .into_iter()
+            .filter_map(|report| report.get_document("storageStats").ok().cloned())
+            .collect())
     }
 }
 
@@ -300,16 +733,21 @@ impl DatabaseProvider for MongoDbProvider {
         _schema: &str,
         table: &str,
     ) -> Result<Vec<ColumnInfo>, ProviderError> {
-        let fields = self.infer_schema(table).await?;
+        let schema = self.sample_schema(table, COLUMNS_SAMPLE_SIZE).await?;
 
-        Ok(fields
+        Ok(schema
+            .fields
             .into_iter()
-            .map(|(name, data_type)| ColumnInfo {
+            .map(|field| (field.data_type(), field.nested_paths(), field.name))
+            .map(|(data_type, nested_paths, name)| ColumnInfo {
                 name: name.clone(),
                 data_type,
+                full_type: None,
+                nested_paths,
                 is_nullable: name != "_id", // _id is always required
                 is_primary_key: name == "_id",
                 has_default: name == "_id", // _id gets auto-generated
+                default_value: None,
             })
             .collect())
     }
@@ -321,84 +759,249 @@ impl DatabaseProvider for MongoDbProvider {
         limit: i64,
         offset: i64,
     ) -> Result<QueryResult, ProviderError> {
-        let db = self.get_database();
-        let collection: Collection<Document> = db.collection(table);
+        self.find_rows(table, doc! {}, doc! {}, limit, offset).await
+    }
 
-        let mut cursor = collection
-            .find(doc! {})
-            .skip(offset as u64)
-            .limit(limit)
-            .await
-            .map_err(|e| ProviderError::new(format!("Failed to query collection: {}", e)))?;
+    /// Runs mongosh-style calls such as `db.users.find({...}).limit(10)` or
+    /// a JSON command document; see [`mongo_query`]
+    async fn execute_query(&self, query: &str) -> Result<QueryResult, ProviderError> {
+        self.run_query(mongo_query::parse(query)?).await
+    }
 
-        let mut rows: Vec<Vec<serde_json::Value>> = Vec::new();
-        let mut all_columns_set: HashSet<String> = HashSet::new();
-        let mut all_columns_order: Vec<String> = Vec::new();
+    async fn get_table_count(&self, _schema: &str, table: &str) -> Result<i64, ProviderError> {
+        self.get_collection_count(table).await
+    }
 
-        // First pass: collect all documents and track all field names
-        let mut documents: Vec<Document> = Vec::new();
+    async fn get_table_data_filtered(
+        &self,
+        _schema: &str,
+        table: &str,
+        query: &TableQuery,
+        limit: i64,
+        offset: i64,
+    ) -> Result<QueryResult, ProviderError> {
+        let filter = Self::filter_document(&query.filters)?;
+        let sort = Self::sort_document(&query.sort);
+        self.find_rows(table, filter, sort, limit, offset).await
+    }
 
-        use futures_util::StreamExt;
-        while let Some(result) = cursor.next().await {
-            match result {
-                Ok(doc) => {
-                    for key in doc.keys() {
-                        if !all_columns_set.contains(key) {
-                            all_columns_set.insert(key.clone());
-                            all_columns_order.push(key.clone());
-                        }
-                    }
-                    documents.push(doc);
-                }
-                Err(e) => {
-                    return Err(ProviderError::new(format!("Error reading document: {}", e)));
-                }
-            }
+    async fn get_table_count_filtered(
+        &self,
+        _schema: &str,
+        table: &str,
+        filters: &[ColumnFilter],
+    ) -> Result<i64, ProviderError> {
+        if filters.is_empty() {
+            return self.get_collection_count(table).await;
         }
+        let collection: Collection<Document> = self.get_database().collection(table);
+        collection
+            .count_documents(Self::filter_document(filters)?)
+            .await
+            .map(|count| count as i64)
+            .map_err(|e| ProviderError::new(format!("Failed to count documents: {}", e)))
+    }
 
-        // Ensure _id is first if present
-        let mut columns: Vec<String> = Vec::new();
-        if all_columns_set.contains("_id") {
-            columns.push("_id".to_string());
+    /// Pages by `_id` (or the requested key) ranges instead of skipping
+    async fn get_table_page(
+        &self,
+        _schema: &str,
+        table: &str,
+        request: &PageRequest,
+    ) -> Result<TablePage, ProviderError> {
+        let key = request.key.clone().unwrap_or_else(|| "_id".to_string());
+        let keys = [key.clone()];
+        let (comparison, direction) = match request.direction {
+            SortDirection::Asc => ("$gt", 1),
+            SortDirection::Desc => ("$lt", -1),
+        };
+
+        let mut filter = Self::filter_document(&request.filters)?;
+        if let Some(token) = &request.cursor {
+            let mut after = pagination::decode_cursor(token, &keys, request.direction)?;
+            let after = Bson::try_from(after.remove(0))
+                .map_err(|e| ProviderError::new(format!("Invalid page cursor: {}", e)))?;
+            let condition = doc! { key.as_str(): { comparison: after } };
+            filter = if filter.is_empty() {
+                condition
+            } else {
+                doc! { "$and": [filter, condition] }
+            };
         }
-        for col in all_columns_order {
-            if col != "_id" {
-                columns.push(col);
+
+        let limit = request.limit.max(0) as usize;
+        let mut documents = self
+            .find_documents(
+                table,
+                filter,
+                doc! { key.as_str(): direction },
+                request.limit + 1,
+                0,
+            )
+            .await?;
+        let mut next_cursor = None;
+        if documents.len() > limit {
+            documents.truncate(limit);
+            if let Some(last) = documents.last() {
+                // Extended JSON keeps ObjectIds and dates intact in the token
+                let value = last.get(&key).cloned().unwrap_or(Bson::Null);
+                next_cursor = Some(pagination::encode_cursor(
+                    &keys,
+                    request.direction,
+                    vec![value.into_relaxed_extjson()],
+                ));
             }
         }
 
-        // Second pass: convert documents to rows with consistent column order
-        for doc in documents {
-            let mut row: Vec<serde_json::Value> = Vec::new();
-            for col in &columns {
-                let value = doc
-                    .get(col)
-                    .map(Self::bson_to_json)
-                    .unwrap_or(serde_json::Value::Null);
-                row.push(value);
-            }
-            rows.push(row);
+        Ok(TablePage {
+            result: Self::documents_to_result(documents),
+            next_cursor,
+        })
+    }
+
+    async fn import_rows(
+        &self,
+        _schema: &str,
+        table: &str,
+        columns: &[String],
+        mut batches: ImportBatches,
+        on_error: ImportErrorMode,
+    ) -> Result<ImportSummary, ProviderError> {
+        let coll: Collection<Document> = self.get_database().collection(table);
+        let mut report = ImportReport::new(on_error);
+        while let Some(batch) = batches.recv().await {
+            Self::import_batch(&coll, columns, batch?, &mut report, on_error).await?;
         }
+        Ok(report.finish())
+    }
 
-        let row_count = rows.len();
+    async fn list_schema_objects(&self) -> Result<SchemaObjects, ProviderError> {
+        use futures_util::TryStreamExt;
 
-        Ok(QueryResult {
-            columns,
-            rows,
-            row_count,
+        let list_error = |e: mongodb::error::Error| {
+            ProviderError::new(format!("Failed to list collections: {}", e))
+        };
+        let specs: Vec<CollectionSpecification> = self
+            .get_database()
+            .list_collections()
+            .await
+            .map_err(list_error)?
+            .try_collect()
+            .await
+            .map_err(list_error)?;
+
+        let views: Vec<ViewInfo> = specs
+            .into_iter()
+            .filter(|spec| matches!(spec.collection_type, CollectionType::View))
+            .map(|spec| {
+                let mut definition = doc! {};
+                if let Some(view_on) = spec.options.view_on {
+                    definition.insert("viewOn", view_on);
+                }
+                if let Some(pipeline) = spec.options.pipeline {
+                    definition.insert("pipeline", pipeline);
+                }
+                ViewInfo {
+                    schema: self.database.clone(),
+                    name: spec.name,
+                    materialized: false,
+                    definition: Some(Self::bson_to_json(&Bson::Document(definition)).to_string()),
+                }
+            })
+            .collect();
+
+        let mut tables = self.list_tables().await?;
+        tables.retain(|table| !views.iter().any(|view| view.name == table.name));
+
+        Ok(SchemaObjects {
+            tables,
+            views,
+            ..Default::default()
         })
     }
 
-    async fn execute_query(&self, _query: &str) -> Result<QueryResult, ProviderError> {
-        // MongoDB doesn't support SQL queries directly
-        // This would require implementing a MongoDB query parser or using aggregation pipelines
-        Err(ProviderError::new(
-            "Direct query execution is not supported for MongoDB. Use the data browser to explore collections.",
-        ).with_hint("MongoDB uses its own query language. Support for MongoDB queries is coming soon."))
+    async fn describe_table(
+        &self,
+        schema: &str,
+        table: &str,
+    ) -> Result<TableDetails, ProviderError> {
+        use futures_util::TryStreamExt;
+
+        let columns = self.get_table_columns(schema, table).await?;
+
+        let collection: Collection<Document> = self.get_database().collection(table);
+        let index_error =
+            |e: mongodb::error::Error| ProviderError::new(format!("Failed to list indexes: {}", e));
+        let models: Vec<IndexModel> = collection
+            .list_indexes()
+            .await
+            .map_err(index_error)?
+            .try_collect()
+            .await
+            .map_err(index_error)?;
+
+        let indexes = models
+            .into_iter()
+            .map(|model| {
+                let options = model.options.unwrap_or_default();
+                let name = options.name.unwrap_or_default();
+                // Special index types are given as strings, e.g. `text` or `2dsphere`
+                let method = model
+                    .keys
+                    .values()
+                    .find_map(|value| value.as_str())
+                    .map(str::to_string);
+                // Text indexes key on internal fields; the indexed fields are the weights
+                let columns = match (&method, options.weights) {
+                    (Some(method), Some(weights)) if method == "text" => {
+                        weights.keys().cloned().collect()
+                    }
+                    _ => model.keys.keys().cloned().collect(),
+                };
+                IndexInfo {
+                    is_primary: name == "_id_",
+                    is_unique: name == "_id_" || options.unique.unwrap_or(false),
+                    name,
+                    columns,
+                    method,
+                    predicate: options
+                        .partial_filter_expression
+                        .map(|filter| Self::bson_to_json(&Bson::Document(filter)).to_string()),
+                }
+            })
+            .collect();
+
+        Ok(TableDetails {
+            schema: schema.to_string(),
+            name: table.to_string(),
+            columns,
+            indexes,
+            ..Default::default()
+        })
     }
 
-    async fn get_table_count(&self, _schema: &str, table: &str) -> Result<i64, ProviderError> {
-        self.get_collection_count(table).await
+    /// `query` is a command document such as
+    /// `{"find": "users", "filter": {"age": {"$gt": 30}}}` or an `aggregate`
+    /// with its pipeline. With `analyze` the plan carries execution stats;
+    /// MongoDB never applies writes while explaining.
+    async fn explain_query(&self, query: &str, analyze: bool) -> Result<QueryPlan, ProviderError> {
+        let command = Self::parse_document(query, "JSON command").map_err(|e| {
+            e.with_hint("Pass a command document, e.g. {\"find\": \"users\", \"filter\": {}}.")
+        })?;
+        let verbosity = if analyze {
+            "executionStats"
+        } else {
+            "queryPlanner"
+        };
+
+        let result = self
+            .get_database()
+            .run_command(doc! { "explain": command, "verbosity": verbosity })
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to explain query: {}", e)))?;
+
+        // Plan statistics may be int64, which the plan reader wants as numbers
+        explain::from_mongodb(Bson::Document(result).into_relaxed_extjson())
     }
 
     async fn insert_document(
@@ -410,8 +1013,7 @@ impl DatabaseProvider for MongoDbProvider {
         let coll: Collection<Document> = db.collection(collection);
 
         // Parse the JSON document
-        let doc: Document = serde_json::from_str(document)
-            .map_err(|e| ProviderError::new(format!("Invalid JSON document: {}", e)))?;
+        let doc = Self::parse_document(document, "JSON document")?;
 
         // Insert the document
         let result = coll
@@ -419,13 +1021,8 @@ impl DatabaseProvider for MongoDbProvider {
             .await
             .map_err(|e| ProviderError::new(format!("Failed to insert document: {}", e)))?;
 
-        // Return the inserted ID as a string
-        let id = match result.inserted_id {
-            Bson::ObjectId(oid) => oid.to_hex(),
-            other => Self::bson_to_json(&other).to_string(),
-        };
-
-        Ok(id)
+        // Return the inserted ID as Extended JSON
+        Ok(Self::bson_to_json(&result.inserted_id).to_string())
     }
 
     async fn update_document(
@@ -438,15 +1035,13 @@ impl DatabaseProvider for MongoDbProvider {
         let coll: Collection<Document> = db.collection(collection);
 
         // Parse the filter JSON
-        let filter_doc: Document = serde_json::from_str(filter)
-            .map_err(|e| ProviderError::new(format!("Invalid filter JSON: {}", e)))?;
+        let filter_doc = Self::parse_document(filter, "filter JSON")?;
 
         // Convert _id string to ObjectId if present
         let filter_doc = Self::convert_id_field(filter_doc)?;
 
         // Parse the update JSON
-        let update_doc: Document = serde_json::from_str(update)
-            .map_err(|e| ProviderError::new(format!("Invalid update JSON: {}", e)))?;
+        let update_doc = Self::parse_document(update, "update JSON")?;
 
         // Wrap in $set if not already using update operators
         let update_doc = if update_doc.keys().any(|k| k.starts_with('$')) {
@@ -469,8 +1064,7 @@ impl DatabaseProvider for MongoDbProvider {
         let coll: Collection<Document> = db.collection(collection);
 
         // Parse the filter JSON
-        let filter_doc: Document = serde_json::from_str(filter)
-            .map_err(|e| ProviderError::new(format!("Invalid filter JSON: {}", e)))?;
+        let filter_doc = Self::parse_document(filter, "filter JSON")?;
 
         // Convert _id string to ObjectId if present
         let filter_doc = Self::convert_id_field(filter_doc)?;
@@ -483,21 +1077,194 @@ impl DatabaseProvider for MongoDbProvider {
 
         Ok(result.deleted_count)
     }
+
+    async fn infer_document_schema(
+        &self,
+        _schema: &str,
+        collection: &str,
+        sample_size: Option<usize>,
+    ) -> Result<DocumentSchema, ProviderError> {
+        self.sample_schema(collection, sample_size.unwrap_or(DEFAULT_SAMPLE_SIZE))
+            .await
+    }
+
+    async fn list_collection_indexes(
+        &self,
+        _schema: &str,
+        collection: &str,
+    ) -> Result<Vec<CollectionIndex>, ProviderError> {
+        use futures_util::TryStreamExt;
+
+        let index_error =
+            |e: mongodb::error::Error| ProviderError::new(format!("Failed to list indexes: {}", e));
+        let models: Vec<IndexModel> = self
+            .get_database()
+            .collection::<Document>(collection)
+            .list_indexes()
+            .await
+            .map_err(index_error)?
+            .try_collect()
+            .await
+            .map_err(index_error)?;
+
+        // Reading stats takes privileges that listing indexes does not
+        let sizes = self
+            .storage_stats(collection)
+            .await
+            .map(|storage| mongo_index::index_sizes(&storage))
+            .ok();
+        let mut usage = self
+            .collection_report(collection, doc! { "$indexStats": {} })
+            .await
+            .map(|stats| mongo_index::index_usage(&stats))
+            .ok();
+
+        Ok(models
+            .into_iter()
+            .map(|model| {
+                let mut index = mongo_index::collection_index(model);
+                index.size = sizes
+                    .as_ref()
+                    .and_then(|sizes| sizes.get(&index.name).copied());
+                index.usage = usage.as_mut().and_then(|usage| usage.remove(&index.name));
+                index
+            })
+            .collect())
+    }
+
+    async fn create_collection_index(
+        &self,
+        _schema: &str,
+        collection: &str,
+        spec: &IndexSpec,
+    ) -> Result<String, ProviderError> {
+        let model = mongo_index::index_model(spec)?;
+        self.get_database()
+            .collection::<Document>(collection)
+            .create_index(model)
+            .await
+            .map(|result| result.index_name)
+            .map_err(|e| ProviderError::new(format!("Failed to create index: {}", e)))
+    }
+
+    async fn drop_collection_index(
+        &self,
+        _schema: &str,
+        collection: &str,
+        name: &str,
+    ) -> Result<(), ProviderError> {
+        self.get_database()
+            .collection::<Document>(collection)
+            .drop_index(name)
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to drop index {}: {}", name, e)))
+    }
+
+    async fn collection_stats(
+        &self,
+        _schema: &str,
+        collection: &str,
+    ) -> Result<CollectionStats, ProviderError> {
+        let storage = self.storage_stats(collection).await?;
+        if storage.is_empty() {
+            return Err(ProviderError::new(format!(
+                "Collection {} does not exist",
+                collection
+            )));
+        }
+        Ok(mongo_index::collection_stats(&storage))
+    }
 }
 
 impl MongoDbProvider {
     /// Convert _id field from string to ObjectId if it looks like a valid ObjectId
     fn convert_id_field(mut doc: Document) -> Result<Document, ProviderError> {
-        if let Some(id_value) = doc.get("_id") {
-            if let Bson::String(id_str) = id_value {
-                // Try to parse as ObjectId (24 hex characters)
-                if id_str.len() == 24 {
-                    if let Ok(oid) = ObjectId::parse_str(id_str) {
-                        doc.insert("_id", Bson::ObjectId(oid));
-                    }
+        if let Some(Bson::String(id_str)) = doc.get("_id") {
+            // Try to parse as ObjectId (24 hex characters)
+            if id_str.len() == 24 {
+                if let Ok(oid) = ObjectId::parse_str(id_str) {
+                    doc.insert("_id", Bson::ObjectId(oid));
                 }
             }
         }
         Ok(doc)
     }
 }
+
+/// An Extended JSON scalar such as `{"$oid": ...}` as the plain value a
+/// SQL column or text file holds: ObjectIds as hex, dates as RFC 3339,
+/// decimals as text, int64 as a number and binary as base64. Other values
+/// are returned unchanged.
+pub fn plain_value(value: serde_json::Value) -> serde_json::Value {
+    let wrapped = value
+        .as_object()
+        .and_then(|object| object.keys().next())
+        .is_some_and(|key| key.starts_with('$'));
+    if !wrapped {
+        return value;
+    }
+    match Bson::try_from(value.clone()) {
+        Ok(Bson::ObjectId(oid)) => serde_json::Value::String(oid.to_hex()),
+        Ok(Bson::DateTime(dt)) => dt
+            .try_to_rfc3339_string()
+            .map(serde_json::Value::String)
+            .unwrap_or(value),
+        Ok(Bson::Decimal128(d)) => serde_json::Value::String(d.to_string()),
+        Ok(Bson::Int64(i)) => serde_json::Value::from(i),
+        Ok(Bson::Binary(bin)) => {
+            use base64::Engine;
+            serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(&bin.bytes))
+        }
+        _ => value,
+    }
+}
+
+/// Escape regex metacharacters so user text matches literally
+fn regex_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::{spec::BinarySubtype, Binary, DateTime, Decimal128};
+    use serde_json::json;
+
+    #[test]
+    fn documents_survive_extended_json() {
+        let document = doc! {
+            "_id": ObjectId::parse_str("65a1b2c3d4e5f60718293a4b").unwrap(),
+            "created": DateTime::from_millis(1_700_000_000_123),
+            "price": "12.50".parse::<Decimal128>().unwrap(),
+            "views": Bson::Int64(7),
+            "count": 3,
+            "ratio": 1.5,
+            "data": Binary { subtype: BinarySubtype::Generic, bytes: vec![1, 2, 3] },
+            "tags": [{ "at": DateTime::from_millis(0) }],
+        };
+        let json = MongoDbProvider::bson_to_json(&Bson::Document(document.clone()));
+        assert_eq!(json["_id"], json!({"$oid": "65a1b2c3d4e5f60718293a4b"}));
+        assert_eq!(json["views"], json!({"$numberLong": "7"}));
+        let parsed = MongoDbProvider::parse_document(&json.to_string(), "document").unwrap();
+        assert_eq!(parsed, document);
+
+        assert_eq!(
+            plain_value(json["_id"].clone()),
+            json!("65a1b2c3d4e5f60718293a4b")
+        );
+        assert_eq!(
+            plain_value(json["created"].clone()),
+            json!("2023-11-14T22:13:20.123Z")
+        );
+        assert_eq!(plain_value(json["price"].clone()), json!("12.50"));
+        assert_eq!(plain_value(json["views"].clone()), json!(7));
+        assert_eq!(plain_value(json!({"a": 1})), json!({"a": 1}));
+    }
+}
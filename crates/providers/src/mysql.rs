@@ -1,10 +1,30 @@
-use async_trait::async_trait;
+use crate::edit;
+use crate::explain;
+use crate::filter;
+use crate::import::{self, ImportReport};
+use crate::pagination;
+use crate::script::ScriptRecorder;
+use crate::stream::RowBatcher;
+use crate::transaction::TransactionSessions;
 use crate::{
-    ColumnInfo, ConnectionParams, DatabaseProvider, DatabaseType, ProviderError,
-    QueryResult, TableInfo,
+    cancel, schema, sql, CancelToken, ColumnFilter, ColumnInfo, ConnectionParams, ConstraintInfo,
+    ConstraintKind, DatabaseProvider, DatabaseType, ForeignKeyInfo, ImportBatch, ImportBatches,
+    ImportErrorMode, ImportSummary, IndexInfo, LogicalType, ObjectKind, PageRequest, ProviderError,
+    QueryCanceller, QueryPlan, QueryResult, QueryStreamEvent, ResultColumn, RoutineInfo,
+    RoutineKind, RowEdit, SchemaObjects, ScriptErrorMode, SequenceInfo, SslMode, StatementResult,
+    TableDetails, TableInfo, TablePage, TableQuery, TlsConfig, TriggerInfo, ViewInfo,
 };
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use mysql_async::consts::{ColumnFlags, ColumnType};
 use mysql_async::prelude::*;
-use mysql_async::{Conn, Opts, Pool, Value as MySqlValue};
+use mysql_async::{
+    ClientIdentity, Column, Conn, DriverError, Opts, OptsBuilder, Params, Pool, SslOpts,
+    Value as MySqlValue,
+};
+use std::path::PathBuf;
+use std::time::Instant;
+use tokio::sync::mpsc;
 
 mod queries {
     pub const LIST_TABLES: &str = r#"
@@ -22,12 +42,109 @@ ORDER BY TABLE_SCHEMA, TABLE_NAME
 SELECT
     c.COLUMN_NAME as name,
     c.DATA_TYPE as data_type,
+    c.COLUMN_TYPE as full_type,
     c.IS_NULLABLE = 'YES' as is_nullable,
     c.COLUMN_KEY = 'PRI' as is_primary_key,
-    c.COLUMN_DEFAULT IS NOT NULL as has_default
+    c.COLUMN_DEFAULT IS NOT NULL as has_default,
+    c.COLUMN_DEFAULT as default_value
 FROM information_schema.COLUMNS c
 WHERE c.TABLE_SCHEMA = ? AND c.TABLE_NAME = ?
 ORDER BY c.ORDINAL_POSITION
+"#;
+
+    pub const LIST_VIEWS: &str = r#"
+SELECT TABLE_SCHEMA, TABLE_NAME, VIEW_DEFINITION
+FROM information_schema.VIEWS
+WHERE TABLE_SCHEMA NOT IN ('mysql', 'information_schema', 'performance_schema', 'sys')
+ORDER BY TABLE_SCHEMA, TABLE_NAME
+"#;
+
+    /// Only MariaDB has sequences; MySQL returns no rows
+    pub const LIST_SEQUENCES: &str = r#"
+SELECT TABLE_SCHEMA, TABLE_NAME
+FROM information_schema.TABLES
+WHERE TABLE_TYPE = 'SEQUENCE'
+    AND TABLE_SCHEMA NOT IN ('mysql', 'information_schema', 'performance_schema', 'sys')
+ORDER BY TABLE_SCHEMA, TABLE_NAME
+"#;
+
+    pub const LIST_ROUTINES: &str = r#"
+SELECT
+    r.ROUTINE_SCHEMA,
+    r.ROUTINE_NAME,
+    r.ROUTINE_TYPE,
+    COALESCE((
+        SELECT GROUP_CONCAT(
+            CONCAT_WS(' ', p.PARAMETER_MODE, p.PARAMETER_NAME, p.DTD_IDENTIFIER)
+            ORDER BY p.ORDINAL_POSITION SEPARATOR ', '
+        )
+        FROM information_schema.PARAMETERS p
+        WHERE p.SPECIFIC_SCHEMA = r.ROUTINE_SCHEMA
+            AND p.SPECIFIC_NAME = r.SPECIFIC_NAME
+            AND p.ROUTINE_TYPE = r.ROUTINE_TYPE
+            AND p.ORDINAL_POSITION > 0
+    ), '') as arguments,
+    CASE WHEN r.ROUTINE_TYPE = 'FUNCTION' THEN r.DTD_IDENTIFIER END as return_type,
+    r.ROUTINE_BODY as language
+FROM information_schema.ROUTINES r
+WHERE r.ROUTINE_SCHEMA NOT IN ('mysql', 'information_schema', 'performance_schema', 'sys')
+ORDER BY r.ROUTINE_SCHEMA, r.ROUTINE_NAME
+"#;
+
+    /// One row per key part; `COLUMN_NAME` is NULL for functional key parts
+    pub const TABLE_INDEXES: &str = r#"
+SELECT INDEX_NAME, COLUMN_NAME, NON_UNIQUE, INDEX_TYPE
+FROM information_schema.STATISTICS
+WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ?
+ORDER BY INDEX_NAME, SEQ_IN_INDEX
+"#;
+
+    pub const TABLE_KEY_CONSTRAINTS: &str = r#"
+SELECT tc.CONSTRAINT_NAME, tc.CONSTRAINT_TYPE, k.COLUMN_NAME
+FROM information_schema.TABLE_CONSTRAINTS tc
+JOIN information_schema.KEY_COLUMN_USAGE k
+    ON k.CONSTRAINT_SCHEMA = tc.CONSTRAINT_SCHEMA
+    AND k.CONSTRAINT_NAME = tc.CONSTRAINT_NAME
+    AND k.TABLE_NAME = tc.TABLE_NAME
+WHERE tc.TABLE_SCHEMA = ? AND tc.TABLE_NAME = ?
+    AND tc.CONSTRAINT_TYPE IN ('PRIMARY KEY', 'UNIQUE')
+ORDER BY tc.CONSTRAINT_NAME, k.ORDINAL_POSITION
+"#;
+
+    /// `CHECK_CONSTRAINTS` exists from MySQL 8.0.16 and MariaDB 10.2
+    pub const TABLE_CHECK_CONSTRAINTS: &str = r#"
+SELECT cc.CONSTRAINT_NAME, cc.CHECK_CLAUSE
+FROM information_schema.CHECK_CONSTRAINTS cc
+JOIN information_schema.TABLE_CONSTRAINTS tc
+    ON tc.CONSTRAINT_SCHEMA = cc.CONSTRAINT_SCHEMA
+    AND tc.CONSTRAINT_NAME = cc.CONSTRAINT_NAME
+WHERE tc.TABLE_SCHEMA = ? AND tc.TABLE_NAME = ? AND tc.CONSTRAINT_TYPE = 'CHECK'
+ORDER BY cc.CONSTRAINT_NAME
+"#;
+
+    pub const TABLE_FOREIGN_KEYS: &str = r#"
+SELECT
+    k.CONSTRAINT_NAME,
+    k.COLUMN_NAME,
+    k.REFERENCED_TABLE_SCHEMA,
+    k.REFERENCED_TABLE_NAME,
+    k.REFERENCED_COLUMN_NAME,
+    r.UPDATE_RULE,
+    r.DELETE_RULE
+FROM information_schema.KEY_COLUMN_USAGE k
+JOIN information_schema.REFERENTIAL_CONSTRAINTS r
+    ON r.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA
+    AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME
+    AND r.TABLE_NAME = k.TABLE_NAME
+WHERE k.TABLE_SCHEMA = ? AND k.TABLE_NAME = ?
+ORDER BY k.CONSTRAINT_NAME, k.ORDINAL_POSITION
+"#;
+
+    pub const TABLE_TRIGGERS: &str = r#"
+SELECT TRIGGER_NAME, ACTION_TIMING, EVENT_MANIPULATION, ACTION_STATEMENT
+FROM information_schema.TRIGGERS
+WHERE EVENT_OBJECT_SCHEMA = ? AND EVENT_OBJECT_TABLE = ?
+ORDER BY TRIGGER_NAME
 "#;
 
     pub fn select_table_data(schema: &str, table: &str, limit: i64, offset: i64) -> String {
@@ -44,6 +161,8 @@ ORDER BY c.ORDINAL_POSITION
 
 pub struct MysqlProvider {
     pool: Pool,
+    /// Connections checked out of the pool for the lifetime of a transaction
+    transactions: TransactionSessions<Conn>,
 }
 
 impl MysqlProvider {
@@ -53,14 +172,112 @@ impl MysqlProvider {
         let opts = Opts::from_url(&url)
             .map_err(|e| ProviderError::new(format!("Invalid MySQL URL: {}", e)))?;
 
+        let Some(tls) = params.tls() else {
+            return Self::open_pool(opts).await.map_err(Self::connection_error);
+        };
+
+        let builder = OptsBuilder::from_opts(opts);
+        let result = Self::open_pool(builder.clone().ssl_opts(Self::ssl_opts(tls)?).into()).await;
+
+        // `prefer` only falls back when the server has TLS switched off.
+        match result {
+            Err(mysql_async::Error::Driver(DriverError::NoClientSslFlagFromServer))
+                if tls.mode == SslMode::Prefer =>
+            {
+                Self::open_pool(builder.ssl_opts(None).into()).await
+            }
+            result => result,
+        }
+        .map_err(Self::connection_error)
+    }
+
+    async fn open_pool(opts: Opts) -> Result<Self, mysql_async::Error> {
         let pool = Pool::new(opts);
+        let _conn = pool.get_conn().await?;
 
-        let _conn = pool
-            .get_conn()
-            .await
-            .map_err(|e| ProviderError::new(format!("MySQL connection failed: {}", e)))?;
+        Ok(Self {
+            pool,
+            transactions: TransactionSessions::default(),
+        })
+    }
+
+    fn connection_error(e: mysql_async::Error) -> ProviderError {
+        ProviderError::new(format!("MySQL connection failed: {}", e))
+    }
+
+    fn ssl_opts(tls: &TlsConfig) -> Result<Option<SslOpts>, ProviderError> {
+        if tls.mode == SslMode::Disable {
+            return Ok(None);
+        }
+
+        let mut ssl_opts = SslOpts::default()
+            .with_danger_accept_invalid_certs(!tls.verifies_certificate())
+            .with_danger_skip_domain_validation(!tls.verifies_hostname())
+            .with_danger_tls_hostname_override(tls.server_name.clone());
 
-        Ok(Self { pool })
+        if let Some(path) = &tls.ca_cert_path {
+            ssl_opts = ssl_opts.with_root_certs(vec![PathBuf::from(path).into()]);
+        }
+
+        if let Some(path) = &tls.client_cert_path {
+            if tls.client_key_path.is_some() {
+                return Err(ProviderError::new(
+                    "MySQL client certificates must be a PKCS#12 archive",
+                )
+                .with_hint(
+                    "Bundle the certificate and key into a .p12 file and leave the key path empty.",
+                ));
+            }
+            ssl_opts = ssl_opts
+                .with_client_identity(Some(ClientIdentity::new(PathBuf::from(path).into())));
+        }
+
+        Ok(Some(ssl_opts))
+    }
+
+    /// Insert one import batch with multi-row `INSERT`s. A failed statement
+    /// leaves the transaction usable, so its rows are retried one at a time
+    /// to find the ones MySQL rejects.
+    async fn import_batch(
+        conn: &mut Conn,
+        target: &str,
+        columns: &[String],
+        batch: &ImportBatch,
+        report: &mut ImportReport,
+    ) -> Result<(), ProviderError> {
+        // The protocol allows at most 65535 placeholders per statement
+        let chunk_rows = (u16::MAX as usize / columns.len().max(1)).max(1);
+        let single = import::insert_sql(DatabaseType::Mysql, target, columns, 1);
+
+        for (chunk_index, chunk) in batch.rows.chunks(chunk_rows).enumerate() {
+            let insert = import::insert_sql(DatabaseType::Mysql, target, columns, chunk.len());
+            let params: Vec<MySqlValue> = chunk
+                .iter()
+                .flatten()
+                .cloned()
+                .map(json_to_mysql_value)
+                .collect();
+            if conn.exec_drop(insert.as_str(), params).await.is_ok() {
+                report.imported(chunk.len() as u64);
+                continue;
+            }
+
+            let first_row = batch.first_row + (chunk_index * chunk_rows) as u64;
+            for (offset, row) in chunk.iter().enumerate() {
+                let params: Vec<MySqlValue> =
+                    row.iter().cloned().map(json_to_mysql_value).collect();
+                match conn.exec_drop(single.as_str(), params).await {
+                    Ok(()) => report.imported(1),
+                    Err(e) => report
+                        .row_failed(first_row + offset as u64, ProviderError::new(e.to_string()))?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn schema_error(e: mysql_async::Error) -> ProviderError {
+        ProviderError::new(format!("Failed to read schema: {}", e))
     }
 
     async fn get_conn(&self) -> Result<Conn, ProviderError> {
@@ -69,6 +286,133 @@ impl MysqlProvider {
             .await
             .map_err(|e| ProviderError::new(format!("Failed to get MySQL connection: {}", e)))
     }
+
+    async fn run_query(conn: &mut Conn, query: &str) -> Result<QueryResult, ProviderError> {
+        let result = conn
+            .query_iter(query)
+            .await
+            .map_err(|e| ProviderError::new(format!("Query failed: {}", e)))?;
+
+        Self::read_result(result, query).await
+    }
+
+    async fn run_query_with_params(
+        conn: &mut Conn,
+        query: &str,
+        params: Vec<serde_json::Value>,
+    ) -> Result<QueryResult, ProviderError> {
+        let params: Vec<MySqlValue> = params.into_iter().map(json_to_mysql_value).collect();
+        let result = conn
+            .exec_iter(query, Params::Positional(params))
+            .await
+            .map_err(|e| ProviderError::new(format!("Query failed: {}", e)))?;
+
+        Self::read_result(result, query).await
+    }
+
+    async fn read_result<P: Protocol>(
+        mut result: mysql_async::QueryResult<'_, '_, P>,
+        query: &str,
+    ) -> Result<QueryResult, ProviderError> {
+        let columns: Vec<String> = result
+            .columns_ref()
+            .iter()
+            .map(|c| c.name_str().to_string())
+            .collect();
+        let column_metadata: Vec<ResultColumn> =
+            result.columns_ref().iter().map(describe_column).collect();
+
+        let rows: Vec<mysql_async::Row> = result
+            .collect()
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to collect results: {}", e)))?;
+
+        // Result sets report 0 affected rows; only writes carry a meaningful count.
+        let rows_affected = columns.is_empty().then(|| result.affected_rows());
+        let last_insert_id = result.last_insert_id().map(|id| id as i64);
+
+        let result_rows: Vec<Vec<serde_json::Value>> = rows
+            .iter()
+            .map(|row| {
+                (0..row.len())
+                    .map(|i| mysql_value_to_json(row.get::<MySqlValue, _>(i)))
+                    .collect()
+            })
+            .collect();
+
+        let count = rows_affected.unwrap_or(result_rows.len() as u64);
+
+        Ok(QueryResult {
+            columns,
+            column_metadata,
+            row_count: result_rows.len(),
+            rows: result_rows,
+            rows_affected,
+            last_insert_id,
+            command_tag: sql::command_tag(query, count),
+        })
+    }
+
+    async fn stream_rows(
+        conn: &mut Conn,
+        query: &str,
+        batch_size: usize,
+        tx: mpsc::Sender<QueryStreamEvent>,
+    ) -> Result<(), ProviderError> {
+        let mut result = conn
+            .query_iter(query)
+            .await
+            .map_err(|e| ProviderError::new(format!("Query failed: {}", e)))?;
+
+        let mut batcher = RowBatcher::new(tx, batch_size);
+        batcher
+            .columns(
+                result
+                    .columns_ref()
+                    .iter()
+                    .map(|c| c.name_str().to_string())
+                    .collect(),
+            )
+            .await?;
+
+        let stream = result
+            .stream::<mysql_async::Row>()
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to read results: {}", e)))?;
+
+        if let Some(mut stream) = stream {
+            while let Some(row) = stream.next().await {
+                let row =
+                    row.map_err(|e| ProviderError::new(format!("Failed to read row: {}", e)))?;
+                let values = (0..row.len())
+                    .map(|i| mysql_value_to_json(row.get::<MySqlValue, _>(i)))
+                    .collect();
+                batcher.push(values).await?;
+            }
+        }
+
+        batcher.finish().await
+    }
+}
+
+/// Kills the running statement of a pooled connection from a second connection.
+struct MysqlCanceller {
+    pool: Pool,
+    connection_id: u32,
+}
+
+#[async_trait]
+impl QueryCanceller for MysqlCanceller {
+    async fn cancel(&self) -> Result<(), ProviderError> {
+        let mut conn =
+            self.pool.get_conn().await.map_err(|e| {
+                ProviderError::new(format!("Failed to get MySQL connection: {}", e))
+            })?;
+
+        conn.query_drop(format!("KILL QUERY {}", self.connection_id))
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to cancel query: {}", e)))
+    }
 }
 
 impl Drop for MysqlProvider {
@@ -117,7 +461,8 @@ impl DatabaseProvider for MysqlProvider {
     ) -> Result<Vec<ColumnInfo>, ProviderError> {
         let mut conn = self.get_conn().await?;
 
-        let rows: Vec<(String, String, i32, i32, i32)> = conn
+        #[allow(clippy::type_complexity)]
+        let rows: Vec<(String, String, String, i32, i32, i32, Option<String>)> = conn
             .exec(queries::GET_TABLE_COLUMNS, (schema, table))
             .await
             .map_err(|e| ProviderError::new(format!("Failed to get columns: {}", e)))?;
@@ -125,12 +470,25 @@ impl DatabaseProvider for MysqlProvider {
         let columns = rows
             .into_iter()
             .map(
-                |(name, data_type, is_nullable, is_primary_key, has_default)| ColumnInfo {
+                |(
                     name,
                     data_type,
-                    is_nullable: is_nullable != 0,
-                    is_primary_key: is_primary_key != 0,
-                    has_default: has_default != 0,
+                    full_type,
+                    is_nullable,
+                    is_primary_key,
+                    has_default,
+                    default_value,
+                )| {
+                    ColumnInfo {
+                        name,
+                        data_type,
+                        full_type: Some(full_type),
+                        nested_paths: Vec::new(),
+                        is_nullable: is_nullable != 0,
+                        is_primary_key: is_primary_key != 0,
+                        has_default: has_default != 0,
+                        default_value,
+                    }
                 },
             )
             .collect();
@@ -151,57 +509,659 @@ impl DatabaseProvider for MysqlProvider {
 
     async fn execute_query(&self, query: &str) -> Result<QueryResult, ProviderError> {
         let mut conn = self.get_conn().await?;
+        Self::run_query(&mut conn, query).await
+    }
 
-        let mut result = conn
-            .query_iter(query)
+    async fn execute_query_with_params(
+        &self,
+        query: &str,
+        params: Vec<serde_json::Value>,
+    ) -> Result<QueryResult, ProviderError> {
+        let mut conn = self.get_conn().await?;
+        Self::run_query_with_params(&mut conn, query, params).await
+    }
+
+    async fn execute_query_cancellable(
+        &self,
+        query: &str,
+        cancel: &CancelToken,
+    ) -> Result<QueryResult, ProviderError> {
+        let mut conn = self.get_conn().await?;
+        cancel.set_canceller(MysqlCanceller {
+            pool: self.pool.clone(),
+            connection_id: conn.id(),
+        });
+
+        let result = tokio::select! {
+            result = Self::run_query(&mut conn, query) => result,
+            _ = cancel.cancelled() => Err(cancel::cancelled_error()),
+        };
+
+        cancel.clear_canceller();
+        result
+    }
+
+    async fn execute_query_stream(
+        &self,
+        query: &str,
+        batch_size: usize,
+        tx: mpsc::Sender<QueryStreamEvent>,
+        cancel: &CancelToken,
+    ) -> Result<(), ProviderError> {
+        let mut conn = self.get_conn().await?;
+        cancel.set_canceller(MysqlCanceller {
+            pool: self.pool.clone(),
+            connection_id: conn.id(),
+        });
+
+        let result = tokio::select! {
+            result = Self::stream_rows(&mut conn, query, batch_size, tx) => result,
+            _ = cancel.cancelled() => Err(cancel::cancelled_error()),
+        };
+
+        cancel.clear_canceller();
+        result
+    }
+
+    /// Runs every statement on one pooled connection so session state such as
+    /// `USE`, variables and temporary tables carries over between statements.
+    async fn execute_script(
+        &self,
+        script: &str,
+        on_error: ScriptErrorMode,
+        cancel: &CancelToken,
+    ) -> Result<Vec<StatementResult>, ProviderError> {
+        let mut conn = self.get_conn().await?;
+        cancel.set_canceller(MysqlCanceller {
+            pool: self.pool.clone(),
+            connection_id: conn.id(),
+        });
+
+        let mut recorder = ScriptRecorder::new(on_error);
+        for statement in sql::split_statements(script, DatabaseType::Mysql) {
+            let started = Instant::now();
+            let result = tokio::select! {
+                result = Self::run_query(&mut conn, &statement) => result,
+                _ = cancel.cancelled() => Err(cancel::cancelled_error()),
+            };
+            if !recorder.record(statement, result, started, cancel) {
+                break;
+            }
+        }
+
+        cancel.clear_canceller();
+        Ok(recorder.finish())
+    }
+
+    async fn get_table_count(&self, schema: &str, table: &str) -> Result<i64, ProviderError> {
+        let mut conn = self.get_conn().await?;
+        let query = queries::count_table_rows(schema, table);
+
+        let count: Option<i64> = conn
+            .query_first(&query)
             .await
-            .map_err(|e| ProviderError::new(format!("Query failed: {}", e)))?;
+            .map_err(|e| ProviderError::new(format!("Failed to get count: {}", e)))?;
 
-        let columns: Vec<String> = result
-            .columns_ref()
-            .iter()
-            .map(|c| c.name_str().to_string())
-            .collect();
+        Ok(count.unwrap_or(0))
+    }
 
-        let rows: Vec<mysql_async::Row> = result
-            .collect()
+    async fn get_table_data_filtered(
+        &self,
+        schema: &str,
+        table: &str,
+        query: &TableQuery,
+        limit: i64,
+        offset: i64,
+    ) -> Result<QueryResult, ProviderError> {
+        let mut params = Vec::new();
+        let statement = filter::select_sql(
+            DatabaseType::Mysql,
+            &sql::qualified_name(schema, table, DatabaseType::Mysql),
+            query,
+            limit,
+            offset,
+            &mut params,
+        )?;
+        self.execute_query_with_params(&statement, params).await
+    }
+
+    async fn get_table_count_filtered(
+        &self,
+        schema: &str,
+        table: &str,
+        filters: &[ColumnFilter],
+    ) -> Result<i64, ProviderError> {
+        let mut params = Vec::new();
+        let statement = filter::count_sql(
+            DatabaseType::Mysql,
+            &sql::qualified_name(schema, table, DatabaseType::Mysql),
+            filters,
+            &mut params,
+        )?;
+        let result = self.execute_query_with_params(&statement, params).await?;
+        Ok(filter::count_from_result(&result))
+    }
+
+    async fn get_table_page(
+        &self,
+        schema: &str,
+        table: &str,
+        request: &PageRequest,
+    ) -> Result<TablePage, ProviderError> {
+        let columns = self.get_table_columns(schema, table).await?;
+        let keys = pagination::key_columns(request, schema, table, &columns)?;
+        let mut params = Vec::new();
+        let statement = pagination::page_sql(
+            DatabaseType::Mysql,
+            &sql::qualified_name(schema, table, DatabaseType::Mysql),
+            &keys,
+            request,
+            &mut params,
+        )?;
+        let result = self.execute_query_with_params(&statement, params).await?;
+        pagination::finish_page(result, &keys, request)
+    }
+
+    async fn list_schema_objects(&self) -> Result<SchemaObjects, ProviderError> {
+        let tables = self.list_tables().await?;
+        let mut conn = self.get_conn().await?;
+
+        let views: Vec<(String, String, Option<String>)> = conn
+            .query(queries::LIST_VIEWS)
             .await
-            .map_err(|e| ProviderError::new(format!("Failed to collect results: {}", e)))?;
+            .map_err(Self::schema_error)?;
+        let sequences: Vec<(String, String)> = conn
+            .query(queries::LIST_SEQUENCES)
+            .await
+            .map_err(Self::schema_error)?;
+        let routines: Vec<(
+            String,
+            String,
+            String,
+            String,
+            Option<String>,
+            Option<String>,
+        )> = conn
+            .query(queries::LIST_ROUTINES)
+            .await
+            .map_err(Self::schema_error)?;
+
+        Ok(SchemaObjects {
+            tables,
+            views: views
+                .into_iter()
+                .map(|(schema, name, definition)| ViewInfo {
+                    schema,
+                    name,
+                    materialized: false,
+                    definition,
+                })
+                .collect(),
+            sequences: sequences
+                .into_iter()
+                .map(|(schema, name)| SequenceInfo {
+                    schema,
+                    name,
+                    data_type: None,
+                })
+                .collect(),
+            routines: routines
+                .into_iter()
+                .map(
+                    |(schema, name, kind, arguments, return_type, language)| RoutineInfo {
+                        schema,
+                        name,
+                        kind: if kind == "PROCEDURE" {
+                            RoutineKind::Procedure
+                        } else {
+                            RoutineKind::Function
+                        },
+                        arguments,
+                        return_type,
+                        language,
+                    },
+                )
+                .collect(),
+        })
+    }
+
+    async fn describe_table(
+        &self,
+        schema: &str,
+        table: &str,
+    ) -> Result<TableDetails, ProviderError> {
+        let mut details = TableDetails {
+            schema: schema.to_string(),
+            name: table.to_string(),
+            columns: self.get_table_columns(schema, table).await?,
+            ..Default::default()
+        };
+        let mut conn = self.get_conn().await?;
 
-        if rows.is_empty() {
-            return Ok(QueryResult {
+        let rows: Vec<(String, Option<String>, i64, String)> = conn
+            .exec(queries::TABLE_INDEXES, (schema, table))
+            .await
+            .map_err(Self::schema_error)?;
+        let rows = rows
+            .into_iter()
+            .map(|(name, column, non_unique, method)| (name, (column, non_unique, method)));
+        for (name, parts) in schema::group_rows(rows) {
+            let (_, non_unique, method) = parts[0].clone();
+            details.indexes.push(IndexInfo {
+                is_primary: name == "PRIMARY",
+                name,
+                columns: parts
+                    .into_iter()
+                    .map(|(column, _, _)| column.unwrap_or_else(|| "<expression>".to_string()))
+                    .collect(),
+                is_unique: non_unique == 0,
+                method: Some(method),
+                predicate: None,
+            });
+        }
+
+        let rows: Vec<(String, String, String)> = conn
+            .exec(queries::TABLE_KEY_CONSTRAINTS, (schema, table))
+            .await
+            .map_err(Self::schema_error)?;
+        let rows = rows
+            .into_iter()
+            .map(|(name, kind, column)| ((name, kind), column));
+        for ((name, kind), columns) in schema::group_rows(rows) {
+            details.constraints.push(ConstraintInfo {
+                name: Some(name),
+                kind: if kind == "PRIMARY KEY" {
+                    ConstraintKind::PrimaryKey
+                } else {
+                    ConstraintKind::Unique
+                },
                 columns,
-                rows: vec![],
-                row_count: 0,
+                definition: None,
             });
         }
 
-        let result_rows: Vec<Vec<serde_json::Value>> = rows
-            .iter()
-            .map(|row| {
-                (0..row.len())
-                    .map(|i| mysql_value_to_json(row.get::<MySqlValue, _>(i)))
-                    .collect()
+        // Servers without CHECK_CONSTRAINTS do not enforce checks either.
+        let checks: Vec<(String, String)> = conn
+            .exec(queries::TABLE_CHECK_CONSTRAINTS, (schema, table))
+            .await
+            .unwrap_or_default();
+        details
+            .constraints
+            .extend(checks.into_iter().map(|(name, clause)| ConstraintInfo {
+                name: Some(name),
+                kind: ConstraintKind::Check,
+                columns: Vec::new(),
+                definition: Some(clause),
+            }));
+
+        let rows: Vec<(String, String, String, String, String, String, String)> = conn
+            .exec(queries::TABLE_FOREIGN_KEYS, (schema, table))
+            .await
+            .map_err(Self::schema_error)?;
+        let rows = rows.into_iter().map(
+            |(name, column, ref_schema, ref_table, ref_column, on_update, on_delete)| {
+                (
+                    name,
+                    (
+                        column, ref_schema, ref_table, ref_column, on_update, on_delete,
+                    ),
+                )
+            },
+        );
+        for (name, parts) in schema::group_rows(rows) {
+            let (_, referenced_schema, referenced_table, _, on_update, on_delete) =
+                parts[0].clone();
+            let (columns, referenced_columns) = parts
+                .into_iter()
+                .map(|(column, _, _, ref_column, _, _)| (column, ref_column))
+                .unzip();
+            details.foreign_keys.push(ForeignKeyInfo {
+                name: Some(name),
+                columns,
+                referenced_schema,
+                referenced_table,
+                referenced_columns,
+                on_update,
+                on_delete,
+            });
+        }
+
+        let triggers: Vec<(String, String, String, String)> = conn
+            .exec(queries::TABLE_TRIGGERS, (schema, table))
+            .await
+            .map_err(Self::schema_error)?;
+        details.triggers = triggers
+            .into_iter()
+            .map(|(name, timing, event, statement)| TriggerInfo {
+                name,
+                timing,
+                events: vec![event],
+                definition: Some(statement),
             })
             .collect();
 
-        Ok(QueryResult {
-            columns,
-            row_count: result_rows.len(),
-            rows: result_rows,
-        })
+        Ok(details)
     }
 
-    async fn get_table_count(&self, schema: &str, table: &str) -> Result<i64, ProviderError> {
+    async fn get_object_ddl(
+        &self,
+        schema: &str,
+        name: &str,
+        kind: ObjectKind,
+    ) -> Result<String, ProviderError> {
+        // Object type for SHOW CREATE and the result column holding the statement
+        let (object_type, column) = match kind {
+            ObjectKind::Table => ("TABLE", 1),
+            ObjectKind::View => ("VIEW", 1),
+            ObjectKind::Sequence => ("SEQUENCE", 1),
+            ObjectKind::Function => ("FUNCTION", 2),
+            ObjectKind::Procedure => ("PROCEDURE", 2),
+            ObjectKind::Trigger => ("TRIGGER", 2),
+            ObjectKind::MaterializedView | ObjectKind::Index => {
+                return Err(schema::ddl_unsupported(kind, DatabaseType::Mysql))
+            }
+        };
+
         let mut conn = self.get_conn().await?;
-        let query = queries::count_table_rows(schema, table);
+        let query = format!(
+            "SHOW CREATE {} {}",
+            object_type,
+            sql::qualified_name(schema, name, DatabaseType::Mysql)
+        );
+        let row: Option<mysql_async::Row> = conn
+            .query_first(query)
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to get DDL: {}", e)))?;
 
-        let count: Option<i64> = conn
-            .query_first(&query)
+        // The statement is NULL when the user may not see the routine body.
+        let ddl = row
+            .and_then(|row| row.get::<Option<String>, _>(column).flatten())
+            .ok_or_else(|| schema::object_not_found(kind, schema, name))?;
+        Ok(format!("{};", ddl))
+    }
+
+    async fn explain_query(&self, query: &str, analyze: bool) -> Result<QueryPlan, ProviderError> {
+        let query = query.trim().trim_end_matches(';');
+        let explain_error =
+            |e: mysql_async::Error| ProviderError::new(format!("Failed to explain query: {}", e));
+        let mut conn = self.get_conn().await?;
+
+        if !analyze {
+            let plan: Option<String> = conn
+                .query_first(format!("EXPLAIN FORMAT=JSON {}", query))
+                .await
+                .map_err(explain_error)?;
+            return explain::from_mysql_json(parse_plan_json(plan)?);
+        }
+
+        // MySQL only prints analyzed plans as a text tree; MariaDB has its
+        // own ANALYZE statement with JSON output.
+        let version: Option<String> = conn
+            .query_first("SELECT VERSION()")
             .await
-            .map_err(|e| ProviderError::new(format!("Failed to get count: {}", e)))?;
+            .map_err(explain_error)?;
+        let is_mariadb = version.is_some_and(|v| v.contains("MariaDB"));
+        let statement = if is_mariadb {
+            format!("ANALYZE FORMAT=JSON {}", query)
+        } else {
+            format!("EXPLAIN ANALYZE {}", query)
+        };
 
-        Ok(count.unwrap_or(0))
+        // ANALYZE executes the statement; roll back whatever it changed.
+        // Implicitly committing statements such as DDL cannot be undone.
+        conn.query_drop("START TRANSACTION")
+            .await
+            .map_err(explain_error)?;
+        let result: Result<Option<String>, _> = conn.query_first(statement).await;
+        let _ = conn.query_drop("ROLLBACK").await;
+        let plan = result.map_err(explain_error)?;
+
+        if is_mariadb {
+            explain::from_mysql_json(parse_plan_json(plan)?)
+        } else {
+            explain::from_mysql_tree(&plan.unwrap_or_default())
+        }
+    }
+
+    async fn apply_row_edits(
+        &self,
+        schema: &str,
+        table: &str,
+        edits: Vec<RowEdit>,
+        allow_without_primary_key: bool,
+    ) -> Result<u64, ProviderError> {
+        let columns = self.get_table_columns(schema, table).await?;
+        let statements = edit::build_statements(
+            DatabaseType::Mysql,
+            schema,
+            table,
+            &columns,
+            &edits,
+            allow_without_primary_key,
+        )?;
+
+        let mut conn = self.get_conn().await?;
+        conn.query_drop("START TRANSACTION")
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to start transaction: {}", e)))?;
+
+        let mut rows_written = 0;
+        for (index, statement) in statements.iter().enumerate() {
+            let params: Vec<MySqlValue> = statement
+                .params
+                .iter()
+                .cloned()
+                .map(json_to_mysql_value)
+                .collect();
+            let result = match conn.exec_drop(statement.sql.as_str(), params).await {
+                Ok(()) => {
+                    let count = rows_matched(&conn);
+                    statement.check_rows_affected(index, count).map(|_| count)
+                }
+                Err(e) => Err(edit::edit_failed(index, ProviderError::new(e.to_string()))),
+            };
+            match result {
+                Ok(count) => rows_written += count,
+                Err(e) => {
+                    let _ = conn.query_drop("ROLLBACK").await;
+                    return Err(e);
+                }
+            }
+        }
+
+        conn.query_drop("COMMIT")
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to commit transaction: {}", e)))?;
+        Ok(rows_written)
+    }
+
+    async fn import_rows(
+        &self,
+        schema: &str,
+        table: &str,
+        columns: &[String],
+        mut batches: ImportBatches,
+        on_error: ImportErrorMode,
+    ) -> Result<ImportSummary, ProviderError> {
+        let target = sql::qualified_name(schema, table, DatabaseType::Mysql);
+
+        let mut conn = self.get_conn().await?;
+        conn.query_drop("START TRANSACTION")
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to start transaction: {}", e)))?;
+
+        let mut report = ImportReport::new(on_error);
+        let mut result = Ok(());
+        while let Some(batch) = batches.recv().await {
+            result = match batch {
+                Ok(batch) => {
+                    Self::import_batch(&mut conn, &target, columns, &batch, &mut report).await
+                }
+                Err(e) => Err(e),
+            };
+            if result.is_err() {
+                break;
+            }
+        }
+        if let Err(e) = result {
+            let _ = conn.query_drop("ROLLBACK").await;
+            return Err(e);
+        }
+
+        conn.query_drop("COMMIT")
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to commit transaction: {}", e)))?;
+        Ok(report.finish())
+    }
+
+    async fn begin_transaction(&self) -> Result<String, ProviderError> {
+        let mut conn = self.get_conn().await?;
+        conn.query_drop("START TRANSACTION")
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to start transaction: {}", e)))?;
+
+        Ok(self.transactions.insert(conn))
+    }
+
+    async fn execute_in_transaction(
+        &self,
+        transaction_id: &str,
+        query: &str,
+    ) -> Result<QueryResult, ProviderError> {
+        let session = self.transactions.get(transaction_id)?;
+        let mut conn = session.lock().await;
+        Self::run_query(&mut conn, query).await
+    }
+
+    async fn commit_transaction(&self, transaction_id: &str) -> Result<(), ProviderError> {
+        let session = self.transactions.get(transaction_id)?;
+        let mut conn = session.lock().await;
+        if let Err(e) = conn.query_drop("COMMIT").await {
+            // After a server error the transaction is still open and can be
+            // retried or rolled back; any other error means the connection,
+            // and with it the transaction, is gone
+            if !matches!(e, mysql_async::Error::Server(_)) {
+                self.transactions.remove(transaction_id)?;
+            }
+            return Err(ProviderError::new(format!(
+                "Failed to commit transaction: {}",
+                e
+            )));
+        }
+        self.transactions.remove(transaction_id)?;
+        Ok(())
+    }
+
+    async fn rollback_transaction(&self, transaction_id: &str) -> Result<(), ProviderError> {
+        let session = self.transactions.get(transaction_id)?;
+        let mut conn = session.lock().await;
+        let result = conn
+            .query_drop("ROLLBACK")
+            .await
+            .map_err(|e| ProviderError::new(format!("Failed to roll back transaction: {}", e)));
+        // The server rolls back a transaction whose connection is dropped,
+        // so a failed ROLLBACK leaves nothing to track
+        self.transactions.remove(transaction_id)?;
+        result
+    }
+
+    fn open_transactions(&self) -> Vec<String> {
+        self.transactions.ids()
+    }
+}
+
+/// Character set number MySQL reports for binary strings.
+const BINARY_CHARSET: u16 = 63;
+
+fn describe_column(column: &Column) -> ResultColumn {
+    let non_empty =
+        |value: std::borrow::Cow<'_, str>| (!value.is_empty()).then(|| value.into_owned());
+    let native_type = format!("{:?}", column.column_type());
+
+    ResultColumn {
+        name: column.name_str().to_string(),
+        native_type: native_type
+            .strip_prefix("MYSQL_TYPE_")
+            .unwrap_or(&native_type)
+            .to_string(),
+        logical_type: mysql_logical_type(column),
+        nullable: Some(!column.flags().contains(ColumnFlags::NOT_NULL_FLAG)),
+        source_schema: non_empty(column.schema_str()),
+        source_table: non_empty(column.org_table_str()),
+        source_column: non_empty(column.org_name_str()),
+    }
+}
+
+fn mysql_logical_type(column: &Column) -> LogicalType {
+    let binary = column.character_set() == BINARY_CHARSET;
+
+    match column.column_type() {
+        ColumnType::MYSQL_TYPE_TINY if column.column_length() == 1 => LogicalType::Boolean,
+        ColumnType::MYSQL_TYPE_TINY
+        | ColumnType::MYSQL_TYPE_SHORT
+        | ColumnType::MYSQL_TYPE_INT24
+        | ColumnType::MYSQL_TYPE_LONG
+        | ColumnType::MYSQL_TYPE_LONGLONG
+        | ColumnType::MYSQL_TYPE_YEAR => LogicalType::Integer,
+        ColumnType::MYSQL_TYPE_FLOAT | ColumnType::MYSQL_TYPE_DOUBLE => LogicalType::Float,
+        ColumnType::MYSQL_TYPE_DECIMAL | ColumnType::MYSQL_TYPE_NEWDECIMAL => LogicalType::Decimal,
+        ColumnType::MYSQL_TYPE_DATE | ColumnType::MYSQL_TYPE_NEWDATE => LogicalType::Date,
+        ColumnType::MYSQL_TYPE_TIME | ColumnType::MYSQL_TYPE_TIME2 => LogicalType::Time,
+        ColumnType::MYSQL_TYPE_DATETIME
+        | ColumnType::MYSQL_TYPE_DATETIME2
+        | ColumnType::MYSQL_TYPE_TIMESTAMP
+        | ColumnType::MYSQL_TYPE_TIMESTAMP2 => LogicalType::Timestamp,
+        ColumnType::MYSQL_TYPE_JSON => LogicalType::Json,
+        ColumnType::MYSQL_TYPE_BIT => LogicalType::Binary,
+        ColumnType::MYSQL_TYPE_VARCHAR
+        | ColumnType::MYSQL_TYPE_VAR_STRING
+        | ColumnType::MYSQL_TYPE_STRING
+        | ColumnType::MYSQL_TYPE_TINY_BLOB
+        | ColumnType::MYSQL_TYPE_MEDIUM_BLOB
+        | ColumnType::MYSQL_TYPE_LONG_BLOB
+        | ColumnType::MYSQL_TYPE_BLOB => {
+            if binary {
+                LogicalType::Binary
+            } else {
+                LogicalType::Text
+            }
+        }
+        ColumnType::MYSQL_TYPE_ENUM | ColumnType::MYSQL_TYPE_SET => LogicalType::Text,
+        _ => LogicalType::Other,
+    }
+}
+
+/// Rows matched by the last statement. An UPDATE that leaves a row as it was
+/// counts as affecting nothing, so prefer the "Rows matched" figure MySQL
+/// reports in the statement info.
+fn rows_matched(conn: &Conn) -> u64 {
+    conn.info()
+        .strip_prefix("Rows matched: ")
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|count| count.parse().ok())
+        .unwrap_or_else(|| conn.affected_rows())
+}
+
+fn parse_plan_json(plan: Option<String>) -> Result<serde_json::Value, ProviderError> {
+    serde_json::from_str(&plan.unwrap_or_default())
+        .map_err(|e| ProviderError::new(format!("Unexpected MySQL EXPLAIN output: {}", e)))
+}
+
+fn json_to_mysql_value(value: serde_json::Value) -> MySqlValue {
+    match value {
+        serde_json::Value::Null => MySqlValue::NULL,
+        serde_json::Value::Bool(b) => MySqlValue::Int(b as i64),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                MySqlValue::Int(i)
+            } else if let Some(u) = n.as_u64() {
+                MySqlValue::UInt(u)
+            } else {
+                MySqlValue::Double(n.as_f64().unwrap_or_default())
+            }
+        }
+        serde_json::Value::String(s) => MySqlValue::Bytes(s.into_bytes()),
+        other => MySqlValue::Bytes(other.to_string().into_bytes()),
     }
 }
 
@@ -236,7 +1196,7 @@ fn mysql_value_to_json(value: Option<MySqlValue>) -> serde_json::Value {
             }
             MySqlValue::Time(is_neg, days, hours, mins, secs, micros) => {
                 let sign = if is_neg { "-" } else { "" };
-                let total_hours = days as u32 * 24 + hours as u32;
+                let total_hours = days * 24 + hours as u32;
                 let time = format!(
                     "{}{:02}:{:02}:{:02}.{:06}",
                     sign, total_hours, mins, secs, micros
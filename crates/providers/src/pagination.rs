@@ -0,0 +1,276 @@
+//! Keyset pagination: pages are ordered by a unique key and each one starts
+//! after the last key of the previous page, so deep pages cost as much as the
+//! first and rows do not shift while paging.
+
+use crate::filter::{self, ColumnFilter, SortDirection};
+use crate::{sql, ColumnInfo, DatabaseType, ProviderError, QueryResult};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A page of `get_table_page`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageRequest {
+    #[serde(default)]
+    pub filters: Vec<ColumnFilter>,
+    /// Unique, non-null column to page by instead of the primary key (`_id`
+    /// for MongoDB)
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub direction: SortDirection,
+    /// `next_cursor` of the previous page; unset for the first page
+    #[serde(default)]
+    pub cursor: Option<String>,
+    pub limit: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TablePage {
+    pub result: QueryResult,
+    /// Token for the page after this one; `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// What a continuation token holds. Only its base64 form leaves the crate.
+#[derive(Serialize, Deserialize)]
+struct Cursor {
+    columns: Vec<String>,
+    direction: SortDirection,
+    /// Key of the last row of the previous page
+    values: Vec<Value>,
+}
+
+pub(crate) fn encode_cursor(
+    columns: &[String],
+    direction: SortDirection,
+    values: Vec<Value>,
+) -> String {
+    let cursor = Cursor {
+        columns: columns.to_vec(),
+        direction,
+        values,
+    };
+    let json = serde_json::to_vec(&cursor).unwrap_or_default();
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json)
+}
+
+/// Key values the page continues after, checked against the ordering the
+/// cursor was issued for
+pub(crate) fn decode_cursor(
+    token: &str,
+    columns: &[String],
+    direction: SortDirection,
+) -> Result<Vec<Value>, ProviderError> {
+    let invalid =
+        || ProviderError::new("Invalid page cursor").with_hint("Start again from the first page.");
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(token)
+        .map_err(|_| invalid())?;
+    let cursor: Cursor = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+    if cursor.columns != columns || cursor.direction != direction {
+        return Err(
+            ProviderError::new("Page cursor is for a different sort order")
+                .with_hint("Start again from the first page."),
+        );
+    }
+    if cursor.values.len() != columns.len() {
+        return Err(invalid());
+    }
+    Ok(cursor.values)
+}
+
+/// Columns to page by: the requested key, or else the primary key
+pub(crate) fn key_columns(
+    request: &PageRequest,
+    schema: &str,
+    table: &str,
+    columns: &[ColumnInfo],
+) -> Result<Vec<String>, ProviderError> {
+    if columns.is_empty() {
+        return Err(ProviderError::new(format!(
+            "Table {}.{} does not exist",
+            schema, table
+        )));
+    }
+    if let Some(key) = &request.key {
+        if !columns.iter().any(|column| &column.name == key) {
+            return Err(ProviderError::new(format!("Unknown column {}", key)));
+        }
+        return Ok(vec![key.clone()]);
+    }
+    let primary_key: Vec<String> = columns
+        .iter()
+        .filter(|column| column.is_primary_key)
+        .map(|column| column.name.clone())
+        .collect();
+    if primary_key.is_empty() {
+        return Err(
+            ProviderError::new(format!("Table {}.{} has no primary key", schema, table))
+                .with_hint("Choose a unique column to page by."),
+        );
+    }
+    Ok(primary_key)
+}
+
+/// `SELECT *` from `target` (an already quoted table name) for the next
+/// page. One row more than the limit is fetched to tell whether another page
+/// follows.
+pub(crate) fn page_sql(
+    db_type: DatabaseType,
+    target: &str,
+    keys: &[String],
+    request: &PageRequest,
+    params: &mut Vec<Value>,
+) -> Result<String, ProviderError> {
+    let mut sql = format!(
+        "SELECT * FROM {}{}",
+        target,
+        filter::where_clause(db_type, &request.filters, params)?
+    );
+
+    let quoted: Vec<String> = keys
+        .iter()
+        .map(|key| sql::quote_identifier(key, db_type))
+        .collect();
+    let (comparison, direction) = match request.direction {
+        SortDirection::Asc => (">", "ASC"),
+        SortDirection::Desc => ("<", "DESC"),
+    };
+
+    if let Some(token) = &request.cursor {
+        let after = decode_cursor(token, keys, request.direction)?;
+        let placeholders: Vec<String> = after
+            .into_iter()
+            .map(|value| {
+                params.push(value);
+                sql::placeholder(db_type, params.len())
+            })
+            .collect();
+        sql.push_str(if request.filters.is_empty() {
+            " WHERE "
+        } else {
+            " AND "
+        });
+        // Row value comparison keeps composite keys in lexicographic order
+        if quoted.len() == 1 {
+            sql.push_str(&format!("{} {} {}", quoted[0], comparison, placeholders[0]));
+        } else {
+            sql.push_str(&format!(
+                "({}) {} ({})",
+                quoted.join(", "),
+                comparison,
+                placeholders.join(", ")
+            ));
+        }
+    }
+
+    let order: Vec<String> = quoted
+        .iter()
+        .map(|column| format!("{} {}", column, direction))
+        .collect();
+    sql.push_str(&format!(
+        " ORDER BY {} LIMIT {}",
+        order.join(", "),
+        request.limit + 1
+    ));
+    Ok(sql)
+}
+
+/// Trim the extra row fetched by `page_sql` and turn the last row's key into
+/// the next cursor
+pub(crate) fn finish_page(
+    mut result: QueryResult,
+    keys: &[String],
+    request: &PageRequest,
+) -> Result<TablePage, ProviderError> {
+    let limit = request.limit.max(0) as usize;
+    let mut next_cursor = None;
+    if result.rows.len() > limit {
+        result.rows.truncate(limit);
+        if let Some(last) = result.rows.last() {
+            let values = keys
+                .iter()
+                .map(|key| {
+                    result
+                        .columns
+                        .iter()
+                        .position(|column| column == key)
+                        .map(|index| last[index].clone())
+                        .ok_or_else(|| {
+                            ProviderError::new(format!("Key column {} is not in the result", key))
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            next_cursor = Some(encode_cursor(keys, request.direction, values));
+        }
+    }
+    result.row_count = result.rows.len();
+    Ok(TablePage {
+        result,
+        next_cursor,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(value: Value) -> PageRequest {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn next_page_continues_after_the_cursor() {
+        let keys = vec!["a".to_string(), "b".to_string()];
+        let cursor = encode_cursor(&keys, SortDirection::Asc, vec![json!(1), json!("x")]);
+        let request = request(json!({
+            "filters": [{"column": "c", "operator": "eq", "value": true}],
+            "cursor": cursor,
+            "limit": 2
+        }));
+
+        let mut params = Vec::new();
+        let sql = page_sql(
+            DatabaseType::Postgres,
+            r#""t""#,
+            &keys,
+            &request,
+            &mut params,
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            r#"SELECT * FROM "t" WHERE "c" = $1 AND ("a", "b") > ($2, $3) ORDER BY "a" ASC, "b" ASC LIMIT 3"#
+        );
+        assert_eq!(params, vec![json!(true), json!(1), json!("x")]);
+
+        let result = QueryResult {
+            columns: vec!["a".into(), "b".into(), "c".into()],
+            rows: vec![
+                vec![json!(1), json!("y"), json!(true)],
+                vec![json!(2), json!("a"), json!(true)],
+                vec![json!(3), json!("b"), json!(true)],
+            ],
+            row_count: 3,
+            ..Default::default()
+        };
+        let page = finish_page(result, &keys, &request).unwrap();
+        assert_eq!(page.result.row_count, 2);
+        let next = page.next_cursor.unwrap();
+        assert_eq!(
+            decode_cursor(&next, &keys, SortDirection::Asc).unwrap(),
+            vec![json!(2), json!("a")]
+        );
+    }
+
+    #[test]
+    fn cursors_only_fit_their_ordering() {
+        let keys = vec!["id".to_string()];
+        let cursor = encode_cursor(&keys, SortDirection::Asc, vec![json!(5)]);
+        assert!(decode_cursor(&cursor, &keys, SortDirection::Desc).is_err());
+        assert!(decode_cursor(&cursor, &["name".to_string()], SortDirection::Asc).is_err());
+        assert!(decode_cursor("not a cursor", &keys, SortDirection::Asc).is_err());
+    }
+}
@@ -1,12 +1,33 @@
-use async_trait::async_trait;
+use crate::edit;
+use crate::explain;
+use crate::filter;
+use crate::import::{self, ImportReport};
+use crate::pagination;
+use crate::postgres_ddl;
+use crate::postgres_types::PgValue;
+use crate::script::ScriptRecorder;
+use crate::stream::RowBatcher;
+use crate::transaction::TransactionSessions;
 use crate::{
-    ColumnInfo, ConnectionParams, DatabaseProvider, DatabaseType, ProviderError,
-    QueryResult, TableInfo,
+    cancel, sql, take_conn_option, CancelToken, ColumnFilter, ColumnInfo, ConnectionParams,
+    ConstraintInfo, ConstraintKind, DatabaseProvider, DatabaseType, ForeignKeyInfo, ImportBatch,
+    ImportBatches, ImportErrorMode, ImportSummary, IndexInfo, LogicalType, ObjectKind, PageRequest,
+    ProviderError, QueryCanceller, QueryPlan, QueryResult, QueryStreamEvent, ResultColumn,
+    RoutineInfo, RoutineKind, RowEdit, SchemaObjects, ScriptErrorMode, SequenceInfo, SslMode,
+    StatementResult, TableDetails, TableInfo, TablePage, TableQuery, TlsConfig, TriggerInfo,
+    ViewInfo,
 };
-use native_tls::TlsConnector;
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use futures_util::{pin_mut, SinkExt, TryStreamExt};
 use postgres_native_tls::MakeTlsConnector;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio_postgres::Client;
+use std::time::Instant;
+use tokio::sync::{mpsc, MappedMutexGuard, Mutex as AsyncMutex, MutexGuard};
+use tokio_postgres::config::SslMode as PgSslMode;
+use tokio_postgres::types::{to_sql_checked, Format, IsNull, Kind, ToSql, Type};
+use tokio_postgres::{Client, Config, Row};
 
 mod queries {
     pub const LIST_TABLES: &str = r#"
@@ -27,10 +48,15 @@ ORDER BY t.table_schema, t.table_name
 SELECT
     c.column_name as name,
     c.data_type,
+    pg_catalog.format_type(a.atttypid, a.atttypmod) as full_type,
     c.is_nullable = 'YES' as is_nullable,
     COALESCE(pk.is_primary_key, false) as is_primary_key,
-    c.column_default IS NOT NULL as has_default
+    c.column_default IS NOT NULL as has_default,
+    c.column_default as default_value
 FROM information_schema.columns c
+JOIN pg_catalog.pg_attribute a
+    ON a.attrelid = format('%I.%I', c.table_schema, c.table_name)::regclass
+    AND a.attname = c.column_name
 LEFT JOIN (
     SELECT kcu.column_name, true as is_primary_key
     FROM information_schema.table_constraints tc
@@ -43,6 +69,132 @@ LEFT JOIN (
 ) pk ON c.column_name = pk.column_name
 WHERE c.table_schema = $1 AND c.table_name = $2
 ORDER BY c.ordinal_position
+"#;
+
+    pub const COLUMN_ORIGINS: &str = r#"
+SELECT
+    a.attrelid as table_oid,
+    a.attnum as column_id,
+    n.nspname as schema,
+    c.relname as table_name,
+    a.attname as column_name,
+    a.attnotnull as not_null
+FROM pg_attribute a
+JOIN pg_class c ON c.oid = a.attrelid
+JOIN pg_namespace n ON n.oid = c.relnamespace
+WHERE a.attrelid = ANY($1) AND a.attnum > 0
+"#;
+
+    pub const LIST_VIEWS: &str = r#"
+SELECT
+    n.nspname as schema,
+    c.relname as name,
+    c.relkind = 'm' as materialized,
+    pg_get_viewdef(c.oid, true) as definition
+FROM pg_class c
+JOIN pg_namespace n ON n.oid = c.relnamespace
+WHERE c.relkind IN ('v', 'm')
+    AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+ORDER BY n.nspname, c.relname
+"#;
+
+    pub const LIST_SEQUENCES: &str = r#"
+SELECT
+    n.nspname as schema,
+    c.relname as name,
+    format_type(s.seqtypid, NULL) as data_type
+FROM pg_sequence s
+JOIN pg_class c ON c.oid = s.seqrelid
+JOIN pg_namespace n ON n.oid = c.relnamespace
+WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
+ORDER BY n.nspname, c.relname
+"#;
+
+    /// Functions and procedures, leaving out those installed by extensions
+    pub const LIST_ROUTINES: &str = r#"
+SELECT
+    n.nspname as schema,
+    p.proname as name,
+    p.prokind = 'p' as is_procedure,
+    pg_get_function_identity_arguments(p.oid) as arguments,
+    CASE WHEN p.prokind = 'p' THEN NULL ELSE pg_get_function_result(p.oid) END as return_type,
+    l.lanname as language
+FROM pg_proc p
+JOIN pg_namespace n ON n.oid = p.pronamespace
+JOIN pg_language l ON l.oid = p.prolang
+WHERE p.prokind IN ('f', 'p')
+    AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+    AND NOT EXISTS (
+        SELECT 1 FROM pg_depend d
+        WHERE d.classid = 'pg_proc'::regclass AND d.objid = p.oid AND d.deptype = 'e'
+    )
+ORDER BY n.nspname, p.proname
+"#;
+
+    pub const TABLE_INDEXES: &str = r#"
+SELECT
+    i.relname as name,
+    ARRAY(
+        SELECT pg_get_indexdef(x.indexrelid, k, true)
+        FROM generate_series(1, x.indnkeyatts::int) k
+        ORDER BY k
+    ) as columns,
+    x.indisunique as is_unique,
+    x.indisprimary as is_primary,
+    am.amname::text as method,
+    pg_get_expr(x.indpred, x.indrelid, true) as predicate
+FROM pg_index x
+JOIN pg_class i ON i.oid = x.indexrelid
+JOIN pg_class t ON t.oid = x.indrelid
+JOIN pg_namespace n ON n.oid = t.relnamespace
+JOIN pg_am am ON am.oid = i.relam
+WHERE n.nspname = $1 AND t.relname = $2
+ORDER BY i.relname
+"#;
+
+    /// Primary key, unique, check, exclusion and foreign key constraints.
+    /// Key columns keep their constraint order.
+    pub const TABLE_CONSTRAINTS: &str = r#"
+SELECT
+    con.conname as name,
+    con.contype::text as kind,
+    ARRAY(
+        SELECT a.attname::text
+        FROM unnest(con.conkey) WITH ORDINALITY k(attnum, ord)
+        JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+        ORDER BY k.ord
+    ) as columns,
+    pg_get_constraintdef(con.oid, true) as definition,
+    fn.nspname as referenced_schema,
+    ft.relname as referenced_table,
+    ARRAY(
+        SELECT a.attname::text
+        FROM unnest(con.confkey) WITH ORDINALITY k(attnum, ord)
+        JOIN pg_attribute a ON a.attrelid = con.confrelid AND a.attnum = k.attnum
+        ORDER BY k.ord
+    ) as referenced_columns,
+    con.confupdtype::text as on_update,
+    con.confdeltype::text as on_delete
+FROM pg_constraint con
+JOIN pg_class t ON t.oid = con.conrelid
+JOIN pg_namespace n ON n.oid = t.relnamespace
+LEFT JOIN pg_class ft ON ft.oid = con.confrelid
+LEFT JOIN pg_namespace fn ON fn.oid = ft.relnamespace
+WHERE n.nspname = $1 AND t.relname = $2
+    AND con.contype IN ('p', 'u', 'c', 'x', 'f')
+ORDER BY con.conname
+"#;
+
+    pub const TABLE_TRIGGERS: &str = r#"
+SELECT
+    tg.tgname as name,
+    tg.tgtype::int as trigger_type,
+    pg_get_triggerdef(tg.oid, true) as definition
+FROM pg_trigger tg
+JOIN pg_class t ON t.oid = tg.tgrelid
+JOIN pg_namespace n ON n.oid = t.relnamespace
+WHERE n.nspname = $1 AND t.relname = $2 AND NOT tg.tgisinternal
+ORDER BY tg.tgname
 "#;
 
     pub fn select_table_data(schema: &str, table: &str, limit: i64, offset: i64) -> String {
@@ -59,40 +211,95 @@ ORDER BY c.ordinal_position
 
 pub struct PostgresProvider {
     client: Arc<Client>,
+    /// TLS connector the client was opened with; cancel requests need the same one.
+    tls: Option<MakeTlsConnector>,
+    config: Config,
+    /// Each open transaction gets its own connection so it does not leak into
+    /// statements running on the shared client.
+    transactions: TransactionSessions<Client>,
+    /// Session for row edits, opened on first use
+    edit_client: AsyncMutex<Option<Client>>,
+    /// Session for editor queries and streams, opened on first use. They
+    /// take turns on it, so session state (`SET`, temp tables, a `BEGIN`)
+    /// carries between them and a cancel request only reaches the query
+    /// that registered it, never the catalog queries on `client`.
+    session: AsyncMutex<Option<Client>>,
 }
 
 impl PostgresProvider {
     pub async fn connect(params: ConnectionParams) -> Result<Self, ProviderError> {
-        let conn_string = params.to_postgres_string();
+        let (config, tls_config) = Self::build_config(&params)?;
 
-        let client = match Self::connect_with_ssl(&conn_string).await {
-            Ok(client) => client,
-            Err(_ssl_err) => Self::connect_without_ssl(&conn_string).await?,
+        let tls = match tls_config.mode {
+            SslMode::Disable => None,
+            _ => Some(MakeTlsConnector::new(tls_config.native_tls_connector()?)),
         };
+        let client = Self::open_client(&config, tls.as_ref()).await?;
 
         Ok(Self {
             client: Arc::new(client),
+            tls,
+            config,
+            transactions: TransactionSessions::default(),
+            edit_client: AsyncMutex::new(None),
+            session: AsyncMutex::new(None),
         })
     }
 
-    async fn connect_with_ssl(conn_string: &str) -> Result<Client, ProviderError> {
-        let tls_connector = TlsConnector::builder()
-            .danger_accept_invalid_certs(true)
-            .danger_accept_invalid_hostnames(true)
-            .build()
-            .map_err(|e| ProviderError::new(format!("Failed to create TLS connector: {}", e)))?;
+    /// Parse the connection string into a driver config. The driver only knows
+    /// a subset of libpq's TLS options, so `sslmode`, `sslrootcert`, `sslcert`
+    /// and `sslkey` are taken out first; explicit TLS params replace them.
+    fn build_config(params: &ConnectionParams) -> Result<(Config, TlsConfig), ProviderError> {
+        let mut conn_string = params.to_postgres_string();
+        let conn_tls = TlsConfig {
+            mode: take_conn_option(&mut conn_string, "sslmode")
+                .map(|mode| SslMode::parse(&mode))
+                .transpose()?
+                .unwrap_or_default(),
+            ca_cert_path: take_conn_option(&mut conn_string, "sslrootcert"),
+            client_cert_path: take_conn_option(&mut conn_string, "sslcert"),
+            client_key_path: take_conn_option(&mut conn_string, "sslkey"),
+            server_name: None,
+        };
+        let tls = params.tls().cloned().unwrap_or(conn_tls);
 
-        let connector = MakeTlsConnector::new(tls_connector);
+        let mut config: Config = conn_string
+            .parse()
+            .map_err(|e| ProviderError::new(format!("Invalid connection string: {}", e)))?;
+        // `prefer` only falls back to plaintext when the server declines TLS,
+        // never after a failed handshake or verification.
+        config.ssl_mode(match tls.mode {
+            SslMode::Disable => PgSslMode::Disable,
+            SslMode::Prefer => PgSslMode::Prefer,
+            SslMode::Require | SslMode::VerifyCa | SslMode::VerifyFull => PgSslMode::Require,
+        });
 
-        let ssl_conn_string = if conn_string.contains("sslmode=") {
-            conn_string.to_string()
-        } else {
-            format!("{} sslmode=require", conn_string)
+        Ok((config, tls))
+    }
+
+    async fn open_client(
+        config: &Config,
+        tls: Option<&MakeTlsConnector>,
+    ) -> Result<Client, ProviderError> {
+        let Some(tls) = tls else {
+            let (client, connection) = config
+                .connect(tokio_postgres::NoTls)
+                .await
+                .map_err(|e| ProviderError::new(format!("Connection failed: {}", e)))?;
+
+            tokio::spawn(async move {
+                if let Err(e) = connection.await {
+                    eprintln!("PostgreSQL connection error: {}", e);
+                }
+            });
+
+            return Ok(client);
         };
 
-        let (client, connection) = tokio_postgres::connect(&ssl_conn_string, connector)
+        let (client, connection) = config
+            .connect(tls.clone())
             .await
-            .map_err(|e| ProviderError::new(format!("SSL connection failed: {}", e)))?;
+            .map_err(|e| ProviderError::new(format!("Connection failed: {}", e)))?;
 
         tokio::spawn(async move {
             if let Err(e) = connection.await {
@@ -103,21 +310,117 @@ impl PostgresProvider {
         Ok(client)
     }
 
-    async fn connect_without_ssl(conn_string: &str) -> Result<Client, ProviderError> {
-        let (client, connection) = tokio_postgres::connect(conn_string, tokio_postgres::NoTls)
+    /// The query session, reopened if its connection has closed
+    async fn session(&self) -> Result<MappedMutexGuard<'_, Client>, ProviderError> {
+        let mut session = self.session.lock().await;
+        let client = match session.take() {
+            Some(client) if !client.is_closed() => client,
+            _ => Self::open_client(&self.config, self.tls.as_ref()).await?,
+        };
+        Ok(MutexGuard::map(session, |session| session.insert(client)))
+    }
+
+    async fn catalog_query(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, ProviderError> {
+        self.client
+            .query(query, params)
             .await
-            .map_err(|e| ProviderError::new(format!("Connection failed: {}", e)))?;
+            .map_err(Self::format_db_error)
+    }
 
-        tokio::spawn(async move {
-            if let Err(e) = connection.await {
-                eprintln!("PostgreSQL connection error: {}", e);
+    /// COPY one import batch. If the database rejects it, the rows are
+    /// inserted one at a time to find the failing ones, each under its own
+    /// savepoint so the transaction survives.
+    async fn import_batch(
+        client: &Client,
+        copy: &str,
+        insert: &str,
+        batch: &ImportBatch,
+        report: &mut ImportReport,
+    ) -> Result<(), ProviderError> {
+        client
+            .batch_execute("SAVEPOINT import_batch")
+            .await
+            .map_err(Self::format_db_error)?;
+
+        let copied = async {
+            let sink = client.copy_in::<_, Bytes>(copy).await?;
+            pin_mut!(sink);
+            sink.send(Bytes::from(import::copy_csv(&batch.rows)))
+                .await?;
+            sink.finish().await
+        }
+        .await;
+        if let Ok(count) = copied {
+            report.imported(count);
+            return client
+                .batch_execute("RELEASE import_batch")
+                .await
+                .map_err(Self::format_db_error);
+        }
+
+        client
+            .batch_execute("ROLLBACK TO import_batch")
+            .await
+            .map_err(Self::format_db_error)?;
+        for (offset, row) in batch.rows.iter().enumerate() {
+            client
+                .batch_execute("SAVEPOINT import_row")
+                .await
+                .map_err(Self::format_db_error)?;
+            let params: Vec<JsonParam> = row.iter().cloned().map(JsonParam).collect();
+            let inserted = client
+                .execute_raw(insert, params.iter().map(|p| p as &(dyn ToSql + Sync)))
+                .await;
+            match inserted {
+                Ok(_) => {
+                    report.imported(1);
+                    client.batch_execute("RELEASE import_row").await
+                }
+                Err(e) => {
+                    report.row_failed(batch.first_row + offset as u64, Self::format_db_error(e))?;
+                    client.batch_execute("ROLLBACK TO import_row").await
+                }
             }
-        });
+            .map_err(Self::format_db_error)?;
+        }
+        client
+            .batch_execute("RELEASE import_batch")
+            .await
+            .map_err(Self::format_db_error)
+    }
 
-        Ok(client)
+    /// Run all edit statements in one transaction, rolling back on the first
+    /// failure or unexpected row count.
+    async fn write_edits(
+        client: &mut Client,
+        statements: &[edit::EditStatement],
+    ) -> Result<u64, ProviderError> {
+        let transaction = client.transaction().await.map_err(Self::format_db_error)?;
+
+        let mut rows_written = 0;
+        for (index, statement) in statements.iter().enumerate() {
+            let params: Vec<JsonParam> = statement.params.iter().cloned().map(JsonParam).collect();
+            let count = transaction
+                .execute_raw(
+                    statement.sql.as_str(),
+                    params.iter().map(|p| p as &(dyn ToSql + Sync)),
+                )
+                .await
+                .map_err(|e| edit::edit_failed(index, Self::format_db_error(e)))?;
+            // Returning early drops the transaction, which rolls it back.
+            statement.check_rows_affected(index, count)?;
+            rows_written += count;
+        }
+
+        transaction.commit().await.map_err(Self::format_db_error)?;
+        Ok(rows_written)
     }
 
-    fn format_db_error(e: tokio_postgres::Error) -> ProviderError {
+    pub(crate) fn format_db_error(e: tokio_postgres::Error) -> ProviderError {
         if let Some(db_err) = e.as_db_error() {
             let mut err = ProviderError::new(db_err.message());
 
@@ -134,6 +437,135 @@ impl PostgresProvider {
 
         ProviderError::new(e.to_string())
     }
+
+    async fn run_query(
+        client: &Client,
+        query: &str,
+        params: &[JsonParam],
+    ) -> Result<QueryResult, ProviderError> {
+        let statement = client.prepare(query).await.map_err(Self::format_db_error)?;
+
+        let columns: Vec<String> = statement
+            .columns()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect();
+        let column_metadata = Self::describe_columns(client, statement.columns()).await;
+
+        let stream = client
+            .query_raw(&statement, params.iter().map(|p| p as &(dyn ToSql + Sync)))
+            .await
+            .map_err(Self::format_db_error)?;
+        pin_mut!(stream);
+
+        let mut result_rows: Vec<Vec<serde_json::Value>> = Vec::new();
+        while let Some(row) = stream.try_next().await.map_err(Self::format_db_error)? {
+            result_rows.push(row_to_json(&row));
+        }
+
+        let rows_affected = stream.rows_affected();
+
+        Ok(QueryResult {
+            columns,
+            column_metadata,
+            row_count: result_rows.len(),
+            rows: result_rows,
+            rows_affected,
+            last_insert_id: None,
+            command_tag: rows_affected.and_then(|count| sql::command_tag(query, count)),
+        })
+    }
+
+    /// Resolve result columns to their source table columns via the catalog.
+    /// Origin details are best-effort: a failed lookup only drops them.
+    async fn describe_columns(
+        client: &Client,
+        columns: &[tokio_postgres::Column],
+    ) -> Vec<ResultColumn> {
+        let mut table_oids: Vec<u32> = columns.iter().filter_map(|c| c.table_oid()).collect();
+        table_oids.sort_unstable();
+        table_oids.dedup();
+
+        let mut origins: HashMap<(u32, i16), tokio_postgres::Row> = HashMap::new();
+        if !table_oids.is_empty() {
+            if let Ok(rows) = client.query(queries::COLUMN_ORIGINS, &[&table_oids]).await {
+                for row in rows {
+                    origins.insert((row.get("table_oid"), row.get("column_id")), row);
+                }
+            }
+        }
+
+        columns
+            .iter()
+            .map(|column| {
+                let origin = column
+                    .table_oid()
+                    .zip(column.column_id())
+                    .and_then(|key| origins.get(&key));
+
+                ResultColumn {
+                    name: column.name().to_string(),
+                    native_type: column.type_().name().to_string(),
+                    logical_type: postgres_logical_type(column.type_()),
+                    nullable: origin.map(|row| !row.get::<_, bool>("not_null")),
+                    source_schema: origin.map(|row| row.get("schema")),
+                    source_table: origin.map(|row| row.get("table_name")),
+                    source_column: origin.map(|row| row.get("column_name")),
+                }
+            })
+            .collect()
+    }
+
+    async fn stream_rows(
+        client: &Client,
+        query: &str,
+        batch_size: usize,
+        tx: mpsc::Sender<QueryStreamEvent>,
+    ) -> Result<(), ProviderError> {
+        let statement = client.prepare(query).await.map_err(Self::format_db_error)?;
+
+        let mut batcher = RowBatcher::new(tx, batch_size);
+        batcher
+            .columns(
+                statement
+                    .columns()
+                    .iter()
+                    .map(|c| c.name().to_string())
+                    .collect(),
+            )
+            .await?;
+
+        let rows = client
+            .query_raw(&statement, std::iter::empty::<&(dyn ToSql + Sync)>())
+            .await
+            .map_err(Self::format_db_error)?;
+        pin_mut!(rows);
+
+        while let Some(row) = rows.try_next().await.map_err(Self::format_db_error)? {
+            batcher.push(row_to_json(&row)).await?;
+        }
+
+        batcher.finish().await
+    }
+}
+
+/// Sends a Postgres cancel request for whatever the query session is
+/// currently running.
+struct PostgresCanceller {
+    token: tokio_postgres::CancelToken,
+    tls: Option<MakeTlsConnector>,
+}
+
+#[async_trait]
+impl QueryCanceller for PostgresCanceller {
+    async fn cancel(&self) -> Result<(), ProviderError> {
+        let result = match &self.tls {
+            Some(tls) => self.token.cancel_query(tls.clone()).await,
+            None => self.token.cancel_query(tokio_postgres::NoTls).await,
+        };
+
+        result.map_err(|e| ProviderError::new(format!("Failed to cancel query: {}", e)))
+    }
 }
 
 #[async_trait]
@@ -181,9 +613,12 @@ impl DatabaseProvider for PostgresProvider {
             .map(|row| ColumnInfo {
                 name: row.get("name"),
                 data_type: row.get("data_type"),
+                full_type: row.get("full_type"),
+                nested_paths: Vec::new(),
                 is_nullable: row.get("is_nullable"),
                 is_primary_key: row.get("is_primary_key"),
                 has_default: row.get("has_default"),
+                default_value: row.get("default_value"),
             })
             .collect();
 
@@ -202,136 +637,621 @@ impl DatabaseProvider for PostgresProvider {
     }
 
     async fn execute_query(&self, query: &str) -> Result<QueryResult, ProviderError> {
-        let rows = self
+        Self::run_query(&self.client, query, &[]).await
+    }
+
+    async fn execute_query_with_params(
+        &self,
+        query: &str,
+        params: Vec<serde_json::Value>,
+    ) -> Result<QueryResult, ProviderError> {
+        let params: Vec<JsonParam> = params.into_iter().map(JsonParam).collect();
+        Self::run_query(&self.client, query, &params).await
+    }
+
+    async fn execute_query_cancellable(
+        &self,
+        query: &str,
+        cancel: &CancelToken,
+    ) -> Result<QueryResult, ProviderError> {
+        let client = self.session().await?;
+        cancel.set_canceller(PostgresCanceller {
+            token: client.cancel_token(),
+            tls: self.tls.clone(),
+        });
+
+        let result = tokio::select! {
+            result = Self::run_query(&client, query, &[]) => result,
+            _ = cancel.cancelled() => Err(cancel::cancelled_error()),
+        };
+
+        cancel.clear_canceller();
+        result
+    }
+
+    /// Every statement runs on the query session, so a script's `BEGIN`,
+    /// `SET` or temp tables hold for the statements after it.
+    async fn execute_script(
+        &self,
+        script: &str,
+        on_error: ScriptErrorMode,
+        cancel: &CancelToken,
+    ) -> Result<Vec<StatementResult>, ProviderError> {
+        let client = self.session().await?;
+        cancel.set_canceller(PostgresCanceller {
+            token: client.cancel_token(),
+            tls: self.tls.clone(),
+        });
+
+        let mut recorder = ScriptRecorder::new(on_error);
+        for statement in sql::split_statements(script, DatabaseType::Postgres) {
+            let started = Instant::now();
+            let result = tokio::select! {
+                result = Self::run_query(&client, &statement, &[]) => result,
+                _ = cancel.cancelled() => Err(cancel::cancelled_error()),
+            };
+            if !recorder.record(statement, result, started, cancel) {
+                break;
+            }
+        }
+
+        cancel.clear_canceller();
+        Ok(recorder.finish())
+    }
+
+    async fn execute_query_stream(
+        &self,
+        query: &str,
+        batch_size: usize,
+        tx: mpsc::Sender<QueryStreamEvent>,
+        cancel: &CancelToken,
+    ) -> Result<(), ProviderError> {
+        let client = self.session().await?;
+        cancel.set_canceller(PostgresCanceller {
+            token: client.cancel_token(),
+            tls: self.tls.clone(),
+        });
+
+        let result = tokio::select! {
+            result = Self::stream_rows(&client, query, batch_size, tx) => result,
+            _ = cancel.cancelled() => Err(cancel::cancelled_error()),
+        };
+
+        cancel.clear_canceller();
+        result
+    }
+
+    async fn get_table_count(&self, schema: &str, table: &str) -> Result<i64, ProviderError> {
+        let query = queries::count_table_rows(schema, table);
+        let row = self
             .client
-            .query(query, &[])
+            .query_one(&query, &[])
             .await
             .map_err(Self::format_db_error)?;
 
-        if rows.is_empty() {
-            return Ok(QueryResult {
-                columns: vec![],
-                rows: vec![],
-                row_count: 0,
-            });
-        }
+        Ok(row.get::<_, i64>("count"))
+    }
 
-        let columns: Vec<String> = rows[0]
-            .columns()
+    async fn get_table_data_filtered(
+        &self,
+        schema: &str,
+        table: &str,
+        query: &TableQuery,
+        limit: i64,
+        offset: i64,
+    ) -> Result<QueryResult, ProviderError> {
+        let mut params = Vec::new();
+        let statement = filter::select_sql(
+            DatabaseType::Postgres,
+            &sql::qualified_name(schema, table, DatabaseType::Postgres),
+            query,
+            limit,
+            offset,
+            &mut params,
+        )?;
+        self.execute_query_with_params(&statement, params).await
+    }
+
+    async fn get_table_count_filtered(
+        &self,
+        schema: &str,
+        table: &str,
+        filters: &[ColumnFilter],
+    ) -> Result<i64, ProviderError> {
+        let mut params = Vec::new();
+        let statement = filter::count_sql(
+            DatabaseType::Postgres,
+            &sql::qualified_name(schema, table, DatabaseType::Postgres),
+            filters,
+            &mut params,
+        )?;
+        let result = self.execute_query_with_params(&statement, params).await?;
+        Ok(filter::count_from_result(&result))
+    }
+
+    async fn get_table_page(
+        &self,
+        schema: &str,
+        table: &str,
+        request: &PageRequest,
+    ) -> Result<TablePage, ProviderError> {
+        let columns = self.get_table_columns(schema, table).await?;
+        let keys = pagination::key_columns(request, schema, table, &columns)?;
+        let mut params = Vec::new();
+        let statement = pagination::page_sql(
+            DatabaseType::Postgres,
+            &sql::qualified_name(schema, table, DatabaseType::Postgres),
+            &keys,
+            request,
+            &mut params,
+        )?;
+        let result = self.execute_query_with_params(&statement, params).await?;
+        pagination::finish_page(result, &keys, request)
+    }
+
+    async fn list_schema_objects(&self) -> Result<SchemaObjects, ProviderError> {
+        let (tables, views, sequences, routines) = tokio::try_join!(
+            self.list_tables(),
+            self.catalog_query(queries::LIST_VIEWS, &[]),
+            self.catalog_query(queries::LIST_SEQUENCES, &[]),
+            self.catalog_query(queries::LIST_ROUTINES, &[]),
+        )?;
+
+        Ok(SchemaObjects {
+            tables,
+            views: views
+                .iter()
+                .map(|row| ViewInfo {
+                    schema: row.get("schema"),
+                    name: row.get("name"),
+                    materialized: row.get("materialized"),
+                    definition: row.get("definition"),
+                })
+                .collect(),
+            sequences: sequences
+                .iter()
+                .map(|row| SequenceInfo {
+                    schema: row.get("schema"),
+                    name: row.get("name"),
+                    data_type: row.get("data_type"),
+                })
+                .collect(),
+            routines: routines
+                .iter()
+                .map(|row| RoutineInfo {
+                    schema: row.get("schema"),
+                    name: row.get("name"),
+                    kind: if row.get("is_procedure") {
+                        RoutineKind::Procedure
+                    } else {
+                        RoutineKind::Function
+                    },
+                    arguments: row.get("arguments"),
+                    return_type: row.get("return_type"),
+                    language: row.get("language"),
+                })
+                .collect(),
+        })
+    }
+
+    async fn describe_table(
+        &self,
+        schema: &str,
+        table: &str,
+    ) -> Result<TableDetails, ProviderError> {
+        let params: [&(dyn ToSql + Sync); 2] = [&schema, &table];
+        let (columns, indexes, constraints, triggers) = tokio::try_join!(
+            self.get_table_columns(schema, table),
+            self.catalog_query(queries::TABLE_INDEXES, &params),
+            self.catalog_query(queries::TABLE_CONSTRAINTS, &params),
+            self.catalog_query(queries::TABLE_TRIGGERS, &params),
+        )?;
+
+        let mut details = TableDetails {
+            schema: schema.to_string(),
+            name: table.to_string(),
+            columns,
+            ..Default::default()
+        };
+
+        details.indexes = indexes
             .iter()
-            .map(|c| c.name().to_string())
+            .map(|row| IndexInfo {
+                name: row.get("name"),
+                columns: row.get("columns"),
+                is_unique: row.get("is_unique"),
+                is_primary: row.get("is_primary"),
+                method: row.get("method"),
+                predicate: row.get("predicate"),
+            })
             .collect();
 
-        let result_rows: Vec<Vec<serde_json::Value>> = rows
+        for row in &constraints {
+            let kind = match row.get::<_, &str>("kind") {
+                "p" => ConstraintKind::PrimaryKey,
+                "u" => ConstraintKind::Unique,
+                "c" => ConstraintKind::Check,
+                "x" => ConstraintKind::Exclusion,
+                _ => {
+                    details.foreign_keys.push(ForeignKeyInfo {
+                        name: row.get("name"),
+                        columns: row.get("columns"),
+                        referenced_schema: row.get("referenced_schema"),
+                        referenced_table: row.get("referenced_table"),
+                        referenced_columns: row.get("referenced_columns"),
+                        on_update: referential_action(row.get("on_update")),
+                        on_delete: referential_action(row.get("on_delete")),
+                    });
+                    continue;
+                }
+            };
+            details.constraints.push(ConstraintInfo {
+                name: row.get("name"),
+                kind,
+                columns: row.get("columns"),
+                definition: row.get("definition"),
+            });
+        }
+
+        details.triggers = triggers
             .iter()
             .map(|row| {
-                row.columns()
-                    .iter()
-                    .enumerate()
-                    .map(|(i, col)| postgres_value_to_json(row, i, col.type_()))
-                    .collect()
+                let (timing, events) = trigger_timing_and_events(row.get("trigger_type"));
+                TriggerInfo {
+                    name: row.get("name"),
+                    timing,
+                    events,
+                    definition: row.get("definition"),
+                }
             })
             .collect();
 
-        Ok(QueryResult {
-            columns,
-            row_count: result_rows.len(),
-            rows: result_rows,
-        })
+        Ok(details)
     }
 
-    async fn get_table_count(&self, schema: &str, table: &str) -> Result<i64, ProviderError> {
-        let query = queries::count_table_rows(schema, table);
-        let row = self
-            .client
-            .query_one(&query, &[])
+    async fn get_object_ddl(
+        &self,
+        schema: &str,
+        name: &str,
+        kind: ObjectKind,
+    ) -> Result<String, ProviderError> {
+        postgres_ddl::object_ddl(&self.client, schema, name, kind).await
+    }
+
+    async fn explain_query(&self, query: &str, analyze: bool) -> Result<QueryPlan, ProviderError> {
+        let query = query.trim().trim_end_matches(';');
+        if !analyze {
+            let row = self
+                .client
+                .query_one(&format!("EXPLAIN (FORMAT JSON) {}", query), &[])
+                .await
+                .map_err(Self::format_db_error)?;
+            return explain::from_postgres(row.get(0));
+        }
+
+        // ANALYZE executes the statement, so run it on a session of its own
+        // and roll back whatever it changed.
+        let client = Self::open_client(&self.config, self.tls.as_ref()).await?;
+        client
+            .batch_execute("BEGIN")
             .await
             .map_err(Self::format_db_error)?;
+        let result = client
+            .query_one(
+                &format!("EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON) {}", query),
+                &[],
+            )
+            .await;
+        // Dropping the session discards the transaction if this fails.
+        let _ = client.batch_execute("ROLLBACK").await;
 
-        Ok(row.get::<_, i64>("count"))
+        explain::from_postgres(result.map_err(Self::format_db_error)?.get(0))
+    }
+
+    async fn apply_row_edits(
+        &self,
+        schema: &str,
+        table: &str,
+        edits: Vec<RowEdit>,
+        allow_without_primary_key: bool,
+    ) -> Result<u64, ProviderError> {
+        let columns = self.get_table_columns(schema, table).await?;
+        let statements = edit::build_statements(
+            DatabaseType::Postgres,
+            schema,
+            table,
+            &columns,
+            &edits,
+            allow_without_primary_key,
+        )?;
+
+        // The shared client may be running other queries, so edit sets run
+        // on a session of their own that is kept between calls.
+        let mut edit_client = self.edit_client.lock().await;
+        let mut client = match edit_client.take() {
+            Some(client) if !client.is_closed() => client,
+            _ => Self::open_client(&self.config, self.tls.as_ref()).await?,
+        };
+        let result = Self::write_edits(&mut client, &statements).await;
+        *edit_client = Some(client);
+        result
+    }
+
+    async fn import_rows(
+        &self,
+        schema: &str,
+        table: &str,
+        columns: &[String],
+        mut batches: ImportBatches,
+        on_error: ImportErrorMode,
+    ) -> Result<ImportSummary, ProviderError> {
+        let target = sql::qualified_name(schema, table, DatabaseType::Postgres);
+        let names: Vec<String> = columns
+            .iter()
+            .map(|column| sql::quote_identifier(column, DatabaseType::Postgres))
+            .collect();
+        let copy = format!(
+            "COPY {} ({}) FROM STDIN WITH (FORMAT csv)",
+            target,
+            names.join(", ")
+        );
+        let insert = import::insert_sql(DatabaseType::Postgres, &target, columns, 1);
+
+        let client = Self::open_client(&self.config, self.tls.as_ref()).await?;
+        client
+            .batch_execute("BEGIN")
+            .await
+            .map_err(Self::format_db_error)?;
+
+        let mut report = ImportReport::new(on_error);
+        let mut result = Ok(());
+        while let Some(batch) = batches.recv().await {
+            result = match batch {
+                Ok(batch) => Self::import_batch(&client, &copy, &insert, &batch, &mut report).await,
+                Err(e) => Err(e),
+            };
+            if result.is_err() {
+                break;
+            }
+        }
+        if let Err(e) = result {
+            let _ = client.batch_execute("ROLLBACK").await;
+            return Err(e);
+        }
+
+        client
+            .batch_execute("COMMIT")
+            .await
+            .map_err(Self::format_db_error)?;
+        Ok(report.finish())
+    }
+
+    async fn begin_transaction(&self) -> Result<String, ProviderError> {
+        let client = Self::open_client(&self.config, self.tls.as_ref()).await?;
+
+        client
+            .batch_execute("BEGIN")
+            .await
+            .map_err(Self::format_db_error)?;
+
+        Ok(self.transactions.insert(client))
+    }
+
+    async fn execute_in_transaction(
+        &self,
+        transaction_id: &str,
+        query: &str,
+    ) -> Result<QueryResult, ProviderError> {
+        let session = self.transactions.get(transaction_id)?;
+        let client = session.lock().await;
+        Self::run_query(&client, query, &[]).await
+    }
+
+    async fn commit_transaction(&self, transaction_id: &str) -> Result<(), ProviderError> {
+        let session = self.transactions.get(transaction_id)?;
+        let client = session.lock().await;
+        let result = client
+            .batch_execute("COMMIT")
+            .await
+            .map_err(Self::format_db_error);
+        // A COMMIT that fails rolls the transaction back, so it is over
+        // either way
+        self.transactions.remove(transaction_id)?;
+        result
+    }
+
+    async fn rollback_transaction(&self, transaction_id: &str) -> Result<(), ProviderError> {
+        let session = self.transactions.get(transaction_id)?;
+        let client = session.lock().await;
+        let result = client
+            .batch_execute("ROLLBACK")
+            .await
+            .map_err(Self::format_db_error);
+        self.transactions.remove(transaction_id)?;
+        result
+    }
+
+    fn open_transactions(&self) -> Vec<String> {
+        self.transactions.ids()
     }
 }
 
-fn postgres_value_to_json(
-    row: &tokio_postgres::Row,
-    idx: usize,
-    pg_type: &tokio_postgres::types::Type,
-) -> serde_json::Value {
-    use tokio_postgres::types::Type;
+/// A JSON value bound as a statement parameter. Booleans, numbers and JSON
+/// columns are sent in binary; everything else goes over the text format so
+/// Postgres parses it with the column type's own input function.
+#[derive(Debug)]
+struct JsonParam(serde_json::Value);
+
+impl JsonParam {
+    fn is_binary(&self, ty: &Type) -> bool {
+        use serde_json::Value;
+
+        match (&self.0, ty) {
+            // Strings destined for JSON columns are raw JSON text.
+            (Value::String(_), _) => false,
+            (_, &Type::JSON | &Type::JSONB) => true,
+            (Value::Bool(_), &Type::BOOL) => true,
+            (
+                Value::Number(_),
+                &Type::INT2 | &Type::INT4 | &Type::INT8 | &Type::FLOAT4 | &Type::FLOAT8,
+            ) => true,
+            _ => false,
+        }
+    }
+}
+
+impl ToSql for JsonParam {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        use serde_json::Value;
+
+        if self.0.is_null() {
+            return Ok(IsNull::Yes);
+        }
+
+        if !self.is_binary(ty) {
+            let text = match &self.0 {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            out.extend_from_slice(text.as_bytes());
+            return Ok(IsNull::No);
+        }
+
+        let out_of_range = || format!("{} is out of range for type {}", self.0, ty);
+        match *ty {
+            Type::JSON | Type::JSONB => self.0.to_sql(ty, out),
+            Type::BOOL => self.0.as_bool().unwrap_or_default().to_sql(ty, out),
+            Type::INT2 => i16::try_from(self.0.as_i64().ok_or_else(out_of_range)?)
+                .map_err(|_| out_of_range())?
+                .to_sql(ty, out),
+            Type::INT4 => i32::try_from(self.0.as_i64().ok_or_else(out_of_range)?)
+                .map_err(|_| out_of_range())?
+                .to_sql(ty, out),
+            Type::INT8 => self.0.as_i64().ok_or_else(out_of_range)?.to_sql(ty, out),
+            Type::FLOAT4 => (self.0.as_f64().unwrap_or_default() as f32).to_sql(ty, out),
+            _ => self.0.as_f64().unwrap_or_default().to_sql(ty, out),
+        }
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+
+    fn encode_format(&self, ty: &Type) -> Format {
+        if self.is_binary(ty) {
+            Format::Binary
+        } else {
+            Format::Text
+        }
+    }
+
+    to_sql_checked!();
+}
+
+/// SQL spelling of a `pg_constraint.confupdtype`/`confdeltype` code
+fn referential_action(code: &str) -> String {
+    match code {
+        "a" => "NO ACTION",
+        "r" => "RESTRICT",
+        "c" => "CASCADE",
+        "n" => "SET NULL",
+        "d" => "SET DEFAULT",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Timing and events encoded in the `pg_trigger.tgtype` bit mask
+fn trigger_timing_and_events(trigger_type: i32) -> (String, Vec<String>) {
+    const BEFORE: i32 = 1 << 1;
+    const INSERT: i32 = 1 << 2;
+    const DELETE: i32 = 1 << 3;
+    const UPDATE: i32 = 1 << 4;
+    const TRUNCATE: i32 = 1 << 5;
+    const INSTEAD: i32 = 1 << 6;
+
+    let timing = if trigger_type & INSTEAD != 0 {
+        "INSTEAD OF"
+    } else if trigger_type & BEFORE != 0 {
+        "BEFORE"
+    } else {
+        "AFTER"
+    };
+    let events = [
+        (INSERT, "INSERT"),
+        (UPDATE, "UPDATE"),
+        (DELETE, "DELETE"),
+        (TRUNCATE, "TRUNCATE"),
+    ]
+    .into_iter()
+    .filter(|(bit, _)| trigger_type & bit != 0)
+    .map(|(_, event)| event.to_string())
+    .collect();
+
+    (timing.to_string(), events)
+}
+
+fn postgres_logical_type(pg_type: &Type) -> LogicalType {
+    if let Kind::Array(_) = pg_type.kind() {
+        return LogicalType::Array;
+    }
 
     match *pg_type {
-        Type::BOOL => row
-            .try_get::<_, Option<bool>>(idx)
-            .ok()
-            .flatten()
-            .map(serde_json::Value::Bool)
-            .unwrap_or(serde_json::Value::Null),
-        Type::INT2 => row
-            .try_get::<_, Option<i16>>(idx)
-            .ok()
-            .flatten()
-            .map(|v| serde_json::Value::Number(v.into()))
-            .unwrap_or(serde_json::Value::Null),
-        Type::INT4 => row
-            .try_get::<_, Option<i32>>(idx)
-            .ok()
-            .flatten()
-            .map(|v| serde_json::Value::Number(v.into()))
-            .unwrap_or(serde_json::Value::Null),
-        Type::INT8 => row
-            .try_get::<_, Option<i64>>(idx)
-            .ok()
-            .flatten()
-            .map(|v| serde_json::Value::Number(v.into()))
-            .unwrap_or(serde_json::Value::Null),
-        Type::FLOAT4 => row
-            .try_get::<_, Option<f32>>(idx)
-            .ok()
-            .flatten()
-            .and_then(|v| serde_json::Number::from_f64(v as f64))
-            .map(serde_json::Value::Number)
-            .unwrap_or(serde_json::Value::Null),
-        Type::FLOAT8 => row
-            .try_get::<_, Option<f64>>(idx)
-            .ok()
-            .flatten()
-            .and_then(serde_json::Number::from_f64)
-            .map(serde_json::Value::Number)
-            .unwrap_or(serde_json::Value::Null),
-        Type::JSON | Type::JSONB => row
-            .try_get::<_, Option<serde_json::Value>>(idx)
-            .ok()
-            .flatten()
-            .unwrap_or(serde_json::Value::Null),
-        Type::UUID => row
-            .try_get::<_, Option<uuid::Uuid>>(idx)
-            .ok()
-            .flatten()
-            .map(|v| serde_json::Value::String(v.to_string()))
-            .unwrap_or(serde_json::Value::Null),
-        Type::TIMESTAMP | Type::TIMESTAMPTZ => row
-            .try_get::<_, Option<chrono::NaiveDateTime>>(idx)
-            .ok()
-            .flatten()
-            .map(|v| serde_json::Value::String(v.to_string()))
-            .unwrap_or(serde_json::Value::Null),
-        Type::DATE => row
-            .try_get::<_, Option<chrono::NaiveDate>>(idx)
-            .ok()
-            .flatten()
-            .map(|v| serde_json::Value::String(v.to_string()))
-            .unwrap_or(serde_json::Value::Null),
-        Type::TIME => row
-            .try_get::<_, Option<chrono::NaiveTime>>(idx)
-            .ok()
-            .flatten()
-            .map(|v| serde_json::Value::String(v.to_string()))
-            .unwrap_or(serde_json::Value::Null),
-        _ => row
-            .try_get::<_, Option<String>>(idx)
-            .ok()
-            .flatten()
-            .map(serde_json::Value::String)
-            .unwrap_or(serde_json::Value::Null),
+        Type::BOOL => LogicalType::Boolean,
+        Type::INT2 | Type::INT4 | Type::INT8 | Type::OID => LogicalType::Integer,
+        Type::FLOAT4 | Type::FLOAT8 => LogicalType::Float,
+        Type::NUMERIC | Type::MONEY => LogicalType::Decimal,
+        Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::NAME | Type::CHAR => LogicalType::Text,
+        Type::UUID => LogicalType::Uuid,
+        Type::JSON | Type::JSONB => LogicalType::Json,
+        Type::DATE => LogicalType::Date,
+        Type::TIME | Type::TIMETZ => LogicalType::Time,
+        Type::TIMESTAMP | Type::TIMESTAMPTZ => LogicalType::Timestamp,
+        Type::INTERVAL => LogicalType::Interval,
+        Type::BYTEA => LogicalType::Binary,
+        _ => match pg_type.kind() {
+            Kind::Enum(_) => LogicalType::Text,
+            _ => LogicalType::Other,
+        },
+    }
+}
+
+fn row_to_json(row: &tokio_postgres::Row) -> Vec<serde_json::Value> {
+    row.columns()
+        .iter()
+        .enumerate()
+        .map(|(i, _)| postgres_value_to_json(row, i))
+        .collect()
+}
+
+fn postgres_value_to_json(row: &tokio_postgres::Row, idx: usize) -> serde_json::Value {
+    row.try_get::<_, PgValue>(idx)
+        .map(|value| value.0)
+        .unwrap_or(serde_json::Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trigger_type_bits_are_decoded() {
+        // AFTER INSERT OR UPDATE ... FOR EACH ROW
+        assert_eq!(
+            trigger_timing_and_events(0b10101),
+            (
+                "AFTER".to_string(),
+                vec!["INSERT".to_string(), "UPDATE".to_string()]
+            )
+        );
+        // INSTEAD OF DELETE ... FOR EACH ROW
+        assert_eq!(
+            trigger_timing_and_events(0b1001001),
+            ("INSTEAD OF".to_string(), vec!["DELETE".to_string()])
+        );
     }
 }
@@ -0,0 +1,388 @@
+//! Reconstruct `CREATE` statements for PostgreSQL objects from the catalog.
+//! Identifiers and literals are quoted by the server (`quote_ident`,
+//! `quote_literal`) so the output matches what `pg_dump` would accept.
+
+use crate::postgres::PostgresProvider;
+use crate::schema::{self, ObjectKind};
+use crate::ProviderError;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::{Client, Row};
+
+mod queries {
+    pub const RELATION: &str = r#"
+SELECT
+    c.oid,
+    quote_ident(n.nspname) || '.' || quote_ident(c.relname) as qualified_name,
+    c.relpersistence = 'u' as unlogged,
+    pg_get_partkeydef(c.oid) as partition_key,
+    CASE WHEN c.relkind IN ('v', 'm') THEN pg_get_viewdef(c.oid, true) END as view_definition,
+    CASE WHEN c.relkind IN ('i', 'I') THEN pg_get_indexdef(c.oid) END as index_definition,
+    quote_literal(obj_description(c.oid, 'pg_class')) as comment
+FROM pg_class c
+JOIN pg_namespace n ON n.oid = c.relnamespace
+WHERE n.nspname = $1 AND c.relname = $2 AND c.relkind::text = ANY($3)
+"#;
+
+    pub const COLUMNS: &str = r#"
+SELECT
+    quote_ident(a.attname) as name,
+    format_type(a.atttypid, a.atttypmod) as data_type,
+    a.attnotnull as not_null,
+    pg_get_expr(d.adbin, d.adrelid) as default_expr,
+    a.attidentity::text as identity,
+    a.attgenerated::text as generated,
+    CASE WHEN a.attcollation <> ty.typcollation THEN quote_ident(co.collname) END as collation,
+    quote_literal(col_description(a.attrelid, a.attnum)) as comment
+FROM pg_attribute a
+JOIN pg_type ty ON ty.oid = a.atttypid
+LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+LEFT JOIN pg_collation co ON co.oid = a.attcollation
+WHERE a.attrelid = $1 AND a.attnum > 0 AND NOT a.attisdropped
+ORDER BY a.attnum
+"#;
+
+    /// Constraints declared on the table itself, primary key first
+    pub const CONSTRAINTS: &str = r#"
+SELECT
+    quote_ident(con.conname) as name,
+    pg_get_constraintdef(con.oid, true) as definition
+FROM pg_constraint con
+WHERE con.conrelid = $1 AND con.conislocal
+    AND con.contype IN ('p', 'u', 'c', 'f', 'x')
+ORDER BY array_position(ARRAY['p', 'u', 'c', 'f', 'x'], con.contype::text), con.conname
+"#;
+
+    /// Indexes not created implicitly by a constraint
+    pub const INDEXES: &str = r#"
+SELECT pg_get_indexdef(x.indexrelid) as definition
+FROM pg_index x
+JOIN pg_class i ON i.oid = x.indexrelid
+WHERE x.indrelid = $1
+    AND NOT EXISTS (
+        SELECT 1 FROM pg_constraint con
+        WHERE con.conrelid = x.indrelid AND con.conindid = x.indexrelid
+            AND con.contype IN ('p', 'u', 'x')
+    )
+ORDER BY i.relname
+"#;
+
+    /// Sequences owned by a column of the table, as created by `serial`
+    pub const OWNED_SEQUENCES: &str = r#"
+SELECT
+    dep.objid as oid,
+    quote_ident(a.attname) as column_name
+FROM pg_depend dep
+JOIN pg_class s ON s.oid = dep.objid AND s.relkind = 'S'
+JOIN pg_attribute a ON a.attrelid = dep.refobjid AND a.attnum = dep.refobjsubid
+WHERE dep.classid = 'pg_class'::regclass
+    AND dep.refclassid = 'pg_class'::regclass
+    AND dep.refobjid = $1
+    AND dep.deptype = 'a'
+ORDER BY a.attnum
+"#;
+
+    pub const SEQUENCE: &str = r#"
+SELECT
+    quote_ident(n.nspname) || '.' || quote_ident(c.relname) as qualified_name,
+    format_type(s.seqtypid, NULL) as data_type,
+    s.seqstart as start,
+    s.seqincrement as increment,
+    s.seqmin as min,
+    s.seqmax as max,
+    s.seqcache as cache,
+    s.seqcycle as cycle
+FROM pg_sequence s
+JOIN pg_class c ON c.oid = s.seqrelid
+JOIN pg_namespace n ON n.oid = c.relnamespace
+WHERE s.seqrelid = $1
+"#;
+
+    /// Every overload of a function or procedure
+    pub const ROUTINES: &str = r#"
+SELECT
+    pg_get_functiondef(p.oid) as definition,
+    quote_ident(n.nspname) || '.' || quote_ident(p.proname)
+        || '(' || pg_get_function_identity_arguments(p.oid) || ')' as signature,
+    quote_literal(obj_description(p.oid, 'pg_proc')) as comment
+FROM pg_proc p
+JOIN pg_namespace n ON n.oid = p.pronamespace
+WHERE n.nspname = $1 AND p.proname = $2 AND p.prokind::text = $3
+ORDER BY p.oid
+"#;
+
+    /// Trigger names are only unique per table, so this can match several
+    pub const TRIGGERS: &str = r#"
+SELECT pg_get_triggerdef(tg.oid) as definition
+FROM pg_trigger tg
+JOIN pg_class c ON c.oid = tg.tgrelid
+JOIN pg_namespace n ON n.oid = c.relnamespace
+WHERE n.nspname = $1 AND tg.tgname = $2 AND NOT tg.tgisinternal
+ORDER BY c.relname
+"#;
+}
+
+/// `CREATE` statement(s) for the object, separated by blank lines
+pub(crate) async fn object_ddl(
+    client: &Client,
+    schema: &str,
+    name: &str,
+    kind: ObjectKind,
+) -> Result<String, ProviderError> {
+    let statements = match kind {
+        ObjectKind::Table => table_ddl(client, schema, name).await?,
+        ObjectKind::View | ObjectKind::MaterializedView => {
+            view_ddl(client, schema, name, kind).await?
+        }
+        ObjectKind::Sequence => match relation(client, schema, name, &["S"]).await? {
+            Some(row) => sequence_ddl(client, row.get("oid")).await?,
+            None => Vec::new(),
+        },
+        ObjectKind::Index => match relation(client, schema, name, &["i", "I"]).await? {
+            Some(row) => vec![format!("{};", row.get::<_, String>("index_definition"))],
+            None => Vec::new(),
+        },
+        ObjectKind::Function | ObjectKind::Procedure => {
+            routine_ddl(client, schema, name, kind).await?
+        }
+        ObjectKind::Trigger => query(client, queries::TRIGGERS, &[&schema, &name])
+            .await?
+            .iter()
+            .map(|row| format!("{};", row.get::<_, String>("definition")))
+            .collect(),
+    };
+
+    if statements.is_empty() {
+        return Err(schema::object_not_found(kind, schema, name));
+    }
+    Ok(statements.join("\n\n"))
+}
+
+async fn query(
+    client: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<Vec<Row>, ProviderError> {
+    client
+        .query(query, params)
+        .await
+        .map_err(PostgresProvider::format_db_error)
+}
+
+async fn relation(
+    client: &Client,
+    schema: &str,
+    name: &str,
+    relkinds: &[&str],
+) -> Result<Option<Row>, ProviderError> {
+    let mut rows = query(client, queries::RELATION, &[&schema, &name, &relkinds]).await?;
+    Ok(rows.pop())
+}
+
+async fn table_ddl(
+    client: &Client,
+    schema: &str,
+    name: &str,
+) -> Result<Vec<String>, ProviderError> {
+    let Some(table) = relation(client, schema, name, &["r", "p"]).await? else {
+        return Ok(Vec::new());
+    };
+    let oid: u32 = table.get("oid");
+    let qualified_name: String = table.get("qualified_name");
+
+    let columns = query(client, queries::COLUMNS, &[&oid]).await?;
+    let constraints = query(client, queries::CONSTRAINTS, &[&oid]).await?;
+    let indexes = query(client, queries::INDEXES, &[&oid]).await?;
+    let owned_sequences = query(client, queries::OWNED_SEQUENCES, &[&oid]).await?;
+
+    let mut statements = Vec::new();
+
+    // Column defaults call nextval() on owned sequences, so they come first.
+    for sequence in &owned_sequences {
+        statements.extend(sequence_ddl(client, sequence.get("oid")).await?);
+    }
+
+    let mut lines: Vec<String> = columns.iter().map(column_definition).collect();
+    lines.extend(constraints.iter().map(|row| {
+        format!(
+            "    CONSTRAINT {} {}",
+            row.get::<_, String>("name"),
+            row.get::<_, String>("definition")
+        )
+    }));
+
+    let mut create = format!(
+        "CREATE {}TABLE {} (\n{}\n)",
+        if table.get("unlogged") {
+            "UNLOGGED "
+        } else {
+            ""
+        },
+        qualified_name,
+        lines.join(",\n")
+    );
+    if let Some(partition_key) = table.get::<_, Option<String>>("partition_key") {
+        create.push_str(&format!("\nPARTITION BY {}", partition_key));
+    }
+    create.push(';');
+    statements.push(create);
+
+    for sequence in &owned_sequences {
+        let rows = query(client, queries::SEQUENCE, &[&sequence.get::<_, u32>("oid")]).await?;
+        if let Some(row) = rows.first() {
+            statements.push(format!(
+                "ALTER SEQUENCE {} OWNED BY {}.{};",
+                row.get::<_, String>("qualified_name"),
+                qualified_name,
+                sequence.get::<_, String>("column_name")
+            ));
+        }
+    }
+
+    statements.extend(
+        indexes
+            .iter()
+            .map(|row| format!("{};", row.get::<_, String>("definition"))),
+    );
+
+    if let Some(comment) = table.get::<_, Option<String>>("comment") {
+        statements.push(format!(
+            "COMMENT ON TABLE {} IS {};",
+            qualified_name, comment
+        ));
+    }
+    for column in &columns {
+        if let Some(comment) = column.get::<_, Option<String>>("comment") {
+            statements.push(format!(
+                "COMMENT ON COLUMN {}.{} IS {};",
+                qualified_name,
+                column.get::<_, String>("name"),
+                comment
+            ));
+        }
+    }
+
+    Ok(statements)
+}
+
+fn column_definition(row: &Row) -> String {
+    let mut line = format!(
+        "    {} {}",
+        row.get::<_, String>("name"),
+        row.get::<_, String>("data_type")
+    );
+
+    if let Some(collation) = row.get::<_, Option<String>>("collation") {
+        line.push_str(&format!(" COLLATE {}", collation));
+    }
+
+    let default_expr: Option<String> = row.get("default_expr");
+    match (
+        row.get::<_, &str>("generated"),
+        row.get::<_, &str>("identity"),
+        default_expr,
+    ) {
+        ("s", _, Some(expr)) => line.push_str(&format!(" GENERATED ALWAYS AS ({}) STORED", expr)),
+        (_, "a", _) => line.push_str(" GENERATED ALWAYS AS IDENTITY"),
+        (_, "d", _) => line.push_str(" GENERATED BY DEFAULT AS IDENTITY"),
+        (_, _, Some(expr)) => line.push_str(&format!(" DEFAULT {}", expr)),
+        _ => {}
+    }
+
+    if row.get("not_null") {
+        line.push_str(" NOT NULL");
+    }
+    line
+}
+
+async fn sequence_ddl(client: &Client, oid: u32) -> Result<Vec<String>, ProviderError> {
+    let rows = query(client, queries::SEQUENCE, &[&oid]).await?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            format!(
+                "CREATE SEQUENCE {} AS {} START WITH {} INCREMENT BY {} MINVALUE {} MAXVALUE {} CACHE {}{};",
+                row.get::<_, String>("qualified_name"),
+                row.get::<_, String>("data_type"),
+                row.get::<_, i64>("start"),
+                row.get::<_, i64>("increment"),
+                row.get::<_, i64>("min"),
+                row.get::<_, i64>("max"),
+                row.get::<_, i64>("cache"),
+                if row.get("cycle") { " CYCLE" } else { "" }
+            )
+        })
+        .collect())
+}
+
+async fn view_ddl(
+    client: &Client,
+    schema: &str,
+    name: &str,
+    kind: ObjectKind,
+) -> Result<Vec<String>, ProviderError> {
+    let (relkind, create) = match kind {
+        ObjectKind::MaterializedView => ("m", "CREATE MATERIALIZED VIEW"),
+        _ => ("v", "CREATE VIEW"),
+    };
+    let Some(view) = relation(client, schema, name, &[relkind]).await? else {
+        return Ok(Vec::new());
+    };
+    let qualified_name: String = view.get("qualified_name");
+    let definition: String = view.get("view_definition");
+
+    let mut statements = vec![format!(
+        "{} {} AS\n{}",
+        create,
+        qualified_name,
+        definition.trim()
+    )];
+
+    if kind == ObjectKind::MaterializedView {
+        let indexes = query(client, queries::INDEXES, &[&view.get::<_, u32>("oid")]).await?;
+        statements.extend(
+            indexes
+                .iter()
+                .map(|row| format!("{};", row.get::<_, String>("definition"))),
+        );
+    }
+
+    if let Some(comment) = view.get::<_, Option<String>>("comment") {
+        statements.push(format!(
+            "COMMENT ON {} {} IS {};",
+            create.trim_start_matches("CREATE "),
+            qualified_name,
+            comment
+        ));
+    }
+    Ok(statements)
+}
+
+async fn routine_ddl(
+    client: &Client,
+    schema: &str,
+    name: &str,
+    kind: ObjectKind,
+) -> Result<Vec<String>, ProviderError> {
+    let (prokind, label) = match kind {
+        ObjectKind::Procedure => ("p", "PROCEDURE"),
+        _ => ("f", "FUNCTION"),
+    };
+    let rows = query(client, queries::ROUTINES, &[&schema, &name, &prokind]).await?;
+
+    let mut statements = Vec::new();
+    for row in &rows {
+        statements.push(format!(
+            "{};",
+            row.get::<_, String>("definition").trim_end()
+        ));
+        if let Some(comment) = row.get::<_, Option<String>>("comment") {
+            statements.push(format!(
+                "COMMENT ON {} {} IS {};",
+                label,
+                row.get::<_, String>("signature"),
+                comment
+            ));
+        }
+    }
+    Ok(statements)
+}
@@ -0,0 +1,681 @@
+//! Decoding of PostgreSQL binary values into JSON.
+//!
+//! Result rows arrive in the binary wire format. Every built-in type is decoded
+//! here, along with enums, domains, composites, arrays, ranges and multiranges
+//! of any of them. Values that JSON cannot hold exactly (NUMERIC, MONEY,
+//! intervals, timestamps) are rendered as strings in PostgreSQL's own text
+//! format. Structured types become objects or arrays.
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat};
+use serde_json::{json, Map, Value};
+use std::error::Error;
+use std::fmt::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use tokio_postgres::types::{Field, FromSql, Kind, Type};
+
+type DecodeError = Box<dyn Error + Sync + Send>;
+
+/// A column value of any type, decoded to JSON.
+pub(crate) struct PgValue(pub serde_json::Value);
+
+impl<'a> FromSql<'a> for PgValue {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, DecodeError> {
+        decode(ty, raw).map(PgValue)
+    }
+
+    fn from_sql_null(_: &Type) -> Result<Self, DecodeError> {
+        Ok(PgValue(Value::Null))
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+fn decode(ty: &Type, raw: &[u8]) -> Result<Value, DecodeError> {
+    match ty.kind() {
+        Kind::Array(member) => return decode_array(member, raw),
+        Kind::Range(member) => return decode_range(member, raw),
+        Kind::Multirange(member) => return decode_multirange(member, raw),
+        Kind::Domain(base) => return decode(base, raw),
+        Kind::Composite(fields) => return decode_composite(fields, raw),
+        Kind::Enum(_) => return text(raw),
+        _ => {}
+    }
+
+    let mut buf = Reader(raw);
+    let value = match *ty {
+        Type::BOOL => Value::Bool(buf.u8()? != 0),
+        Type::INT2 => json!(buf.i16()?),
+        Type::INT4 => json!(buf.i32()?),
+        Type::INT8 => json!(buf.i64()?),
+        Type::OID
+        | Type::XID
+        | Type::CID
+        | Type::REGPROC
+        | Type::REGPROCEDURE
+        | Type::REGOPER
+        | Type::REGOPERATOR
+        | Type::REGCLASS
+        | Type::REGTYPE
+        | Type::REGCONFIG
+        | Type::REGDICTIONARY
+        | Type::REGNAMESPACE
+        | Type::REGROLE => json!(buf.u32()?),
+        Type::XID8 => json!(buf.u64()?),
+        // Round-trip through the shortest decimal form so 0.1 stays 0.1.
+        Type::FLOAT4 => float(buf.f32()?.to_string().parse()?),
+        Type::FLOAT8 => float(buf.f64()?),
+        Type::NUMERIC => Value::String(numeric(raw)?),
+        Type::MONEY => Value::String(money(buf.i64()?)),
+        Type::CHAR => Value::String((buf.u8()? as char).to_string()),
+        Type::TEXT
+        | Type::VARCHAR
+        | Type::BPCHAR
+        | Type::NAME
+        | Type::UNKNOWN
+        | Type::XML
+        | Type::REFCURSOR => text(raw)?,
+        Type::JSON => json_text(std::str::from_utf8(raw)?),
+        Type::JSONB => {
+            let (_version, body) = raw.split_first().ok_or("empty jsonb value")?;
+            json_text(std::str::from_utf8(body)?)
+        }
+        Type::JSONPATH => {
+            let (_version, body) = raw.split_first().ok_or("empty jsonpath value")?;
+            Value::String(std::str::from_utf8(body)?.to_string())
+        }
+        Type::UUID => Value::String(uuid::Uuid::from_slice(raw)?.to_string()),
+        Type::BYTEA => Value::String(bytea(raw)),
+        Type::DATE => Value::String(date(buf.i32()?)),
+        Type::TIME => Value::String(time(buf.i64()?)?),
+        Type::TIMETZ => {
+            let time = time(buf.i64()?)?;
+            // The wire format stores the zone as seconds west of UTC.
+            Value::String(format!("{}{}", time, utc_offset(-buf.i32()?)))
+        }
+        Type::TIMESTAMP => Value::String(timestamp(buf.i64()?, false)?),
+        Type::TIMESTAMPTZ => Value::String(timestamp(buf.i64()?, true)?),
+        Type::INTERVAL => {
+            let micros = buf.i64()?;
+            let days = buf.i32()?;
+            let months = buf.i32()?;
+            Value::String(interval(months, days, micros))
+        }
+        Type::INET | Type::CIDR => Value::String(inet(&mut buf, *ty == Type::CIDR)?),
+        Type::MACADDR | Type::MACADDR8 => Value::String(
+            raw.iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<_>>()
+                .join(":"),
+        ),
+        Type::BIT | Type::VARBIT => Value::String(bits(&mut buf)?),
+        Type::PG_LSN => {
+            let lsn = buf.u64()?;
+            Value::String(format!("{:X}/{:X}", lsn >> 32, lsn & 0xffff_ffff))
+        }
+        Type::POINT => point(&mut buf)?,
+        Type::LINE => {
+            json!({ "a": float(buf.f64()?), "b": float(buf.f64()?), "c": float(buf.f64()?) })
+        }
+        Type::LSEG | Type::BOX => Value::Array(vec![point(&mut buf)?, point(&mut buf)?]),
+        Type::PATH => {
+            let closed = buf.u8()? != 0;
+            json!({ "closed": closed, "points": points(&mut buf)? })
+        }
+        Type::POLYGON => points(&mut buf)?,
+        Type::CIRCLE => json!({ "center": point(&mut buf)?, "radius": float(buf.f64()?) }),
+        Type::TS_VECTOR => Value::String(tsvector(&mut buf)?),
+        _ => decode_extension(ty.name(), raw)?,
+    };
+
+    Ok(value)
+}
+
+/// Types from common extensions, identified by name since their OIDs vary.
+fn decode_extension(name: &str, raw: &[u8]) -> Result<Value, DecodeError> {
+    match name {
+        "citext" => text(raw),
+        "ltree" | "lquery" | "ltxtquery" => {
+            let (_version, body) = raw.split_first().ok_or("empty ltree value")?;
+            text(body)
+        }
+        "hstore" => hstore(raw),
+        // Anything else is shown as text when it is text, and as bytes otherwise.
+        _ => Ok(match std::str::from_utf8(raw) {
+            Ok(s) if !s.contains('\0') => Value::String(s.to_string()),
+            _ => Value::String(bytea(raw)),
+        }),
+    }
+}
+
+fn decode_array(member: &Type, raw: &[u8]) -> Result<Value, DecodeError> {
+    let mut buf = Reader(raw);
+    let dimensions = buf.i32()?;
+    let _has_nulls = buf.i32()?;
+    let _element_oid = buf.u32()?;
+
+    let mut lengths = Vec::with_capacity(dimensions.max(0) as usize);
+    for _ in 0..dimensions {
+        lengths.push(buf.i32()?.max(0) as usize);
+        let _lower_bound = buf.i32()?;
+    }
+
+    if lengths.is_empty() {
+        return Ok(Value::Array(vec![]));
+    }
+    array_dimension(member, &mut buf, &lengths)
+}
+
+/// Read one dimension of a row-major array, nesting the inner dimensions.
+fn array_dimension(
+    member: &Type,
+    buf: &mut Reader<'_>,
+    lengths: &[usize],
+) -> Result<Value, DecodeError> {
+    let (len, inner) = lengths.split_first().ok_or("missing array dimension")?;
+    let mut values = Vec::with_capacity(*len);
+    for _ in 0..*len {
+        values.push(if inner.is_empty() {
+            decode_field(member, buf.field()?)?
+        } else {
+            array_dimension(member, buf, inner)?
+        });
+    }
+    Ok(Value::Array(values))
+}
+
+fn decode_range(member: &Type, raw: &[u8]) -> Result<Value, DecodeError> {
+    const EMPTY: u8 = 0x01;
+    const LOWER_INCLUSIVE: u8 = 0x02;
+    const UPPER_INCLUSIVE: u8 = 0x04;
+    const LOWER_UNBOUNDED: u8 = 0x08;
+    const UPPER_UNBOUNDED: u8 = 0x10;
+
+    let mut buf = Reader(raw);
+    let flags = buf.u8()?;
+    if flags & EMPTY != 0 {
+        return Ok(json!({ "empty": true }));
+    }
+
+    let mut bound = |unbounded: u8| -> Result<Value, DecodeError> {
+        if flags & unbounded != 0 {
+            Ok(Value::Null)
+        } else {
+            decode_field(member, buf.field()?)
+        }
+    };
+    let lower = bound(LOWER_UNBOUNDED)?;
+    let upper = bound(UPPER_UNBOUNDED)?;
+
+    Ok(json!({
+        "empty": false,
+        "lower": lower,
+        "upper": upper,
+        "lower_inclusive": flags & LOWER_INCLUSIVE != 0,
+        "upper_inclusive": flags & UPPER_INCLUSIVE != 0,
+    }))
+}
+
+fn decode_multirange(member: &Type, raw: &[u8]) -> Result<Value, DecodeError> {
+    let mut buf = Reader(raw);
+    let count = buf.i32()?;
+    let mut ranges = Vec::with_capacity(count.max(0) as usize);
+    for _ in 0..count {
+        let range = buf.field()?.ok_or("null range in multirange")?;
+        ranges.push(decode_range(member, range)?);
+    }
+    Ok(Value::Array(ranges))
+}
+
+fn decode_composite(fields: &[Field], raw: &[u8]) -> Result<Value, DecodeError> {
+    let mut buf = Reader(raw);
+    let count = buf.i32()?.max(0) as usize;
+    if count != fields.len() {
+        return Err(format!("expected {} composite fields, got {}", fields.len(), count).into());
+    }
+
+    let mut object = Map::new();
+    for field in fields {
+        let _oid = buf.u32()?;
+        let value = decode_field(field.type_(), buf.field()?)?;
+        object.insert(field.name().to_string(), value);
+    }
+    Ok(Value::Object(object))
+}
+
+fn decode_field(ty: &Type, raw: Option<&[u8]>) -> Result<Value, DecodeError> {
+    match raw {
+        Some(raw) => decode(ty, raw),
+        None => Ok(Value::Null),
+    }
+}
+
+fn hstore(raw: &[u8]) -> Result<Value, DecodeError> {
+    let mut buf = Reader(raw);
+    let count = buf.i32()?;
+    let mut object = Map::new();
+    for _ in 0..count {
+        let key = buf.field()?.ok_or("null hstore key")?;
+        let value = match buf.field()? {
+            Some(value) => text(value)?,
+            None => Value::Null,
+        };
+        object.insert(std::str::from_utf8(key)?.to_string(), value);
+    }
+    Ok(Value::Object(object))
+}
+
+fn text(raw: &[u8]) -> Result<Value, DecodeError> {
+    Ok(Value::String(std::str::from_utf8(raw)?.to_string()))
+}
+
+fn json_text(s: &str) -> Value {
+    serde_json::from_str(s).unwrap_or_else(|_| Value::String(s.to_string()))
+}
+
+/// JSON has no NaN or infinities, so those are spelled out like PostgreSQL does.
+fn float(v: f64) -> Value {
+    match serde_json::Number::from_f64(v) {
+        Some(n) => Value::Number(n),
+        None if v.is_nan() => Value::String("NaN".to_string()),
+        None if v > 0.0 => Value::String("Infinity".to_string()),
+        None => Value::String("-Infinity".to_string()),
+    }
+}
+
+fn bytea(raw: &[u8]) -> String {
+    let mut s = String::with_capacity(2 + raw.len() * 2);
+    s.push_str("\\x");
+    for b in raw {
+        let _ = write!(s, "{:02x}", b);
+    }
+    s
+}
+
+fn numeric(raw: &[u8]) -> Result<String, DecodeError> {
+    const NEGATIVE: u16 = 0x4000;
+    const NAN: u16 = 0xC000;
+    const POS_INFINITY: u16 = 0xD000;
+    const NEG_INFINITY: u16 = 0xF000;
+
+    let mut buf = Reader(raw);
+    let ndigits = buf.i16()?.max(0) as usize;
+    let weight = buf.i16()? as i32;
+    let sign = buf.u16()?;
+    let scale = buf.u16()? as usize;
+    let digits = (0..ndigits)
+        .map(|_| buf.i16())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match sign {
+        NAN => return Ok("NaN".to_string()),
+        POS_INFINITY => return Ok("Infinity".to_string()),
+        NEG_INFINITY => return Ok("-Infinity".to_string()),
+        _ => {}
+    }
+
+    // Digits are base 10000; `weight` is the power of the first one.
+    let digit = |i: i32| {
+        usize::try_from(i)
+            .ok()
+            .and_then(|i| digits.get(i))
+            .copied()
+            .unwrap_or(0)
+    };
+
+    let mut s = String::new();
+    if sign == NEGATIVE {
+        s.push('-');
+    }
+    if weight < 0 {
+        s.push('0');
+    } else {
+        let _ = write!(s, "{}", digit(0));
+        for i in 1..=weight {
+            let _ = write!(s, "{:04}", digit(i));
+        }
+    }
+
+    if scale > 0 {
+        let mut fraction = String::with_capacity(scale + 4);
+        let mut i = weight + 1;
+        while fraction.len() < scale {
+            let _ = write!(fraction, "{:04}", digit(i));
+            i += 1;
+        }
+        fraction.truncate(scale);
+        s.push('.');
+        s.push_str(&fraction);
+    }
+
+    Ok(s)
+}
+
+/// MONEY is an integer count of the currency's minor unit; two fractional
+/// digits are assumed, as in the default `lc_monetary` locales.
+fn money(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{}{}.{:02}", sign, cents / 100, cents % 100)
+}
+
+fn epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2000, 1, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .expect("valid epoch")
+}
+
+fn date(days: i32) -> String {
+    match days {
+        i32::MAX => "infinity".to_string(),
+        i32::MIN => "-infinity".to_string(),
+        _ => (epoch().date() + Duration::days(days as i64)).to_string(),
+    }
+}
+
+fn time(micros: i64) -> Result<String, DecodeError> {
+    const DAY: i64 = 86_400_000_000;
+    match micros {
+        // 24:00:00 is a valid PostgreSQL time but would wrap around in chrono.
+        DAY => Ok("24:00:00".to_string()),
+        0..DAY => Ok((NaiveTime::MIN + Duration::microseconds(micros)).to_string()),
+        _ => Err("time out of range".into()),
+    }
+}
+
+fn timestamp(micros: i64, with_zone: bool) -> Result<String, DecodeError> {
+    match micros {
+        i64::MAX => return Ok("infinity".to_string()),
+        i64::MIN => return Ok("-infinity".to_string()),
+        _ => {}
+    }
+
+    let timestamp = epoch()
+        .checked_add_signed(Duration::microseconds(micros))
+        .ok_or("timestamp out of range")?;
+    Ok(if with_zone {
+        timestamp
+            .and_utc()
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    } else {
+        timestamp.to_string()
+    })
+}
+
+fn utc_offset(seconds_east: i32) -> String {
+    let sign = if seconds_east < 0 { '-' } else { '+' };
+    let seconds = seconds_east.unsigned_abs();
+    let mut s = format!("{}{:02}:{:02}", sign, seconds / 3600, seconds / 60 % 60);
+    if !seconds.is_multiple_of(60) {
+        let _ = write!(s, ":{:02}", seconds % 60);
+    }
+    s
+}
+
+/// Render an interval in PostgreSQL's default `postgres` output style,
+/// e.g. `1 year 2 mons 3 days 04:05:06.5`.
+fn interval(months: i32, days: i32, micros: i64) -> String {
+    fn unit(parts: &mut Vec<String>, n: i64, singular: &str, plural: &str) {
+        if n != 0 {
+            let name = if n == 1 { singular } else { plural };
+            parts.push(format!("{} {}", n, name));
+        }
+    }
+
+    let mut parts = Vec::new();
+    unit(&mut parts, (months / 12) as i64, "year", "years");
+    unit(&mut parts, (months % 12) as i64, "mon", "mons");
+    unit(&mut parts, days as i64, "day", "days");
+
+    if micros != 0 || parts.is_empty() {
+        let sign = if micros < 0 { "-" } else { "" };
+        let micros = micros.unsigned_abs();
+        let seconds = micros / 1_000_000;
+        let mut clock = format!(
+            "{}{:02}:{:02}:{:02}",
+            sign,
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        );
+        let fraction = micros % 1_000_000;
+        if fraction != 0 {
+            let digits = format!("{:06}", fraction);
+            clock.push('.');
+            clock.push_str(digits.trim_end_matches('0'));
+        }
+        parts.push(clock);
+    }
+
+    parts.join(" ")
+}
+
+fn inet(buf: &mut Reader<'_>, cidr: bool) -> Result<String, DecodeError> {
+    let family = buf.u8()?;
+    let bits = buf.u8()?;
+    let _is_cidr = buf.u8()?;
+    let len = buf.u8()? as usize;
+    let addr = buf.take(len)?;
+
+    let (addr, max_bits) = match family {
+        2 => {
+            let octets: [u8; 4] = addr.try_into()?;
+            (IpAddr::V4(Ipv4Addr::from(octets)), 32)
+        }
+        3 => {
+            let octets: [u8; 16] = addr.try_into()?;
+            (IpAddr::V6(Ipv6Addr::from(octets)), 128)
+        }
+        _ => return Err(format!("unknown inet address family {}", family).into()),
+    };
+
+    // Like PostgreSQL, inet omits a full-length netmask while cidr always shows it.
+    Ok(if cidr || bits != max_bits {
+        format!("{}/{}", addr, bits)
+    } else {
+        addr.to_string()
+    })
+}
+
+fn bits(buf: &mut Reader<'_>) -> Result<String, DecodeError> {
+    let len = buf.i32()?.max(0) as usize;
+    let bytes = buf.take(len.div_ceil(8))?;
+    Ok((0..len)
+        .map(|i| {
+            if bytes[i / 8] & (0x80 >> (i % 8)) != 0 {
+                '1'
+            } else {
+                '0'
+            }
+        })
+        .collect())
+}
+
+fn point(buf: &mut Reader<'_>) -> Result<Value, DecodeError> {
+    Ok(json!({ "x": float(buf.f64()?), "y": float(buf.f64()?) }))
+}
+
+fn points(buf: &mut Reader<'_>) -> Result<Value, DecodeError> {
+    let count = buf.i32()?;
+    (0..count)
+        .map(|_| point(buf))
+        .collect::<Result<Vec<_>, _>>()
+        .map(Value::Array)
+}
+
+/// Render a tsvector as its text form, e.g. `'cat':3 'fat':2A,4`.
+fn tsvector(buf: &mut Reader<'_>) -> Result<String, DecodeError> {
+    let count = buf.i32()?;
+    let mut lexemes = Vec::with_capacity(count.max(0) as usize);
+
+    for _ in 0..count {
+        let end = buf
+            .0
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or("unterminated lexeme")?;
+        let word = std::str::from_utf8(buf.take(end)?)?;
+        buf.u8()?;
+
+        let mut lexeme = format!("'{}'", word.replace('\'', "''"));
+        let positions = buf.u16()?;
+        for i in 0..positions {
+            let entry = buf.u16()?;
+            lexeme.push(if i == 0 { ':' } else { ',' });
+            let _ = write!(lexeme, "{}", entry & 0x3fff);
+            match entry >> 14 {
+                3 => lexeme.push('A'),
+                2 => lexeme.push('B'),
+                1 => lexeme.push('C'),
+                _ => {}
+            }
+        }
+        lexemes.push(lexeme);
+    }
+
+    Ok(lexemes.join(" "))
+}
+
+/// Big-endian cursor over a binary value.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.0.len() < len {
+            return Err("unexpected end of value".into());
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self.take(N)?.try_into()?)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn i16(&mut self) -> Result<i16, DecodeError> {
+        Ok(i16::from_be_bytes(self.array()?))
+    }
+
+    fn u16(&mut self) -> Result<u16, DecodeError> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, DecodeError> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> Result<i64, DecodeError> {
+        Ok(i64::from_be_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_be_bytes(self.array()?))
+    }
+
+    fn f32(&mut self) -> Result<f32, DecodeError> {
+        Ok(f32::from_be_bytes(self.array()?))
+    }
+
+    fn f64(&mut self) -> Result<f64, DecodeError> {
+        Ok(f64::from_be_bytes(self.array()?))
+    }
+
+    /// A length-prefixed value, where a negative length means NULL.
+    fn field(&mut self) -> Result<Option<&'a [u8]>, DecodeError> {
+        let len = self.i32()?;
+        if len < 0 {
+            Ok(None)
+        } else {
+            self.take(len as usize).map(Some)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numeric_bytes(weight: i16, sign: u16, scale: u16, digits: &[i16]) -> Vec<u8> {
+        let mut raw = Vec::new();
+        raw.extend((digits.len() as i16).to_be_bytes());
+        raw.extend(weight.to_be_bytes());
+        raw.extend(sign.to_be_bytes());
+        raw.extend(scale.to_be_bytes());
+        for d in digits {
+            raw.extend(d.to_be_bytes());
+        }
+        raw
+    }
+
+    #[test]
+    fn numeric_is_rendered_losslessly() {
+        // 12345678.9012
+        let raw = numeric_bytes(1, 0, 4, &[1234, 5678, 9012]);
+        assert_eq!(numeric(&raw).unwrap(), "12345678.9012");
+        // -0.00012
+        let raw = numeric_bytes(-1, 0x4000, 5, &[1, 2000]);
+        assert_eq!(numeric(&raw).unwrap(), "-0.00012");
+        // 10000 with no stored trailing digit group
+        let raw = numeric_bytes(1, 0, 0, &[1]);
+        assert_eq!(numeric(&raw).unwrap(), "10000");
+        assert_eq!(numeric(&numeric_bytes(0, 0xC000, 0, &[])).unwrap(), "NaN");
+    }
+
+    #[test]
+    fn interval_matches_postgres_style() {
+        assert_eq!(
+            interval(14, 3, 4 * 3_600_000_000 + 5 * 60_000_000 + 6_500_000),
+            "1 year 2 mons 3 days 04:05:06.5"
+        );
+        assert_eq!(interval(0, -1, 0), "-1 days");
+        assert_eq!(interval(0, 0, 0), "00:00:00");
+    }
+
+    #[test]
+    fn arrays_keep_their_dimensions() {
+        let mut raw = Vec::new();
+        for n in [2i32, 1, 23, 2, 1, 2, 1] {
+            raw.extend(n.to_be_bytes());
+        }
+        for v in [Some(1i32), None, Some(3), Some(4)] {
+            match v {
+                Some(v) => {
+                    raw.extend(4i32.to_be_bytes());
+                    raw.extend(v.to_be_bytes());
+                }
+                None => raw.extend((-1i32).to_be_bytes()),
+            }
+        }
+        assert_eq!(
+            decode(&Type::INT4_ARRAY, &raw).unwrap(),
+            json!([[1, null], [3, 4]])
+        );
+    }
+
+    #[test]
+    fn ranges_report_bounds() {
+        let mut raw = vec![0x02 | 0x10];
+        raw.extend(4i32.to_be_bytes());
+        raw.extend(5i32.to_be_bytes());
+        assert_eq!(
+            decode(&Type::INT4_RANGE, &raw).unwrap(),
+            json!({
+                "empty": false,
+                "lower": 5,
+                "upper": null,
+                "lower_inclusive": true,
+                "upper_inclusive": false,
+            })
+        );
+    }
+}
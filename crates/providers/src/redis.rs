@@ -1,10 +1,12 @@
 use async_trait::async_trait;
 use crate::{
-    ColumnInfo, ConnectionParams, DatabaseProvider, DatabaseType, ProviderError,
-    QueryResult, TableInfo,
+    tls, ColumnFilter, ColumnInfo, ConnectionParams, DatabaseProvider, DatabaseType,
+    FilterOperator, PageRequest, ProviderError, QueryResult, SortDirection, SslMode, TableInfo,
+    TablePage, TableQuery, TlsConfig,
 };
 use redis::{
-    aio::ConnectionManager, cluster_async::ClusterConnection, AsyncCommands, Client, RedisError,
+    aio::ConnectionManager, cluster_async::ClusterConnection, AsyncCommands, Client,
+    ClientTlsConfig, RedisError, TlsCertificates,
 };
 use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
@@ -86,22 +88,18 @@ pub struct RedisProvider {
 #[allow(dead_code)]
 impl RedisProvider {
     pub async fn connect(params: ConnectionParams) -> Result<Self, ProviderError> {
-        match params {
-            ConnectionParams::ConnectionString { connection_string } => {
-                if connection_string.contains(',')
-                    || connection_string.starts_with("redis-cluster://")
-                {
-                    Self::connect_cluster(connection_string).await
-                } else {
-                    Self::connect_single(connection_string).await
-                }
-            }
+        let tls = params.tls().cloned();
+        let url = match params {
+            ConnectionParams::ConnectionString {
+                connection_string, ..
+            } => connection_string,
             ConnectionParams::Parameters {
                 host,
                 port,
                 database,
                 username,
                 password,
+                ..
             } => {
                 let auth = if !username.is_empty() && !password.is_empty() {
                     format!("{}:{}@", username, password)
@@ -115,15 +113,93 @@ impl RedisProvider {
                 } else {
                     String::new()
                 };
-                let url = format!("redis://{}{}:{}{}", auth, host, port, db);
-                Self::connect_single(url).await
+                format!("redis://{}{}:{}{}", auth, host, port, db)
+            }
+        };
+
+        let Some(tls) = tls else {
+            return Self::connect_url(url, None).await;
+        };
+
+        tls.require_address_as_server_name("Redis")?;
+        let result = Self::connect_url(tls_urls(&url, &tls), Self::certificates(&tls)?).await;
+        match result {
+            // Redis cannot negotiate TLS, so `prefer` retries in plaintext when
+            // the handshake itself fails and reports the TLS error if that
+            // fails too. A configured CA bundle or a rejected certificate means
+            // the server does speak TLS, so those never fall back.
+            Err(tls_err)
+                if tls.mode == SslMode::Prefer
+                    && tls.ca_cert_path.is_none()
+                    && !is_certificate_error(&tls_err) =>
+            {
+                let plaintext = TlsConfig {
+                    mode: SslMode::Disable,
+                    ..TlsConfig::default()
+                };
+                Self::connect_url(tls_urls(&url, &plaintext), None)
+                    .await
+                    .map_err(|_| tls_err)
             }
+            result => result,
         }
     }
 
-    async fn connect_single(url: String) -> Result<Self, ProviderError> {
-        let client = Client::open(url.as_str())
-            .map_err(|e| ProviderError::new(format!("Failed to create Redis client: {}", e)))?;
+    async fn connect_url(
+        url: String,
+        certificates: Option<TlsCertificates>,
+    ) -> Result<Self, ProviderError> {
+        if url.contains(',') || url.starts_with("redis-cluster://") {
+            Self::connect_cluster(url, certificates).await
+        } else {
+            Self::connect_single(url, certificates).await
+        }
+    }
+
+    /// Custom CA and client certificates for a TLS connection, if any.
+    /// Without a CA bundle the system roots are used.
+    fn certificates(tls: &TlsConfig) -> Result<Option<TlsCertificates>, ProviderError> {
+        if tls.mode == SslMode::Disable
+            || (tls.ca_cert_path.is_none() && tls.client_cert_path.is_none())
+        {
+            return Ok(None);
+        }
+
+        let client_tls = match (&tls.client_cert_path, &tls.client_key_path) {
+            (Some(cert), Some(key)) => Some(ClientTlsConfig {
+                client_cert: tls::read_file(cert, "client certificate")?,
+                client_key: tls::read_file(key, "client key")?,
+            }),
+            (Some(_), None) => {
+                return Err(
+                    ProviderError::new("Redis client certificates need a PEM private key")
+                        .with_hint("Set the client key path; PKCS#12 archives are not supported."),
+                )
+            }
+            (None, _) => None,
+        };
+
+        let root_cert = tls
+            .ca_cert_path
+            .as_deref()
+            .map(|path| tls::read_file(path, "CA bundle"))
+            .transpose()?;
+
+        Ok(Some(TlsCertificates {
+            client_tls,
+            root_cert,
+        }))
+    }
+
+    async fn connect_single(
+        url: String,
+        certificates: Option<TlsCertificates>,
+    ) -> Result<Self, ProviderError> {
+        let client = match certificates {
+            Some(certificates) => Client::build_with_tls(url.as_str(), certificates),
+            None => Client::open(url.as_str()),
+        }
+        .map_err(|e| ProviderError::new(format!("Failed to create Redis client: {}", e)))?;
 
         let conn = ConnectionManager::new(client.clone())
             .await
@@ -139,18 +215,23 @@ impl RedisProvider {
         })
     }
 
-    async fn connect_cluster(urls: String) -> Result<Self, ProviderError> {
+    async fn connect_cluster(
+        urls: String,
+        certificates: Option<TlsCertificates>,
+    ) -> Result<Self, ProviderError> {
         let url_list: Vec<String> = if urls.starts_with("redis-cluster://") {
             vec![urls]
         } else {
             urls.split(',').map(|s| s.trim().to_string()).collect()
         };
 
-        let client = redis::cluster::ClusterClient::builder(url_list)
-            .build()
-            .map_err(|e| {
-                ProviderError::new(format!("Failed to create Redis cluster client: {}", e))
-            })?;
+        let mut builder = redis::cluster::ClusterClient::builder(url_list);
+        if let Some(certificates) = certificates {
+            builder = builder.certs(certificates);
+        }
+        let client = builder.build().map_err(|e| {
+            ProviderError::new(format!("Failed to create Redis cluster client: {}", e))
+        })?;
 
         let conn = client.get_async_connection().await.map_err(|e| {
             ProviderError::new(format!("Failed to connect to Redis cluster: {}", e))
@@ -205,64 +286,148 @@ impl RedisProvider {
 
     /// Scan keys matching a pattern
     async fn scan_keys(&self, pattern: &str, count: usize) -> Result<Vec<String>, ProviderError> {
+        self.scan_keys_where(pattern, count, &|_| true).await
+    }
+
+    /// Scan keys matching a pattern, keeping only those `keep` accepts
+    async fn scan_keys_where(
+        &self,
+        pattern: &str,
+        count: usize,
+        keep: &(dyn Fn(&str) -> bool + Sync),
+    ) -> Result<Vec<String>, ProviderError> {
+        let (_, mut keys) = self.scan_from(pattern, 0, count, keep).await?;
+        keys.truncate(count);
+        Ok(keys)
+    }
+
+    /// One SCAN round trip from `cursor`, returning the next cursor (0 once
+    /// done) and the batch. The connection is locked for this call only, so
+    /// other commands can run between the batches of a long walk.
+    async fn scan_batch(
+        &self,
+        pattern: &str,
+        cursor: u64,
+    ) -> Result<(u64, Vec<String>), ProviderError> {
+        let mut cmd = redis::cmd("SCAN");
+        cmd.arg(cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(100);
         match &self.conn {
             RedisConnection::Single(conn) => {
                 let mut c = conn.lock().await;
-                let mut keys: Vec<String> = Vec::new();
-                let mut cursor: u64 = 0;
-
-                loop {
-                    let (new_cursor, batch): (u64, Vec<String>) = redis::cmd("SCAN")
-                        .arg(cursor)
-                        .arg("MATCH")
-                        .arg(pattern)
-                        .arg("COUNT")
-                        .arg(100)
-                        .query_async(&mut *c)
-                        .await
-                        .map_err(Self::format_error)?;
-
-                    keys.extend(batch);
-                    cursor = new_cursor;
-
-                    if cursor == 0 || keys.len() >= count {
-                        break;
-                    }
-                }
-
-                keys.truncate(count);
-                Ok(keys)
+                cmd.query_async(&mut *c).await.map_err(Self::format_error)
             }
             RedisConnection::Cluster(conn) => {
                 let mut c = conn.lock().await;
-                let mut keys: Vec<String> = Vec::new();
-                let mut cursor: u64 = 0;
-
-                loop {
-                    let (new_cursor, batch): (u64, Vec<String>) = redis::cmd("SCAN")
-                        .arg(cursor)
-                        .arg("MATCH")
-                        .arg(pattern)
-                        .arg("COUNT")
-                        .arg(100)
-                        .query_async(&mut *c)
-                        .await
-                        .map_err(Self::format_error)?;
-
-                    keys.extend(batch);
-                    cursor = new_cursor;
+                cmd.query_async(&mut *c).await.map_err(Self::format_error)
+            }
+        }
+    }
 
-                    if cursor == 0 || keys.len() >= count {
-                        break;
-                    }
-                }
+    /// Continue a SCAN from `cursor` until `count` keys are kept or the scan
+    /// completes, returning the cursor to resume from (0 once done). SCAN
+    /// batches are kept whole, so a few more than `count` keys can come back.
+    async fn scan_from(
+        &self,
+        pattern: &str,
+        mut cursor: u64,
+        count: usize,
+        keep: &(dyn Fn(&str) -> bool + Sync),
+    ) -> Result<(u64, Vec<String>), ProviderError> {
+        let mut keys: Vec<String> = Vec::new();
+        loop {
+            let (next, batch) = self.scan_batch(pattern, cursor).await?;
+            keys.extend(batch.into_iter().filter(|key| keep(key)));
+            cursor = next;
+            if cursor == 0 || keys.len() >= count {
+                return Ok((cursor, keys));
+            }
+        }
+    }
 
-                keys.truncate(count);
-                Ok(keys)
+    /// Walk the whole SCAN counting the keys `keep` accepts, without holding
+    /// them
+    async fn count_keys_where(
+        &self,
+        pattern: &str,
+        keep: &(dyn Fn(&str) -> bool + Sync),
+    ) -> Result<i64, ProviderError> {
+        let mut cursor = 0;
+        let mut total = 0;
+        loop {
+            let (next, batch) = self.scan_batch(pattern, cursor).await?;
+            total += batch.iter().filter(|key| keep(key)).count() as i64;
+            cursor = next;
+            if cursor == 0 {
+                return Ok(total);
             }
         }
     }
 
+    /// One row per key with its type, TTL, size, encoding and value
+    async fn rows_for_keys(&self, keys: Vec<String>) -> Result<QueryResult, ProviderError> {
+        let mut rows: Vec<Vec<serde_json::Value>> = Vec::new();
+
+        for key in keys {
+            let key_type = self.get_key_type(&key).await?;
+            let ttl = self.get_key_ttl(&key).await?;
+            let size = self.get_key_size(&key).await.unwrap_or(0);
+            let encoding = self.get_key_encoding(&key).await.unwrap_or_default();
+            let value = self.get_value(&key, &key_type).await?;
+
+            let ttl_value = if ttl == -1 {
+                serde_json::Value::Null
+            } else if ttl == -2 {
+                serde_json::Value::String("expired".to_string())
+            } else {
+                serde_json::Value::Number(ttl.into())
+            };
+
+            rows.push(vec![
+                serde_json::Value::String(key),
+                serde_json::Value::String(key_type),
+                ttl_value,
+                serde_json::Value::Number(size.into()),
+                serde_json::Value::String(encoding),
+                value,
+            ]);
+        }
+
+        Ok(QueryResult {
+            columns: vec![
+                "key".to_string(),
+                "type".to_string(),
+                "ttl".to_string(),
+                "size".to_string(),
+                "encoding".to_string(),
+                "value".to_string(),
+            ],
+            row_count: rows.len(),
+            rows,
+            ..Default::default()
+        })
+    }
+
+    /// Keys of the group `table` that pass every filter. Only the key name
+    /// can be filtered; when browsing all keys the first filter SCAN can
+    /// express becomes the MATCH pattern, and every filter is checked on the
+    /// names returned.
+    async fn scan_filtered_keys(
+        &self,
+        table: &str,
+        filters: &[ColumnFilter],
+        count: usize,
+    ) -> Result<Vec<String>, ProviderError> {
+        let pattern = filtered_scan_pattern(table, filters)?;
+        self.scan_keys_where(&pattern, count, &|key| {
+            filters.iter().all(|filter| key_filter_matches(filter, key))
+        })
+        .await
+    }
+
     /// Get the type of a key
     async fn get_key_type(&self, key: &str) -> Result<String, ProviderError> {
         match &self.conn {
@@ -1066,6 +1231,7 @@ impl RedisProvider {
                     ],
                     row_count: rows.len(),
                     rows,
+                    ..Default::default()
                 })
             }
             RedisConnection::Cluster(conn) => {
@@ -1112,6 +1278,7 @@ impl RedisProvider {
                     ],
                     row_count: rows.len(),
                     rows,
+                    ..Default::default()
                 })
             }
         }
@@ -1145,11 +1312,11 @@ impl RedisProvider {
         match &self.conn {
             RedisConnection::Single(conn) => {
                 let mut c = conn.lock().await;
-                Self::execute_command_internal_single(&mut *c, &cmd_name, args).await
+                Self::execute_command_internal_single(&mut c, &cmd_name, args).await
             }
             RedisConnection::Cluster(conn) => {
                 let mut c = conn.lock().await;
-                Self::execute_command_internal_cluster(&mut *c, &cmd_name, args).await
+                Self::execute_command_internal_cluster(&mut c, &cmd_name, args).await
             }
         }
     }
@@ -1205,6 +1372,7 @@ fn format_command_result(result: redis::Value) -> Result<QueryResult, ProviderEr
                 columns: vec!["index".to_string(), "value".to_string()],
                 row_count: rows.len(),
                 rows,
+                ..Default::default()
             })
         }
         serde_json::Value::Object(obj) => {
@@ -1216,16 +1384,67 @@ fn format_command_result(result: redis::Value) -> Result<QueryResult, ProviderEr
                 columns: vec!["key".to_string(), "value".to_string()],
                 row_count: rows.len(),
                 rows,
+                ..Default::default()
             })
         }
         _ => Ok(QueryResult {
             columns: vec!["result".to_string()],
             row_count: 1,
             rows: vec![vec![json_value]],
+            ..Default::default()
         }),
     }
 }
 
+/// MATCH pattern for the keys of group `table` under `filters`, after
+/// checking that every filter applies to the key name
+fn filtered_scan_pattern(table: &str, filters: &[ColumnFilter]) -> Result<String, ProviderError> {
+    for filter in filters {
+        validate_key_filter(filter)?;
+    }
+    Ok(if table == "*" {
+        filters
+            .iter()
+            .find_map(key_glob)
+            .unwrap_or_else(|| "*".to_string())
+    } else {
+        table.to_string()
+    })
+}
+
+/// Whether a failed TLS connection got as far as a certificate check, which
+/// only happens when the server offered TLS.
+fn is_certificate_error(err: &ProviderError) -> bool {
+    err.message.to_ascii_lowercase().contains("certificate")
+}
+
+/// Rewrite the connection URLs for the requested TLS mode. The rustls
+/// backend checks hostnames whenever it verifies the chain, so `verify-ca`
+/// behaves like `verify-full`.
+fn tls_urls(urls: &str, tls: &TlsConfig) -> String {
+    urls.split(',')
+        .map(|url| {
+            let url = url.trim();
+            let url = url.strip_suffix("#insecure").unwrap_or(url);
+            let Some(rest) = url
+                .strip_prefix("rediss://")
+                .or_else(|| url.strip_prefix("redis://"))
+            else {
+                return url.to_string();
+            };
+
+            if tls.mode == SslMode::Disable {
+                format!("redis://{}", rest)
+            } else if tls.verifies_certificate() {
+                format!("rediss://{}", rest)
+            } else {
+                format!("rediss://{}#insecure", rest)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 #[async_trait]
 impl DatabaseProvider for RedisProvider {
     fn database_type(&self) -> DatabaseType {
@@ -1287,44 +1506,62 @@ impl DatabaseProvider for RedisProvider {
             ColumnInfo {
                 name: "key".to_string(),
                 data_type: "string".to_string(),
+                full_type: None,
+                nested_paths: Vec::new(),
                 is_nullable: false,
                 is_primary_key: true,
                 has_default: false,
+                default_value: None,
             },
             ColumnInfo {
                 name: "type".to_string(),
                 data_type: "string".to_string(),
+                full_type: None,
+                nested_paths: Vec::new(),
                 is_nullable: false,
                 is_primary_key: false,
                 has_default: false,
+                default_value: None,
             },
             ColumnInfo {
                 name: "ttl".to_string(),
                 data_type: "integer".to_string(),
+                full_type: None,
+                nested_paths: Vec::new(),
                 is_nullable: true,
                 is_primary_key: false,
                 has_default: false,
+                default_value: None,
             },
             ColumnInfo {
                 name: "size".to_string(),
                 data_type: "integer".to_string(),
+                full_type: None,
+                nested_paths: Vec::new(),
                 is_nullable: true,
                 is_primary_key: false,
                 has_default: false,
+                default_value: None,
             },
             ColumnInfo {
                 name: "encoding".to_string(),
                 data_type: "string".to_string(),
+                full_type: None,
+                nested_paths: Vec::new(),
                 is_nullable: true,
                 is_primary_key: false,
                 has_default: false,
+                default_value: None,
             },
             ColumnInfo {
                 name: "value".to_string(),
                 data_type: "any".to_string(),
+                full_type: None,
+                nested_paths: Vec::new(),
                 is_nullable: true,
                 is_primary_key: false,
                 has_default: false,
+                default_value: None,
             },
         ])
     }
@@ -1349,47 +1586,88 @@ impl DatabaseProvider for RedisProvider {
             .take(limit as usize)
             .collect();
 
-        let mut rows: Vec<Vec<serde_json::Value>> = Vec::new();
+        self.rows_for_keys(keys).await
+    }
 
-        for key in keys {
-            let key_type = self.get_key_type(&key).await?;
-            let ttl = self.get_key_ttl(&key).await?;
-            let size = self.get_key_size(&key).await.unwrap_or(0);
-            let encoding = self.get_key_encoding(&key).await.unwrap_or_default();
-            let value = self.get_value(&key, &key_type).await?;
+    async fn get_table_data_filtered(
+        &self,
+        schema: &str,
+        table: &str,
+        query: &TableQuery,
+        limit: i64,
+        offset: i64,
+    ) -> Result<QueryResult, ProviderError> {
+        if !query.sort.is_empty() {
+            return Err(ProviderError::new("Redis keys cannot be sorted"));
+        }
+        if query.filters.is_empty() {
+            return self.get_table_data(schema, table, limit, offset).await;
+        }
 
-            let ttl_value = if ttl == -1 {
-                serde_json::Value::Null
-            } else if ttl == -2 {
-                serde_json::Value::String("expired".to_string())
-            } else {
-                serde_json::Value::Number(ttl.into())
-            };
+        let keys = self
+            .scan_filtered_keys(table, &query.filters, (offset + limit) as usize)
+            .await?;
+        let keys = keys
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect();
+        self.rows_for_keys(keys).await
+    }
 
-            rows.push(vec![
-                serde_json::Value::String(key),
-                serde_json::Value::String(key_type),
-                ttl_value,
-                serde_json::Value::Number(size.into()),
-                serde_json::Value::String(encoding),
-                value,
-            ]);
+    /// Pages follow a single SCAN cursor, so a key present for the whole
+    /// walk is returned and none is skipped however long it takes. Keys come
+    /// in SCAN order and a page can hold a few more than the limit.
+    async fn get_table_page(
+        &self,
+        _schema: &str,
+        table: &str,
+        request: &PageRequest,
+    ) -> Result<TablePage, ProviderError> {
+        if request.key.is_some() || request.direction != SortDirection::Asc {
+            return Err(ProviderError::new(
+                "Redis keys can only be paged in SCAN order",
+            ));
         }
+        let pattern = filtered_scan_pattern(table, &request.filters)?;
+        let cursor = match &request.cursor {
+            Some(token) => token.parse().map_err(|_| {
+                ProviderError::new("Invalid page cursor")
+                    .with_hint("Start again from the first page.")
+            })?,
+            None => 0,
+        };
 
-        Ok(QueryResult {
-            columns: vec![
-                "key".to_string(),
-                "type".to_string(),
-                "ttl".to_string(),
-                "size".to_string(),
-                "encoding".to_string(),
-                "value".to_string(),
-            ],
-            row_count: rows.len(),
-            rows,
+        let (next, keys) = self
+            .scan_from(&pattern, cursor, request.limit.max(1) as usize, &|key| {
+                request
+                    .filters
+                    .iter()
+                    .all(|filter| key_filter_matches(filter, key))
+            })
+            .await?;
+        Ok(TablePage {
+            result: self.rows_for_keys(keys).await?,
+            next_cursor: (next != 0).then(|| next.to_string()),
         })
     }
 
+    async fn get_table_count_filtered(
+        &self,
+        schema: &str,
+        table: &str,
+        filters: &[ColumnFilter],
+    ) -> Result<i64, ProviderError> {
+        if filters.is_empty() {
+            return self.get_table_count(schema, table).await;
+        }
+        let pattern = filtered_scan_pattern(table, filters)?;
+        self.count_keys_where(&pattern, &|key| {
+            filters.iter().all(|filter| key_filter_matches(filter, key))
+        })
+        .await
+    }
+
     async fn execute_query(&self, query: &str) -> Result<QueryResult, ProviderError> {
         let trimmed = query.trim();
         let commands: Vec<&str> = trimmed.lines().filter(|l| !l.trim().is_empty()).collect();
@@ -1398,11 +1676,7 @@ impl DatabaseProvider for RedisProvider {
             return Err(ProviderError::new("No command provided"));
         }
 
-        let mut last_result = QueryResult {
-            columns: vec![],
-            rows: vec![],
-            row_count: 0,
-        };
+        let mut last_result = QueryResult::default();
 
         for cmd in commands {
             last_result = self.execute_command(cmd.trim()).await?;
@@ -1465,3 +1739,92 @@ fn redis_value_to_json(value: &redis::Value) -> serde_json::Value {
         _ => serde_json::Value::Null,
     }
 }
+
+fn validate_key_filter(filter: &ColumnFilter) -> Result<(), ProviderError> {
+    if filter.column != "key" {
+        return Err(
+            ProviderError::new(format!("Cannot filter Redis keys by {}", filter.column))
+                .with_hint("Only the key name can be filtered."),
+        );
+    }
+    let valid = match filter.operator {
+        FilterOperator::In | FilterOperator::NotIn => filter
+            .value
+            .as_array()
+            .is_some_and(|values| values.iter().all(|value| value.is_string())),
+        FilterOperator::Eq
+        | FilterOperator::Ne
+        | FilterOperator::Contains
+        | FilterOperator::NotContains
+        | FilterOperator::StartsWith
+        | FilterOperator::EndsWith => filter.value.is_string(),
+        _ => false,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(ProviderError::new(format!(
+            "Unsupported filter on Redis key names: {:?}",
+            filter.operator
+        )))
+    }
+}
+
+/// Whether `key` passes the filter; substring matches ignore case like the
+/// SQL providers' `LIKE`
+fn key_filter_matches(filter: &ColumnFilter, key: &str) -> bool {
+    let text = filter.value.as_str().unwrap_or_default();
+    let lower_key = key.to_lowercase();
+    let lower_text = text.to_lowercase();
+    let listed = || {
+        filter
+            .value
+            .as_array()
+            .is_some_and(|values| values.iter().any(|value| value.as_str() == Some(key)))
+    };
+    match filter.operator {
+        FilterOperator::Eq => key == text,
+        FilterOperator::Ne => key != text,
+        FilterOperator::Contains => lower_key.contains(&lower_text),
+        FilterOperator::NotContains => !lower_key.contains(&lower_text),
+        FilterOperator::StartsWith => lower_key.starts_with(&lower_text),
+        FilterOperator::EndsWith => lower_key.ends_with(&lower_text),
+        FilterOperator::In => listed(),
+        FilterOperator::NotIn => !listed(),
+        _ => false,
+    }
+}
+
+/// SCAN MATCH pattern selecting the keys a filter accepts, if it has one
+fn key_glob(filter: &ColumnFilter) -> Option<String> {
+    let text = filter.value.as_str()?;
+    match filter.operator {
+        FilterOperator::Eq => Some(glob_escape(text, false)),
+        FilterOperator::Contains => Some(format!("*{}*", glob_escape(text, true))),
+        FilterOperator::StartsWith => Some(format!("{}*", glob_escape(text, true))),
+        FilterOperator::EndsWith => Some(format!("*{}", glob_escape(text, true))),
+        _ => None,
+    }
+}
+
+/// Escape glob metacharacters, optionally turning letters into `[aA]`
+/// classes so the pattern ignores case
+fn glob_escape(text: &str, ignore_case: bool) -> String {
+    let mut glob = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '*' | '?' | '[' | ']' | '\\' => {
+                glob.push('\\');
+                glob.push(c);
+            }
+            c if ignore_case && c.is_ascii_alphabetic() => {
+                glob.push('[');
+                glob.push(c.to_ascii_lowercase());
+                glob.push(c.to_ascii_uppercase());
+                glob.push(']');
+            }
+            c => glob.push(c),
+        }
+    }
+    glob
+}
@@ -0,0 +1,190 @@
+//! Structured catalog introspection beyond tables and columns: indexes, keys,
+//! constraints, triggers, views, sequences and routines.
+
+use crate::{ColumnInfo, DatabaseType, ProviderError, TableInfo};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Everything in the connection's catalog the sidebar lists, grouped by kind
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchemaObjects {
+    pub tables: Vec<TableInfo>,
+    pub views: Vec<ViewInfo>,
+    pub sequences: Vec<SequenceInfo>,
+    pub routines: Vec<RoutineInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ViewInfo {
+    pub schema: String,
+    pub name: String,
+    pub materialized: bool,
+    /// The view's query (or aggregation pipeline for MongoDB)
+    pub definition: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SequenceInfo {
+    pub schema: String,
+    pub name: String,
+    pub data_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoutineKind {
+    Function,
+    Procedure,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutineInfo {
+    pub schema: String,
+    pub name: String,
+    pub kind: RoutineKind,
+    /// Argument list without parentheses, e.g. `a integer, b text`
+    pub arguments: String,
+    /// `None` for procedures
+    pub return_type: Option<String>,
+    pub language: Option<String>,
+}
+
+/// One table with its columns and everything defined on it
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TableDetails {
+    pub schema: String,
+    pub name: String,
+    pub columns: Vec<ColumnInfo>,
+    pub indexes: Vec<IndexInfo>,
+    pub foreign_keys: Vec<ForeignKeyInfo>,
+    /// Primary key, unique, check and exclusion constraints. Foreign keys are
+    /// listed separately in `foreign_keys`.
+    pub constraints: Vec<ConstraintInfo>,
+    pub triggers: Vec<TriggerInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexInfo {
+    pub name: String,
+    /// Key columns in index order; expressions are given as SQL text
+    pub columns: Vec<String>,
+    pub is_unique: bool,
+    pub is_primary: bool,
+    /// Access method, e.g. `btree`, `gin` or `FULLTEXT`
+    pub method: Option<String>,
+    /// Predicate of a partial index
+    pub predicate: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForeignKeyInfo {
+    /// SQLite does not report constraint names
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+    pub referenced_schema: String,
+    pub referenced_table: String,
+    pub referenced_columns: Vec<String>,
+    /// Referential actions, e.g. `CASCADE` or `NO ACTION`
+    pub on_update: String,
+    pub on_delete: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConstraintKind {
+    PrimaryKey,
+    Unique,
+    Check,
+    Exclusion,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConstraintInfo {
+    /// SQLite does not report constraint names
+    pub name: Option<String>,
+    pub kind: ConstraintKind,
+    pub columns: Vec<String>,
+    /// Check expression or full constraint definition, where available
+    pub definition: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TriggerInfo {
+    pub name: String,
+    /// `BEFORE`, `AFTER` or `INSTEAD OF`
+    pub timing: String,
+    /// `INSERT`, `UPDATE`, `DELETE` and/or `TRUNCATE`
+    pub events: Vec<String>,
+    pub definition: Option<String>,
+}
+
+/// Kinds of catalog object DDL can be generated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectKind {
+    Table,
+    View,
+    MaterializedView,
+    Sequence,
+    Function,
+    Procedure,
+    Trigger,
+    Index,
+}
+
+impl fmt::Display for ObjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjectKind::Table => write!(f, "table"),
+            ObjectKind::View => write!(f, "view"),
+            ObjectKind::MaterializedView => write!(f, "materialized view"),
+            ObjectKind::Sequence => write!(f, "sequence"),
+            ObjectKind::Function => write!(f, "function"),
+            ObjectKind::Procedure => write!(f, "procedure"),
+            ObjectKind::Trigger => write!(f, "trigger"),
+            ObjectKind::Index => write!(f, "index"),
+        }
+    }
+}
+
+pub(crate) fn object_not_found(kind: ObjectKind, schema: &str, name: &str) -> ProviderError {
+    ProviderError::new(format!("{} {}.{} does not exist", kind, schema, name))
+}
+
+pub(crate) fn ddl_unsupported(kind: ObjectKind, db_type: DatabaseType) -> ProviderError {
+    ProviderError::new(format!("{} DDL is not supported for {}", kind, db_type))
+}
+
+/// Collapse consecutive rows sharing a name into one entry per name, keeping
+/// row order. Catalog queries return one row per key column, ordered by
+/// object name and then key position.
+pub(crate) fn group_rows<K: PartialEq, T>(
+    rows: impl IntoIterator<Item = (K, T)>,
+) -> Vec<(K, Vec<T>)> {
+    let mut groups: Vec<(K, Vec<T>)> = Vec::new();
+    for (key, value) in rows {
+        match groups.last_mut() {
+            Some((last, values)) if *last == key => values.push(value),
+            _ => groups.push((key, vec![value])),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_are_grouped_by_consecutive_name() {
+        let rows = vec![("pk", "id"), ("uq", "a"), ("uq", "b"), ("ix", "c")];
+        assert_eq!(
+            group_rows(rows),
+            vec![
+                ("pk", vec!["id"]),
+                ("uq", vec!["a", "b"]),
+                ("ix", vec!["c"])
+            ]
+        );
+    }
+}
@@ -0,0 +1,959 @@
+//! Compare the tables of two schemas, on one connection or two, and generate
+//! the DDL that brings the target in line with the source.
+
+use crate::copy::map_type;
+use crate::schema::{ConstraintInfo, ConstraintKind, ForeignKeyInfo, IndexInfo, TableDetails};
+use crate::{sql, ColumnInfo, DatabaseProvider, DatabaseType, ProviderError};
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Change {
+    /// Only in the source
+    Added,
+    /// Only in the target
+    Removed,
+    Changed,
+}
+
+/// One object that exists on one side only or differs between the two
+#[derive(Debug, Clone, Serialize)]
+pub struct ObjectDiff<T> {
+    pub name: String,
+    pub change: Change,
+    pub source: Option<T>,
+    pub target: Option<T>,
+    /// Properties that differ, e.g. `data_type` or `nullable`; empty unless
+    /// the change is `Changed`
+    pub differences: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TableDiff {
+    pub name: String,
+    pub change: Change,
+    pub columns: Vec<ObjectDiff<ColumnInfo>>,
+    /// Indexes that do not back a constraint
+    pub indexes: Vec<ObjectDiff<IndexInfo>>,
+    pub constraints: Vec<ObjectDiff<ConstraintInfo>>,
+    pub foreign_keys: Vec<ObjectDiff<ForeignKeyInfo>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaDiff {
+    /// Tables that differ; identical tables are left out
+    pub tables: Vec<TableDiff>,
+    /// DDL that makes the target match the source, when requested
+    pub script: Option<String>,
+}
+
+/// The two schemas being compared
+struct Sides<'a> {
+    source_type: DatabaseType,
+    source_schema: &'a str,
+    target_type: DatabaseType,
+    target_schema: &'a str,
+}
+
+impl Sides<'_> {
+    fn same_dialect(&self) -> bool {
+        self.source_type == self.target_type
+    }
+}
+
+/// Compare the tables of `source_schema` on `source` with those of
+/// `target_schema` on `target`. With `script`, also generate the statements
+/// that migrate the target.
+pub async fn diff_schemas(
+    source: &dyn DatabaseProvider,
+    source_schema: &str,
+    target: &dyn DatabaseProvider,
+    target_schema: &str,
+    script: bool,
+) -> Result<SchemaDiff, ProviderError> {
+    let sides = Sides {
+        source_type: source.database_type(),
+        source_schema,
+        target_type: target.database_type(),
+        target_schema,
+    };
+    if script && !is_sql(sides.target_type) {
+        return Err(ProviderError::new(format!(
+            "Migration scripts are not supported for {}",
+            sides.target_type
+        )));
+    }
+
+    let source_tables = describe_schema(source, source_schema).await?;
+    let target_tables = describe_schema(target, target_schema).await?;
+    let tables = compare(&sides, &source_tables, &target_tables);
+    let script = script.then(|| migration_script(&sides, &tables));
+    Ok(SchemaDiff { tables, script })
+}
+
+fn is_sql(db_type: DatabaseType) -> bool {
+    matches!(
+        db_type,
+        DatabaseType::Postgres | DatabaseType::Mysql | DatabaseType::Sqlite
+    )
+}
+
+async fn describe_schema(
+    provider: &dyn DatabaseProvider,
+    schema: &str,
+) -> Result<Vec<TableDetails>, ProviderError> {
+    let mut tables = Vec::new();
+    for table in provider.list_tables().await? {
+        if table.schema == schema {
+            tables.push(provider.describe_table(schema, &table.name).await?);
+        }
+    }
+    Ok(tables)
+}
+
+fn compare(sides: &Sides, source: &[TableDetails], target: &[TableDetails]) -> Vec<TableDiff> {
+    let mut tables: Vec<TableDiff> = source
+        .iter()
+        .filter_map(|table| {
+            let other = target.iter().find(|t| t.name == table.name);
+            diff_table(sides, Some(table), other)
+        })
+        .collect();
+    tables.extend(
+        target
+            .iter()
+            .filter(|table| !source.iter().any(|t| t.name == table.name))
+            .filter_map(|table| diff_table(sides, None, Some(table))),
+    );
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+    tables
+}
+
+fn diff_table(
+    sides: &Sides,
+    source: Option<&TableDetails>,
+    target: Option<&TableDetails>,
+) -> Option<TableDiff> {
+    let (name, change) = match (source, target) {
+        (Some(table), None) => (table.name.clone(), Change::Added),
+        (None, Some(table)) => (table.name.clone(), Change::Removed),
+        (Some(table), Some(_)) => (table.name.clone(), Change::Changed),
+        (None, None) => return None,
+    };
+    let empty = TableDetails::default();
+    let source = source.unwrap_or(&empty);
+    let target = target.unwrap_or(&empty);
+
+    let diff = TableDiff {
+        name,
+        change,
+        columns: diff_objects(
+            &source.columns,
+            &target.columns,
+            |column| column.name.clone(),
+            |a, b| column_differences(sides, a, b),
+        ),
+        indexes: diff_objects(
+            &plain_indexes(source),
+            &plain_indexes(target),
+            |index| index.name.clone(),
+            |a, b| index_differences(sides, a, b),
+        ),
+        constraints: diff_objects(
+            &source.constraints,
+            &target.constraints,
+            constraint_key,
+            |a, b| constraint_differences(sides, a, b),
+        ),
+        foreign_keys: diff_objects(
+            &source.foreign_keys,
+            &target.foreign_keys,
+            |fk| foreign_key_key(sides, fk),
+            |a, b| foreign_key_differences(sides, a, b),
+        ),
+    };
+    let unchanged = diff.columns.is_empty()
+        && diff.indexes.is_empty()
+        && diff.constraints.is_empty()
+        && diff.foreign_keys.is_empty();
+    (change != Change::Changed || !unchanged).then_some(diff)
+}
+
+/// Pair up objects by `key` and keep those on one side only or with
+/// differences
+fn diff_objects<T: Clone>(
+    source: &[T],
+    target: &[T],
+    key: impl Fn(&T) -> String,
+    differences: impl Fn(&T, &T) -> Vec<&'static str>,
+) -> Vec<ObjectDiff<T>> {
+    let mut diffs = Vec::new();
+    for item in source {
+        let name = key(item);
+        match target.iter().find(|other| key(other) == name) {
+            None => diffs.push(ObjectDiff {
+                name,
+                change: Change::Added,
+                source: Some(item.clone()),
+                target: None,
+                differences: Vec::new(),
+            }),
+            Some(other) => {
+                let differences = differences(item, other);
+                if !differences.is_empty() {
+                    diffs.push(ObjectDiff {
+                        name,
+                        change: Change::Changed,
+                        source: Some(item.clone()),
+                        target: Some(other.clone()),
+                        differences,
+                    });
+                }
+            }
+        }
+    }
+    for item in target {
+        let name = key(item);
+        if !source.iter().any(|other| key(other) == name) {
+            diffs.push(ObjectDiff {
+                name,
+                change: Change::Removed,
+                source: None,
+                target: Some(item.clone()),
+                differences: Vec::new(),
+            });
+        }
+    }
+    diffs
+}
+
+fn column_differences(sides: &Sides, a: &ColumnInfo, b: &ColumnInfo) -> Vec<&'static str> {
+    let mut differences = Vec::new();
+    // Across dialects types are compared by what the source type becomes in
+    // the target
+    let same_type = if sides.same_dialect() {
+        a.declared_type().eq_ignore_ascii_case(b.declared_type())
+    } else {
+        let (source, target) = (sides.source_type, sides.target_type);
+        map_type(a.declared_type(), source, target, a.is_primary_key)
+            == map_type(b.declared_type(), target, target, b.is_primary_key)
+    };
+    if !same_type {
+        differences.push("data_type");
+    }
+    // Primary key columns cannot hold nulls whatever the catalog says
+    let nullable = |column: &ColumnInfo| column.is_nullable && !column.is_primary_key;
+    if nullable(a) != nullable(b) {
+        differences.push("nullable");
+    }
+    // Default expressions are dialect-specific, and sequences are named per
+    // schema, so any two sequence defaults are taken as equal
+    let is_sequence = |column: &ColumnInfo| {
+        column
+            .default_value
+            .as_deref()
+            .is_some_and(|default| default.starts_with("nextval("))
+    };
+    let same_default = !sides.same_dialect()
+        || is_sequence(a) && is_sequence(b)
+        || a.default_value == b.default_value;
+    if !same_default {
+        differences.push("default");
+    }
+    differences
+}
+
+/// Indexes other than those that back the primary key or a unique
+/// constraint, which are compared as constraints
+fn plain_indexes(table: &TableDetails) -> Vec<IndexInfo> {
+    table
+        .indexes
+        .iter()
+        .filter(|index| {
+            !index.is_primary
+                && !index.name.starts_with("sqlite_autoindex_")
+                && !table
+                    .constraints
+                    .iter()
+                    .any(|c| c.name.as_deref() == Some(index.name.as_str()))
+        })
+        .cloned()
+        .collect()
+}
+
+fn index_differences(sides: &Sides, a: &IndexInfo, b: &IndexInfo) -> Vec<&'static str> {
+    let mut differences = Vec::new();
+    if a.columns != b.columns {
+        differences.push("columns");
+    }
+    if a.is_unique != b.is_unique {
+        differences.push("unique");
+    }
+    if sides.same_dialect() {
+        let method = |index: &IndexInfo| index.method.as_deref().map(str::to_ascii_lowercase);
+        if method(a) != method(b) {
+            differences.push("method");
+        }
+        if a.predicate != b.predicate {
+            differences.push("predicate");
+        }
+    }
+    differences
+}
+
+/// Primary keys and unique constraints are matched by their columns, other
+/// constraints by name, or kind and columns when unnamed (SQLite)
+fn constraint_key(constraint: &ConstraintInfo) -> String {
+    match (&constraint.kind, &constraint.name) {
+        (ConstraintKind::PrimaryKey, _) => "PRIMARY KEY".to_string(),
+        (ConstraintKind::Unique, _) => format!("UNIQUE ({})", constraint.columns.join(", ")),
+        (_, Some(name)) => name.clone(),
+        (kind, None) => format!("{:?} ({})", kind, constraint.columns.join(", ")),
+    }
+}
+
+fn constraint_differences(
+    sides: &Sides,
+    a: &ConstraintInfo,
+    b: &ConstraintInfo,
+) -> Vec<&'static str> {
+    let mut differences = Vec::new();
+    if a.kind != b.kind {
+        differences.push("kind");
+    }
+    if a.columns != b.columns {
+        differences.push("columns");
+    }
+    if sides.same_dialect() && a.definition != b.definition {
+        differences.push("definition");
+    }
+    differences
+}
+
+/// References into the compared schema itself are taken as relative, so
+/// the same foreign key matches in both schemas
+fn referenced_schema<'a>(sides: &Sides, fk: &'a ForeignKeyInfo) -> &'a str {
+    if fk.referenced_schema == sides.source_schema || fk.referenced_schema == sides.target_schema {
+        ""
+    } else {
+        &fk.referenced_schema
+    }
+}
+
+/// Foreign keys are matched by what they reference, since SQLite does not
+/// name them
+fn foreign_key_key(sides: &Sides, fk: &ForeignKeyInfo) -> String {
+    format!(
+        "({}) -> {}{}({})",
+        fk.columns.join(", "),
+        match referenced_schema(sides, fk) {
+            "" => String::new(),
+            schema => format!("{}.", schema),
+        },
+        fk.referenced_table,
+        fk.referenced_columns.join(", ")
+    )
+}
+
+fn foreign_key_differences(
+    sides: &Sides,
+    a: &ForeignKeyInfo,
+    b: &ForeignKeyInfo,
+) -> Vec<&'static str> {
+    let mut differences = Vec::new();
+    if sides.same_dialect() && a.name != b.name {
+        differences.push("name");
+    }
+    if !a.on_update.eq_ignore_ascii_case(&b.on_update)
+        || !a.on_delete.eq_ignore_ascii_case(&b.on_delete)
+    {
+        differences.push("actions");
+    }
+    differences
+}
+
+/// Statements that turn the target schema into the source, ordered so that
+/// foreign keys are dropped first and added last. Changes the target
+/// database cannot make in place are left as comments.
+fn migration_script(sides: &Sides, tables: &[TableDiff]) -> String {
+    let mut script = Script {
+        sides,
+        drop_foreign_keys: Vec::new(),
+        drops: Vec::new(),
+        tables: Vec::new(),
+        columns: Vec::new(),
+        adds: Vec::new(),
+        add_foreign_keys: Vec::new(),
+    };
+    for table in tables {
+        script.table(table);
+    }
+
+    let statements: Vec<String> = [
+        script.drop_foreign_keys,
+        script.drops,
+        script.tables,
+        script.columns,
+        script.adds,
+        script.add_foreign_keys,
+    ]
+    .concat();
+    let mut out = String::new();
+    for statement in statements {
+        out.push_str(&statement);
+        if !statement.starts_with("--") {
+            out.push(';');
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Statements collected per phase of the migration
+struct Script<'a> {
+    sides: &'a Sides<'a>,
+    drop_foreign_keys: Vec<String>,
+    drops: Vec<String>,
+    tables: Vec<String>,
+    columns: Vec<String>,
+    adds: Vec<String>,
+    add_foreign_keys: Vec<String>,
+}
+
+impl Script<'_> {
+    fn db_type(&self) -> DatabaseType {
+        self.sides.target_type
+    }
+
+    fn quote(&self, ident: &str) -> String {
+        sql::quote_identifier(ident, self.db_type())
+    }
+
+    fn quote_list(&self, idents: &[String]) -> String {
+        idents
+            .iter()
+            .map(|ident| self.quote(ident))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn table_name(&self, table: &str) -> String {
+        sql::table_name(self.sides.target_schema, table, self.db_type())
+    }
+
+    fn unsupported(&self, what: String) -> String {
+        format!(
+            "-- {} cannot {} in place; rebuild the table",
+            self.db_type(),
+            what
+        )
+    }
+
+    fn not_portable(&self, what: String) -> String {
+        format!(
+            "-- {} is written for {}; recreate it by hand",
+            what, self.sides.source_type
+        )
+    }
+
+    fn table(&mut self, table: &TableDiff) {
+        let name = self.table_name(&table.name);
+        match table.change {
+            Change::Removed => self.drops.push(format!("DROP TABLE {}", name)),
+            Change::Added => self.create_table(table, &name),
+            Change::Changed => {
+                for column in &table.columns {
+                    self.column(&table.name, &name, column);
+                }
+                for constraint in &table.constraints {
+                    self.constraint(&table.name, &name, constraint);
+                }
+            }
+        }
+        for index in &table.indexes {
+            self.index(&name, index);
+        }
+        for fk in &table.foreign_keys {
+            if table.change == Change::Added && self.db_type() == DatabaseType::Sqlite {
+                continue; // Declared in CREATE TABLE
+            }
+            self.foreign_key(&table.name, &name, fk);
+        }
+    }
+
+    fn create_table(&mut self, table: &TableDiff, name: &str) {
+        let mut definitions: Vec<String> = table
+            .columns
+            .iter()
+            .filter_map(|column| column.source.as_ref())
+            .map(|column| self.column_definition(column))
+            .collect();
+        for constraint in table.constraints.iter().filter_map(|c| c.source.as_ref()) {
+            match self.constraint_definition(constraint) {
+                Some(definition) => definitions.push(definition),
+                None => {
+                    let comment =
+                        self.not_portable(format!("Constraint {}", constraint_key(constraint)));
+                    self.adds.push(comment);
+                }
+            }
+        }
+        // SQLite can only declare foreign keys with the table
+        if self.db_type() == DatabaseType::Sqlite {
+            definitions.extend(
+                table
+                    .foreign_keys
+                    .iter()
+                    .filter_map(|fk| fk.source.as_ref())
+                    .map(|fk| self.foreign_key_definition(fk)),
+            );
+        }
+        self.tables.push(format!(
+            "CREATE TABLE {} (\n    {}\n)",
+            name,
+            definitions.join(",\n    ")
+        ));
+    }
+
+    /// The column's type in the target's dialect
+    fn target_type(&self, column: &ColumnInfo) -> String {
+        if self.sides.same_dialect() {
+            column.declared_type().to_string()
+        } else {
+            map_type(
+                column.declared_type(),
+                self.sides.source_type,
+                self.db_type(),
+                column.is_primary_key,
+            )
+        }
+    }
+
+    /// The type a new column is declared with
+    fn column_type(&self, column: &ColumnInfo) -> String {
+        let data_type = self.target_type(column);
+        if !self.sides.same_dialect() {
+            return data_type;
+        }
+        // A sequence default belongs to the source schema; let a serial
+        // column create its own
+        let serial = self.db_type() == DatabaseType::Postgres
+            && column
+                .default_value
+                .as_deref()
+                .is_some_and(|default| default.starts_with("nextval("));
+        match (serial, data_type.as_str()) {
+            (true, "integer") => "serial".to_string(),
+            (true, "bigint") => "bigserial".to_string(),
+            (true, "smallint") => "smallserial".to_string(),
+            _ => data_type,
+        }
+    }
+
+    /// The default to declare, if it carries over to the target
+    fn column_default(&self, column: &ColumnInfo) -> Option<String> {
+        let default = column.default_value.as_deref()?;
+        if !self.sides.same_dialect() || default.starts_with("nextval(") {
+            return None;
+        }
+        Some(match self.db_type() {
+            DatabaseType::Mysql => mysql_default(default),
+            _ => default.to_string(),
+        })
+    }
+
+    fn column_definition(&self, column: &ColumnInfo) -> String {
+        let mut definition = format!("{} {}", self.quote(&column.name), self.column_type(column));
+        if !column.is_nullable {
+            definition.push_str(" NOT NULL");
+        }
+        if let Some(default) = self.column_default(column) {
+            definition.push_str(&format!(" DEFAULT {}", default));
+        }
+        definition
+    }
+
+    fn column(&mut self, table: &str, name: &str, diff: &ObjectDiff<ColumnInfo>) {
+        match (diff.change, &diff.source) {
+            (Change::Added, Some(column)) => self.columns.push(format!(
+                "ALTER TABLE {} ADD COLUMN {}",
+                name,
+                self.column_definition(column)
+            )),
+            (Change::Removed, _) => self.columns.push(format!(
+                "ALTER TABLE {} DROP COLUMN {}",
+                name,
+                self.quote(&diff.name)
+            )),
+            (Change::Changed, Some(column)) => match self.db_type() {
+                DatabaseType::Postgres => self.alter_postgres_column(name, column, diff),
+                DatabaseType::Mysql => self.columns.push(format!(
+                    "ALTER TABLE {} MODIFY COLUMN {}",
+                    name,
+                    self.column_definition(column)
+                )),
+                _ => {
+                    let statement =
+                        self.unsupported(format!("change column {}.{}", table, diff.name));
+                    self.columns.push(statement);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    fn alter_postgres_column(
+        &mut self,
+        name: &str,
+        column: &ColumnInfo,
+        diff: &ObjectDiff<ColumnInfo>,
+    ) {
+        let quoted = self.quote(&column.name);
+        let mut actions = Vec::new();
+        // serial is only a shorthand for CREATE; an existing column keeps
+        // its sequence and changes to the plain integer type
+        if diff.differences.contains(&"data_type") {
+            let data_type = self.target_type(column);
+            actions.push(format!(
+                "ALTER COLUMN {} TYPE {} USING {}::{}",
+                quoted, data_type, quoted, data_type
+            ));
+        }
+        if diff.differences.contains(&"nullable") {
+            let action = if column.is_nullable { "DROP" } else { "SET" };
+            actions.push(format!("ALTER COLUMN {} {} NOT NULL", quoted, action));
+        }
+        if diff.differences.contains(&"default") {
+            actions.push(match self.column_default(column) {
+                Some(default) => format!("ALTER COLUMN {} SET DEFAULT {}", quoted, default),
+                None => format!("ALTER COLUMN {} DROP DEFAULT", quoted),
+            });
+        }
+        self.columns
+            .push(format!("ALTER TABLE {} {}", name, actions.join(", ")));
+    }
+
+    /// `PRIMARY KEY (...)`, `UNIQUE (...)` or `CHECK (...)`, prefixed with
+    /// the constraint's name when it has one
+    fn constraint_definition(&self, constraint: &ConstraintInfo) -> Option<String> {
+        let body = match constraint.kind {
+            ConstraintKind::PrimaryKey => {
+                format!("PRIMARY KEY ({})", self.quote_list(&constraint.columns))
+            }
+            ConstraintKind::Unique => format!("UNIQUE ({})", self.quote_list(&constraint.columns)),
+            ConstraintKind::Check | ConstraintKind::Exclusion => {
+                // The expression is in the source's SQL dialect
+                if !self.sides.same_dialect() {
+                    return None;
+                }
+                let definition = constraint.definition.as_deref()?;
+                // Postgres reports the whole definition, MySQL just the
+                // check clause
+                if definition.len() >= 5 && definition[..5].eq_ignore_ascii_case("check")
+                    || constraint.kind == ConstraintKind::Exclusion
+                {
+                    definition.to_string()
+                } else {
+                    format!("CHECK ({})", definition)
+                }
+            }
+        };
+        Some(match (&constraint.name, constraint.kind) {
+            // MySQL names every primary key PRIMARY
+            (_, ConstraintKind::PrimaryKey) if self.db_type() == DatabaseType::Mysql => body,
+            (Some(name), _) => format!("CONSTRAINT {} {}", self.quote(name), body),
+            (None, _) => body,
+        })
+    }
+
+    fn constraint(&mut self, table: &str, name: &str, diff: &ObjectDiff<ConstraintInfo>) {
+        if self.db_type() == DatabaseType::Sqlite {
+            let statement =
+                self.unsupported(format!("change constraint {} of {}", diff.name, table));
+            self.drops.push(statement);
+            return;
+        }
+        if let Some(constraint) = &diff.target {
+            let drop = match (self.db_type(), constraint.kind, &constraint.name) {
+                (DatabaseType::Mysql, ConstraintKind::PrimaryKey, _) => {
+                    Some("DROP PRIMARY KEY".to_string())
+                }
+                (DatabaseType::Mysql, ConstraintKind::Unique, Some(constraint_name)) => {
+                    Some(format!("DROP INDEX {}", self.quote(constraint_name)))
+                }
+                (DatabaseType::Mysql, _, Some(constraint_name)) => {
+                    Some(format!("DROP CHECK {}", self.quote(constraint_name)))
+                }
+                (_, _, Some(constraint_name)) => {
+                    Some(format!("DROP CONSTRAINT {}", self.quote(constraint_name)))
+                }
+                (_, _, None) => None,
+            };
+            if let Some(drop) = drop {
+                self.drops.push(format!("ALTER TABLE {} {}", name, drop));
+            }
+        }
+        if let Some(constraint) = &diff.source {
+            let statement = match self.constraint_definition(constraint) {
+                Some(definition) => format!("ALTER TABLE {} ADD {}", name, definition),
+                None => self.not_portable(format!("Constraint {}", diff.name)),
+            };
+            self.adds.push(statement);
+        }
+    }
+
+    fn index(&mut self, table_name: &str, diff: &ObjectDiff<IndexInfo>) {
+        if let Some(index) = &diff.target {
+            self.drops.push(match self.db_type() {
+                DatabaseType::Mysql => {
+                    format!("DROP INDEX {} ON {}", self.quote(&index.name), table_name)
+                }
+                db_type => format!(
+                    "DROP INDEX {}",
+                    sql::table_name(self.sides.target_schema, &index.name, db_type)
+                ),
+            });
+        }
+        let Some(index) = &diff.source else {
+            return;
+        };
+        // Expressions come as SQL text; plain names are quoted
+        let is_expression = |column: &String| column.contains(['(', ' ']);
+        let same_dialect = self.sides.same_dialect();
+        if !same_dialect && index.columns.iter().any(is_expression) {
+            let comment = self.not_portable(format!("Index {}", index.name));
+            self.adds.push(comment);
+            return;
+        }
+        let columns: Vec<String> = index
+            .columns
+            .iter()
+            .map(|column| {
+                if is_expression(column) {
+                    column.clone()
+                } else {
+                    self.quote(column)
+                }
+            })
+            .collect();
+        let method = match (self.db_type(), &index.method) {
+            (DatabaseType::Postgres, Some(method)) if same_dialect => {
+                format!(" USING {}", method)
+            }
+            _ => String::new(),
+        };
+        let predicate = match (&index.predicate, self.db_type()) {
+            (Some(predicate), DatabaseType::Postgres | DatabaseType::Sqlite) if same_dialect => {
+                format!(" WHERE {}", predicate)
+            }
+            _ => String::new(),
+        };
+        self.adds.push(format!(
+            "CREATE {}INDEX {} ON {}{} ({}){}",
+            if index.is_unique { "UNIQUE " } else { "" },
+            self.quote(&index.name),
+            table_name,
+            method,
+            columns.join(", "),
+            predicate
+        ));
+    }
+
+    fn foreign_key_definition(&self, fk: &ForeignKeyInfo) -> String {
+        let referenced = match referenced_schema(self.sides, fk) {
+            "" => self.table_name(&fk.referenced_table),
+            schema => sql::table_name(schema, &fk.referenced_table, self.db_type()),
+        };
+        let mut definition = format!(
+            "FOREIGN KEY ({}) REFERENCES {} ({}) ON UPDATE {} ON DELETE {}",
+            self.quote_list(&fk.columns),
+            referenced,
+            self.quote_list(&fk.referenced_columns),
+            fk.on_update,
+            fk.on_delete
+        );
+        if let Some(name) = &fk.name {
+            definition = format!("CONSTRAINT {} {}", self.quote(name), definition);
+        }
+        definition
+    }
+
+    fn foreign_key(&mut self, table: &str, name: &str, diff: &ObjectDiff<ForeignKeyInfo>) {
+        if self.db_type() == DatabaseType::Sqlite {
+            let statement =
+                self.unsupported(format!("change foreign key {} of {}", diff.name, table));
+            self.drop_foreign_keys.push(statement);
+            return;
+        }
+        if let Some(Some(fk_name)) = diff.target.as_ref().map(|fk| &fk.name) {
+            let drop = match self.db_type() {
+                DatabaseType::Mysql => "DROP FOREIGN KEY",
+                _ => "DROP CONSTRAINT",
+            };
+            self.drop_foreign_keys.push(format!(
+                "ALTER TABLE {} {} {}",
+                name,
+                drop,
+                self.quote(fk_name)
+            ));
+        }
+        if let Some(fk) = &diff.source {
+            let definition = self.foreign_key_definition(fk);
+            self.add_foreign_keys
+                .push(format!("ALTER TABLE {} ADD {}", name, definition));
+        }
+    }
+}
+
+/// MySQL reports literal defaults without their quotes
+fn mysql_default(default: &str) -> String {
+    let upper = default.to_ascii_uppercase();
+    let is_expression = default.parse::<f64>().is_ok()
+        || default.starts_with(['\'', '('])
+        || default.starts_with("b'")
+        || upper == "NULL"
+        || upper.starts_with("CURRENT_TIMESTAMP")
+        || upper.starts_with("NOW(");
+    if is_expression {
+        default.to_string()
+    } else {
+        sql::literal(&Value::String(default.to_string()), DatabaseType::Mysql)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, data_type: &str, nullable: bool, default: Option<&str>) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            full_type: None,
+            nested_paths: Vec::new(),
+            is_nullable: nullable,
+            is_primary_key: name == "id",
+            has_default: default.is_some(),
+            default_value: default.map(str::to_string),
+        }
+    }
+
+    fn table(name: &str, columns: Vec<ColumnInfo>) -> TableDetails {
+        TableDetails {
+            schema: "app".to_string(),
+            name: name.to_string(),
+            constraints: vec![ConstraintInfo {
+                name: Some(format!("{}_pkey", name)),
+                kind: ConstraintKind::PrimaryKey,
+                columns: vec!["id".to_string()],
+                definition: Some("PRIMARY KEY (id)".to_string()),
+            }],
+            columns,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diff_and_script_reconcile_tables() {
+        let sides = Sides {
+            source_type: DatabaseType::Postgres,
+            source_schema: "staging",
+            target_type: DatabaseType::Postgres,
+            target_schema: "app",
+        };
+        let id = column(
+            "id",
+            "integer",
+            false,
+            Some("nextval('users_id_seq'::regclass)"),
+        );
+        let source = vec![
+            table(
+                "users",
+                vec![
+                    id.clone(),
+                    column("email", "text", false, None),
+                    column(
+                        "plan",
+                        "character varying(20)",
+                        true,
+                        Some("'free'::character varying"),
+                    ),
+                ],
+            ),
+            table("teams", vec![id.clone()]),
+        ];
+        let target = vec![
+            table(
+                "users",
+                vec![
+                    id.clone(),
+                    column("email", "text", true, None),
+                    column("legacy", "boolean", true, None),
+                ],
+            ),
+            table("old", vec![id.clone()]),
+        ];
+
+        let tables = compare(&sides, &source, &target);
+        let changes: Vec<(&str, Change)> =
+            tables.iter().map(|t| (t.name.as_str(), t.change)).collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("old", Change::Removed),
+                ("teams", Change::Added),
+                ("users", Change::Changed)
+            ]
+        );
+        let users = &tables[2];
+        assert_eq!(users.columns.len(), 3);
+        assert_eq!(users.columns[0].differences, vec!["nullable"]);
+
+        assert_eq!(
+            migration_script(&sides, &tables),
+            concat!(
+                "DROP TABLE \"app\".\"old\";\n",
+                "CREATE TABLE \"app\".\"teams\" (\n",
+                "    \"id\" serial NOT NULL,\n",
+                "    CONSTRAINT \"teams_pkey\" PRIMARY KEY (\"id\")\n",
+                ");\n",
+                "ALTER TABLE \"app\".\"users\" ALTER COLUMN \"email\" SET NOT NULL;\n",
+                "ALTER TABLE \"app\".\"users\" ADD COLUMN \"plan\" character varying(20) DEFAULT 'free'::character varying;\n",
+                "ALTER TABLE \"app\".\"users\" DROP COLUMN \"legacy\";\n",
+            )
+        );
+    }
+
+    #[test]
+    fn mysql_literal_defaults_are_quoted() {
+        assert_eq!(mysql_default("free"), "'free'");
+        assert_eq!(mysql_default("0"), "0");
+        assert_eq!(mysql_default("CURRENT_TIMESTAMP"), "CURRENT_TIMESTAMP");
+        assert_eq!(mysql_default("big"), "'big'");
+        assert_eq!(mysql_default("b'1'"), "b'1'");
+    }
+
+    #[test]
+    fn sequence_columns_change_to_the_integer_type() {
+        let sides = Sides {
+            source_type: DatabaseType::Postgres,
+            source_schema: "staging",
+            target_type: DatabaseType::Postgres,
+            target_schema: "app",
+        };
+        let default = Some("nextval('users_id_seq'::regclass)");
+        let source = vec![table("users", vec![column("id", "bigint", false, default)])];
+        let target = vec![table(
+            "users",
+            vec![column("id", "integer", false, default)],
+        )];
+
+        let tables = compare(&sides, &source, &target);
+        assert_eq!(
+            migration_script(&sides, &tables),
+            "ALTER TABLE \"app\".\"users\" ALTER COLUMN \"id\" TYPE bigint USING \"id\"::bigint;\n"
+        );
+    }
+}
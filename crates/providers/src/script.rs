@@ -0,0 +1,66 @@
+use crate::{CancelToken, ProviderError, QueryResult};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// What to do when a statement in a script fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptErrorMode {
+    #[default]
+    StopOnError,
+    Continue,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatementResult {
+    pub index: usize,
+    pub statement: String,
+    pub result: Option<QueryResult>,
+    pub error: Option<String>,
+    pub duration_ms: f64,
+}
+
+/// Collects per-statement outcomes and decides whether the script goes on.
+pub(crate) struct ScriptRecorder {
+    mode: ScriptErrorMode,
+    results: Vec<StatementResult>,
+}
+
+impl ScriptRecorder {
+    pub fn new(mode: ScriptErrorMode) -> Self {
+        Self {
+            mode,
+            results: Vec::new(),
+        }
+    }
+
+    /// Record a finished statement; returns `false` when execution should stop.
+    pub fn record(
+        &mut self,
+        statement: String,
+        result: Result<QueryResult, ProviderError>,
+        started: Instant,
+        cancel: &CancelToken,
+    ) -> bool {
+        let failed = result.is_err();
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+
+        self.results.push(StatementResult {
+            index: self.results.len(),
+            statement,
+            result,
+            error,
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+        });
+
+        let stop_on_error = failed && self.mode == ScriptErrorMode::StopOnError;
+        !cancel.is_cancelled() && !stop_on_error
+    }
+
+    pub fn finish(self) -> Vec<StatementResult> {
+        self.results
+    }
+}
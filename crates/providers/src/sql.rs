@@ -0,0 +1,407 @@
+//! Helpers for inspecting and splitting SQL text.
+
+use crate::DatabaseType;
+
+/// Strip leading whitespace and `--` / `/* */` comments.
+fn skip_leading_trivia(mut sql: &str) -> &str {
+    loop {
+        sql = sql.trim_start();
+        if let Some(rest) = sql.strip_prefix("--") {
+            sql = rest.find('\n').map(|i| &rest[i + 1..]).unwrap_or("");
+        } else if let Some(rest) = sql.strip_prefix("/*") {
+            sql = rest.find("*/").map(|i| &rest[i + 2..]).unwrap_or("");
+        } else {
+            return sql;
+        }
+    }
+}
+
+/// The statement's first keyword in upper case, e.g. `UPDATE`.
+pub fn leading_keyword(sql: &str) -> Option<String> {
+    let keyword: String = skip_leading_trivia(sql)
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+
+    if keyword.is_empty() {
+        None
+    } else {
+        Some(keyword.to_ascii_uppercase())
+    }
+}
+
+/// The keyword of the statement that actually runs: the leading keyword, or
+/// for `WITH ...` the one following the CTE list, e.g. `UPDATE`.
+pub fn statement_keyword(sql: &str) -> Option<String> {
+    let keyword = leading_keyword(sql)?;
+    if keyword != "WITH" {
+        return Some(keyword);
+    }
+
+    let body = skip_leading_trivia(sql);
+    let mut chars = body[keyword.len()..].chars().peekable();
+    let mut depth = 0usize;
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            '\'' | '"' | '`' => {
+                for next in chars.by_ref() {
+                    if next == c {
+                        break;
+                    }
+                }
+            }
+            c if c.is_ascii_alphabetic() && depth == 0 => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_ascii_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                let word = word.to_ascii_uppercase();
+                if matches!(
+                    word.as_str(),
+                    "SELECT" | "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "REPLACE" | "VALUES"
+                ) {
+                    return Some(word);
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(keyword)
+}
+
+/// Build a Postgres-style command tag such as `DELETE 3`.
+pub fn command_tag(sql: &str, count: u64) -> Option<String> {
+    statement_keyword(sql).map(|keyword| format!("{} {}", keyword, count))
+}
+
+/// Quote an identifier for `db_type`: backticks for MySQL and double quotes
+/// elsewhere, doubling any embedded quote character.
+pub fn quote_identifier(ident: &str, db_type: DatabaseType) -> String {
+    match db_type {
+        DatabaseType::Mysql => format!("`{}`", ident.replace('`', "``")),
+        _ => format!("\"{}\"", ident.replace('"', "\"\"")),
+    }
+}
+
+/// `schema.name` with both parts quoted for `db_type`.
+pub fn qualified_name(schema: &str, name: &str, db_type: DatabaseType) -> String {
+    format!(
+        "{}.{}",
+        quote_identifier(schema, db_type),
+        quote_identifier(name, db_type)
+    )
+}
+
+/// The name statements use for a table: schema-qualified, except for SQLite
+/// where the schema is the attached database and always `main` here.
+pub fn table_name(schema: &str, table: &str, db_type: DatabaseType) -> String {
+    match db_type {
+        DatabaseType::Sqlite => quote_identifier(table, db_type),
+        _ => qualified_name(schema, table, db_type),
+    }
+}
+
+/// Positional parameter marker for the `index`th (1-based) bound value:
+/// `$1` for Postgres, `?` for MySQL and SQLite.
+pub fn placeholder(db_type: DatabaseType, index: usize) -> String {
+    match db_type {
+        DatabaseType::Postgres => format!("${}", index),
+        _ => "?".to_string(),
+    }
+}
+
+/// A JSON value as a SQL literal for `db_type`. Objects and arrays become
+/// their JSON text.
+pub fn literal(value: &serde_json::Value, db_type: DatabaseType) -> String {
+    use serde_json::Value;
+
+    let text = match value {
+        Value::Null => return "NULL".to_string(),
+        Value::Bool(b) => {
+            return match (db_type, b) {
+                (DatabaseType::Sqlite, true) => "1".to_string(),
+                (DatabaseType::Sqlite, false) => "0".to_string(),
+                (_, true) => "TRUE".to_string(),
+                (_, false) => "FALSE".to_string(),
+            }
+        }
+        Value::Number(n) => return n.to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    match db_type {
+        // Backslash is an escape character in MySQL string literals
+        DatabaseType::Mysql => format!("'{}'", text.replace('\\', "\\\\").replace('\'', "''")),
+        _ => format!("'{}'", text.replace('\'', "''")),
+    }
+}
+
+/// Split a script into individual statements for `db_type`.
+///
+/// SQL dialects split on `;` outside of quotes and comments, honouring
+/// Postgres dollar-quoting and MySQL `DELIMITER` lines. Redis scripts are one
+/// command per line and MongoDB scripts are passed through whole.
+pub fn split_statements(script: &str, db_type: DatabaseType) -> Vec<String> {
+    match db_type {
+        DatabaseType::Postgres | DatabaseType::Mysql | DatabaseType::Sqlite => {
+            SqlSplitter::new(script, db_type).split()
+        }
+        DatabaseType::Redis => script
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect(),
+        DatabaseType::Mongodb => {
+            let script = script.trim();
+            if script.is_empty() {
+                vec![]
+            } else {
+                vec![script.to_string()]
+            }
+        }
+    }
+}
+
+struct SqlSplitter<'a> {
+    sql: &'a str,
+    bytes: &'a [u8],
+    mysql: bool,
+    postgres: bool,
+    delimiter: String,
+    statements: Vec<String>,
+}
+
+impl<'a> SqlSplitter<'a> {
+    fn new(sql: &'a str, db_type: DatabaseType) -> Self {
+        Self {
+            sql,
+            bytes: sql.as_bytes(),
+            mysql: db_type == DatabaseType::Mysql,
+            postgres: db_type == DatabaseType::Postgres,
+            delimiter: ";".to_string(),
+            statements: Vec::new(),
+        }
+    }
+
+    fn split(mut self) -> Vec<String> {
+        let len = self.bytes.len();
+        let mut start = 0;
+        let mut i = 0;
+
+        while i < len {
+            if self.mysql && (i == 0 || self.bytes[i - 1] == b'\n') {
+                if let Some((delimiter, next_line)) = self.delimiter_command(i) {
+                    self.push(start, i);
+                    self.delimiter = delimiter;
+                    i = next_line;
+                    start = i;
+                    continue;
+                }
+            }
+
+            let rest = &self.sql[i..];
+            if rest.starts_with("--") || (self.mysql && rest.starts_with('#')) {
+                i = self.line_end(i);
+            } else if rest.starts_with("/*") {
+                i = rest.find("*/").map(|end| i + end + 2).unwrap_or(len);
+            } else if matches!(self.bytes[i], b'\'' | b'"') || (self.mysql && self.bytes[i] == b'`')
+            {
+                i = self.quoted_end(i);
+            } else if self.postgres && self.bytes[i] == b'$' {
+                i = self.dollar_quoted_end(i);
+            } else if rest.starts_with(self.delimiter.as_str()) {
+                self.push(start, i);
+                i += self.delimiter.len();
+                start = i;
+            } else {
+                // Step over whole characters so `i` stays a char boundary
+                i += rest.chars().next().map_or(1, char::len_utf8);
+            }
+        }
+
+        self.push(start, len);
+        self.statements
+    }
+
+    fn push(&mut self, start: usize, end: usize) {
+        let statement = self.sql[start..end].trim();
+        if !skip_leading_trivia(statement).is_empty() {
+            self.statements.push(statement.to_string());
+        }
+    }
+
+    fn line_end(&self, from: usize) -> usize {
+        self.sql[from..]
+            .find('\n')
+            .map(|end| from + end + 1)
+            .unwrap_or(self.bytes.len())
+    }
+
+    /// Parse a MySQL client `DELIMITER <token>` line starting at `line_start`.
+    fn delimiter_command(&self, line_start: usize) -> Option<(String, usize)> {
+        let next_line = self.line_end(line_start);
+        let line = self.sql[line_start..next_line].trim();
+        let keyword = line.get(..9)?;
+        if !keyword.eq_ignore_ascii_case("delimiter") {
+            return None;
+        }
+
+        let delimiter = line[9..].trim();
+        if delimiter.is_empty() || !line[9..].starts_with(char::is_whitespace) {
+            return None;
+        }
+
+        Some((delimiter.to_string(), next_line))
+    }
+
+    /// End of a quoted string or identifier; doubled quotes are escapes, and
+    /// MySQL additionally allows backslash escapes inside strings.
+    fn quoted_end(&self, open: usize) -> usize {
+        let quote = self.bytes[open];
+        let mut i = open + 1;
+
+        while i < self.bytes.len() {
+            let c = self.bytes[i];
+            if self.mysql && quote != b'`' && c == b'\\' {
+                i += 2;
+            } else if c == quote {
+                if self.bytes.get(i + 1) == Some(&quote) {
+                    i += 2;
+                } else {
+                    return i + 1;
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        self.bytes.len()
+    }
+
+    /// End of a `$tag$ ... $tag$` body, or just past the `$` if this is not a
+    /// dollar quote (e.g. a `$1` parameter).
+    fn dollar_quoted_end(&self, open: usize) -> usize {
+        let tag_len = self.bytes[open + 1..]
+            .iter()
+            .take_while(|c| c.is_ascii_alphanumeric() || **c == b'_')
+            .count();
+        let close = open + 1 + tag_len;
+
+        let starts_with_digit = tag_len > 0 && self.bytes[open + 1].is_ascii_digit();
+        if starts_with_digit || self.bytes.get(close) != Some(&b'$') {
+            return open + 1;
+        }
+
+        let tag = &self.sql[open..=close];
+        self.sql[close + 1..]
+            .find(tag)
+            .map(|end| close + 1 + end + tag.len())
+            .unwrap_or(self.bytes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leading_keyword_skips_comments() {
+        assert_eq!(
+            leading_keyword("  -- note\n/* block */ update t set a = 1").as_deref(),
+            Some("UPDATE")
+        );
+        assert_eq!(leading_keyword("-- only a comment"), None);
+    }
+
+    #[test]
+    fn identifiers_are_quoted_per_dialect() {
+        assert_eq!(
+            qualified_name("app", "we\"ird", DatabaseType::Postgres),
+            "\"app\".\"we\"\"ird\""
+        );
+        assert_eq!(quote_identifier("a`b", DatabaseType::Mysql), "`a``b`");
+    }
+
+    #[test]
+    fn command_tag_includes_count() {
+        assert_eq!(command_tag("delete from t", 3).as_deref(), Some("DELETE 3"));
+    }
+
+    #[test]
+    fn statement_keyword_looks_past_ctes() {
+        assert_eq!(
+            statement_keyword("with a as (select 1), \"b\" as (select ')') update t set x = 1")
+                .as_deref(),
+            Some("UPDATE")
+        );
+        assert_eq!(
+            statement_keyword("WITH RECURSIVE r(n) AS (SELECT 1) SELECT * FROM r").as_deref(),
+            Some("SELECT")
+        );
+        assert_eq!(
+            statement_keyword("create table t (a int)").as_deref(),
+            Some("CREATE")
+        );
+    }
+
+    #[test]
+    fn split_ignores_delimiters_in_quotes_and_comments() {
+        let script = "select ';' as a; -- trailing; comment\nselect \"x;y\" from t /* ; */;\n";
+        assert_eq!(
+            split_statements(script, DatabaseType::Sqlite),
+            vec![
+                "select ';' as a",
+                "-- trailing; comment\nselect \"x;y\" from t /* ; */"
+            ]
+        );
+    }
+
+    #[test]
+    fn split_handles_postgres_dollar_quotes() {
+        let script =
+            "create function f() returns int as $body$ select 1; $body$ language sql;\nselect $1;";
+        assert_eq!(
+            split_statements(script, DatabaseType::Postgres),
+            vec![
+                "create function f() returns int as $body$ select 1; $body$ language sql",
+                "select $1"
+            ]
+        );
+    }
+
+    #[test]
+    fn split_handles_mysql_delimiter_and_escapes() {
+        let script = "select 'it\\'s;';\nDELIMITER $$\ncreate procedure p() begin select 1; end$$\nDELIMITER ;\nselect 2;";
+        assert_eq!(
+            split_statements(script, DatabaseType::Mysql),
+            vec![
+                "select 'it\\'s;'",
+                "create procedure p() begin select 1; end",
+                "select 2"
+            ]
+        );
+    }
+
+    #[test]
+    fn split_handles_unicode_outside_quotes() {
+        assert_eq!(
+            split_statements("SELECT * FROM café; SELECT 1", DatabaseType::Postgres),
+            vec!["SELECT * FROM café", "SELECT 1"]
+        );
+        assert_eq!(
+            split_statements(
+                "select ü from t; -- ß
+select 2",
+                DatabaseType::Mysql
+            ),
+            vec!["select ü from t", "-- ß\nselect 2"]
+        );
+    }
+}
@@ -1,11 +1,22 @@
-use async_trait::async_trait;
+use crate::edit;
+use crate::explain;
+use crate::filter;
+use crate::import::{self, ImportReport};
+use crate::pagination;
+use crate::stream::RowBatcher;
+use crate::transaction;
 use crate::{
-    ColumnInfo, ConnectionParams, DatabaseProvider, DatabaseType, ProviderError,
-    QueryResult, TableInfo,
+    cancel, schema, sql, CancelToken, ColumnFilter, ColumnInfo, ConnectionParams, ConstraintInfo,
+    ConstraintKind, DatabaseProvider, DatabaseType, ForeignKeyInfo, ImportBatch, ImportBatches,
+    ImportErrorMode, ImportSummary, IndexInfo, LogicalType, ObjectKind, PageRequest, ProviderError,
+    QueryCanceller, QueryPlan, QueryResult, QueryStreamEvent, ResultColumn, RowEdit, SchemaObjects,
+    TableDetails, TableInfo, TablePage, TableQuery, TriggerInfo, ViewInfo,
 };
-use rusqlite::{types::Value, Connection};
+use async_trait::async_trait;
+use rusqlite::{params_from_iter, types::Value, Connection, InterruptHandle};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Instant;
+use tokio::sync::{mpsc, Mutex};
 
 mod queries {
     pub const LIST_TABLES: &str = r#"
@@ -25,9 +36,60 @@ SELECT
     type as data_type,
     "notnull" = 0 as is_nullable,
     pk > 0 as is_primary_key,
-    dflt_value IS NOT NULL as has_default
+    dflt_value IS NOT NULL as has_default,
+    dflt_value as default_value
 FROM pragma_table_info(?)
 ORDER BY cid
+"#;
+
+    pub const LIST_VIEWS: &str = r#"
+SELECT name, sql
+FROM sqlite_master
+WHERE type = 'view'
+ORDER BY name
+"#;
+
+    pub const TABLE_INDEXES: &str = r#"
+SELECT name, "unique", origin
+FROM pragma_index_list(?)
+ORDER BY name
+"#;
+
+    /// Key columns of an index; `name` is NULL for expressions
+    pub const INDEX_COLUMNS: &str = r#"
+SELECT name
+FROM pragma_index_info(?)
+ORDER BY seqno
+"#;
+
+    pub const PRIMARY_KEY_COLUMNS: &str = r#"
+SELECT name
+FROM pragma_table_info(?)
+WHERE pk > 0
+ORDER BY pk
+"#;
+
+    /// One row per key column; `to` is NULL when the parent's primary key is implied
+    pub const TABLE_FOREIGN_KEYS: &str = r#"
+SELECT id, "table", "from", "to", on_update, on_delete
+FROM pragma_foreign_key_list(?)
+ORDER BY id, seq
+"#;
+
+    pub const TABLE_TRIGGERS: &str = r#"
+SELECT name, sql
+FROM sqlite_master
+WHERE type = 'trigger' AND tbl_name = ?
+ORDER BY name
+"#;
+
+    /// The object's own statement and, for tables, those of its explicit indexes
+    pub const OBJECT_DDL: &str = r#"
+SELECT sql
+FROM sqlite_master
+WHERE sql IS NOT NULL
+    AND ((type = ?1 AND name = ?2) OR (?1 = 'table' AND type = 'index' AND tbl_name = ?2))
+ORDER BY type = 'index', name
 "#;
 
     pub fn select_table_data(table: &str, limit: i64, offset: i64) -> String {
@@ -49,12 +111,17 @@ ORDER BY cid
 
 pub struct SqliteProvider {
     conn: Arc<Mutex<Connection>>,
+    /// SQLite has a single connection, so at most one transaction can be open
+    /// and every other statement runs inside it while it is.
+    transaction: std::sync::Mutex<Option<String>>,
 }
 
 impl SqliteProvider {
     pub async fn connect(params: ConnectionParams) -> Result<Self, ProviderError> {
         let path = match params {
-            ConnectionParams::ConnectionString { connection_string } => connection_string,
+            ConnectionParams::ConnectionString {
+                connection_string, ..
+            } => connection_string,
             ConnectionParams::Parameters { database, .. } => database,
         };
 
@@ -63,12 +130,278 @@ impl SqliteProvider {
 
         Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
+            transaction: std::sync::Mutex::new(None),
         })
     }
 
     fn format_error(e: rusqlite::Error) -> ProviderError {
         ProviderError::new(e.to_string())
     }
+
+    /// Insert one import batch row by row; a rejected row only undoes its
+    /// own statement.
+    fn import_batch(
+        conn: &Connection,
+        insert: &str,
+        batch: ImportBatch,
+        report: &mut ImportReport,
+    ) -> Result<(), ProviderError> {
+        let mut statement = conn.prepare_cached(insert).map_err(Self::format_error)?;
+        for (offset, row) in batch.rows.into_iter().enumerate() {
+            let params = row.into_iter().map(json_to_sqlite_value);
+            match statement.execute(params_from_iter(params)) {
+                Ok(_) => report.imported(1),
+                Err(e) => {
+                    report.row_failed(batch.first_row + offset as u64, Self::format_error(e))?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_transaction(&self, transaction_id: &str) -> Result<(), ProviderError> {
+        match self.transaction.lock().unwrap().as_deref() {
+            Some(id) if id == transaction_id => Ok(()),
+            _ => Err(transaction::transaction_not_found(transaction_id)),
+        }
+    }
+
+    async fn finish_transaction(
+        &self,
+        transaction_id: &str,
+        statement: &str,
+    ) -> Result<(), ProviderError> {
+        self.check_transaction(transaction_id)?;
+        let conn = self.conn.lock().await;
+        let result = conn.execute_batch(statement).map_err(Self::format_error);
+
+        // A failed COMMIT leaves the transaction open; keep tracking it then.
+        if result.is_ok() || conn.is_autocommit() {
+            self.transaction.lock().unwrap().take();
+        }
+        result
+    }
+
+    /// Indexes, keys and triggers of `table`. SQLite's pragmas do not expose
+    /// CHECK constraints or partial index predicates.
+    fn read_table_details(
+        conn: &Connection,
+        details: &mut TableDetails,
+    ) -> Result<(), rusqlite::Error> {
+        let table = details.name.as_str();
+
+        let primary_key = Self::column_list(conn, queries::PRIMARY_KEY_COLUMNS, table)?;
+        if !primary_key.is_empty() {
+            details.constraints.push(ConstraintInfo {
+                name: None,
+                kind: ConstraintKind::PrimaryKey,
+                columns: primary_key,
+                definition: None,
+            });
+        }
+
+        let indexes: Vec<(String, bool, String)> = conn
+            .prepare(queries::TABLE_INDEXES)?
+            .query_map([table], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<_, _>>()?;
+        for (name, is_unique, origin) in indexes {
+            let columns = Self::column_list(conn, queries::INDEX_COLUMNS, &name)?;
+            // Automatic indexes back UNIQUE constraints, which have no name of their own
+            if origin == "u" {
+                details.constraints.push(ConstraintInfo {
+                    name: None,
+                    kind: ConstraintKind::Unique,
+                    columns: columns.clone(),
+                    definition: None,
+                });
+            }
+            details.indexes.push(IndexInfo {
+                name,
+                columns,
+                is_unique,
+                is_primary: origin == "pk",
+                method: None,
+                predicate: None,
+            });
+        }
+
+        type ForeignKeyRow = (String, String, Option<String>, String, String);
+        let rows: Vec<(i64, ForeignKeyRow)> = conn
+            .prepare(queries::TABLE_FOREIGN_KEYS)?
+            .query_map([table], |row| {
+                Ok((
+                    row.get(0)?,
+                    (
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                    ),
+                ))
+            })?
+            .collect::<Result<_, _>>()?;
+        for (_, key) in schema::group_rows(rows) {
+            let (referenced_table, _, _, on_update, on_delete) = key[0].clone();
+            let mut referenced_columns: Vec<String> = key
+                .iter()
+                .filter_map(|(_, _, to, _, _)| to.clone())
+                .collect();
+            if referenced_columns.len() < key.len() {
+                referenced_columns =
+                    Self::column_list(conn, queries::PRIMARY_KEY_COLUMNS, &referenced_table)?;
+            }
+            details.foreign_keys.push(ForeignKeyInfo {
+                name: None,
+                columns: key.into_iter().map(|(_, from, _, _, _)| from).collect(),
+                referenced_schema: details.schema.clone(),
+                referenced_table,
+                referenced_columns,
+                on_update,
+                on_delete,
+            });
+        }
+
+        details.triggers = conn
+            .prepare(queries::TABLE_TRIGGERS)?
+            .query_map([table], |row| {
+                let definition: Option<String> = row.get(1)?;
+                let (timing, events) =
+                    sqlite_trigger_timing_and_event(definition.as_deref().unwrap_or_default());
+                Ok(TriggerInfo {
+                    name: row.get(0)?,
+                    timing,
+                    events,
+                    definition,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(())
+    }
+
+    /// First column of `query` run with a single text argument. Expression
+    /// columns, which SQLite reports as NULL, are shown as `<expression>`.
+    fn column_list(
+        conn: &Connection,
+        query: &str,
+        arg: &str,
+    ) -> Result<Vec<String>, rusqlite::Error> {
+        conn.prepare(query)?
+            .query_map([arg], |row| {
+                Ok(row
+                    .get::<_, Option<String>>(0)?
+                    .unwrap_or_else(|| "<expression>".to_string()))
+            })?
+            .collect()
+    }
+
+    fn run_query(
+        conn: &Connection,
+        query: &str,
+        params: &[Value],
+    ) -> Result<QueryResult, ProviderError> {
+        let mut stmt = conn.prepare(query).map_err(Self::format_error)?;
+
+        let column_count = stmt.column_count();
+        let columns: Vec<String> = stmt
+            .column_names()
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect();
+        let column_metadata: Vec<ResultColumn> = stmt
+            .columns()
+            .iter()
+            .map(|column| {
+                let native_type = column.decl_type().unwrap_or_default().to_string();
+                ResultColumn {
+                    name: column.name().to_string(),
+                    logical_type: sqlite_logical_type(&native_type),
+                    native_type,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        let mut result_rows: Vec<Vec<serde_json::Value>> = Vec::new();
+
+        let mut rows = stmt
+            .query(params_from_iter(params))
+            .map_err(Self::format_error)?;
+
+        while let Some(row) = rows.next().map_err(Self::format_error)? {
+            let mut row_values: Vec<serde_json::Value> = Vec::new();
+            for i in 0..column_count {
+                let value = sqlite_value_to_json(row, i);
+                row_values.push(value);
+            }
+            result_rows.push(row_values);
+        }
+
+        // changes() keeps the count of the last INSERT, UPDATE or DELETE, even
+        // across DDL, so it is only reported for those statements.
+        let keyword = sql::statement_keyword(query);
+        let is_dml = matches!(
+            keyword.as_deref(),
+            Some("INSERT" | "REPLACE" | "UPDATE" | "DELETE")
+        );
+        let rows_affected = is_dml.then(|| conn.changes());
+        let last_insert_id = matches!(keyword.as_deref(), Some("INSERT" | "REPLACE"))
+            .then(|| conn.last_insert_rowid());
+        let count = rows_affected.unwrap_or(result_rows.len() as u64);
+
+        Ok(QueryResult {
+            columns,
+            column_metadata,
+            row_count: result_rows.len(),
+            rows: result_rows,
+            rows_affected,
+            last_insert_id,
+            command_tag: sql::command_tag(query, count),
+        })
+    }
+
+    /// Runs on a blocking thread: rusqlite rows cannot be held across an await.
+    fn stream_rows(
+        conn: &Connection,
+        query: &str,
+        batch_size: usize,
+        tx: mpsc::Sender<QueryStreamEvent>,
+    ) -> Result<(), ProviderError> {
+        let runtime = tokio::runtime::Handle::current();
+        let mut stmt = conn.prepare(query).map_err(Self::format_error)?;
+
+        let column_count = stmt.column_count();
+        let columns: Vec<String> = stmt
+            .column_names()
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let mut batcher = RowBatcher::new(tx, batch_size);
+        runtime.block_on(batcher.columns(columns))?;
+
+        let mut rows = stmt.query([]).map_err(Self::format_error)?;
+        while let Some(row) = rows.next().map_err(Self::format_error)? {
+            let values = (0..column_count)
+                .map(|i| sqlite_value_to_json(row, i))
+                .collect();
+            runtime.block_on(batcher.push(values))?;
+        }
+
+        runtime.block_on(batcher.finish())
+    }
+}
+
+/// Interrupts the statement running on the provider's connection.
+struct SqliteCanceller(InterruptHandle);
+
+#[async_trait]
+impl QueryCanceller for SqliteCanceller {
+    async fn cancel(&self) -> Result<(), ProviderError> {
+        self.0.interrupt();
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -130,9 +463,12 @@ impl DatabaseProvider for SqliteProvider {
                 Ok(ColumnInfo {
                     name: row.get(0)?,
                     data_type: row.get(1)?,
+                    full_type: None,
+                    nested_paths: Vec::new(),
                     is_nullable: row.get::<_, i64>(2)? != 0,
                     is_primary_key: row.get::<_, i64>(3)? != 0,
                     has_default: row.get::<_, i64>(4)? != 0,
+                    default_value: row.get(5)?,
                 })
             })
             .map_err(Self::format_error)?
@@ -155,33 +491,67 @@ impl DatabaseProvider for SqliteProvider {
 
     async fn execute_query(&self, query: &str) -> Result<QueryResult, ProviderError> {
         let conn = self.conn.lock().await;
-        let mut stmt = conn.prepare(query).map_err(Self::format_error)?;
+        Self::run_query(&conn, query, &[])
+    }
 
-        let column_count = stmt.column_count();
-        let columns: Vec<String> = stmt
-            .column_names()
-            .into_iter()
-            .map(|s| s.to_string())
-            .collect();
+    async fn execute_query_with_params(
+        &self,
+        query: &str,
+        params: Vec<serde_json::Value>,
+    ) -> Result<QueryResult, ProviderError> {
+        let params: Vec<Value> = params.into_iter().map(json_to_sqlite_value).collect();
+        let conn = self.conn.lock().await;
+        Self::run_query(&conn, query, &params)
+    }
 
-        let mut result_rows: Vec<Vec<serde_json::Value>> = Vec::new();
+    async fn execute_query_cancellable(
+        &self,
+        query: &str,
+        cancel: &CancelToken,
+    ) -> Result<QueryResult, ProviderError> {
+        let conn = self.conn.lock().await;
+        if cancel.is_cancelled() {
+            return Err(cancel::cancelled_error());
+        }
 
-        let mut rows = stmt.query([]).map_err(Self::format_error)?;
+        cancel.set_canceller(SqliteCanceller(conn.get_interrupt_handle()));
+        let result = Self::run_query(&conn, query, &[]);
+        cancel.clear_canceller();
 
-        while let Some(row) = rows.next().map_err(Self::format_error)? {
-            let mut row_values: Vec<serde_json::Value> = Vec::new();
-            for i in 0..column_count {
-                let value = sqlite_value_to_json(row, i);
-                row_values.push(value);
-            }
-            result_rows.push(row_values);
+        if cancel.is_cancelled() {
+            return Err(cancel::cancelled_error());
         }
+        result
+    }
 
-        Ok(QueryResult {
-            columns,
-            row_count: result_rows.len(),
-            rows: result_rows,
+    async fn execute_query_stream(
+        &self,
+        query: &str,
+        batch_size: usize,
+        tx: mpsc::Sender<QueryStreamEvent>,
+        cancel: &CancelToken,
+    ) -> Result<(), ProviderError> {
+        let conn = self.conn.clone();
+        let query = query.to_string();
+        let cancel = cancel.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            if cancel.is_cancelled() {
+                return Err(cancel::cancelled_error());
+            }
+
+            cancel.set_canceller(SqliteCanceller(conn.get_interrupt_handle()));
+            let result = Self::stream_rows(&conn, &query, batch_size, tx);
+            cancel.clear_canceller();
+
+            if cancel.is_cancelled() {
+                return Err(cancel::cancelled_error());
+            }
+            result
         })
+        .await
+        .map_err(|e| ProviderError::new(format!("Query task failed: {}", e)))?
     }
 
     async fn get_table_count(&self, _schema: &str, table: &str) -> Result<i64, ProviderError> {
@@ -192,6 +562,360 @@ impl DatabaseProvider for SqliteProvider {
             .map_err(Self::format_error)?;
         Ok(count)
     }
+
+    async fn get_table_data_filtered(
+        &self,
+        _schema: &str,
+        table: &str,
+        query: &TableQuery,
+        limit: i64,
+        offset: i64,
+    ) -> Result<QueryResult, ProviderError> {
+        let mut params = Vec::new();
+        let statement = filter::select_sql(
+            DatabaseType::Sqlite,
+            &sql::quote_identifier(table, DatabaseType::Sqlite),
+            query,
+            limit,
+            offset,
+            &mut params,
+        )?;
+        self.execute_query_with_params(&statement, params).await
+    }
+
+    async fn get_table_count_filtered(
+        &self,
+        _schema: &str,
+        table: &str,
+        filters: &[ColumnFilter],
+    ) -> Result<i64, ProviderError> {
+        let mut params = Vec::new();
+        let statement = filter::count_sql(
+            DatabaseType::Sqlite,
+            &sql::quote_identifier(table, DatabaseType::Sqlite),
+            filters,
+            &mut params,
+        )?;
+        let result = self.execute_query_with_params(&statement, params).await?;
+        Ok(filter::count_from_result(&result))
+    }
+
+    async fn get_table_page(
+        &self,
+        schema: &str,
+        table: &str,
+        request: &PageRequest,
+    ) -> Result<TablePage, ProviderError> {
+        let columns = self.get_table_columns(schema, table).await?;
+        let keys = pagination::key_columns(request, schema, table, &columns)?;
+        let mut params = Vec::new();
+        let statement = pagination::page_sql(
+            DatabaseType::Sqlite,
+            &sql::quote_identifier(table, DatabaseType::Sqlite),
+            &keys,
+            request,
+            &mut params,
+        )?;
+        let result = self.execute_query_with_params(&statement, params).await?;
+        pagination::finish_page(result, &keys, request)
+    }
+
+    async fn list_schema_objects(&self) -> Result<SchemaObjects, ProviderError> {
+        let tables = self.list_tables().await?;
+
+        let conn = self.conn.lock().await;
+        let views = conn
+            .prepare(queries::LIST_VIEWS)
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| {
+                    Ok(ViewInfo {
+                        schema: "main".to_string(),
+                        name: row.get(0)?,
+                        materialized: false,
+                        definition: row.get(1)?,
+                    })
+                })?
+                .collect()
+            })
+            .map_err(Self::format_error)?;
+
+        Ok(SchemaObjects {
+            tables,
+            views,
+            ..Default::default()
+        })
+    }
+
+    async fn describe_table(
+        &self,
+        schema: &str,
+        table: &str,
+    ) -> Result<TableDetails, ProviderError> {
+        let mut details = TableDetails {
+            schema: schema.to_string(),
+            name: table.to_string(),
+            columns: self.get_table_columns(schema, table).await?,
+            ..Default::default()
+        };
+
+        let conn = self.conn.lock().await;
+        Self::read_table_details(&conn, &mut details).map_err(Self::format_error)?;
+        Ok(details)
+    }
+
+    async fn get_object_ddl(
+        &self,
+        schema: &str,
+        name: &str,
+        kind: ObjectKind,
+    ) -> Result<String, ProviderError> {
+        let object_type = match kind {
+            ObjectKind::Table => "table",
+            ObjectKind::View => "view",
+            ObjectKind::Index => "index",
+            ObjectKind::Trigger => "trigger",
+            _ => return Err(schema::ddl_unsupported(kind, DatabaseType::Sqlite)),
+        };
+
+        let conn = self.conn.lock().await;
+        let statements: Vec<String> = conn
+            .prepare(queries::OBJECT_DDL)
+            .and_then(|mut stmt| {
+                stmt.query_map([object_type, name], |row| row.get::<_, String>(0))?
+                    .map(|sql| sql.map(|sql| format!("{};", sql)))
+                    .collect()
+            })
+            .map_err(Self::format_error)?;
+
+        if statements.is_empty() {
+            return Err(schema::object_not_found(kind, schema, name));
+        }
+        Ok(statements.join("\n\n"))
+    }
+
+    /// SQLite only describes the plan. With `analyze` the statement is also
+    /// run inside a savepoint that is rolled back, giving the total time.
+    async fn explain_query(&self, query: &str, analyze: bool) -> Result<QueryPlan, ProviderError> {
+        let query = query.trim().trim_end_matches(';');
+        let conn = self.conn.lock().await;
+
+        let rows: Vec<(i64, i64, String)> = conn
+            .prepare(&format!("EXPLAIN QUERY PLAN {}", query))
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(3)?)))?
+                    .collect()
+            })
+            .map_err(Self::format_error)?;
+        let mut plan = explain::from_sqlite(rows);
+
+        if analyze {
+            conn.execute_batch("SAVEPOINT explain_analyze")
+                .map_err(Self::format_error)?;
+            let started = Instant::now();
+            let result = conn.prepare(query).and_then(|mut stmt| {
+                let mut rows = stmt.raw_query();
+                let mut count = 0;
+                while rows.next()?.is_some() {
+                    count += 1;
+                }
+                Ok(count)
+            });
+            let elapsed = started.elapsed();
+            let _ = conn.execute_batch("ROLLBACK TO explain_analyze; RELEASE explain_analyze");
+
+            plan.root.actual_rows = Some(result.map_err(Self::format_error)? as f64);
+            plan.execution_time_ms = Some(elapsed.as_secs_f64() * 1000.0);
+        }
+        Ok(plan)
+    }
+
+    async fn apply_row_edits(
+        &self,
+        schema: &str,
+        table: &str,
+        edits: Vec<RowEdit>,
+        allow_without_primary_key: bool,
+    ) -> Result<u64, ProviderError> {
+        let columns = self.get_table_columns(schema, table).await?;
+        let statements = edit::build_statements(
+            DatabaseType::Sqlite,
+            schema,
+            table,
+            &columns,
+            &edits,
+            allow_without_primary_key,
+        )?;
+
+        // A savepoint nests inside a transaction the user may have open.
+        let conn = self.conn.lock().await;
+        conn.execute_batch("SAVEPOINT row_edits")
+            .map_err(Self::format_error)?;
+
+        let mut rows_written = 0;
+        for (index, statement) in statements.iter().enumerate() {
+            let params = statement.params.iter().cloned().map(json_to_sqlite_value);
+            let result = conn
+                .execute(&statement.sql, params_from_iter(params))
+                .map_err(|e| edit::edit_failed(index, Self::format_error(e)))
+                .and_then(|count| {
+                    let count = count as u64;
+                    statement.check_rows_affected(index, count).map(|_| count)
+                });
+            match result {
+                Ok(count) => rows_written += count,
+                Err(e) => {
+                    let _ = conn.execute_batch("ROLLBACK TO row_edits; RELEASE row_edits");
+                    return Err(e);
+                }
+            }
+        }
+
+        conn.execute_batch("RELEASE row_edits")
+            .map_err(Self::format_error)?;
+        Ok(rows_written)
+    }
+
+    async fn import_rows(
+        &self,
+        _schema: &str,
+        table: &str,
+        columns: &[String],
+        mut batches: ImportBatches,
+        on_error: ImportErrorMode,
+    ) -> Result<ImportSummary, ProviderError> {
+        let target = sql::quote_identifier(table, DatabaseType::Sqlite);
+        let insert = import::insert_sql(DatabaseType::Sqlite, &target, columns, 1);
+
+        // The connection stays locked for the whole import so other queries
+        // cannot interleave with the savepoint.
+        let conn = self.conn.lock().await;
+        conn.execute_batch("SAVEPOINT import")
+            .map_err(Self::format_error)?;
+
+        let mut report = ImportReport::new(on_error);
+        let mut result = Ok(());
+        while let Some(batch) = batches.recv().await {
+            result = batch.and_then(|batch| Self::import_batch(&conn, &insert, batch, &mut report));
+            if result.is_err() {
+                break;
+            }
+        }
+        if let Err(e) = result {
+            let _ = conn.execute_batch("ROLLBACK TO import; RELEASE import");
+            return Err(e);
+        }
+
+        conn.execute_batch("RELEASE import")
+            .map_err(Self::format_error)?;
+        Ok(report.finish())
+    }
+
+    async fn begin_transaction(&self) -> Result<String, ProviderError> {
+        let conn = self.conn.lock().await;
+        if self.transaction.lock().unwrap().is_some() {
+            return Err(
+                ProviderError::new("A transaction is already open on this connection")
+                    .with_hint("SQLite supports one transaction per connection."),
+            );
+        }
+
+        conn.execute_batch("BEGIN").map_err(Self::format_error)?;
+
+        let id = uuid::Uuid::new_v4().to_string();
+        *self.transaction.lock().unwrap() = Some(id.clone());
+        Ok(id)
+    }
+
+    async fn execute_in_transaction(
+        &self,
+        transaction_id: &str,
+        query: &str,
+    ) -> Result<QueryResult, ProviderError> {
+        self.check_transaction(transaction_id)?;
+        self.execute_query(query).await
+    }
+
+    async fn commit_transaction(&self, transaction_id: &str) -> Result<(), ProviderError> {
+        self.finish_transaction(transaction_id, "COMMIT").await
+    }
+
+    async fn rollback_transaction(&self, transaction_id: &str) -> Result<(), ProviderError> {
+        self.finish_transaction(transaction_id, "ROLLBACK").await
+    }
+
+    fn open_transactions(&self) -> Vec<String> {
+        self.transaction.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// Timing and event of a `CREATE TRIGGER` statement, which SQLite only keeps
+/// as text. The timing defaults to BEFORE when the statement omits it.
+fn sqlite_trigger_timing_and_event(sql: &str) -> (String, Vec<String>) {
+    let mut timing = "BEFORE";
+    for word in sql.split_whitespace() {
+        let word = word.to_ascii_uppercase();
+        match word.as_str() {
+            "BEFORE" => timing = "BEFORE",
+            "AFTER" => timing = "AFTER",
+            "INSTEAD" => timing = "INSTEAD OF",
+            "INSERT" | "UPDATE" | "DELETE" => return (timing.to_string(), vec![word]),
+            _ => {}
+        }
+    }
+    (timing.to_string(), Vec::new())
+}
+
+fn json_to_sqlite_value(value: serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::Text(s),
+        other => Value::Text(other.to_string()),
+    }
+}
+
+/// Map a declared column type to a logical type. Common type names are checked
+/// first, then SQLite's own affinity rules; expressions have no declared type.
+fn sqlite_logical_type(decl_type: &str) -> LogicalType {
+    let decl_type = decl_type.to_ascii_uppercase();
+
+    if decl_type.is_empty() {
+        LogicalType::Other
+    } else if decl_type.starts_with("BOOL") {
+        LogicalType::Boolean
+    } else if decl_type.contains("TIMESTAMP") || decl_type.contains("DATETIME") {
+        LogicalType::Timestamp
+    } else if decl_type.starts_with("DATE") {
+        LogicalType::Date
+    } else if decl_type.starts_with("TIME") {
+        LogicalType::Time
+    } else if decl_type.starts_with("JSON") {
+        LogicalType::Json
+    } else if decl_type.starts_with("UUID") {
+        LogicalType::Uuid
+    } else if decl_type.contains("INT") {
+        LogicalType::Integer
+    } else if ["CHAR", "CLOB", "TEXT"]
+        .iter()
+        .any(|t| decl_type.contains(t))
+    {
+        LogicalType::Text
+    } else if decl_type.contains("BLOB") {
+        LogicalType::Binary
+    } else if ["REAL", "FLOA", "DOUB"]
+        .iter()
+        .any(|t| decl_type.contains(t))
+    {
+        LogicalType::Float
+    } else if decl_type.starts_with("NUMERIC") || decl_type.starts_with("DECIMAL") {
+        LogicalType::Decimal
+    } else {
+        LogicalType::Other
+    }
 }
 
 fn sqlite_value_to_json(row: &rusqlite::Row, idx: usize) -> serde_json::Value {
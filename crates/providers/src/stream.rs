@@ -0,0 +1,124 @@
+use crate::{ProviderError, QueryResult};
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+pub const DEFAULT_STREAM_BATCH_SIZE: usize = 500;
+
+/// Events emitted while streaming a result set: one `Columns`, any number of
+/// `Rows` batches, then `Done` (or `Error` if the query failed midway).
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum QueryStreamEvent {
+    Columns(Vec<String>),
+    Rows(Vec<Vec<serde_json::Value>>),
+    Done { row_count: usize },
+    Error(String),
+}
+
+/// Buffers rows and sends them downstream once a batch is full.
+pub(crate) struct RowBatcher {
+    tx: mpsc::Sender<QueryStreamEvent>,
+    batch_size: usize,
+    batch: Vec<Vec<serde_json::Value>>,
+    row_count: usize,
+}
+
+impl RowBatcher {
+    pub fn new(tx: mpsc::Sender<QueryStreamEvent>, batch_size: usize) -> Self {
+        let batch_size = batch_size.max(1);
+        Self {
+            tx,
+            batch_size,
+            batch: Vec::with_capacity(batch_size),
+            row_count: 0,
+        }
+    }
+
+    pub async fn columns(&self, columns: Vec<String>) -> Result<(), ProviderError> {
+        self.send(QueryStreamEvent::Columns(columns)).await
+    }
+
+    pub async fn push(&mut self, row: Vec<serde_json::Value>) -> Result<(), ProviderError> {
+        self.batch.push(row);
+        self.row_count += 1;
+
+        if self.batch.len() >= self.batch_size {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    pub async fn finish(mut self) -> Result<(), ProviderError> {
+        self.flush().await?;
+        self.send(QueryStreamEvent::Done {
+            row_count: self.row_count,
+        })
+        .await
+    }
+
+    async fn flush(&mut self) -> Result<(), ProviderError> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+
+        let rows = std::mem::replace(&mut self.batch, Vec::with_capacity(self.batch_size));
+        self.send(QueryStreamEvent::Rows(rows)).await
+    }
+
+    async fn send(&self, event: QueryStreamEvent) -> Result<(), ProviderError> {
+        self.tx
+            .send(event)
+            .await
+            .map_err(|_| ProviderError::new("Result stream was closed by the receiver"))
+    }
+}
+
+/// Stream an already materialized result; used by providers without a native
+/// row-by-row path.
+pub(crate) async fn stream_query_result(
+    result: QueryResult,
+    batch_size: usize,
+    tx: mpsc::Sender<QueryStreamEvent>,
+) -> Result<(), ProviderError> {
+    let mut batcher = RowBatcher::new(tx, batch_size);
+    batcher.columns(result.columns).await?;
+
+    for row in result.rows {
+        batcher.push(row).await?;
+    }
+
+    batcher.finish().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn batches_rows_and_reports_total() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let result = QueryResult {
+            columns: vec!["id".to_string()],
+            rows: (0..5).map(|i| vec![json!(i)]).collect(),
+            row_count: 5,
+            ..Default::default()
+        };
+
+        stream_query_result(result, 2, tx).await.unwrap();
+
+        let mut batch_sizes = Vec::new();
+        let mut done = None;
+        while let Some(event) = rx.recv().await {
+            match event {
+                QueryStreamEvent::Columns(columns) => assert_eq!(columns, vec!["id"]),
+                QueryStreamEvent::Rows(rows) => batch_sizes.push(rows.len()),
+                QueryStreamEvent::Done { row_count } => done = Some(row_count),
+                QueryStreamEvent::Error(e) => panic!("unexpected error: {}", e),
+            }
+        }
+
+        assert_eq!(batch_sizes, vec![2, 2, 1]);
+        assert_eq!(done, Some(5));
+    }
+}
@@ -0,0 +1,170 @@
+//! TLS settings shared by the network providers.
+
+use crate::ProviderError;
+use serde::{Deserialize, Serialize};
+
+/// How strictly a connection must use TLS, named after libpq's `sslmode`.
+///
+/// `prefer` and `require` encrypt without checking the server certificate
+/// unless a CA bundle is given, in which case the chain is verified. The
+/// `verify-*` modes always verify and never fall back to plaintext.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SslMode {
+    Disable,
+    #[default]
+    Prefer,
+    Require,
+    VerifyCa,
+    VerifyFull,
+}
+
+impl SslMode {
+    pub fn parse(value: &str) -> Result<Self, ProviderError> {
+        match value {
+            "disable" => Ok(SslMode::Disable),
+            "allow" | "prefer" => Ok(SslMode::Prefer),
+            "require" => Ok(SslMode::Require),
+            "verify-ca" => Ok(SslMode::VerifyCa),
+            "verify-full" => Ok(SslMode::VerifyFull),
+            _ => Err(ProviderError::new(format!("Invalid sslmode: {}", value))
+                .with_hint("Use disable, prefer, require, verify-ca or verify-full.")),
+        }
+    }
+
+    /// Whether the server certificate chain must be trusted.
+    pub fn verifies_certificate(self) -> bool {
+        matches!(self, SslMode::VerifyCa | SslMode::VerifyFull)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TlsConfig {
+    #[serde(default)]
+    pub mode: SslMode,
+    /// PEM bundle of CA certificates to trust instead of the system roots
+    pub ca_cert_path: Option<String>,
+    /// PEM client certificate, or a PKCS#12 archive when no key path is set
+    pub client_cert_path: Option<String>,
+    /// PEM (PKCS#8) private key for `client_cert_path`
+    pub client_key_path: Option<String>,
+    /// Name the server certificate is checked against when the connection
+    /// goes to another address, e.g. the local end of an SSH tunnel
+    #[serde(skip)]
+    pub server_name: Option<String>,
+}
+
+impl TlsConfig {
+    /// Whether the certificate chain is checked, either because the mode asks
+    /// for it or because a CA bundle was supplied.
+    pub fn verifies_certificate(&self) -> bool {
+        self.mode.verifies_certificate() || self.ca_cert_path.is_some()
+    }
+
+    pub fn verifies_hostname(&self) -> bool {
+        self.mode == SslMode::VerifyFull
+    }
+
+    /// Reject a verified connection that would be checked against the
+    /// address it dials rather than `server_name`, for drivers that take the
+    /// certificate name from the connection address.
+    pub(crate) fn require_address_as_server_name(&self, driver: &str) -> Result<(), ProviderError> {
+        if self.server_name.is_none()
+            || self.mode == SslMode::Disable
+            || !self.verifies_certificate()
+        {
+            return Ok(());
+        }
+        Err(ProviderError::new(format!(
+            "{} cannot verify the server certificate through an SSH tunnel",
+            driver
+        ))
+        .with_hint("The driver would check the certificate against the local tunnel address. Use require without a CA bundle, or connect without the tunnel."))
+    }
+
+    /// Build a native-tls connector honouring the mode, CA bundle and client
+    /// certificate.
+    pub fn native_tls_connector(&self) -> Result<native_tls::TlsConnector, ProviderError> {
+        let mut builder = native_tls::TlsConnector::builder();
+        builder
+            .danger_accept_invalid_certs(!self.verifies_certificate())
+            .danger_accept_invalid_hostnames(!self.verifies_hostname());
+
+        if let Some(path) = &self.ca_cert_path {
+            let bundle = read_file(path, "CA bundle")?;
+            let blocks = pem_certificates(&bundle);
+            if blocks.is_empty() {
+                return Err(tls_file_error(
+                    "CA bundle",
+                    path,
+                    "no PEM certificates found",
+                ));
+            }
+            for block in blocks {
+                let cert = native_tls::Certificate::from_pem(block.as_bytes())
+                    .map_err(|e| tls_file_error("CA bundle", path, e))?;
+                builder.add_root_certificate(cert);
+            }
+        }
+
+        if let Some(path) = &self.client_cert_path {
+            let cert = read_file(path, "client certificate")?;
+            let identity = match &self.client_key_path {
+                Some(key_path) => {
+                    native_tls::Identity::from_pkcs8(&cert, &read_file(key_path, "client key")?)
+                }
+                None => native_tls::Identity::from_pkcs12(&cert, ""),
+            }
+            .map_err(|e| tls_file_error("client certificate", path, e))?;
+            builder.identity(identity);
+        }
+
+        builder
+            .build()
+            .map_err(|e| ProviderError::new(format!("Failed to create TLS connector: {}", e)))
+    }
+}
+
+/// Split a PEM bundle into its individual `CERTIFICATE` blocks, since
+/// native-tls only parses one certificate at a time.
+fn pem_certificates(bundle: &[u8]) -> Vec<String> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+
+    let text = String::from_utf8_lossy(bundle);
+    let mut blocks = Vec::new();
+    let mut rest = text.as_ref();
+    while let Some(start) = rest.find(BEGIN) {
+        let Some(len) = rest[start..].find(END) else {
+            break;
+        };
+        let end = start + len + END.len();
+        blocks.push(format!("{}\n", &rest[start..end]));
+        rest = &rest[end..];
+    }
+    blocks
+}
+
+pub(crate) fn read_file(path: &str, what: &str) -> Result<Vec<u8>, ProviderError> {
+    std::fs::read(path).map_err(|e| tls_file_error(what, path, e))
+}
+
+fn tls_file_error(what: &str, path: &str, e: impl std::fmt::Display) -> ProviderError {
+    ProviderError::new(format!("Failed to load {} from {}", what, path)).with_detail(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_pem_bundle_into_certificates() {
+        let bundle = b"# root\n-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n\
+            junk\n-----BEGIN CERTIFICATE-----\nBBBB\n-----END CERTIFICATE-----\n";
+        let blocks = pem_certificates(bundle);
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks[0].contains("AAAA") && !blocks[0].contains("BBBB"));
+        assert!(blocks[1].starts_with("-----BEGIN CERTIFICATE-----\nBBBB"));
+        assert!(pem_certificates(b"not a certificate").is_empty());
+    }
+}
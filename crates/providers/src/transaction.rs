@@ -0,0 +1,62 @@
+use crate::ProviderError;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Mutex as AsyncMutex;
+
+/// Open transactions of a provider, keyed by transaction id. Each entry owns
+/// the session the transaction was started on so later statements reuse it.
+pub(crate) struct TransactionSessions<T> {
+    sessions: Mutex<HashMap<String, Arc<AsyncMutex<T>>>>,
+}
+
+impl<T> Default for TransactionSessions<T> {
+    fn default() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T> TransactionSessions<T> {
+    pub fn insert(&self, session: T) -> String {
+        let id = uuid::Uuid::new_v4().to_string();
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(id.clone(), Arc::new(AsyncMutex::new(session)));
+        id
+    }
+
+    pub fn get(&self, id: &str) -> Result<Arc<AsyncMutex<T>>, ProviderError> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| transaction_not_found(id))
+    }
+
+    /// Forget a transaction once it has ended on the server, or can no
+    /// longer end because its connection failed. A session that stays here
+    /// is still open and keeps a non-forced disconnect waiting.
+    pub fn remove(&self, id: &str) -> Result<Arc<AsyncMutex<T>>, ProviderError> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| transaction_not_found(id))
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        self.sessions.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+pub(crate) fn transaction_not_found(id: &str) -> ProviderError {
+    ProviderError::new(format!("Transaction {} not found", id))
+        .with_hint("It may already have been committed or rolled back.")
+}
+
+pub(crate) fn transactions_unsupported() -> ProviderError {
+    ProviderError::new("Transactions are not supported for this database type")
+}
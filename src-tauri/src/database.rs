@@ -2,12 +2,17 @@ use async_trait::async_trait;
 use parking_lot::RwLock;
 use querystudio_ai::DatabaseOperations;
 use querystudio_providers::{
-    create_provider, ColumnInfo, ConnectionParams, DatabaseProvider, DatabaseType, QueryResult,
-    TableInfo,
+    copy, create_provider, data_diff, export, import, schema_diff, CancelToken, CollectionIndex,
+    CollectionStats, ColumnFilter, ColumnInfo, ConnectionParams, CopyEvent, CopyOptions,
+    DataDiffEvent, DataDiffOptions, DatabaseProvider, DatabaseType, DocumentSchema, ExportEvent,
+    ExportOptions, ExportSource, ImportEvent, ImportOptions, IndexSpec, ObjectKind, PageRequest,
+    QueryPlan, QueryResult, QueryStreamEvent, RowEdit, SchemaDiff, SchemaObjects, ScriptErrorMode,
+    SshTunnel, StatementResult, TableDetails, TableInfo, TablePage, TableQuery, TableRef,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::mpsc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionConfig {
@@ -17,14 +22,33 @@ pub struct ConnectionConfig {
     pub params: ConnectionParams,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct RunningQueryInfo {
+    pub query_id: String,
+    pub connection_id: String,
+    pub query: String,
+    /// Unix timestamp in milliseconds
+    pub started_at: u64,
+}
+
+struct RunningQuery {
+    info: RunningQueryInfo,
+    cancel: CancelToken,
+}
+
 pub struct ConnectionManager {
     connections: RwLock<HashMap<String, Arc<Box<dyn DatabaseProvider>>>>,
+    /// SSH tunnels of connections that use one; dropping a tunnel closes it
+    tunnels: RwLock<HashMap<String, SshTunnel>>,
+    running_queries: RwLock<HashMap<String, RunningQuery>>,
 }
 
 impl ConnectionManager {
     pub fn new() -> Self {
         Self {
             connections: RwLock::new(HashMap::new()),
+            tunnels: RwLock::new(HashMap::new()),
+            running_queries: RwLock::new(HashMap::new()),
         }
     }
 
@@ -52,19 +76,36 @@ impl ConnectionManager {
     }
 
     pub async fn connect(&self, id: String, config: ConnectionConfig) -> Result<(), String> {
-        let provider = create_provider(config.db_type, config.params)
+        let connection = create_provider(config.db_type, config.params)
             .await
             .map_err(|e| e.to_string())?;
 
-        self.connections.write().insert(id, Arc::new(provider));
+        match connection.tunnel {
+            Some(tunnel) => self.tunnels.write().insert(id.clone(), tunnel),
+            None => self.tunnels.write().remove(&id),
+        };
+        self.connections
+            .write()
+            .insert(id, Arc::new(connection.provider));
         Ok(())
     }
 
-    pub fn disconnect(&self, id: &str) -> Result<(), String> {
+    /// Drop a connection. Unless `force` is set, refuse while transactions
+    /// are still open so the caller can warn before they are rolled back.
+    pub fn disconnect(&self, id: &str, force: bool) -> Result<(), String> {
+        let open = self.get_provider(id)?.open_transactions().len();
+        if open > 0 && !force {
+            return Err(format!(
+                "Connection has {} uncommitted transaction(s). Commit or roll back first, or disconnect anyway to roll them back.",
+                open
+            ));
+        }
+
         self.connections
             .write()
             .remove(id)
             .ok_or_else(|| "Connection not found".to_string())?;
+        self.tunnels.write().remove(id);
         Ok(())
     }
 
@@ -94,42 +135,426 @@ impl ConnectionManager {
             .map_err(|e| e.to_string())
     }
 
+    pub async fn list_schema_objects(&self, connection_id: &str) -> Result<SchemaObjects, String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .list_schema_objects()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn describe_table(
+        &self,
+        connection_id: &str,
+        schema: &str,
+        table: &str,
+    ) -> Result<TableDetails, String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .describe_table(schema, table)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn get_object_ddl(
+        &self,
+        connection_id: &str,
+        schema: &str,
+        name: &str,
+        kind: ObjectKind,
+    ) -> Result<String, String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .get_object_ddl(schema, name, kind)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
     pub async fn get_table_data(
         &self,
         connection_id: &str,
         schema: &str,
         table: &str,
+        query: &TableQuery,
         limit: i64,
         offset: i64,
     ) -> Result<QueryResult, String> {
         let provider = self.get_provider(connection_id)?;
         provider
-            .get_table_data(schema, table, limit, offset)
+            .get_table_data_filtered(schema, table, query, limit, offset)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn get_table_page(
+        &self,
+        connection_id: &str,
+        schema: &str,
+        table: &str,
+        request: &PageRequest,
+    ) -> Result<TablePage, String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .get_table_page(schema, table, request)
             .await
             .map_err(|e| e.to_string())
     }
 
     pub async fn execute_query(
+        &self,
+        connection_id: &str,
+        query_id: Option<String>,
+        query: &str,
+    ) -> Result<QueryResult, String> {
+        let provider = self.get_provider(connection_id)?;
+        let query_id = query_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let cancel = self.register_query(&query_id, connection_id, query)?;
+
+        let result = provider
+            .execute_query_cancellable(query, &cancel)
+            .await
+            .map_err(|e| e.to_string());
+
+        self.running_queries.write().remove(&query_id);
+        result
+    }
+
+    pub async fn execute_query_with_params(
         &self,
         connection_id: &str,
         query: &str,
+        params: Vec<serde_json::Value>,
     ) -> Result<QueryResult, String> {
         let provider = self.get_provider(connection_id)?;
         provider
-            .execute_query(query)
+            .execute_query_with_params(query, params)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn explain_query(
+        &self,
+        connection_id: &str,
+        query: &str,
+        analyze: bool,
+    ) -> Result<QueryPlan, String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .explain_query(query, analyze)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn begin_transaction(&self, connection_id: &str) -> Result<String, String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .begin_transaction()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn execute_in_transaction(
+        &self,
+        connection_id: &str,
+        transaction_id: &str,
+        query: &str,
+    ) -> Result<QueryResult, String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .execute_in_transaction(transaction_id, query)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn commit_transaction(
+        &self,
+        connection_id: &str,
+        transaction_id: &str,
+    ) -> Result<(), String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .commit_transaction(transaction_id)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn rollback_transaction(
+        &self,
+        connection_id: &str,
+        transaction_id: &str,
+    ) -> Result<(), String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .rollback_transaction(transaction_id)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn open_transactions(&self, connection_id: &str) -> Result<Vec<String>, String> {
+        let provider = self.get_provider(connection_id)?;
+        Ok(provider.open_transactions())
+    }
+
+    pub async fn execute_script(
+        &self,
+        connection_id: &str,
+        query_id: Option<String>,
+        script: &str,
+        on_error: ScriptErrorMode,
+    ) -> Result<Vec<StatementResult>, String> {
+        let provider = self.get_provider(connection_id)?;
+        let query_id = query_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let cancel = self.register_query(&query_id, connection_id, script)?;
+
+        let result = provider
+            .execute_script(script, on_error, &cancel)
+            .await
+            .map_err(|e| e.to_string());
+
+        self.running_queries.write().remove(&query_id);
+        result
+    }
+
+    /// Start streaming a query in the background. The receiver yields the
+    /// provider's batches followed by `Done`, or `Error` if the query fails.
+    pub fn execute_query_stream(
+        self: Arc<Self>,
+        connection_id: &str,
+        query_id: String,
+        query: String,
+        batch_size: usize,
+    ) -> Result<mpsc::Receiver<QueryStreamEvent>, String> {
+        let provider = self.get_provider(connection_id)?;
+        let cancel = self.register_query(&query_id, connection_id, &query)?;
+        let (tx, rx) = mpsc::channel(8);
+
+        tokio::spawn(async move {
+            if let Err(e) = provider
+                .execute_query_stream(&query, batch_size, tx.clone(), &cancel)
+                .await
+            {
+                let _ = tx.send(QueryStreamEvent::Error(e.to_string())).await;
+            }
+            self.running_queries.write().remove(&query_id);
+        });
+
+        Ok(rx)
+    }
+
+    /// Start exporting in the background. The receiver yields progress after
+    /// every batch followed by `Done`, or `Error` if the export fails. Exports
+    /// are listed and cancelled like queries, under `export_id`.
+    pub fn export(
+        self: Arc<Self>,
+        connection_id: &str,
+        export_id: String,
+        source: ExportSource,
+        options: ExportOptions,
+    ) -> Result<mpsc::Receiver<ExportEvent>, String> {
+        let provider = self.get_provider(connection_id)?;
+        let description = format!("Export to {}", options.path);
+        let cancel = self.register_query(&export_id, connection_id, &description)?;
+        let (tx, rx) = mpsc::channel(8);
+
+        tokio::spawn(async move {
+            let event = match export::export(&**provider, &source, &options, &cancel, &tx).await {
+                Ok(summary) => ExportEvent::Done(summary),
+                Err(e) => ExportEvent::Error(e.to_string()),
+            };
+            let _ = tx.send(event).await;
+            self.running_queries.write().remove(&export_id);
+        });
+
+        Ok(rx)
+    }
+
+    pub fn import(
+        self: Arc<Self>,
+        connection_id: &str,
+        import_id: String,
+        options: ImportOptions,
+    ) -> Result<mpsc::Receiver<ImportEvent>, String> {
+        let provider = self.get_provider(connection_id)?;
+        let description = format!("Import from {}", options.path);
+        let cancel = self.register_query(&import_id, connection_id, &description)?;
+        let (tx, rx) = mpsc::channel(8);
+
+        tokio::spawn(async move {
+            let event = match import::import(&**provider, &options, &cancel, &tx).await {
+                Ok(summary) => ImportEvent::Done(summary),
+                Err(e) => ImportEvent::Error(e.to_string()),
+            };
+            let _ = tx.send(event).await;
+            self.running_queries.write().remove(&import_id);
+        });
+
+        Ok(rx)
+    }
+
+    /// Copy a table into another (or the same) connection. The copy runs as
+    /// a query of the source connection, so `cancel_query(copy_id)` stops it.
+    pub fn copy_table(
+        self: Arc<Self>,
+        source_connection_id: &str,
+        copy_id: String,
+        from: TableRef,
+        target_connection_id: &str,
+        to: TableRef,
+        options: CopyOptions,
+    ) -> Result<mpsc::Receiver<CopyEvent>, String> {
+        let source = self.get_provider(source_connection_id)?;
+        let target = self.get_provider(target_connection_id)?;
+        let description = format!(
+            "Copy {}.{} to {}.{}",
+            from.schema, from.table, to.schema, to.table
+        );
+        let cancel = self.register_query(&copy_id, source_connection_id, &description)?;
+        let (tx, rx) = mpsc::channel(8);
+
+        tokio::spawn(async move {
+            let result =
+                copy::copy_table(&**source, &from, &**target, &to, &options, &cancel, &tx).await;
+            let event = match result {
+                Ok(summary) => CopyEvent::Done(summary),
+                Err(e) => CopyEvent::Error(e.to_string()),
+            };
+            let _ = tx.send(event).await;
+            self.running_queries.write().remove(&copy_id);
+        });
+
+        Ok(rx)
+    }
+
+    pub async fn diff_schemas(
+        &self,
+        source_connection_id: &str,
+        source_schema: &str,
+        target_connection_id: &str,
+        target_schema: &str,
+        script: bool,
+    ) -> Result<SchemaDiff, String> {
+        let source = self.get_provider(source_connection_id)?;
+        let target = self.get_provider(target_connection_id)?;
+        schema_diff::diff_schemas(&**source, source_schema, &**target, target_schema, script)
             .await
             .map_err(|e| e.to_string())
     }
 
+    pub fn diff_data(
+        self: Arc<Self>,
+        source_connection_id: &str,
+        diff_id: String,
+        from: TableRef,
+        target_connection_id: &str,
+        to: TableRef,
+        options: DataDiffOptions,
+    ) -> Result<mpsc::Receiver<DataDiffEvent>, String> {
+        let source = self.get_provider(source_connection_id)?;
+        let target = self.get_provider(target_connection_id)?;
+        let description = format!(
+            "Compare {}.{} with {}.{}",
+            from.schema, from.table, to.schema, to.table
+        );
+        let cancel = self.register_query(&diff_id, source_connection_id, &description)?;
+        let (tx, rx) = mpsc::channel(8);
+
+        tokio::spawn(async move {
+            let result =
+                data_diff::diff_data(&**source, &from, &**target, &to, &options, &cancel, &tx)
+                    .await;
+            let event = match result {
+                Ok(summary) => DataDiffEvent::Done(summary),
+                Err(e) => DataDiffEvent::Error(e.to_string()),
+            };
+            let _ = tx.send(event).await;
+            self.running_queries.write().remove(&diff_id);
+        });
+
+        Ok(rx)
+    }
+
+    fn register_query(
+        &self,
+        query_id: &str,
+        connection_id: &str,
+        query: &str,
+    ) -> Result<CancelToken, String> {
+        let mut running = self.running_queries.write();
+        if running.contains_key(query_id) {
+            return Err(format!("Query {} is already running", query_id));
+        }
+
+        let started_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        let cancel = CancelToken::new();
+        running.insert(
+            query_id.to_string(),
+            RunningQuery {
+                info: RunningQueryInfo {
+                    query_id: query_id.to_string(),
+                    connection_id: connection_id.to_string(),
+                    query: query.to_string(),
+                    started_at,
+                },
+                cancel: cancel.clone(),
+            },
+        );
+
+        Ok(cancel)
+    }
+
+    pub async fn cancel_query(&self, query_id: &str) -> Result<(), String> {
+        let cancel = self
+            .running_queries
+            .read()
+            .get(query_id)
+            .map(|q| q.cancel.clone())
+            .ok_or_else(|| "Query not found".to_string())?;
+
+        cancel.cancel().await.map_err(|e| e.to_string())
+    }
+
+    pub fn list_running_queries(&self, connection_id: &str) -> Vec<RunningQueryInfo> {
+        let mut queries: Vec<RunningQueryInfo> = self
+            .running_queries
+            .read()
+            .values()
+            .filter(|q| q.info.connection_id == connection_id)
+            .map(|q| q.info.clone())
+            .collect();
+
+        queries.sort_by_key(|q| q.started_at);
+        queries
+    }
+
     pub async fn get_table_count(
         &self,
         connection_id: &str,
         schema: &str,
         table: &str,
+        filters: &[ColumnFilter],
     ) -> Result<i64, String> {
         let provider = self.get_provider(connection_id)?;
         provider
-            .get_table_count(schema, table)
+            .get_table_count_filtered(schema, table, filters)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn apply_row_edits(
+        &self,
+        connection_id: &str,
+        schema: &str,
+        table: &str,
+        edits: Vec<RowEdit>,
+        allow_without_primary_key: bool,
+    ) -> Result<u64, String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .apply_row_edits(schema, table, edits, allow_without_primary_key)
             .await
             .map_err(|e| e.to_string())
     }
@@ -174,6 +599,74 @@ impl ConnectionManager {
             .map_err(|e| e.to_string())
     }
 
+    pub async fn infer_document_schema(
+        &self,
+        connection_id: &str,
+        schema: &str,
+        collection: &str,
+        sample_size: Option<usize>,
+    ) -> Result<DocumentSchema, String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .infer_document_schema(schema, collection, sample_size)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn list_collection_indexes(
+        &self,
+        connection_id: &str,
+        schema: &str,
+        collection: &str,
+    ) -> Result<Vec<CollectionIndex>, String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .list_collection_indexes(schema, collection)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn create_collection_index(
+        &self,
+        connection_id: &str,
+        schema: &str,
+        collection: &str,
+        spec: &IndexSpec,
+    ) -> Result<String, String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .create_collection_index(schema, collection, spec)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn drop_collection_index(
+        &self,
+        connection_id: &str,
+        schema: &str,
+        collection: &str,
+        name: &str,
+    ) -> Result<(), String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .drop_collection_index(schema, collection, name)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn collection_stats(
+        &self,
+        connection_id: &str,
+        schema: &str,
+        collection: &str,
+    ) -> Result<CollectionStats, String> {
+        let provider = self.get_provider(connection_id)?;
+        provider
+            .collection_stats(schema, collection)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
     pub async fn create_redis_key(
         &self,
         connection_id: &str,
@@ -225,12 +718,17 @@ impl DatabaseOperations for ConnectionManager {
             .map_err(|e| e.to_string())
     }
 
+    async fn describe_table(
+        &self,
+        connection_id: &str,
+        schema: &str,
+        table: &str,
+    ) -> Result<TableDetails, String> {
+        ConnectionManager::describe_table(self, connection_id, schema, table).await
+    }
+
     async fn execute_query(&self, connection_id: &str, query: &str) -> Result<QueryResult, String> {
-        let provider = self.get_provider(connection_id)?;
-        provider
-            .execute_query(query)
-            .await
-            .map_err(|e| e.to_string())
+        ConnectionManager::execute_query(self, connection_id, None, query).await
     }
 
     async fn get_table_data(
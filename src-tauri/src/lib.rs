@@ -15,7 +15,7 @@ use ai_commands::{
     ai_fetch_openrouter_models, ai_fetch_vercel_models, ai_get_models, ai_validate_key,
 };
 use chat_storage::{get_chat_history, set_chat_history};
-use database::{test_connection, ConnectionConfig, ConnectionManager};
+use database::{test_connection, ConnectionConfig, ConnectionManager, RunningQueryInfo};
 use debug::{get_process_stats, DebugState};
 use fonts::{list_local_fonts, refresh_local_fonts_cache, warm_font_cache};
 use keychain::{
@@ -23,13 +23,19 @@ use keychain::{
     keychain_set_connection_secret,
 };
 use log::{debug, error, info, warn};
-use querystudio_providers::{ColumnInfo, QueryResult, TableInfo};
+use querystudio_providers::{
+    import, CollectionIndex, CollectionStats, ColumnFilter, ColumnInfo, CopyEvent, CopyOptions,
+    DataDiffEvent, DataDiffOptions, DocumentSchema, ExportEvent, ExportOptions, ExportSource,
+    ImportEvent, ImportOptions, ImportPreview, IndexSpec, ObjectKind, PageRequest, QueryPlan,
+    QueryResult, RowEdit, SchemaDiff, SchemaObjects, ScriptErrorMode, StatementResult,
+    TableDetails, TableInfo, TablePage, TableQuery, TableRef, DEFAULT_STREAM_BATCH_SIZE,
+};
 use settings::{get_settings, load_settings, patch_settings, reset_settings, set_settings};
 use std::sync::Arc;
 use storage::CONNECTIONS_DB;
 use tauri::{
     menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
-    Emitter, Manager, State,
+    AppHandle, Emitter, Manager, State,
 };
 use tauri_plugin_sql::{Migration, MigrationKind};
 use terminal::{
@@ -102,15 +108,20 @@ async fn connect(
 }
 
 #[tauri::command]
-async fn disconnect(state: State<'_, DbState>, id: String) -> Result<(), String> {
+async fn disconnect(
+    state: State<'_, DbState>,
+    id: String,
+    force: Option<bool>,
+) -> Result<(), String> {
     info!("Disconnecting [id={}]", id);
-    let result = state.disconnect(&id);
-    if result.is_ok() {
-        info!(
+    let result = state.disconnect(&id, force.unwrap_or(false));
+    match &result {
+        Ok(_) => info!(
             "Disconnected [id={}] (active={})",
             id,
             state.connection_count()
-        );
+        ),
+        Err(e) => warn!("Disconnect refused [id={}]: {}", id, e),
     }
     result
 }
@@ -146,6 +157,37 @@ async fn get_table_columns(
         .await
 }
 
+#[tauri::command]
+async fn list_schema_objects(
+    state: State<'_, DbState>,
+    connection_id: String,
+) -> Result<SchemaObjects, String> {
+    state.list_schema_objects(&connection_id).await
+}
+
+#[tauri::command]
+async fn describe_table(
+    state: State<'_, DbState>,
+    connection_id: String,
+    schema: String,
+    table: String,
+) -> Result<TableDetails, String> {
+    state.describe_table(&connection_id, &schema, &table).await
+}
+
+#[tauri::command]
+async fn get_object_ddl(
+    state: State<'_, DbState>,
+    connection_id: String,
+    schema: String,
+    name: String,
+    kind: ObjectKind,
+) -> Result<String, String> {
+    state
+        .get_object_ddl(&connection_id, &schema, &name, kind)
+        .await
+}
+
 #[tauri::command]
 async fn get_table_data(
     state: State<'_, DbState>,
@@ -154,9 +196,30 @@ async fn get_table_data(
     table: String,
     limit: i64,
     offset: i64,
+    query: Option<TableQuery>,
 ) -> Result<QueryResult, String> {
     state
-        .get_table_data(&connection_id, &schema, &table, limit, offset)
+        .get_table_data(
+            &connection_id,
+            &schema,
+            &table,
+            &query.unwrap_or_default(),
+            limit,
+            offset,
+        )
+        .await
+}
+
+#[tauri::command]
+async fn get_table_page(
+    state: State<'_, DbState>,
+    connection_id: String,
+    schema: String,
+    table: String,
+    request: PageRequest,
+) -> Result<TablePage, String> {
+    state
+        .get_table_page(&connection_id, &schema, &table, &request)
         .await
 }
 
@@ -165,6 +228,7 @@ async fn execute_query(
     state: State<'_, DbState>,
     connection_id: String,
     query: String,
+    query_id: Option<String>,
 ) -> Result<QueryResult, String> {
     let start = std::time::Instant::now();
     debug!(
@@ -172,7 +236,7 @@ async fn execute_query(
         connection_id,
         query.chars().take(200).collect::<String>()
     );
-    let result = state.execute_query(&connection_id, &query).await;
+    let result = state.execute_query(&connection_id, query_id, &query).await;
     let elapsed = start.elapsed();
     match &result {
         Ok(r) => info!(
@@ -186,14 +250,473 @@ async fn execute_query(
     result
 }
 
+#[tauri::command]
+async fn execute_query_with_params(
+    state: State<'_, DbState>,
+    connection_id: String,
+    query: String,
+    params: Vec<serde_json::Value>,
+) -> Result<QueryResult, String> {
+    debug!(
+        "Executing parameterized query [conn={}] ({} params): {}",
+        connection_id,
+        params.len(),
+        query.chars().take(200).collect::<String>()
+    );
+    let result = state
+        .execute_query_with_params(&connection_id, &query, params)
+        .await;
+    if let Err(e) = &result {
+        error!("Query failed [conn={}]: {}", connection_id, e);
+    }
+    result
+}
+
+#[tauri::command]
+async fn explain_query(
+    state: State<'_, DbState>,
+    connection_id: String,
+    query: String,
+    analyze: bool,
+) -> Result<QueryPlan, String> {
+    debug!(
+        "Explaining query [conn={}] (analyze={}): {}",
+        connection_id,
+        analyze,
+        query.chars().take(200).collect::<String>()
+    );
+    state.explain_query(&connection_id, &query, analyze).await
+}
+
+#[tauri::command]
+async fn begin_transaction(
+    state: State<'_, DbState>,
+    connection_id: String,
+) -> Result<String, String> {
+    let result = state.begin_transaction(&connection_id).await;
+    if let Ok(transaction_id) = &result {
+        info!(
+            "Transaction started [conn={} tx={}]",
+            connection_id, transaction_id
+        );
+    }
+    result
+}
+
+#[tauri::command]
+async fn execute_in_transaction(
+    state: State<'_, DbState>,
+    connection_id: String,
+    transaction_id: String,
+    query: String,
+) -> Result<QueryResult, String> {
+    debug!(
+        "Executing query [conn={} tx={}]: {}",
+        connection_id,
+        transaction_id,
+        query.chars().take(200).collect::<String>()
+    );
+    state
+        .execute_in_transaction(&connection_id, &transaction_id, &query)
+        .await
+}
+
+#[tauri::command]
+async fn commit_transaction(
+    state: State<'_, DbState>,
+    connection_id: String,
+    transaction_id: String,
+) -> Result<(), String> {
+    info!(
+        "Committing transaction [conn={} tx={}]",
+        connection_id, transaction_id
+    );
+    state
+        .commit_transaction(&connection_id, &transaction_id)
+        .await
+}
+
+#[tauri::command]
+async fn rollback_transaction(
+    state: State<'_, DbState>,
+    connection_id: String,
+    transaction_id: String,
+) -> Result<(), String> {
+    info!(
+        "Rolling back transaction [conn={} tx={}]",
+        connection_id, transaction_id
+    );
+    state
+        .rollback_transaction(&connection_id, &transaction_id)
+        .await
+}
+
+#[tauri::command]
+fn list_open_transactions(
+    state: State<'_, DbState>,
+    connection_id: String,
+) -> Result<Vec<String>, String> {
+    state.open_transactions(&connection_id)
+}
+
+#[tauri::command]
+async fn execute_script(
+    state: State<'_, DbState>,
+    connection_id: String,
+    script: String,
+    query_id: Option<String>,
+    on_error: Option<ScriptErrorMode>,
+) -> Result<Vec<StatementResult>, String> {
+    let start = std::time::Instant::now();
+    let result = state
+        .execute_script(
+            &connection_id,
+            query_id,
+            &script,
+            on_error.unwrap_or_default(),
+        )
+        .await;
+    match &result {
+        Ok(results) => info!(
+            "Script done [conn={}]: {} statements, {} failed in {:.1}ms",
+            connection_id,
+            results.len(),
+            results.iter().filter(|r| r.error.is_some()).count(),
+            start.elapsed().as_secs_f64() * 1000.0
+        ),
+        Err(e) => error!("Script failed [conn={}]: {}", connection_id, e),
+    }
+    result
+}
+
+#[tauri::command]
+async fn execute_query_stream(
+    app_handle: AppHandle,
+    state: State<'_, DbState>,
+    connection_id: String,
+    query: String,
+    query_id: String,
+    batch_size: Option<usize>,
+) -> Result<(), String> {
+    debug!(
+        "Streaming query [conn={} id={}]: {}",
+        connection_id,
+        query_id,
+        query.chars().take(200).collect::<String>()
+    );
+
+    let mut rx = state.inner().clone().execute_query_stream(
+        &connection_id,
+        query_id.clone(),
+        query,
+        batch_size.unwrap_or(DEFAULT_STREAM_BATCH_SIZE),
+    )?;
+
+    let event_name = format!("query-stream-{}", query_id);
+    let start = std::time::Instant::now();
+
+    tokio::spawn(async move {
+        let mut batch_count = 0;
+        while let Some(event) = rx.recv().await {
+            batch_count += 1;
+            if let Err(e) = app_handle.emit(&event_name, &event) {
+                error!("Query stream emit failed [id={}]: {}", query_id, e);
+            }
+        }
+        info!(
+            "Query stream done [conn={} id={}]: {} events in {:.1}ms",
+            connection_id,
+            query_id,
+            batch_count,
+            start.elapsed().as_secs_f64() * 1000.0
+        );
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn export_data(
+    app_handle: AppHandle,
+    state: State<'_, DbState>,
+    connection_id: String,
+    export_id: String,
+    source: ExportSource,
+    options: ExportOptions,
+) -> Result<(), String> {
+    info!(
+        "Exporting [conn={} id={}] to {}",
+        connection_id, export_id, options.path
+    );
+
+    let mut rx =
+        state
+            .inner()
+            .clone()
+            .export(&connection_id, export_id.clone(), source, options)?;
+
+    let event_name = format!("export-progress-{}", export_id);
+    let start = std::time::Instant::now();
+
+    tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match &event {
+                ExportEvent::Done(summary) => info!(
+                    "Export done [id={}]: {} rows, {} bytes in {:.1}ms",
+                    export_id,
+                    summary.rows,
+                    summary.bytes,
+                    start.elapsed().as_secs_f64() * 1000.0
+                ),
+                ExportEvent::Error(e) => error!("Export failed [id={}]: {}", export_id, e),
+                ExportEvent::Progress { .. } => {}
+            }
+            if let Err(e) = app_handle.emit(&event_name, &event) {
+                error!("Export progress emit failed [id={}]: {}", export_id, e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn preview_import(options: ImportOptions) -> Result<ImportPreview, String> {
+    tokio::task::spawn_blocking(move || import::preview(&options))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn import_data(
+    app_handle: AppHandle,
+    state: State<'_, DbState>,
+    connection_id: String,
+    import_id: String,
+    options: ImportOptions,
+) -> Result<(), String> {
+    info!(
+        "Importing [conn={} id={}] from {}",
+        connection_id, import_id, options.path
+    );
+
+    let mut rx = state
+        .inner()
+        .clone()
+        .import(&connection_id, import_id.clone(), options)?;
+
+    let event_name = format!("import-progress-{}", import_id);
+    let start = std::time::Instant::now();
+
+    tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match &event {
+                ImportEvent::Done(summary) => info!(
+                    "Import done [id={}]: {} rows imported, {} skipped in {:.1}ms",
+                    import_id,
+                    summary.rows_imported,
+                    summary.rows_skipped,
+                    start.elapsed().as_secs_f64() * 1000.0
+                ),
+                ImportEvent::Error(e) => error!("Import failed [id={}]: {}", import_id, e),
+                ImportEvent::Progress { .. } => {}
+            }
+            if let Err(e) = app_handle.emit(&event_name, &event) {
+                error!("Import progress emit failed [id={}]: {}", import_id, e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn copy_table(
+    app_handle: AppHandle,
+    state: State<'_, DbState>,
+    source_connection_id: String,
+    source: TableRef,
+    target_connection_id: String,
+    target: TableRef,
+    copy_id: String,
+    options: CopyOptions,
+) -> Result<(), String> {
+    info!(
+        "Copying [id={}] {}.{} [conn={}] to {}.{} [conn={}]",
+        copy_id,
+        source.schema,
+        source.table,
+        source_connection_id,
+        target.schema,
+        target.table,
+        target_connection_id
+    );
+
+    let mut rx = state.inner().clone().copy_table(
+        &source_connection_id,
+        copy_id.clone(),
+        source,
+        &target_connection_id,
+        target,
+        options,
+    )?;
+
+    let event_name = format!("copy-progress-{}", copy_id);
+    let start = std::time::Instant::now();
+
+    tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match &event {
+                CopyEvent::Done(summary) => info!(
+                    "Copy done [id={}]: {} rows copied, {} skipped in {:.1}ms",
+                    copy_id,
+                    summary.rows_copied,
+                    summary.rows_skipped,
+                    start.elapsed().as_secs_f64() * 1000.0
+                ),
+                CopyEvent::Error(e) => error!("Copy failed [id={}]: {}", copy_id, e),
+                CopyEvent::Progress { .. } => {}
+            }
+            if let Err(e) = app_handle.emit(&event_name, &event) {
+                error!("Copy progress emit failed [id={}]: {}", copy_id, e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn diff_schemas(
+    state: State<'_, DbState>,
+    source_connection_id: String,
+    source_schema: String,
+    target_connection_id: String,
+    target_schema: String,
+    script: bool,
+) -> Result<SchemaDiff, String> {
+    info!(
+        "Diffing schema {} [conn={}] against {} [conn={}]",
+        source_schema, source_connection_id, target_schema, target_connection_id
+    );
+    state
+        .diff_schemas(
+            &source_connection_id,
+            &source_schema,
+            &target_connection_id,
+            &target_schema,
+            script,
+        )
+        .await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn diff_data(
+    app_handle: AppHandle,
+    state: State<'_, DbState>,
+    source_connection_id: String,
+    source: TableRef,
+    target_connection_id: String,
+    target: TableRef,
+    diff_id: String,
+    options: DataDiffOptions,
+) -> Result<(), String> {
+    info!(
+        "Comparing data [id={}] {}.{} [conn={}] with {}.{} [conn={}]",
+        diff_id,
+        source.schema,
+        source.table,
+        source_connection_id,
+        target.schema,
+        target.table,
+        target_connection_id
+    );
+
+    let mut rx = state.inner().clone().diff_data(
+        &source_connection_id,
+        diff_id.clone(),
+        source,
+        &target_connection_id,
+        target,
+        options,
+    )?;
+
+    let event_name = format!("data-diff-progress-{}", diff_id);
+    let start = std::time::Instant::now();
+
+    tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match &event {
+                DataDiffEvent::Done(summary) => info!(
+                    "Data diff done [id={}]: {} inserted, {} deleted, {} changed in {:.1}ms",
+                    diff_id,
+                    summary.inserted,
+                    summary.deleted,
+                    summary.changed,
+                    start.elapsed().as_secs_f64() * 1000.0
+                ),
+                DataDiffEvent::Error(e) => error!("Data diff failed [id={}]: {}", diff_id, e),
+                DataDiffEvent::Rows(_) | DataDiffEvent::Progress { .. } => {}
+            }
+            if let Err(e) = app_handle.emit(&event_name, &event) {
+                error!("Data diff emit failed [id={}]: {}", diff_id, e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn cancel_query(state: State<'_, DbState>, query_id: String) -> Result<(), String> {
+    info!("Cancelling query [id={}]", query_id);
+    state.cancel_query(&query_id).await
+}
+
+#[tauri::command]
+fn list_running_queries(state: State<'_, DbState>, connection_id: String) -> Vec<RunningQueryInfo> {
+    state.list_running_queries(&connection_id)
+}
+
 #[tauri::command]
 async fn get_table_count(
     state: State<'_, DbState>,
     connection_id: String,
     schema: String,
     table: String,
+    filters: Option<Vec<ColumnFilter>>,
 ) -> Result<i64, String> {
-    state.get_table_count(&connection_id, &schema, &table).await
+    state
+        .get_table_count(
+            &connection_id,
+            &schema,
+            &table,
+            &filters.unwrap_or_default(),
+        )
+        .await
+}
+
+#[tauri::command]
+async fn apply_row_edits(
+    state: State<'_, DbState>,
+    connection_id: String,
+    schema: String,
+    table: String,
+    edits: Vec<RowEdit>,
+    allow_without_primary_key: Option<bool>,
+) -> Result<u64, String> {
+    state
+        .apply_row_edits(
+            &connection_id,
+            &schema,
+            &table,
+            edits,
+            allow_without_primary_key.unwrap_or(false),
+        )
+        .await
 }
 
 #[tauri::command]
@@ -233,6 +756,69 @@ async fn delete_document(
         .await
 }
 
+#[tauri::command]
+async fn infer_document_schema(
+    state: State<'_, DbState>,
+    connection_id: String,
+    schema: String,
+    collection: String,
+    sample_size: Option<usize>,
+) -> Result<DocumentSchema, String> {
+    state
+        .infer_document_schema(&connection_id, &schema, &collection, sample_size)
+        .await
+}
+
+#[tauri::command]
+async fn list_collection_indexes(
+    state: State<'_, DbState>,
+    connection_id: String,
+    schema: String,
+    collection: String,
+) -> Result<Vec<CollectionIndex>, String> {
+    state
+        .list_collection_indexes(&connection_id, &schema, &collection)
+        .await
+}
+
+#[tauri::command]
+async fn create_collection_index(
+    state: State<'_, DbState>,
+    connection_id: String,
+    schema: String,
+    collection: String,
+    spec: IndexSpec,
+) -> Result<String, String> {
+    state
+        .create_collection_index(&connection_id, &schema, &collection, &spec)
+        .await
+}
+
+#[tauri::command]
+async fn drop_collection_index(
+    state: State<'_, DbState>,
+    connection_id: String,
+    schema: String,
+    collection: String,
+    name: String,
+) -> Result<(), String> {
+    state
+        .drop_collection_index(&connection_id, &schema, &collection, &name)
+        .await
+}
+
+#[tauri::command]
+async fn collection_stats(
+    state: State<'_, DbState>,
+    connection_id: String,
+    schema: String,
+    collection: String,
+) -> Result<CollectionStats, String> {
+    state
+        .collection_stats(&connection_id, &schema, &collection)
+        .await
+}
+
 #[tauri::command]
 async fn create_redis_key(
     state: State<'_, DbState>,
@@ -528,14 +1114,42 @@ pub fn run() {
             test_connection_handler,
             list_tables,
             get_table_columns,
+            list_schema_objects,
+            describe_table,
+            get_object_ddl,
             get_table_data,
+            get_table_page,
             execute_query,
+            execute_query_stream,
+            export_data,
+            preview_import,
+            import_data,
+            copy_table,
+            diff_schemas,
+            diff_data,
+            execute_query_with_params,
+            explain_query,
+            execute_script,
+            cancel_query,
+            list_running_queries,
+            // Transaction commands
+            begin_transaction,
+            execute_in_transaction,
+            commit_transaction,
+            rollback_transaction,
+            list_open_transactions,
             get_table_count,
             get_connection_count,
+            apply_row_edits,
             // MongoDB document commands
             insert_document,
             update_document,
             delete_document,
+            infer_document_schema,
+            list_collection_indexes,
+            create_collection_index,
+            drop_collection_index,
+            collection_stats,
             // Redis commands
             create_redis_key,
             // AI commands
@@ -41,6 +41,25 @@ const MAX_EDITOR_HEIGHT = 600;
 const DEFAULT_EDITOR_HEIGHT = 200;
 const EDITOR_HEIGHT_KEY = "querystudio_editor_height";
 const MONACO_THEME_NAME = "querystudio-active-theme";
+const MONGODB_METHODS = [
+  "find",
+  "findOne",
+  "aggregate",
+  "countDocuments",
+  "distinct",
+  "insertOne",
+  "insertMany",
+  "updateOne",
+  "updateMany",
+  "deleteOne",
+  "deleteMany",
+  "runCommand",
+  "getCollection",
+  "sort",
+  "limit",
+  "skip",
+  "projection",
+];
 
 import type { TabContentProps } from "@/lib/tab-sdk";
 
@@ -76,6 +95,7 @@ export const QueryEditor = memo(function QueryEditor({
   const tables = useConnectionStore((s) => s.tables);
   const isRedis = connection?.db_type === "redis";
   const isMongodb = connection?.db_type === "mongodb";
+  const editorLanguage = isRedis ? "plaintext" : isMongodb ? "javascript" : "sql";
   const activeTheme = useThemeStore(
     (state) => state.themes[state.activeTheme] ?? state.themes.dark,
   );
@@ -335,6 +355,31 @@ export const QueryEditor = memo(function QueryEditor({
         .map((s) => s.trim())
         .filter((s) => s.length > 0 && !s.startsWith("#") && !s.startsWith("//"));
     }
+    if (isMongodb) {
+      // For MongoDB, split at semicolons outside strings and brackets
+      const statements: string[] = [];
+      let depth = 0;
+      let quote: string | null = null;
+      let start = 0;
+      for (let i = 0; i < input.length; i++) {
+        const char = input[i];
+        if (quote) {
+          if (char === "\\") i++;
+          else if (char === quote) quote = null;
+        } else if (char === '"' || char === "'") {
+          quote = char;
+        } else if (char === "(" || char === "[" || char === "{") {
+          depth++;
+        } else if (char === ")" || char === "]" || char === "}") {
+          depth--;
+        } else if (char === ";" && depth === 0) {
+          statements.push(input.slice(start, i));
+          start = i + 1;
+        }
+      }
+      statements.push(input.slice(start));
+      return statements.map((s) => s.trim()).filter((s) => s.length > 0);
+    }
     return input
       .split(";")
       .map((s) => s.trim())
@@ -351,7 +396,13 @@ export const QueryEditor = memo(function QueryEditor({
     const statements = splitQueries(query);
 
     if (statements.length === 0) {
-      setError(isRedis ? "No valid Redis commands found" : "No valid SQL statements found");
+      setError(
+        isRedis
+          ? "No valid Redis commands found"
+          : isMongodb
+            ? "No valid MongoDB queries found"
+            : "No valid SQL statements found",
+      );
       return;
     }
 
@@ -457,9 +508,8 @@ export const QueryEditor = memo(function QueryEditor({
     }
 
     // Configure language completions based on database type
-    const language = isRedis ? "plaintext" : "sql";
-    completionProviderRef.current = monaco.languages.registerCompletionItemProvider(language, {
-      triggerCharacters: isRedis ? [" "] : [],
+    completionProviderRef.current = monaco.languages.registerCompletionItemProvider(editorLanguage, {
+      triggerCharacters: isRedis ? [" "] : isMongodb ? ["."] : [],
       provideCompletionItems: (model: editor.ITextModel, position: any) => {
         const wordUntilPosition = model.getWordUntilPosition(position);
         const range = {
@@ -469,84 +519,91 @@ export const QueryEditor = memo(function QueryEditor({
           endColumn: wordUntilPosition.endColumn,
         };
 
-        const keywords = isRedis
-          ? REDIS_COMMANDS.map((cmd) => ({
-              label: cmd.name,
-              kind: monaco.languages.CompletionItemKind.Function,
-              insertText: cmd.args ? `${cmd.name} ${cmd.args}` : cmd.name,
-              insertTextRules: monaco.languages.CompletionItemInsertTextRule.InsertAsSnippet,
-              detail: `${cmd.name} - ${cmd.complexity}`,
-              documentation: {
-                value: `**${cmd.name}**\n\n${cmd.summary}\n\n**Complexity:** ${cmd.complexity}\n\n**Arguments:** ${cmd.args || "None"}`,
-                isTrusted: true,
-              },
+        const keywords = isMongodb
+          ? MONGODB_METHODS.map((method) => ({
+              label: method,
+              kind: monaco.languages.CompletionItemKind.Method,
+              insertText: method,
               range,
-              sortText: cmd.name,
             }))
-          : [
-              "SELECT",
-              "FROM",
-              "WHERE",
-              "AND",
-              "OR",
-              "INSERT",
-              "INTO",
-              "VALUES",
-              "UPDATE",
-              "SET",
-              "DELETE",
-              "CREATE",
-              "TABLE",
-              "DROP",
-              "ALTER",
-              "JOIN",
-              "LEFT",
-              "RIGHT",
-              "INNER",
-              "OUTER",
-              "ON",
-              "GROUP",
-              "BY",
-              "ORDER",
-              "ASC",
-              "DESC",
-              "LIMIT",
-              "OFFSET",
-              "HAVING",
-              "DISTINCT",
-              "COUNT",
-              "SUM",
-              "AVG",
-              "MIN",
-              "MAX",
-              "AS",
-              "NULL",
-              "NOT",
-              "IN",
-              "LIKE",
-              "BETWEEN",
-              "EXISTS",
-              "CASE",
-              "WHEN",
-              "THEN",
-              "ELSE",
-              "END",
-              "PRIMARY",
-              "KEY",
-              "FOREIGN",
-              "REFERENCES",
-              "UNIQUE",
-              "INDEX",
-              "BEGIN",
-              "COMMIT",
-              "ROLLBACK",
-              "TRANSACTION",
-            ].map((keyword) => ({
-              label: keyword,
-              kind: monaco.languages.CompletionItemKind.Keyword,
-              insertText: keyword,
-              range,
-            }));
+          : isRedis
+            ? REDIS_COMMANDS.map((cmd) => ({
+                label: cmd.name,
+                kind: monaco.languages.CompletionItemKind.Function,
+                insertText: cmd.args ? `${cmd.name} ${cmd.args}` : cmd.name,
+                insertTextRules: monaco.languages.CompletionItemInsertTextRule.InsertAsSnippet,
+                detail: `${cmd.name} - ${cmd.complexity}`,
+                documentation: {
+                  value: `**${cmd.name}**\n\n${cmd.summary}\n\n**Complexity:** ${cmd.complexity}\n\n**Arguments:** ${cmd.args || "None"}`,
+                  isTrusted: true,
+                },
+                range,
+                sortText: cmd.name,
+              }))
+            : [
+                "SELECT",
+                "FROM",
+                "WHERE",
+                "AND",
+                "OR",
+                "INSERT",
+                "INTO",
+                "VALUES",
+                "UPDATE",
+                "SET",
+                "DELETE",
+                "CREATE",
+                "TABLE",
+                "DROP",
+                "ALTER",
+                "JOIN",
+                "LEFT",
+                "RIGHT",
+                "INNER",
+                "OUTER",
+                "ON",
+                "GROUP",
+                "BY",
+                "ORDER",
+                "ASC",
+                "DESC",
+                "LIMIT",
+                "OFFSET",
+                "HAVING",
+                "DISTINCT",
+                "COUNT",
+                "SUM",
+                "AVG",
+                "MIN",
+                "MAX",
+                "AS",
+                "NULL",
+                "NOT",
+                "IN",
+                "LIKE",
+                "BETWEEN",
+                "EXISTS",
+                "CASE",
+                "WHEN",
+                "THEN",
+                "ELSE",
+                "END",
+                "PRIMARY",
+                "KEY",
+                "FOREIGN",
+                "REFERENCES",
+                "UNIQUE",
+                "INDEX",
+                "BEGIN",
+                "COMMIT",
+                "ROLLBACK",
+                "TRANSACTION",
+              ].map((keyword) => ({
+                label: keyword,
+                kind: monaco.languages.CompletionItemKind.Keyword,
+                insertText: keyword,
+                range,
+              }));
 
         // Add table names from connection
         const tableItems = tables.map((table) => ({
@@ -626,17 +683,6 @@ export const QueryEditor = memo(function QueryEditor({
     );
   }
 
-  if (isMongodb) {
-    return (
-      <div className="flex h-full flex-col items-center justify-center text-muted-foreground">
-        <p className="text-lg font-medium">Query Editor Not Available</p>
-        <p className="mt-2 text-sm">
-          MongoDB uses its own query language. Use the Documents tab to browse collections.
-        </p>
-      </div>
-    );
-  }
-
   if (isRedis) {
     return <RedisConsole tabId={tabId} paneId={paneId} connectionId={connectionId} />;
   }
@@ -651,8 +697,8 @@ export const QueryEditor = memo(function QueryEditor({
         <div style={{ height: editorHeight }} className="w-full overflow-hidden rounded-none">
           <Editor
             height="100%"
-            defaultLanguage={isRedis ? "plaintext" : "sql"}
-            language={isRedis ? "plaintext" : "sql"}
+            defaultLanguage={editorLanguage}
+            language={editorLanguage}
             value={query}
             onChange={(value) => setQuery(value || "")}
             onMount={handleEditorMount}
@@ -90,9 +90,22 @@ type DensityMode = "compact" | "comfortable" | "spacious";
 
 interface TableViewerProps extends TabContentProps {}
 
+// MongoDB values arrive as Extended JSON; show the common scalar wrappers
+// the way mongosh prints them
+function formatExtendedJson(value: object): string | null {
+  const entries = Object.entries(value);
+  if (entries.length !== 1) return null;
+  const [key, inner] = entries[0];
+  if (key === "$oid" && typeof inner === "string") return `ObjectId("${inner}")`;
+  if (key === "$date" && typeof inner === "string") return `ISODate("${inner}")`;
+  if (key === "$numberDecimal" && typeof inner === "string") return `NumberDecimal("${inner}")`;
+  if (key === "$numberLong" && typeof inner === "string") return `NumberLong("${inner}")`;
+  return null;
+}
+
 function formatCellValue(value: unknown): string {
   if (value === null || value === undefined) return "NULL";
-  if (typeof value === "object") return JSON.stringify(value);
+  if (typeof value === "object") return formatExtendedJson(value) ?? JSON.stringify(value);
   return String(value);
 }
 
@@ -670,9 +683,13 @@ export const TableViewer = memo(function TableViewer({
       const nextDocument: Record<string, unknown> = {};
       allColumns.forEach((col) => {
         if (col.name === "_id") return;
+        // Objects include Extended JSON values such as {"$date": ...}, which
+        // must go back as JSON to keep their type
+        const original = selectedOriginalRecord[col.name];
+        const isObject = original !== null && typeof original === "object";
         nextDocument[col.name] = parseDocumentFieldValue(
           editedForm[col.name] ?? "",
-          col.data_type,
+          isObject ? "json" : col.data_type,
           rowNullFields.has(col.name),
         );
       });
@@ -5,7 +5,31 @@ import type {
   SavedConnection,
   TableInfo,
   ColumnInfo,
+  SchemaObjects,
+  TableDetails,
+  ObjectKind,
   QueryResult,
+  QueryPlan,
+  RowEdit,
+  TableQuery,
+  ColumnFilter,
+  PageRequest,
+  TablePage,
+  ExportSource,
+  ExportOptions,
+  ImportOptions,
+  ImportPreview,
+  TableRef,
+  CopyOptions,
+  SchemaDiff,
+  DataDiffOptions,
+  DocumentSchema,
+  CollectionIndex,
+  CollectionStats,
+  IndexSpec,
+  RunningQueryInfo,
+  ScriptErrorMode,
+  StatementResult,
   AIModelInfo,
   ChatRequest,
   ChatResponse,
@@ -17,7 +41,8 @@ export const api = {
   connect: (id: string, config: ConnectionConfig) =>
     invoke<void>("connect", { id, config }),
 
-  disconnect: (id: string) => invoke<void>("disconnect", { id }),
+  disconnect: (id: string, force?: boolean) =>
+    invoke<void>("disconnect", { id, force }),
 
   testConnection: (config: ConnectionConfig) =>
     invoke<void>("test_connection_handler", { config }),
@@ -28,12 +53,27 @@ export const api = {
   getTableColumns: (connectionId: string, schema: string, table: string) =>
     invoke<ColumnInfo[]>("get_table_columns", { connectionId, schema, table }),
 
+  listSchemaObjects: (connectionId: string) =>
+    invoke<SchemaObjects>("list_schema_objects", { connectionId }),
+
+  describeTable: (connectionId: string, schema: string, table: string) =>
+    invoke<TableDetails>("describe_table", { connectionId, schema, table }),
+
+  getObjectDdl: (
+    connectionId: string,
+    schema: string,
+    name: string,
+    kind: ObjectKind,
+  ) =>
+    invoke<string>("get_object_ddl", { connectionId, schema, name, kind }),
+
   getTableData: (
     connectionId: string,
     schema: string,
     table: string,
     limit: number,
     offset: number,
+    query?: TableQuery,
   ) =>
     invoke<QueryResult>("get_table_data", {
       connectionId,
@@ -41,13 +81,206 @@ export const api = {
       table,
       limit,
       offset,
+      query,
+    }),
+
+  // Keyset pagination; pass the previous page's next_cursor as `cursor`
+  getTablePage: (
+    connectionId: string,
+    schema: string,
+    table: string,
+    request: PageRequest,
+  ) =>
+    invoke<TablePage>("get_table_page", {
+      connectionId,
+      schema,
+      table,
+      request,
+    }),
+
+  executeQuery: (connectionId: string, query: string, queryId?: string) =>
+    invoke<QueryResult>("execute_query", { connectionId, query, queryId }),
+
+  executeQueryWithParams: (
+    connectionId: string,
+    query: string,
+    params: unknown[],
+  ) =>
+    invoke<QueryResult>("execute_query_with_params", {
+      connectionId,
+      query,
+      params,
+    }),
+
+  // With `analyze` the statement runs (and is rolled back) to get actual figures
+  explainQuery: (connectionId: string, query: string, analyze: boolean) =>
+    invoke<QueryPlan>("explain_query", { connectionId, query, analyze }),
+
+  // Streams batches as `query-stream-${queryId}` events (see QueryStreamEvent)
+  executeQueryStream: (
+    connectionId: string,
+    query: string,
+    queryId: string,
+    batchSize?: number,
+  ) =>
+    invoke<void>("execute_query_stream", {
+      connectionId,
+      query,
+      queryId,
+      batchSize,
+    }),
+
+  executeScript: (
+    connectionId: string,
+    script: string,
+    onError?: ScriptErrorMode,
+    queryId?: string,
+  ) =>
+    invoke<StatementResult[]>("execute_script", {
+      connectionId,
+      script,
+      onError,
+      queryId,
+    }),
+
+  // Reports `export-progress-${exportId}` events (see ExportEvent); cancel
+  // with cancelQuery(exportId)
+  exportData: (
+    connectionId: string,
+    exportId: string,
+    source: ExportSource,
+    options: ExportOptions,
+  ) =>
+    invoke<void>("export_data", {
+      connectionId,
+      exportId,
+      source,
+      options,
+    }),
+
+  previewImport: (options: ImportOptions) =>
+    invoke<ImportPreview>("preview_import", { options }),
+
+  // Reports `import-progress-${importId}` events (see ImportEvent); cancel
+  // with cancelQuery(importId)
+  importData: (
+    connectionId: string,
+    importId: string,
+    options: ImportOptions,
+  ) => invoke<void>("import_data", { connectionId, importId, options }),
+
+  // Reports `copy-progress-${copyId}` events (see CopyEvent); cancel with
+  // cancelQuery(copyId)
+  copyTable: (
+    sourceConnectionId: string,
+    source: TableRef,
+    targetConnectionId: string,
+    target: TableRef,
+    copyId: string,
+    options: CopyOptions,
+  ) =>
+    invoke<void>("copy_table", {
+      sourceConnectionId,
+      source,
+      targetConnectionId,
+      target,
+      copyId,
+      options,
+    }),
+
+  // Compares the tables of two schemas; with script, also returns the DDL
+  // that migrates the target
+  diffSchemas: (
+    sourceConnectionId: string,
+    sourceSchema: string,
+    targetConnectionId: string,
+    targetSchema: string,
+    script = false,
+  ) =>
+    invoke<SchemaDiff>("diff_schemas", {
+      sourceConnectionId,
+      sourceSchema,
+      targetConnectionId,
+      targetSchema,
+      script,
+    }),
+
+  // Reports `data-diff-progress-${diffId}` events (see DataDiffEvent);
+  // cancel with cancelQuery(diffId)
+  diffData: (
+    sourceConnectionId: string,
+    source: TableRef,
+    targetConnectionId: string,
+    target: TableRef,
+    diffId: string,
+    options: DataDiffOptions = {},
+  ) =>
+    invoke<void>("diff_data", {
+      sourceConnectionId,
+      source,
+      targetConnectionId,
+      target,
+      diffId,
+      options,
+    }),
+
+  cancelQuery: (queryId: string) => invoke<void>("cancel_query", { queryId }),
+
+  listRunningQueries: (connectionId: string) =>
+    invoke<RunningQueryInfo[]>("list_running_queries", { connectionId }),
+
+  // Transactions pinned to a dedicated session
+  beginTransaction: (connectionId: string) =>
+    invoke<string>("begin_transaction", { connectionId }),
+
+  executeInTransaction: (
+    connectionId: string,
+    transactionId: string,
+    query: string,
+  ) =>
+    invoke<QueryResult>("execute_in_transaction", {
+      connectionId,
+      transactionId,
+      query,
     }),
 
-  executeQuery: (connectionId: string, query: string) =>
-    invoke<QueryResult>("execute_query", { connectionId, query }),
+  commitTransaction: (connectionId: string, transactionId: string) =>
+    invoke<void>("commit_transaction", { connectionId, transactionId }),
 
-  getTableCount: (connectionId: string, schema: string, table: string) =>
-    invoke<number>("get_table_count", { connectionId, schema, table }),
+  rollbackTransaction: (connectionId: string, transactionId: string) =>
+    invoke<void>("rollback_transaction", { connectionId, transactionId }),
+
+  listOpenTransactions: (connectionId: string) =>
+    invoke<string[]>("list_open_transactions", { connectionId }),
+
+  getTableCount: (
+    connectionId: string,
+    schema: string,
+    table: string,
+    filters?: ColumnFilter[],
+  ) =>
+    invoke<number>("get_table_count", {
+      connectionId,
+      schema,
+      table,
+      filters,
+    }),
+
+  // Applied in one transaction; rows are matched by primary key
+  applyRowEdits: (
+    connectionId: string,
+    schema: string,
+    table: string,
+    edits: RowEdit[],
+    allowWithoutPrimaryKey?: boolean,
+  ) =>
+    invoke<number>("apply_row_edits", {
+      connectionId,
+      schema,
+      table,
+      edits,
+      allowWithoutPrimaryKey,
+    }),
 
   // MongoDB document operations
   insertDocument: (
@@ -73,6 +306,61 @@ export const api = {
   deleteDocument: (connectionId: string, collection: string, filter: string) =>
     invoke<number>("delete_document", { connectionId, collection, filter }),
 
+  /** Samples 1000 random documents unless `sampleSize` is given */
+  inferDocumentSchema: (
+    connectionId: string,
+    schema: string,
+    collection: string,
+    sampleSize?: number,
+  ) =>
+    invoke<DocumentSchema>("infer_document_schema", {
+      connectionId,
+      schema,
+      collection,
+      sampleSize,
+    }),
+
+  listCollectionIndexes: (connectionId: string, schema: string, collection: string) =>
+    invoke<CollectionIndex[]>("list_collection_indexes", {
+      connectionId,
+      schema,
+      collection,
+    }),
+
+  /** Resolves to the index name */
+  createCollectionIndex: (
+    connectionId: string,
+    schema: string,
+    collection: string,
+    spec: IndexSpec,
+  ) =>
+    invoke<string>("create_collection_index", {
+      connectionId,
+      schema,
+      collection,
+      spec,
+    }),
+
+  dropCollectionIndex: (
+    connectionId: string,
+    schema: string,
+    collection: string,
+    name: string,
+  ) =>
+    invoke<void>("drop_collection_index", {
+      connectionId,
+      schema,
+      collection,
+      name,
+    }),
+
+  collectionStats: (connectionId: string, schema: string, collection: string) =>
+    invoke<CollectionStats>("collection_stats", {
+      connectionId,
+      schema,
+      collection,
+    }),
+
   // Redis key operations
   createRedisKey: (
     connectionId: string,
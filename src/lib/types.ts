@@ -1,13 +1,43 @@
 export type DatabaseType = "postgres" | "mysql" | "sqlite" | "redis" | "mongodb";
 
+export type SslMode =
+  | "disable"
+  | "prefer"
+  | "require"
+  | "verify-ca"
+  | "verify-full";
+
+export interface TlsConfig {
+  mode?: SslMode;
+  ca_cert_path?: string | null;
+  client_cert_path?: string | null;
+  client_key_path?: string | null;
+}
+
+export interface SshTunnelConfig {
+  host: string;
+  port?: number;
+  username: string;
+  password?: string | null;
+  private_key_path?: string | null;
+  passphrase?: string | null;
+  known_hosts_path?: string | null;
+}
+
 export type ConnectionParams =
-  | { connection_string: string }
+  | {
+      connection_string: string;
+      tls?: TlsConfig;
+      ssh_tunnel?: SshTunnelConfig;
+    }
   | {
       host: string;
       port: number;
       database: string;
       username: string;
       password: string;
+      tls?: TlsConfig;
+      ssh_tunnel?: SshTunnelConfig;
     };
 
 export type ConnectionConfig = {
@@ -46,15 +76,505 @@ export interface TableInfo {
 export interface ColumnInfo {
   name: string;
   data_type: string;
+  /** Type with its length, precision or element type, when data_type lacks them */
+  full_type: string | null;
+  /** Dotted paths of fields nested in this column's documents (MongoDB) */
+  nested_paths: string[];
   is_nullable: boolean;
   is_primary_key: boolean;
   has_default: boolean;
+  /** Default expression as the database reports it */
+  default_value: string | null;
+}
+
+export interface ViewInfo {
+  schema: string;
+  name: string;
+  materialized: boolean;
+  definition: string | null;
+}
+
+export interface SequenceInfo {
+  schema: string;
+  name: string;
+  data_type: string | null;
+}
+
+export type RoutineKind = "function" | "procedure";
+
+export interface RoutineInfo {
+  schema: string;
+  name: string;
+  kind: RoutineKind;
+  arguments: string;
+  return_type: string | null;
+  language: string | null;
+}
+
+export interface SchemaObjects {
+  tables: TableInfo[];
+  views: ViewInfo[];
+  sequences: SequenceInfo[];
+  routines: RoutineInfo[];
+}
+
+export interface IndexInfo {
+  name: string;
+  columns: string[];
+  is_unique: boolean;
+  is_primary: boolean;
+  method: string | null;
+  predicate: string | null;
+}
+
+export interface ForeignKeyInfo {
+  name: string | null;
+  columns: string[];
+  referenced_schema: string;
+  referenced_table: string;
+  referenced_columns: string[];
+  on_update: string;
+  on_delete: string;
+}
+
+export type ConstraintKind = "primary_key" | "unique" | "check" | "exclusion";
+
+export interface ConstraintInfo {
+  name: string | null;
+  kind: ConstraintKind;
+  columns: string[];
+  definition: string | null;
+}
+
+export interface TriggerInfo {
+  name: string;
+  timing: string;
+  events: string[];
+  definition: string | null;
+}
+
+export interface TableDetails {
+  schema: string;
+  name: string;
+  columns: ColumnInfo[];
+  indexes: IndexInfo[];
+  foreign_keys: ForeignKeyInfo[];
+  constraints: ConstraintInfo[];
+  triggers: TriggerInfo[];
+}
+
+export type ObjectKind =
+  | "table"
+  | "view"
+  | "materialized_view"
+  | "sequence"
+  | "function"
+  | "procedure"
+  | "trigger"
+  | "index";
+
+export type LogicalType =
+  | "boolean"
+  | "integer"
+  | "float"
+  | "decimal"
+  | "text"
+  | "uuid"
+  | "json"
+  | "date"
+  | "time"
+  | "timestamp"
+  | "interval"
+  | "binary"
+  | "array"
+  | "other";
+
+export interface ResultColumn {
+  name: string;
+  native_type: string;
+  logical_type: LogicalType;
+  nullable: boolean | null;
+  source_schema: string | null;
+  source_table: string | null;
+  source_column: string | null;
 }
 
 export interface QueryResult {
   columns: string[];
+  column_metadata: ResultColumn[];
   rows: unknown[][];
   row_count: number;
+  rows_affected: number | null;
+  last_insert_id: number | null;
+  command_tag: string | null;
+}
+
+export type FilterOperator =
+  | "eq"
+  | "ne"
+  | "lt"
+  | "lte"
+  | "gt"
+  | "gte"
+  | "contains"
+  | "not_contains"
+  | "starts_with"
+  | "ends_with"
+  | "in"
+  | "not_in"
+  | "is_null"
+  | "is_not_null";
+
+/** `value` is an array for `in` / `not_in` and unused for null checks */
+export interface ColumnFilter {
+  column: string;
+  operator: FilterOperator;
+  value?: unknown;
+}
+
+export interface SortColumn {
+  column: string;
+  direction?: "asc" | "desc";
+  nulls?: "first" | "last";
+}
+
+/** Filters are combined with AND */
+export interface TableQuery {
+  filters?: ColumnFilter[];
+  sort?: SortColumn[];
+}
+
+/** Pages are ordered by `key` (the primary key or `_id` when unset) */
+export interface PageRequest {
+  filters?: ColumnFilter[];
+  key?: string;
+  direction?: "asc" | "desc";
+  cursor?: string | null;
+  limit: number;
+}
+
+export interface TablePage {
+  result: QueryResult;
+  next_cursor: string | null;
+}
+
+/** `key` holds primary key values, or every column's value without one */
+export type RowEdit =
+  | { op: "insert"; values: Record<string, unknown> }
+  | {
+      op: "update";
+      key: Record<string, unknown>;
+      values: Record<string, unknown>;
+    }
+  | { op: "delete"; key: Record<string, unknown> };
+
+export type QueryStreamEvent =
+  | { type: "Columns"; data: string[] }
+  | { type: "Rows"; data: unknown[][] }
+  | { type: "Done"; data: { row_count: number } }
+  | { type: "Error"; data: string };
+
+export type ExportSource =
+  | { kind: "table"; schema: string; table: string }
+  | { kind: "query"; query: string };
+
+export type ExportFormat =
+  | {
+      format: "csv";
+      delimiter?: string;
+      quoting?: "necessary" | "always" | "non_numeric" | "never";
+      header?: boolean;
+    }
+  | { format: "json" }
+  | { format: "ndjson" }
+  | {
+      format: "sql_insert";
+      /** Defaults to the source table */
+      table?: string;
+      /** Defaults to the connection's dialect */
+      dialect?: "postgres" | "mysql" | "sqlite";
+      rows_per_statement?: number;
+    }
+  | { format: "parquet" };
+
+export type ExportOptions = ExportFormat & {
+  path: string;
+  batch_size?: number;
+};
+
+export type ExportEvent =
+  | { type: "Progress"; data: { rows: number } }
+  | { type: "Done"; data: { rows: number; bytes: number } }
+  | { type: "Error"; data: string };
+
+export type ImportFormat =
+  | { format: "csv"; delimiter?: string; header?: boolean }
+  | { format: "json" }
+  | { format: "ndjson" };
+
+export type ImportErrorMode = "skip" | "abort";
+
+export interface ColumnMapping {
+  source: string;
+  target: string;
+  /** Column type when the table is created; inferred when unset */
+  data_type?: string;
+}
+
+export type ImportOptions = ImportFormat & {
+  path: string;
+  schema: string;
+  table: string;
+  create_table?: boolean;
+  /** Every file column under its own name when empty */
+  columns?: ColumnMapping[];
+  on_error?: ImportErrorMode;
+  batch_size?: number;
+  sample_size?: number;
+};
+
+export type InferredType =
+  | "boolean"
+  | "integer"
+  | "float"
+  | "date"
+  | "timestamp"
+  | "text"
+  | "json";
+
+export interface ImportPreview {
+  columns: { name: string; data_type: InferredType }[];
+  rows: unknown[][];
+}
+
+export interface ImportSummary {
+  rows_imported: number;
+  rows_skipped: number;
+  errors: { row: number; message: string }[];
+}
+
+export type ImportEvent =
+  | { type: "Progress"; data: { rows: number } }
+  | { type: "Done"; data: ImportSummary }
+  | { type: "Error"; data: string };
+
+export interface TableRef {
+  schema: string;
+  table: string;
+}
+
+/** What happens to a target table that already exists */
+export type CopyMode = "append" | "replace" | "upsert";
+
+export interface CopyOptions {
+  filters?: ColumnFilter[];
+  limit?: number;
+  mode?: CopyMode;
+  on_error?: ImportErrorMode;
+  batch_size?: number;
+}
+
+export interface CopySummary {
+  rows_copied: number;
+  rows_skipped: number;
+  errors: { row: number; message: string }[];
+  created_table: boolean;
+}
+
+export type CopyEvent =
+  | { type: "Progress"; data: { rows: number } }
+  | { type: "Done"; data: CopySummary }
+  | { type: "Error"; data: string };
+
+export interface DataDiffOptions {
+  /** Columns that identify a row; the source's primary key when empty */
+  key?: string[];
+  /** Columns to compare; all columns both tables have when empty */
+  columns?: string[];
+  batch_size?: number;
+}
+
+/** Inserted: only in the target; deleted: only in the source */
+export type RowChange = "inserted" | "deleted" | "changed";
+
+export interface RowDiff {
+  change: RowChange;
+  /** Key values, in the order of DataDiffSummary.key */
+  key: unknown[];
+  columns: { column: string; source: unknown; target: unknown }[];
+}
+
+export interface DataDiffSummary {
+  key: string[];
+  columns: string[];
+  inserted: number;
+  deleted: number;
+  changed: number;
+  unchanged: number;
+}
+
+export type DataDiffEvent =
+  | { type: "Rows"; data: RowDiff[] }
+  | { type: "Progress"; data: { rows: number } }
+  | { type: "Done"; data: DataDiffSummary }
+  | { type: "Error"; data: string };
+
+/** Added: only in the source; removed: only in the target */
+export type SchemaChange = "added" | "removed" | "changed";
+
+export interface ObjectDiff<T> {
+  name: string;
+  change: SchemaChange;
+  source: T | null;
+  target: T | null;
+  /** Properties that differ, e.g. "data_type", "nullable" or "default" */
+  differences: string[];
+}
+
+export interface TableDiff {
+  name: string;
+  change: SchemaChange;
+  columns: ObjectDiff<ColumnInfo>[];
+  indexes: ObjectDiff<IndexInfo>[];
+  constraints: ObjectDiff<ConstraintInfo>[];
+  foreign_keys: ObjectDiff<ForeignKeyInfo>[];
+}
+
+export interface SchemaDiff {
+  tables: TableDiff[];
+  /** DDL that makes the target match the source, when requested */
+  script: string | null;
+}
+
+export interface TypeCount {
+  type_name: string;
+  count: number;
+}
+
+/** `path` is dotted from the document root; arrays of documents are looked through */
+export interface FieldInfo {
+  name: string;
+  path: string;
+  /** Most frequent first */
+  types: TypeCount[];
+  /** Element types of arrays at this path */
+  item_types: TypeCount[];
+  /** Sampled documents that have the path */
+  count: number;
+  /** `count` as a percentage of the sampled documents */
+  presence: number;
+  fields: FieldInfo[];
+}
+
+export interface DocumentSchema {
+  sampled: number;
+  fields: FieldInfo[];
+}
+
+export type IndexKeyKind = "ascending" | "descending" | "text" | "hashed" | "2d" | "2dsphere";
+
+export interface IndexKey {
+  field: string;
+  kind: IndexKeyKind;
+}
+
+/** One key makes a single-field index, several a compound one */
+export interface IndexSpec {
+  keys: IndexKey[];
+  /** Generated from the keys when unset */
+  name?: string;
+  unique?: boolean;
+  sparse?: boolean;
+  /** Makes a TTL index on a single date field */
+  expire_after_seconds?: number;
+  /** Extended JSON filter of a partial index */
+  partial_filter?: Record<string, unknown>;
+  /** Field weights of a text index */
+  weights?: Record<string, number>;
+  default_language?: string;
+}
+
+export interface IndexUsage {
+  ops: number;
+  /** When the server started counting */
+  since: string | null;
+}
+
+/** `size` and `usage` are null when the stats cannot be read */
+export interface CollectionIndex {
+  name: string;
+  keys: IndexKey[];
+  unique: boolean;
+  sparse: boolean;
+  hidden: boolean;
+  expire_after_seconds: number | null;
+  partial_filter: Record<string, unknown> | null;
+  weights: Record<string, unknown> | null;
+  size: number | null;
+  usage: IndexUsage | null;
+}
+
+/** Sizes are in bytes */
+export interface CollectionStats {
+  documents: number;
+  size: number;
+  average_document_size: number;
+  storage_size: number;
+  free_storage_size: number;
+  indexes: number;
+  total_index_size: number;
+  capped: boolean;
+}
+
+export type ScriptErrorMode = "stop_on_error" | "continue";
+
+export interface StatementResult {
+  index: number;
+  statement: string;
+  result: QueryResult | null;
+  error: string | null;
+  duration_ms: number;
+}
+
+/** Block counts from PostgreSQL's BUFFERS option */
+export interface PlanBuffers {
+  shared_hit: number;
+  shared_read: number;
+  shared_dirtied: number;
+  shared_written: number;
+  temp_read: number;
+  temp_written: number;
+}
+
+/** One plan operator; rows and times are per loop */
+export interface PlanNode {
+  node_type: string;
+  relation: string | null;
+  index: string | null;
+  detail: string | null;
+  estimated_rows: number | null;
+  actual_rows: number | null;
+  rows_examined: number | null;
+  estimated_cost: number | null;
+  actual_time_ms: number | null;
+  loops: number | null;
+  buffers: PlanBuffers | null;
+  children: PlanNode[];
+}
+
+export interface QueryPlan {
+  root: PlanNode;
+  planning_time_ms: number | null;
+  execution_time_ms: number | null;
+  /** The plan as the database returned it */
+  raw: unknown;
+}
+
+export interface RunningQueryInfo {
+  query_id: string;
+  connection_id: string;
+  query: string;
+  started_at: number;
 }
 
 // ============================================================================